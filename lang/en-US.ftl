@@ -13,6 +13,8 @@ cli-unable-to-request-confirmation = Unable to request confirmation.
     .winpty-workaround = If you are using a Bash emulator (like Git Bash), try running winpty.
 cli-backup-id-with-multiple-games = Cannot specify backup ID when restoring multiple games.
 cli-invalid-backup-id = Invalid backup ID.
+cli-invalid-glob = Invalid file pattern: {$path}
+cli-enter-passphrase = Passphrase for encrypted backups
 
 badge-failed = FAILED
 badge-duplicates = DUPLICATES
@@ -20,11 +22,17 @@ badge-duplicated = DUPLICATED
 badge-ignored = IGNORED
 badge-redirected-from = FROM: {$path}
 badge-redirecting-to = TO: {$path}
+# This is shown when a local file is newer than the backup that would replace it,
+# or when it doesn't match any backed up version.
+badge-conflict = CONFLICT
+badge-filtered = FILTERED
 
 some-entries-failed = Some entries failed to process; look for {badge-failed} in the output for details. Double check whether you can access those files or whether their paths are very long.
 
 cli-game-line-item-redirected = Redirected from: {$path}
 cli-game-line-item-redirecting = Redirecting to: {$path}
+cli-game-line-item-conflict = Conflict: The local file is newer than this backup or doesn't match any backed up version.
+prompt-conflict = This local file is newer than the backup or doesn't match any backed up version. What should be done with it? {$path}
 
 button-backup = Back up
 button-preview = Preview
@@ -47,9 +55,17 @@ button-exit = Exit
 button-comment = Comment
 button-lock = Lock
 button-unlock = Unlock
+button-consolidate = Consolidate
+button-extract = Extract to folder
 # This opens a download page.
 button-get-app = Get {$app}
 button-validate = Validate
+# This checks the content of each backed up file, which is slower than a normal validation.
+button-validate-deep = Deep validate
+# This restores the saves that were overwritten by the last restore.
+button-undo-restore = Undo restore
+# This shows how much space the backups use.
+button-statistics = Statistics
 button-override-manifest = Override manifest
 button-extend-manifest = Extend manifest
 button-sort = Sort
@@ -62,6 +78,15 @@ manifest-cannot-be-updated = Error: Unable to check for an update to the manifes
 cannot-prepare-backup-target = Error: Unable to prepare backup target (either creating or emptying the folder). If you have the folder open in your file browser, try closing it: {$path}
 restoration-source-is-invalid = Error: The restoration source is invalid (either doesn't exist or isn't a directory). Please double check the location: {$path}
 registry-issue = Error: Some registry entries were skipped.
+backup-passphrase-required = Error: This backup is encrypted, but no passphrase was provided. Set one in the config file or the LUDUSAVI_PASSPHRASE environment variable.
+backup-passphrase-invalid = Error: The passphrase does not match the one used for this encrypted backup.
+backup-encryption-failed = Error: Unable to encrypt the backup.
+backup-quota-exceeded = Error: This backup would need {$total-size}, which exceeds the storage quota of {$quota}, even after removing older unlocked backups.
+invalid-backups = Error: Some backups are invalid.
+restore-snapshot-failed = Error: Unable to back up the current saves before restoring, so they were left as-is.
+no-restore-to-undo = Error: There is no restore to undo.
+unsupported-backup-format = Error: This operation does not support the "{$format}" backup format.
+newer-backups-depend-on-chain = Error: Only the latest differential backup in a chain can be consolidated, since newer ones still depend on the same full backup.
 unable-to-browse-file-system = Error: Unable to browse on your system.
 unable-to-open-directory = Error: Unable to open directory:
 unable-to-open-url = Error: Unable to open URL:
@@ -87,6 +112,7 @@ field-backup-target = Back up to:
 field-restore-source = Restore from:
 field-custom-files = Paths:
 field-custom-registry = Registry:
+field-custom-exclude = Exclude:
 field-sort = Sort:
 field-redirect-source =
     .placeholder = Source (original location)
@@ -105,6 +131,8 @@ field-backup-format = Format:
 field-backup-compression = Compression:
 # The compression level determines how much compresison we perform.
 field-backup-compression-level = Level:
+field-backup-encryption = Encrypt
+field-conflict-policy = On conflict:
 
 label-manifest = Manifest
 # This shows the time when we checked for an update to the manifest.
@@ -161,16 +189,20 @@ label-primary-manifest = Primary manifest
 # This refers to how we integrate a custom game with the manifest data.
 label-integration = Integration
 
+store-bottles = Bottles
 store-ea = EA
 store-epic = Epic
 store-gog = GOG
 store-gog-galaxy = GOG Galaxy
 store-heroic = Heroic
+store-itch = itch.io
 store-legendary = Legendary
 store-lutris = Lutris
 store-microsoft = Microsoft
+store-minecraft = Minecraft
 store-origin = Origin
 store-prime = Prime Gaming
+store-retroarch = RetroArch
 store-steam = Steam
 store-uplay = Uplay
 store-other-home = Home folder
@@ -189,6 +221,8 @@ store-other = Other
 
 backup-format-simple = Simple
 backup-format-zip = Zip
+backup-format-tar-zstd = Tar (zstd)
+backup-format-dedup = Deduplicated
 
 compression-none = None
 # "Deflate" is a proper noun: https://en.wikipedia.org/wiki/Deflate
@@ -196,6 +230,11 @@ compression-deflate = Deflate
 compression-bzip2 = Bzip2
 compression-zstd = Zstd
 
+conflict-policy-overwrite = Overwrite
+conflict-policy-skip = Skip
+conflict-policy-keep-both = Keep both
+conflict-policy-prompt = Ask
+
 theme = Theme
 theme-light = Light
 theme-dark = Dark
@@ -208,6 +247,7 @@ show-unchanged-games = Show unchanged games
 show-unscanned-games = Show unscanned games
 override-max-threads = Override max threads
 synchronize-automatically = Synchronize automatically
+snapshot-before-restore = Back up current saves before restoring
 prefer-alias-display = Display alias instead of original name
 
 explanation-for-exclude-store-screenshots =
@@ -251,6 +291,37 @@ backups-are-valid =
 backups-are-invalid =
     These games' backups appear to be invalid.
     Do you want to create new full backups for these games?
+validation-unreadable-backup = Unable to open backup: { $backup }
+validation-missing-file = Missing file in backup { $backup }: { $path }
+validation-unreadable-file = Unable to read file in backup { $backup }: { $path }
+validation-changed-file = Changed file in backup { $backup }: { $path }
+validation-changed-registry = Changed registry in backup { $backup }
+no-renamed-games = No backups need to be moved for renamed games.
+renamed-game = {$game} -> {$name}
+confirm-migrate-renames = These games were renamed in the manifest. Do you want to move their backups to the new names?
+no-orphans = No unused files found.
+orphans-summary = {$total} unused {$total ->
+    [one] item
+    *[other] items
+} ({$total-size})
+confirm-remove-orphans =
+    Do you want to remove {$total} unused {$total ->
+        [one] item
+        *[other] items
+    } ({$total-size}) from the backup folder?
+    {$path}
+stats-full-backups = Full backups
+stats-differential-backups = Differential backups
+# This is how much space could be saved by storing identical files only once.
+stats-duplicate-data = Duplicate data
+stats-oldest-backup = Oldest backup
+stats-newest-backup = Newest backup
+stats-growth = Growth by month
+orphan-kind-file = Stray file
+orphan-kind-archive = Unreferenced archive
+orphan-kind-game-folder = Folder without a mapping
+orphan-kind-invalid-mapping = Folder with an invalid mapping
+orphan-kind-blob = Unreferenced blob
 
 saves-found = Save data found.
 no-saves-found = No save data found.
@@ -273,6 +344,7 @@ game-is-unrecognized = Ludusavi does not recognize this game.
 game-has-nothing-to-restore = This game does not have a backup to restore.
 launch-game-after-error = Launch the game anyway?
 game-did-not-launch = Game failed to launch.
+unable-to-watch-saves = Unable to watch for changes to save files.
 
 back-up-specific-game =
     .confirm = Back up save data for {$game}?
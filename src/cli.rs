@@ -2,6 +2,7 @@ mod api;
 mod parse;
 mod report;
 mod ui;
+mod watch;
 
 use std::{collections::BTreeSet, process::Command, time::Duration};
 
@@ -14,8 +15,8 @@ use rayon::{
 
 use crate::{
     cli::{
-        parse::{Cli, CompletionShell, ManifestSubcommand, Subcommand},
-        report::{report_cloud_changes, Reporter},
+        parse::{BackupsSubcommand, Cli, CompletionShell, ManifestSubcommand, Subcommand},
+        report::{report_cloud_changes, report_orphans, Reporter},
     },
     cloud::{CloudChange, Rclone, Remote},
     lang::{Language, TRANSLATOR},
@@ -23,9 +24,16 @@ use crate::{
         app_dir, get_threads_from_env, initialize_rayon, register_sigint, unregister_sigint, Error, Finality,
         StrictPath, SyncDirection,
     },
-    resource::{cache::Cache, config::Config, manifest::Manifest, ResourceFile, SaveableResourceFile},
+    resource::{
+        cache::Cache,
+        config::{BackupFormat, BackupFormats, Config, ConflictPolicy, Encryption, ZipCompression},
+        manifest::Manifest,
+        ResourceFile, SaveableResourceFile,
+    },
     scan::{
-        layout::BackupLayout, prepare_backup_target, scan_game_for_backup, BackupId, DuplicateDetector, Launchers,
+        encryption::Passphrase,
+        layout::{BackupLayout, FileSelection, OrphanKind},
+        prepare_backup_target, scan_game_for_backup, BackupId, DuplicateDetector, HashCache, Launchers,
         OperationStepDecision, ScanKind, SteamShortcuts, TitleFinder, TitleQuery,
     },
     wrap,
@@ -62,6 +70,53 @@ fn load_manifest(
     }
 }
 
+/// Apply any format options from the command line on top of the config file.
+fn override_backup_format(
+    config: &Config,
+    format: Option<BackupFormat>,
+    compression: Option<ZipCompression>,
+    compression_level: Option<i32>,
+    encrypt: bool,
+) -> BackupFormats {
+    let mut backup_format = config.backup.format.clone();
+    if let Some(format) = format {
+        backup_format.chosen = format;
+    }
+    if let Some(compression) = compression {
+        backup_format.zip.compression = compression;
+    }
+    if let Some(level) = compression_level {
+        let method = match backup_format.chosen {
+            BackupFormat::TarZstd => ZipCompression::Zstd,
+            _ => backup_format.zip.compression,
+        };
+        backup_format.compression.set_level(&method, level);
+    }
+    backup_format.encryption.enabled = encrypt;
+    backup_format
+}
+
+/// Use the configured passphrase if there is one,
+/// or else prompt for it when we'll need it.
+fn get_passphrase(config: &Config, needed: bool) -> Result<Option<Passphrase>, Error> {
+    if let Some(passphrase) = config.backup.format.encryption.passphrase() {
+        return Ok(Some(passphrase));
+    }
+
+    use std::io::IsTerminal;
+    if !needed || !std::io::stdin().is_terminal() {
+        return Ok(None);
+    }
+
+    match dialoguer::Password::new()
+        .with_prompt(TRANSLATOR.enter_passphrase())
+        .interact()
+    {
+        Ok(passphrase) => Ok(Passphrase::new(passphrase)),
+        Err(_) => Err(Error::CliUnableToRequestConfirmation),
+    }
+}
+
 fn parse_games(games: Vec<String>) -> Vec<String> {
     if !games.is_empty() {
         games
@@ -140,8 +195,15 @@ pub fn run(sub: Subcommand, no_manifest_update: bool, try_manifest_update: bool)
             compression_level,
             full_limit,
             differential_limit,
+            keep_hours,
+            keep_daily,
+            keep_weekly,
+            keep_monthly,
+            encrypt,
+            no_encrypt,
             cloud_sync,
             no_cloud_sync,
+            rehash,
             games,
         } => {
             let games = parse_games(games);
@@ -171,9 +233,19 @@ pub fn run(sub: Subcommand, no_manifest_update: bool, try_manifest_update: bool)
                 prepare_backup_target(&backup_dir)?;
             }
 
-            let retention = config.backup.retention.with_limits(full_limit, differential_limit);
+            let retention = config
+                .backup
+                .retention
+                .with_limits(full_limit, differential_limit)
+                .with_calendar(keep_hours, keep_daily, keep_weekly, keep_monthly);
 
-            let layout = BackupLayout::new(backup_dir.clone());
+            let encrypt = negatable_flag(encrypt, no_encrypt, config.backup.format.encryption.enabled);
+            let passphrase = get_passphrase(
+                &config,
+                !preview && encrypt && Encryption::can_apply_to(format.unwrap_or(config.backup.format.chosen)),
+            )?;
+
+            let layout = BackupLayout::new(backup_dir.clone()).with_passphrase(passphrase);
             let title_finder = TitleFinder::new(&config, &manifest, layout.restorable_game_set());
 
             let games_specified = !games.is_empty();
@@ -191,6 +263,10 @@ pub fn run(sub: Subcommand, no_manifest_update: bool, try_manifest_update: bool)
             let toggled_paths = config.backup.toggled_paths.clone();
             let toggled_registry = config.backup.toggled_registry.clone();
             let steam_shortcuts = SteamShortcuts::scan();
+            let hash_cache = HashCache::load().unwrap_or_default();
+            if rehash {
+                hash_cache.clear();
+            }
 
             let cloud_sync = negatable_flag(
                 cloud_sync && !preview,
@@ -260,6 +336,7 @@ pub fn run(sub: Subcommand, no_manifest_update: bool, try_manifest_update: bool)
                         &config.redirects,
                         config.restore.reverse_redirects,
                         &steam_shortcuts,
+                        &hash_cache,
                     );
                     let ignored = !&config.is_game_enabled_for_backup(name) && !games_specified;
                     let decision = if ignored {
@@ -270,22 +347,10 @@ pub fn run(sub: Subcommand, no_manifest_update: bool, try_manifest_update: bool)
                     let backup_info = if preview || ignored {
                         None
                     } else {
-                        let mut backup_format = config.backup.format.clone();
-                        if let Some(format) = format {
-                            backup_format.chosen = format;
-                        }
-                        if let Some(compression) = compression {
-                            backup_format.zip.compression = compression;
-                        }
-                        if let Some(level) = compression_level {
-                            backup_format
-                                .compression
-                                .set_level(&backup_format.zip.compression, level);
-                        }
+                        let backup_format =
+                            override_backup_format(&config, format, compression, compression_level, encrypt);
 
-                        layout
-                            .game_layout(name)
-                            .back_up(&scan_info, &chrono::Utc::now(), &backup_format, retention)
+                        layout.back_up_game(name, &scan_info, &chrono::Utc::now(), &backup_format, retention)
                     };
                     log::trace!("step {i} completed");
                     if !scan_info.can_report_game() {
@@ -298,6 +363,12 @@ pub fn run(sub: Subcommand, no_manifest_update: bool, try_manifest_update: bool)
                 .collect();
             log::info!("completed backup");
 
+            if !preview {
+                layout.prune_blobs();
+                hash_cache.prune();
+                hash_cache.save();
+            }
+
             if should_sync_cloud_after {
                 let changed_games: Vec<_> = info
                     .iter()
@@ -360,6 +431,12 @@ pub fn run(sub: Subcommand, no_manifest_update: bool, try_manifest_update: bool)
             backup,
             cloud_sync,
             no_cloud_sync,
+            destination,
+            files,
+            snapshot,
+            no_snapshot,
+            on_conflict,
+            undo,
             games,
         } => {
             let games = parse_games(games);
@@ -371,7 +448,13 @@ pub fn run(sub: Subcommand, no_manifest_update: bool, try_manifest_update: bool)
                 Some(p) => p,
             };
 
-            if !preview && !force {
+            let selection = FileSelection::new(&files)?;
+
+            // Extracting to another folder doesn't touch the live saves.
+            let snapshot = negatable_flag(snapshot, no_snapshot, config.restore.snapshot) && destination.is_none();
+            let now = chrono::Utc::now();
+
+            if !preview && !force && destination.is_none() {
                 match dialoguer::Confirm::new()
                     .with_prompt(TRANSLATOR.confirm_restore(&restore_dir, false))
                     .interact()
@@ -383,6 +466,13 @@ pub fn run(sub: Subcommand, no_manifest_update: bool, try_manifest_update: bool)
             }
 
             let layout = BackupLayout::new(restore_dir.clone());
+            let passphrase = get_passphrase(
+                &config,
+                !preview
+                    && (layout.has_encrypted_backups()
+                        || (snapshot && config.backup.format.encryption.applies_to(config.backup.format.chosen))),
+            )?;
+            let layout = layout.with_passphrase(passphrase);
 
             if backup.is_some() && games.len() != 1 {
                 return Err(Error::CliBackupIdWithMultipleGames);
@@ -392,8 +482,9 @@ pub fn run(sub: Subcommand, no_manifest_update: bool, try_manifest_update: bool)
             let manifest = load_manifest(&config, &mut cache, true, false).unwrap_or_default();
             let title_finder = TitleFinder::new(&config, &manifest, layout.restorable_game_set());
 
-            let games_specified = !games.is_empty();
-            let games = match evaluate_games(layout.restorable_game_set(), games, &title_finder) {
+            // When undoing, the games were already chosen by the last restore.
+            let games_specified = !games.is_empty() || undo;
+            let mut games = match evaluate_games(layout.restorable_game_set(), games, &title_finder) {
                 Ok(games) => games,
                 Err(games) => {
                     reporter.trip_unknown_games(games.clone());
@@ -402,6 +493,17 @@ pub fn run(sub: Subcommand, no_manifest_update: bool, try_manifest_update: bool)
                 }
             };
 
+            let snapshots = if undo {
+                let snapshots = layout.last_restore_snapshots();
+                games.retain(|game| snapshots.contains_key(game));
+                if games.is_empty() {
+                    return Err(Error::NoRestoreToUndo);
+                }
+                snapshots
+            } else {
+                Default::default()
+            };
+
             let cloud_sync = negatable_flag(
                 cloud_sync && !preview,
                 no_cloud_sync,
@@ -430,23 +532,51 @@ pub fn run(sub: Subcommand, no_manifest_update: bool, try_manifest_update: bool)
                 }
             }
 
+            let conflicts = on_conflict.unwrap_or(config.restore.conflicts);
+            let progress = scan_progress_bar(games.len() as u64);
+            let prompt_lock = std::sync::Mutex::new(());
+            let prompt_conflict = |path: &StrictPath| {
+                if force {
+                    return ConflictPolicy::Skip;
+                }
+
+                // Only one game at a time can ask.
+                let _lock = prompt_lock.lock().unwrap_or_else(|e| e.into_inner());
+                let options = [
+                    ConflictPolicy::Overwrite,
+                    ConflictPolicy::Skip,
+                    ConflictPolicy::KeepBoth,
+                ];
+                progress.suspend(|| {
+                    dialoguer::Select::new()
+                        .with_prompt(TRANSLATOR.prompt_conflict(&path.render()))
+                        .items(&options.iter().map(|x| x.to_string()).collect::<Vec<_>>())
+                        .default(1)
+                        .interact()
+                        .map(|i| options[i])
+                        .unwrap_or(ConflictPolicy::Skip)
+                })
+            };
+
             log::info!("beginning restore with {} steps", games.len());
 
             let mut info: Vec<_> = games
                 .par_iter()
                 .enumerate()
-                .progress_with(scan_progress_bar(games.len() as u64))
+                .progress_with(progress.clone())
                 .filter_map(|(i, name)| {
                     log::trace!("step {i} / {}: {name}", games.len());
                     let mut layout = layout.game_layout(name);
-                    let scan_info = layout.scan_for_restoration(
+                    let mut scan_info = layout.scan_for_restoration(
                         name,
-                        backup_id.as_ref().unwrap_or(&BackupId::Latest),
+                        snapshots.get(name).or(backup_id.as_ref()).unwrap_or(&BackupId::Latest),
                         &config.redirects,
                         config.restore.reverse_redirects,
                         &config.restore.toggled_paths,
                         &config.restore.toggled_registry,
+                        &config.restore.steam_accounts,
                     );
+                    layout.select_for_restoration(&mut scan_info, &selection, destination.as_ref());
                     let ignored = !&config.is_game_enabled_for_restore(name) && !games_specified;
                     let decision = if ignored {
                         OperationStepDecision::Ignored
@@ -473,7 +603,19 @@ pub fn run(sub: Subcommand, no_manifest_update: bool, try_manifest_update: bool)
                     let restore_info = if scan_info.backup.is_none() || preview || ignored {
                         crate::scan::BackupInfo::default()
                     } else {
-                        layout.restore(&scan_info, &config.restore.toggled_registry)
+                        let unresolved = layout.resolve_conflicts(&mut scan_info, conflicts, &now, &prompt_conflict);
+                        let mut restore_info = if snapshot {
+                            layout.restore_with_snapshot(
+                                &scan_info,
+                                &config.restore.toggled_registry,
+                                &now,
+                                &config.backup.format,
+                            )
+                        } else {
+                            layout.restore(&scan_info, &config.restore.toggled_registry)
+                        };
+                        restore_info.failed_files.extend(unresolved);
+                        restore_info
                     };
                     log::trace!("step {i} completed");
                     if !scan_info.can_report_game() {
@@ -536,7 +678,443 @@ pub fn run(sub: Subcommand, no_manifest_update: bool, try_manifest_update: bool)
                 &mut std::io::stdout(),
             )
         }
-        Subcommand::Backups { path, api, games } => {
+        Subcommand::Backups {
+            sub: Some(BackupsSubcommand::Validate { path, api, deep, games }),
+            ..
+        } => {
+            let games = parse_games(games);
+
+            let mut reporter = if api { Reporter::json() } else { Reporter::standard() };
+            reporter.suppress_overall();
+
+            let restore_dir = match path {
+                None => config.restore.path.clone(),
+                Some(p) => p,
+            };
+
+            let layout = BackupLayout::new(restore_dir.clone());
+            let passphrase = get_passphrase(&config, layout.has_encrypted_backups())?;
+            let layout = layout.with_passphrase(passphrase);
+            let manifest = load_manifest(&config, &mut cache, true, false).unwrap_or_default();
+            let title_finder = TitleFinder::new(&config, &manifest, layout.restorable_game_set());
+
+            let games = match evaluate_games(layout.restorable_game_set(), games, &title_finder) {
+                Ok(games) => games,
+                Err(games) => {
+                    reporter.trip_unknown_games(games.clone());
+                    reporter.print_failure();
+                    return Err(Error::CliUnrecognizedGames { games });
+                }
+            };
+
+            let info: Vec<_> = games
+                .par_iter()
+                .progress_count(games.len() as u64)
+                .map(|name| {
+                    let layout = layout.game_layout(name);
+                    let issues = layout.validate_all(deep);
+                    let display_title = config.display_name(name);
+                    let backup_dir = layout.path;
+                    (name, display_title, backup_dir, issues)
+                })
+                .collect();
+
+            let mut invalid = false;
+            for (name, display_title, backup_dir, issues) in info {
+                if !reporter.add_validation(name, display_title, backup_dir, &issues) {
+                    invalid = true;
+                }
+            }
+
+            if invalid || api {
+                reporter.print(&restore_dir);
+            } else {
+                println!("{}", TRANSLATOR.backups_are_valid());
+            }
+
+            if invalid {
+                return Err(Error::InvalidBackups);
+            }
+        }
+        Subcommand::Backups {
+            sub:
+                Some(BackupsSubcommand::Convert {
+                    path,
+                    api,
+                    backup,
+                    format,
+                    compression,
+                    compression_level,
+                    encrypt,
+                    no_encrypt,
+                    games,
+                }),
+            ..
+        } => {
+            let games = parse_games(games);
+
+            let mut reporter = if api { Reporter::json() } else { Reporter::standard() };
+            reporter.suppress_overall();
+
+            let restore_dir = match path {
+                None => config.restore.path.clone(),
+                Some(p) => p,
+            };
+
+            let encrypt = negatable_flag(encrypt, no_encrypt, config.backup.format.encryption.enabled);
+            let backup_format = override_backup_format(&config, format, compression, compression_level, encrypt);
+
+            let layout = BackupLayout::new(restore_dir.clone());
+            let passphrase = get_passphrase(
+                &config,
+                layout.has_encrypted_backups() || backup_format.encryption.applies_to(backup_format.chosen),
+            )?;
+            let layout = layout.with_passphrase(passphrase);
+
+            if backup.is_some() && games.len() != 1 {
+                return Err(Error::CliBackupIdWithMultipleGames);
+            }
+            let backup_id = backup.map(BackupId::Named);
+
+            let manifest = load_manifest(&config, &mut cache, true, false).unwrap_or_default();
+            let title_finder = TitleFinder::new(&config, &manifest, layout.restorable_game_set());
+
+            let games = match evaluate_games(layout.restorable_game_set(), games, &title_finder) {
+                Ok(games) => games,
+                Err(games) => {
+                    reporter.trip_unknown_games(games.clone());
+                    reporter.print_failure();
+                    return Err(Error::CliUnrecognizedGames { games });
+                }
+            };
+
+            let info: Vec<_> = games
+                .par_iter()
+                .progress_count(games.len() as u64)
+                .map(|name| {
+                    let mut layout = layout.game_layout(name);
+                    let outcome = match &backup_id {
+                        Some(id) if layout.find_by_id(id).is_none() => Err(Error::CliInvalidBackupId),
+                        _ => layout.convert(backup_id.as_ref(), &backup_format),
+                    };
+                    let backups = layout.get_backups();
+                    let display_title = config.display_name(name);
+                    let backup_dir = layout.path;
+                    (name, display_title, backup_dir, backups, outcome)
+                })
+                .collect();
+
+            layout.prune_blobs();
+
+            let mut failure = None;
+            for (name, display_title, backup_dir, backups, outcome) in info {
+                match outcome {
+                    Ok(converted) => {
+                        if !converted.is_empty() {
+                            reporter.add_backups(name, display_title, backup_dir, &backups);
+                        }
+                    }
+                    Err(e) => {
+                        log::error!("Unable to convert backups for {name}: {e:?}");
+                        reporter.trip_some_games_failed();
+                        failure.get_or_insert(e);
+                    }
+                }
+            }
+            reporter.print(&restore_dir);
+
+            if let Some(e) = failure {
+                return Err(e);
+            }
+        }
+        Subcommand::Backups {
+            sub:
+                Some(BackupsSubcommand::Consolidate {
+                    path,
+                    api,
+                    backup,
+                    games,
+                }),
+            ..
+        } => {
+            let games = parse_games(games);
+
+            let mut reporter = if api { Reporter::json() } else { Reporter::standard() };
+            reporter.suppress_overall();
+
+            let restore_dir = match path {
+                None => config.restore.path.clone(),
+                Some(p) => p,
+            };
+
+            let layout = BackupLayout::new(restore_dir.clone());
+            let passphrase = get_passphrase(
+                &config,
+                layout.has_encrypted_backups() || config.backup.format.encryption.enabled,
+            )?;
+            let layout = layout.with_passphrase(passphrase);
+
+            if backup.is_some() && games.len() != 1 {
+                return Err(Error::CliBackupIdWithMultipleGames);
+            }
+            let backup_id = backup.map(BackupId::Named).unwrap_or(BackupId::Latest);
+
+            let manifest = load_manifest(&config, &mut cache, true, false).unwrap_or_default();
+            let title_finder = TitleFinder::new(&config, &manifest, layout.restorable_game_set());
+
+            let games = match evaluate_games(layout.restorable_game_set(), games, &title_finder) {
+                Ok(games) => games,
+                Err(games) => {
+                    reporter.trip_unknown_games(games.clone());
+                    reporter.print_failure();
+                    return Err(Error::CliUnrecognizedGames { games });
+                }
+            };
+
+            let now = chrono::Utc::now();
+            let info: Vec<_> = games
+                .par_iter()
+                .progress_count(games.len() as u64)
+                .map(|name| {
+                    let mut layout = layout.game_layout(name);
+                    let outcome = if layout.find_by_id(&backup_id).is_none() && backup_id != BackupId::Latest {
+                        Err(Error::CliInvalidBackupId)
+                    } else {
+                        layout.consolidate(&backup_id, &now, &config.backup.format, config.backup.retention)
+                    };
+                    let backups = layout.get_backups();
+                    let display_title = config.display_name(name);
+                    let backup_dir = layout.path;
+                    (name, display_title, backup_dir, backups, outcome)
+                })
+                .collect();
+
+            layout.prune_blobs();
+
+            let mut failure = None;
+            for (name, display_title, backup_dir, backups, outcome) in info {
+                match outcome {
+                    Ok(Some(_)) => {
+                        reporter.add_backups(name, display_title, backup_dir, &backups);
+                    }
+                    Ok(None) => {}
+                    Err(e) => {
+                        log::error!("Unable to consolidate backup for {name}: {e:?}");
+                        reporter.trip_some_games_failed();
+                        failure.get_or_insert(e);
+                    }
+                }
+            }
+            reporter.print(&restore_dir);
+
+            if let Some(e) = failure {
+                return Err(e);
+            }
+        }
+        Subcommand::Backups {
+            sub:
+                Some(BackupsSubcommand::Diff {
+                    path,
+                    api,
+                    from,
+                    to,
+                    game,
+                }),
+            ..
+        } => {
+            let mut reporter = if api { Reporter::json() } else { Reporter::standard() };
+            reporter.suppress_overall();
+
+            let restore_dir = match path {
+                None => config.restore.path.clone(),
+                Some(p) => p,
+            };
+
+            let layout = BackupLayout::new(restore_dir.clone());
+            let manifest = load_manifest(&config, &mut cache, true, false).unwrap_or_default();
+            let title_finder = TitleFinder::new(&config, &manifest, layout.restorable_game_set());
+
+            let Some(name) = title_finder.find_one_by_name(&game) else {
+                reporter.trip_unknown_games(vec![game.clone()]);
+                reporter.print_failure();
+                return Err(Error::CliUnrecognizedGames { games: vec![game] });
+            };
+            let layout = layout.game_layout(&name);
+
+            let to = to.map(BackupId::Named).unwrap_or(BackupId::Latest);
+            let from = match from {
+                Some(from) => Some(BackupId::Named(from)),
+                None => layout.previous_backup_id(&to),
+            };
+            let Some(comparison) = layout.compare_backups(from.as_ref(), &to) else {
+                return Err(Error::CliInvalidBackupId);
+            };
+
+            reporter.add_comparison(&name, config.display_name(&name), layout.path, comparison);
+            reporter.print(&restore_dir);
+        }
+        Subcommand::Backups {
+            sub: Some(BackupsSubcommand::History { path, api, game, file }),
+            ..
+        } => {
+            let mut reporter = if api { Reporter::json() } else { Reporter::standard() };
+            reporter.suppress_overall();
+
+            let restore_dir = match path {
+                None => config.restore.path.clone(),
+                Some(p) => p,
+            };
+
+            let layout = BackupLayout::new(restore_dir.clone());
+            let manifest = load_manifest(&config, &mut cache, true, false).unwrap_or_default();
+            let title_finder = TitleFinder::new(&config, &manifest, layout.restorable_game_set());
+
+            let Some(name) = title_finder.find_one_by_name(&game) else {
+                reporter.trip_unknown_games(vec![game.clone()]);
+                reporter.print_failure();
+                return Err(Error::CliUnrecognizedGames { games: vec![game] });
+            };
+            let layout = layout.game_layout(&name);
+
+            let file = file.render();
+            let history = layout.file_history(&file);
+            reporter.add_file_history(&name, config.display_name(&name), layout.path, &file, history);
+            reporter.print(&restore_dir);
+        }
+        Subcommand::Backups {
+            sub:
+                Some(BackupsSubcommand::Gc {
+                    path,
+                    preview,
+                    include_invalid_mappings,
+                    force,
+                    api,
+                }),
+            ..
+        } => {
+            let restore_dir = match path {
+                None => config.restore.path.clone(),
+                Some(p) => p,
+            };
+            let finality = if preview { Finality::Preview } else { Finality::Final };
+
+            let layout = BackupLayout::new(restore_dir.clone());
+            let orphans = layout.find_orphans();
+            let (removable, ignored): (Vec<_>, Vec<_>) = orphans
+                .iter()
+                .cloned()
+                .partition(|x| include_invalid_mappings || x.kind != OrphanKind::InvalidMapping);
+            let ignored: Vec<_> = ignored.into_iter().map(|x| x.path).collect();
+
+            if !removable.is_empty()
+                && !ask(
+                    TRANSLATOR.confirm_remove_orphans(
+                        removable.len(),
+                        removable.iter().map(|x| x.size).sum(),
+                        &restore_dir,
+                    ),
+                    finality,
+                    force,
+                )?
+            {
+                return Ok(());
+            }
+
+            let failed = if finality.preview() {
+                vec![]
+            } else {
+                layout.remove_orphans(&removable)
+            };
+            report_orphans(&orphans, &failed, &ignored, api);
+
+            if !failed.is_empty() {
+                return Err(Error::SomeEntriesFailed);
+            }
+        }
+        Subcommand::Backups {
+            sub: Some(BackupsSubcommand::Stats { path, api, games }),
+            ..
+        } => {
+            let games = parse_games(games);
+
+            let mut reporter = if api { Reporter::json() } else { Reporter::standard() };
+            reporter.suppress_overall();
+
+            let restore_dir = match path {
+                None => config.restore.path.clone(),
+                Some(p) => p,
+            };
+
+            let layout = BackupLayout::new(restore_dir.clone());
+            let manifest = load_manifest(&config, &mut cache, true, false).unwrap_or_default();
+            let title_finder = TitleFinder::new(&config, &manifest, layout.restorable_game_set());
+
+            let games = match evaluate_games(layout.restorable_game_set(), games, &title_finder) {
+                Ok(games) => games,
+                Err(games) => {
+                    reporter.trip_unknown_games(games.clone());
+                    reporter.print_failure();
+                    return Err(Error::CliUnrecognizedGames { games });
+                }
+            };
+
+            let stats = layout.stats(&games);
+            let total = stats.games.len();
+            let mut by_size: Vec<_> = stats.games.into_iter().collect();
+            by_size.sort_by_key(|(_, x)| std::cmp::Reverse(x.bytes));
+            for (name, game) in by_size {
+                let display_title = config.display_name(&name);
+                reporter.add_backup_stats(&name, display_title, layout.game_folder(&name), game);
+            }
+            reporter.add_overall_backup_stats(total, stats.overall);
+            reporter.print(&restore_dir);
+        }
+        Subcommand::Backups {
+            sub: Some(BackupsSubcommand::MigrateRenames { path, preview, force }),
+            ..
+        } => {
+            let restore_dir = match path {
+                None => config.restore.path.clone(),
+                Some(p) => p,
+            };
+            let finality = if preview { Finality::Preview } else { Finality::Final };
+
+            // Updating the manifest is what detects new renames.
+            load_manifest(&config, &mut cache, no_manifest_update, try_manifest_update)?;
+
+            let layout = BackupLayout::new(restore_dir);
+            let renames = layout.pending_renames(&cache.renamed_games);
+            if renames.is_empty() {
+                println!("{}", TRANSLATOR.no_renamed_games());
+                return Ok(());
+            }
+
+            for (old, new) in &renames {
+                println!("{}", TRANSLATOR.renamed_game(old, new));
+            }
+
+            if !ask(TRANSLATOR.confirm_migrate_renames(), finality, force)? || finality.preview() {
+                return Ok(());
+            }
+
+            let mut failed = false;
+            for (old, new) in &renames {
+                if let Err(e) = layout.migrate_renamed_game(old, new) {
+                    eprintln!("{}", TRANSLATOR.handle_error(&e));
+                    failed = true;
+                }
+            }
+
+            if failed {
+                return Err(Error::SomeEntriesFailed);
+            }
+        }
+        Subcommand::Backups {
+            sub: None,
+            path,
+            api,
+            games,
+        } => {
             let games = parse_games(games);
 
             let mut reporter = if api { Reporter::json() } else { Reporter::standard() };
@@ -822,6 +1400,43 @@ pub fn run(sub: Subcommand, no_manifest_update: bool, try_manifest_update: bool)
                 report_cloud_changes(&changes, api);
             }
         },
+        Subcommand::Watch { path, delay, games } => {
+            let games = parse_games(games);
+
+            let manifest = load_manifest(&config, &mut cache, no_manifest_update, try_manifest_update)?;
+
+            let backup_dir = match path {
+                None => config.backup.path.clone(),
+                Some(p) => p,
+            };
+            prepare_backup_target(&backup_dir)?;
+
+            let passphrase = get_passphrase(
+                &config,
+                config.backup.format.encryption.enabled && Encryption::can_apply_to(config.backup.format.chosen),
+            )?;
+
+            let layout = BackupLayout::new(backup_dir.clone());
+            let title_finder = TitleFinder::new(&config, &manifest, layout.restorable_game_set());
+
+            let games_specified = !games.is_empty();
+            let games: Vec<_> = match evaluate_games(manifest.primary_titles(), games, &title_finder) {
+                Ok(games) => games
+                    .into_iter()
+                    .filter(|name| games_specified || config.is_game_enabled_for_backup(name))
+                    .collect(),
+                Err(games) => return Err(Error::CliUnrecognizedGames { games }),
+            };
+
+            watch::watch(
+                &config,
+                &manifest,
+                backup_dir,
+                passphrase,
+                games,
+                std::time::Duration::from_secs(delay),
+            )?;
+        }
         Subcommand::Wrap {
             name_source,
             force,
@@ -922,6 +1537,12 @@ pub fn run(sub: Subcommand, no_manifest_update: bool, try_manifest_update: bool)
                         backup: Default::default(),
                         cloud_sync: Default::default(),
                         no_cloud_sync: Default::default(),
+                        destination: Default::default(),
+                        files: Default::default(),
+                        snapshot: Default::default(),
+                        no_snapshot: Default::default(),
+                        on_conflict: Default::default(),
+                        undo: Default::default(),
                     },
                     no_manifest_update,
                     try_manifest_update,
@@ -974,8 +1595,15 @@ pub fn run(sub: Subcommand, no_manifest_update: bool, try_manifest_update: bool)
                         compression_level: Default::default(),
                         full_limit: Default::default(),
                         differential_limit: Default::default(),
+                        keep_hours: Default::default(),
+                        keep_daily: Default::default(),
+                        keep_weekly: Default::default(),
+                        keep_monthly: Default::default(),
+                        encrypt: Default::default(),
+                        no_encrypt: Default::default(),
                         cloud_sync: Default::default(),
                         no_cloud_sync: Default::default(),
+                        rehash: Default::default(),
                     },
                     no_manifest_update,
                     try_manifest_update,
@@ -1098,8 +1726,7 @@ fn sync_cloud(
 
     let games = if !games.is_empty() {
         let layout = BackupLayout::new(local.clone());
-        let games: Vec<_> = games.iter().filter_map(|x| layout.game_folder(x).leaf()).collect();
-        games
+        layout.cloud_folders(games)
     } else {
         vec![]
     };
@@ -5,7 +5,7 @@ use crate::{
     path::StrictPath,
     prelude::Error,
     resource::{config::Config, manifest::Manifest},
-    scan::{layout::BackupLayout, TitleFinder, TitleQuery},
+    scan::{layout::BackupLayout, BackupId, TitleFinder, TitleQuery},
 };
 
 /// The full input to the `api` command.
@@ -47,6 +47,8 @@ pub enum Output {
 pub enum Request {
     FindTitle(request::FindTitle),
     CheckAppUpdate(request::CheckAppUpdate),
+    CompareBackups(request::CompareBackups),
+    FileHistory(request::FileHistory),
 }
 
 /// A response to an individual request.
@@ -56,6 +58,8 @@ pub enum Response {
     Error(response::Error),
     FindTitle(response::FindTitle),
     CheckAppUpdate(response::CheckAppUpdate),
+    CompareBackups(response::CompareBackups),
+    FileHistory(response::FileHistory),
 }
 
 pub mod request {
@@ -98,10 +102,36 @@ pub mod request {
     #[derive(Debug, Default, Clone, PartialEq, Eq, serde::Serialize, serde::Deserialize, schemars::JsonSchema)]
     #[serde(default, rename_all = "camelCase")]
     pub struct CheckAppUpdate {}
+
+    /// Compare the files in two backups of a game.
+    #[derive(Debug, Default, Clone, PartialEq, Eq, serde::Serialize, serde::Deserialize, schemars::JsonSchema)]
+    #[serde(default, rename_all = "camelCase")]
+    pub struct CompareBackups {
+        /// Title of the game.
+        pub game: String,
+        /// ID of the older backup.
+        /// When unset, this defaults to the backup just before `to`.
+        pub from: Option<String>,
+        /// ID of the newer backup.
+        /// When unset, this defaults to the latest backup.
+        pub to: Option<String>,
+    }
+
+    /// List each backup where a file was added, changed, or removed.
+    #[derive(Debug, Default, Clone, PartialEq, Eq, serde::Serialize, serde::Deserialize, schemars::JsonSchema)]
+    #[serde(default, rename_all = "camelCase")]
+    pub struct FileHistory {
+        /// Title of the game.
+        pub game: String,
+        /// Original path of the file.
+        pub file: String,
+    }
 }
 
 pub mod response {
-    use std::collections::BTreeSet;
+    use std::collections::{BTreeMap, BTreeSet};
+
+    use crate::scan::layout::{BackupReference, FileComparison, FileHistoryEntry};
 
     #[derive(Debug, Default, Clone, PartialEq, Eq, serde::Serialize, serde::Deserialize, schemars::JsonSchema)]
     #[serde(default, rename_all = "camelCase")]
@@ -132,6 +162,25 @@ pub mod response {
         /// Release URL to open in browser.
         pub url: String,
     }
+
+    #[derive(Debug, Default, Clone, PartialEq, Eq, serde::Serialize, serde::Deserialize, schemars::JsonSchema)]
+    #[serde(default, rename_all = "camelCase")]
+    pub struct CompareBackups {
+        /// The older backup, if there is one.
+        pub from: Option<BackupReference>,
+        /// The newer backup.
+        pub to: BackupReference,
+        /// Each key is a file path.
+        /// Files that are the same in both backups are omitted.
+        pub files: BTreeMap<String, FileComparison>,
+    }
+
+    #[derive(Debug, Default, Clone, PartialEq, Eq, serde::Serialize, serde::Deserialize, schemars::JsonSchema)]
+    #[serde(default, rename_all = "camelCase")]
+    pub struct FileHistory {
+        /// Each backup where the file was added, changed, or removed, from oldest to newest.
+        pub history: Vec<FileHistoryEntry>,
+    }
 }
 
 fn parse_input(input: Option<String>) -> Result<Input, String> {
@@ -154,6 +203,12 @@ fn parse_input(input: Option<String>) -> Result<Input, String> {
     }
 }
 
+fn error_response(error: Error) -> Response {
+    Response::Error(response::Error {
+        message: TRANSLATOR.handle_error(&error),
+    })
+}
+
 pub fn abort_error(error: Error) -> ! {
     let output = Output::Failure {
         error: response::Error {
@@ -222,6 +277,41 @@ pub fn process(input: Option<String>, config: &Config, manifest: &Manifest) -> R
                     responses.push(Response::Error(response::Error { message: e.to_string() }));
                 }
             },
+            Request::CompareBackups(request::CompareBackups { game, from, to }) => {
+                let Some(name) = title_finder.find_one_by_name(&game) else {
+                    responses.push(error_response(Error::CliUnrecognizedGames { games: vec![game] }));
+                    continue;
+                };
+                let layout = layout.game_layout(&name);
+
+                let to = to.map(BackupId::Named).unwrap_or(BackupId::Latest);
+                let from = match from {
+                    Some(from) => Some(BackupId::Named(from)),
+                    None => layout.previous_backup_id(&to),
+                };
+                match layout.compare_backups(from.as_ref(), &to) {
+                    Some(comparison) => {
+                        responses.push(Response::CompareBackups(response::CompareBackups {
+                            from: comparison.from,
+                            to: comparison.to,
+                            files: comparison.files,
+                        }));
+                    }
+                    None => {
+                        responses.push(error_response(Error::CliInvalidBackupId));
+                    }
+                }
+            }
+            Request::FileHistory(request::FileHistory { game, file }) => {
+                let Some(name) = title_finder.find_one_by_name(&game) else {
+                    responses.push(error_response(Error::CliUnrecognizedGames { games: vec![game] }));
+                    continue;
+                };
+                let layout = layout.game_layout(&name);
+
+                let history = layout.file_history(&file);
+                responses.push(Response::FileHistory(response::FileHistory { history }));
+            }
         }
     }
 
@@ -3,7 +3,7 @@ use std::path::PathBuf;
 use crate::{
     cloud::WebDavProvider,
     prelude::StrictPath,
-    resource::config::{BackupFormat, Sort, SortKey, ZipCompression},
+    resource::config::{BackupFormat, ConflictPolicy, Sort, SortKey, ZipCompression},
 };
 
 use clap::{ArgGroup, Args, ValueEnum};
@@ -174,7 +174,7 @@ pub enum Subcommand {
         #[clap(long, value_parser = possible_values!(ZipCompression, ALL_NAMES))]
         compression: Option<ZipCompression>,
 
-        /// Compression level to use for new zip backups.
+        /// Compression level to use for new zip or tar.zst backups.
         /// When not specified, this defers to the config file.
         /// Valid ranges: 1 to 9 for deflate/bzip2, -7 to 22 for zstd.
         #[clap(long, allow_hyphen_values(true))]
@@ -192,6 +192,47 @@ pub enum Subcommand {
         #[clap(long)]
         differential_limit: Option<u8>,
 
+        /// Also keep every backup from this many recent hours,
+        /// even beyond the full and differential limits.
+        /// Use 0 to disable.
+        /// When not specified, this defers to the config file.
+        #[clap(long)]
+        keep_hours: Option<u16>,
+
+        /// Also keep the newest backup from each of this many recent days (UTC),
+        /// even beyond the full and differential limits.
+        /// Use 0 to disable.
+        /// When not specified, this defers to the config file.
+        #[clap(long)]
+        keep_daily: Option<u16>,
+
+        /// Also keep the newest backup from each of this many recent weeks (UTC, starting on Monday),
+        /// even beyond the full and differential limits.
+        /// Use 0 to disable.
+        /// When not specified, this defers to the config file.
+        #[clap(long)]
+        keep_weekly: Option<u16>,
+
+        /// Also keep the newest backup from each of this many recent months (UTC),
+        /// even beyond the full and differential limits.
+        /// Use 0 to disable.
+        /// When not specified, this defers to the config file.
+        #[clap(long)]
+        keep_monthly: Option<u16>,
+
+        /// Encrypt new backups with a passphrase.
+        /// This only applies to the simple and zip formats.
+        /// The passphrase is read from the config file or the `LUDUSAVI_PASSPHRASE` environment variable,
+        /// or else you will be prompted for it.
+        /// When not specified, this defers to the config file.
+        #[clap(long)]
+        encrypt: bool,
+
+        /// Don't encrypt new backups.
+        /// When not specified, this defers to the config file.
+        #[clap(long, conflicts_with("encrypt"))]
+        no_encrypt: bool,
+
         /// Upload any changes to the cloud when the backup is complete.
         /// If the local and cloud backups are not in sync to begin with,
         /// then nothing will be uploaded.
@@ -205,6 +246,11 @@ pub enum Subcommand {
         #[clap(long, conflicts_with("cloud_sync"))]
         no_cloud_sync: bool,
 
+        /// Hash every file again instead of reusing hashes from previous scans.
+        /// Normally, a file's hash is reused if its size and modification time haven't changed.
+        #[clap(long)]
+        rehash: bool,
+
         /// Only back up these specific games.
         /// Alternatively supports stdin (one value per line).
         #[clap()]
@@ -252,6 +298,41 @@ pub enum Subcommand {
         #[clap(long, conflicts_with("cloud_sync"))]
         no_cloud_sync: bool,
 
+        /// Restore into this directory instead of the original save locations.
+        /// Each game gets its own subfolder, which mirrors the simple backup structure.
+        /// Registry data is not restored in this mode.
+        #[clap(long, value_parser = parse_strict_path)]
+        destination: Option<StrictPath>,
+
+        /// Only restore files whose path matches this glob,
+        /// using the paths shown by `--preview`.
+        /// A glob without a slash only needs to match the file name.
+        /// This may be specified multiple times.
+        /// Registry data is not restored in this mode.
+        #[clap(long = "file", value_name = "GLOB")]
+        files: Vec<String>,
+
+        /// Before overwriting any save files, back them up as a locked backup.
+        /// When not specified, this defers to the config file.
+        #[clap(long)]
+        snapshot: bool,
+
+        /// Don't back up save files before overwriting them.
+        /// When not specified, this defers to the config file.
+        #[clap(long, conflicts_with("snapshot"))]
+        no_snapshot: bool,
+
+        /// What to do with local files that were changed after the backup was made.
+        /// When not specified, this defers to the config file.
+        /// With `--force`, the `prompt` policy skips conflicting files.
+        #[clap(long, value_parser = possible_values!(ConflictPolicy, ALL_NAMES))]
+        on_conflict: Option<ConflictPolicy>,
+
+        /// Undo the last restore by restoring the snapshots made right before it.
+        /// When games are specified, only those games are affected.
+        #[clap(long, conflicts_with_all(["backup", "destination", "files", "snapshot"]))]
+        undo: bool,
+
         /// Only restore these specific games.
         /// Alternatively supports stdin (one value per line).
         #[clap()]
@@ -263,7 +344,11 @@ pub enum Subcommand {
         shell: CompletionShell,
     },
     /// Show backups
+    #[clap(args_conflicts_with_subcommands = true)]
     Backups {
+        #[clap(subcommand)]
+        sub: Option<BackupsSubcommand>,
+
         /// Directory in which to find backups.
         /// When unset, this defaults to the restore path from the config file.
         #[clap(long, value_parser = parse_strict_path)]
@@ -371,6 +456,32 @@ pub enum Subcommand {
         #[clap(required = true)]
         commands: Vec<String>,
     },
+    /// Watch save locations and back up games when their saves change.
+    ///
+    /// This scans for the save files of each game that is enabled for backup,
+    /// then watches the folders that contain them.
+    /// When a game's saves change, Ludusavi waits for them to stop changing
+    /// and then backs up that game.
+    /// After each backup, a line of JSON is printed to stdout describing the result.
+    ///
+    /// This runs until interrupted (e.g., with Ctrl+C),
+    /// at which point any pending changes are backed up before exiting.
+    Watch {
+        /// Directory in which to store the backups.
+        /// When unset, this defaults to the value from the config file.
+        #[clap(long, value_parser = parse_strict_path)]
+        path: Option<StrictPath>,
+
+        /// How many seconds to wait after the last change to a game's saves
+        /// before backing it up.
+        #[clap(long, default_value_t = 10)]
+        delay: u64,
+
+        /// Only watch these specific games.
+        /// Alternatively supports stdin (one value per line).
+        #[clap()]
+        games: Vec<String>,
+    },
     /// Execute bulk requests using JSON input.
     ///
     /// If there is a problem with the entire input
@@ -400,6 +511,224 @@ pub enum Subcommand {
     },
 }
 
+#[derive(clap::Subcommand, Clone, Debug, PartialEq, Eq)]
+pub enum BackupsSubcommand {
+    /// Check that backups are intact.
+    /// This checks every backup of each game, and exits with an error if any are invalid.
+    Validate {
+        /// Directory in which to find backups.
+        /// When unset, this defaults to the restore path from the config file.
+        #[clap(long, value_parser = parse_strict_path)]
+        path: Option<StrictPath>,
+
+        /// Print information to stdout in machine-readable JSON.
+        /// This replaces the default, human-readable output.
+        #[clap(long)]
+        api: bool,
+
+        /// Also re-hash the content of each stored file
+        /// and compare it against the hash recorded when it was backed up.
+        /// This is slower, but it can detect silent corruption.
+        #[clap(long)]
+        deep: bool,
+
+        /// Only validate these specific games.
+        /// Alternatively supports stdin (one value per line).
+        #[clap()]
+        games: Vec<String>,
+    },
+    /// Rewrite existing backups in a different format.
+    /// This can also recompress zip and tar.zst backups with different settings.
+    /// Each converted backup is validated before it replaces the original.
+    Convert {
+        /// Directory in which to find backups.
+        /// When unset, this defaults to the restore path from the config file.
+        #[clap(long, value_parser = parse_strict_path)]
+        path: Option<StrictPath>,
+
+        /// Print information to stdout in machine-readable JSON.
+        /// This replaces the default, human-readable output.
+        #[clap(long)]
+        api: bool,
+
+        /// Convert a specific backup, using an ID returned by the `backups` command.
+        /// This is only valid when converting a single game.
+        /// When unset, this converts all backups.
+        #[clap(long)]
+        backup: Option<String>,
+
+        /// Format to convert the backups into.
+        /// When not specified, this defers to the config file.
+        #[clap(long, value_parser = possible_values!(BackupFormat, ALL_NAMES))]
+        format: Option<BackupFormat>,
+
+        /// Compression method to use for zip backups.
+        /// When not specified, this defers to the config file.
+        #[clap(long, value_parser = possible_values!(ZipCompression, ALL_NAMES))]
+        compression: Option<ZipCompression>,
+
+        /// Compression level to use for zip or tar.zst backups.
+        /// When not specified, this defers to the config file.
+        /// Valid ranges: 1 to 9 for deflate/bzip2, -7 to 22 for zstd.
+        #[clap(long, allow_hyphen_values(true))]
+        compression_level: Option<i32>,
+
+        /// Encrypt the converted backups (simple and zip formats only).
+        /// When not specified, this defers to the config file.
+        #[clap(long)]
+        encrypt: bool,
+
+        /// Don't encrypt the converted backups.
+        /// When not specified, this defers to the config file.
+        #[clap(long, conflicts_with("encrypt"))]
+        no_encrypt: bool,
+
+        /// Only convert backups for these specific games.
+        /// Alternatively supports stdin (one value per line).
+        #[clap()]
+        games: Vec<String>,
+    },
+    /// Compare the files in two backups of a game.
+    /// This lists each file that was added, changed, or removed.
+    Diff {
+        /// Directory in which to find backups.
+        /// When unset, this defaults to the restore path from the config file.
+        #[clap(long, value_parser = parse_strict_path)]
+        path: Option<StrictPath>,
+
+        /// Print information to stdout in machine-readable JSON.
+        /// This replaces the default, human-readable output.
+        #[clap(long)]
+        api: bool,
+
+        /// The older backup, using an ID returned by the `backups` command.
+        /// When unset, this defaults to the backup just before `--to`.
+        #[clap(long)]
+        from: Option<String>,
+
+        /// The newer backup, using an ID returned by the `backups` command.
+        /// When unset, this defaults to the latest backup.
+        #[clap(long)]
+        to: Option<String>,
+
+        /// Game whose backups to compare.
+        #[clap()]
+        game: String,
+    },
+    /// List each backup where a file was added, changed, or removed.
+    History {
+        /// Directory in which to find backups.
+        /// When unset, this defaults to the restore path from the config file.
+        #[clap(long, value_parser = parse_strict_path)]
+        path: Option<StrictPath>,
+
+        /// Print information to stdout in machine-readable JSON.
+        /// This replaces the default, human-readable output.
+        #[clap(long)]
+        api: bool,
+
+        /// Game whose backups to check.
+        #[clap()]
+        game: String,
+
+        /// Original path of the file, as shown by the `restore --preview` command.
+        #[clap(value_parser = parse_strict_path)]
+        file: StrictPath,
+    },
+    /// Turn a differential backup into a new full backup.
+    /// This uses the content already in the backup, without rescanning the save files,
+    /// and then applies the retention settings from the config file to the old backups.
+    /// Only the simple and zip formats are supported.
+    Consolidate {
+        /// Directory in which to find backups.
+        /// When unset, this defaults to the restore path from the config file.
+        #[clap(long, value_parser = parse_strict_path)]
+        path: Option<StrictPath>,
+
+        /// Print information to stdout in machine-readable JSON.
+        /// This replaces the default, human-readable output.
+        #[clap(long)]
+        api: bool,
+
+        /// Consolidate a specific backup, using an ID returned by the `backups` command.
+        /// This is only valid when consolidating a single game.
+        /// When unset, this defaults to the latest backup.
+        #[clap(long)]
+        backup: Option<String>,
+
+        /// Only consolidate these specific games.
+        /// Alternatively supports stdin (one value per line).
+        #[clap()]
+        games: Vec<String>,
+    },
+    /// Find and remove files in the backup folder that no backup refers to.
+    /// This includes stray files and archives in each game's folder
+    /// and deduplicated blobs that are no longer used.
+    /// Folders with a `mapping.yaml` that can't be read are also listed,
+    /// but they are only removed with `--include-invalid-mappings`.
+    Gc {
+        /// Directory in which to find backups.
+        /// When unset, this defaults to the restore path from the config file.
+        #[clap(long, value_parser = parse_strict_path)]
+        path: Option<StrictPath>,
+
+        /// List what would be removed, but don't actually remove anything.
+        #[clap(long)]
+        preview: bool,
+
+        /// Also remove folders with a `mapping.yaml` that can't be read.
+        /// These may still contain backups if the mapping was only damaged
+        /// or was written by a newer version of Ludusavi,
+        /// so they are left alone by default.
+        #[clap(long)]
+        include_invalid_mappings: bool,
+
+        /// Don't ask for confirmation.
+        #[clap(long)]
+        force: bool,
+
+        /// Print information to stdout in machine-readable JSON.
+        /// This replaces the default, human-readable output.
+        #[clap(long)]
+        api: bool,
+    },
+    /// Show how much space each game's backups use and how it has grown over time.
+    /// Games are listed from largest to smallest, followed by the totals.
+    Stats {
+        /// Directory in which to find backups.
+        /// When unset, this defaults to the restore path from the config file.
+        #[clap(long, value_parser = parse_strict_path)]
+        path: Option<StrictPath>,
+
+        /// Print information to stdout in machine-readable JSON.
+        /// This replaces the default, human-readable output.
+        #[clap(long)]
+        api: bool,
+
+        /// Only include these specific games.
+        /// Alternatively supports stdin (one value per line).
+        #[clap()]
+        games: Vec<String>,
+    },
+    /// Move backups for games that were renamed in the primary manifest.
+    /// Renames are detected by Ludusavi when it updates the manifest,
+    /// and each game's backups are moved to the folder for its new name.
+    MigrateRenames {
+        /// Directory in which to find backups.
+        /// When unset, this defaults to the restore path from the config file.
+        #[clap(long, value_parser = parse_strict_path)]
+        path: Option<StrictPath>,
+
+        /// List what would be moved, but don't actually move anything.
+        #[clap(long)]
+        preview: bool,
+
+        /// Don't ask for confirmation.
+        #[clap(long)]
+        force: bool,
+    },
+}
+
 #[derive(clap::Subcommand, Clone, Debug, PartialEq, Eq)]
 pub enum ManifestSubcommand {
     /// Print the content of the manifest, including any custom entries.
@@ -652,8 +981,15 @@ mod tests {
                     compression_level: None,
                     full_limit: None,
                     differential_limit: None,
+                    keep_hours: None,
+                    keep_daily: None,
+                    keep_weekly: None,
+                    keep_monthly: None,
+                    encrypt: false,
+                    no_encrypt: false,
                     cloud_sync: false,
                     no_cloud_sync: false,
+                    rehash: false,
                     games: vec![],
                 }),
             },
@@ -685,7 +1021,17 @@ mod tests {
                 "1",
                 "--differential-limit",
                 "2",
+                "--keep-hours",
+                "24",
+                "--keep-daily",
+                "7",
+                "--keep-weekly",
+                "4",
+                "--keep-monthly",
+                "12",
+                "--encrypt",
                 "--cloud-sync",
+                "--rehash",
                 "game1",
                 "game2",
             ],
@@ -705,8 +1051,15 @@ mod tests {
                     compression_level: Some(5),
                     full_limit: Some(1),
                     differential_limit: Some(2),
+                    keep_hours: Some(24),
+                    keep_daily: Some(7),
+                    keep_weekly: Some(4),
+                    keep_monthly: Some(12),
+                    encrypt: true,
+                    no_encrypt: false,
                     cloud_sync: true,
                     no_cloud_sync: false,
+                    rehash: true,
                     games: vec![s("game1"), s("game2")],
                 }),
             },
@@ -733,8 +1086,15 @@ mod tests {
                     compression_level: None,
                     full_limit: None,
                     differential_limit: None,
+                    keep_hours: None,
+                    keep_daily: None,
+                    keep_weekly: None,
+                    keep_monthly: None,
+                    encrypt: false,
+                    no_encrypt: false,
                     cloud_sync: false,
                     no_cloud_sync: false,
+                    rehash: false,
                     games: vec![],
                 }),
             },
@@ -769,8 +1129,15 @@ mod tests {
                         compression_level: None,
                         full_limit: None,
                         differential_limit: None,
+                        keep_hours: None,
+                        keep_daily: None,
+                        keep_weekly: None,
+                        keep_monthly: None,
+                        encrypt: false,
+                        no_encrypt: false,
                         cloud_sync: false,
                         no_cloud_sync: false,
+                        rehash: false,
                         games: vec![],
                     }),
                 },
@@ -798,8 +1165,15 @@ mod tests {
                     compression_level: Some(-7),
                     full_limit: None,
                     differential_limit: None,
+                    keep_hours: None,
+                    keep_daily: None,
+                    keep_weekly: None,
+                    keep_monthly: None,
+                    encrypt: false,
+                    no_encrypt: false,
                     cloud_sync: false,
                     no_cloud_sync: false,
+                    rehash: false,
                     games: vec![],
                 }),
             },
@@ -823,6 +1197,12 @@ mod tests {
                     backup: None,
                     cloud_sync: false,
                     no_cloud_sync: false,
+                    destination: None,
+                    files: vec![],
+                    snapshot: false,
+                    no_snapshot: false,
+                    on_conflict: None,
+                    undo: false,
                     games: vec![],
                 }),
             },
@@ -845,6 +1225,15 @@ mod tests {
                 "--backup",
                 ".",
                 "--cloud-sync",
+                "--destination",
+                "tests/extract",
+                "--file",
+                "*.sav",
+                "--file",
+                "/saves/**",
+                "--snapshot",
+                "--on-conflict",
+                "keep-both",
                 "game1",
                 "game2",
             ],
@@ -864,6 +1253,15 @@ mod tests {
                     backup: Some(s(".")),
                     cloud_sync: true,
                     no_cloud_sync: false,
+                    destination: Some(StrictPath::relative(
+                        s("tests/extract"),
+                        Some(StrictPath::cwd().interpret().unwrap()),
+                    )),
+                    files: vec![s("*.sav"), s("/saves/**")],
+                    snapshot: true,
+                    no_snapshot: false,
+                    on_conflict: Some(ConflictPolicy::KeepBoth),
+                    undo: false,
                     games: vec![s("game1"), s("game2")],
                 }),
             },
@@ -878,6 +1276,14 @@ mod tests {
         );
     }
 
+    #[test]
+    fn rejects_cli_restore_undo_with_backup() {
+        check_args_err(
+            &["ludusavi", "restore", "--undo", "--backup", ".", "game1"],
+            clap::error::ErrorKind::ArgumentConflict,
+        );
+    }
+
     #[test]
     fn accepts_cli_restore_with_sort_variants() {
         let cases = [
@@ -903,6 +1309,12 @@ mod tests {
                         backup: None,
                         cloud_sync: false,
                         no_cloud_sync: false,
+                        destination: None,
+                        files: vec![],
+                        snapshot: false,
+                        no_snapshot: false,
+                        on_conflict: None,
+                        undo: false,
                         games: vec![],
                     }),
                 },
@@ -994,6 +1406,7 @@ mod tests {
                 no_manifest_update: false,
                 try_manifest_update: false,
                 sub: Some(Subcommand::Backups {
+                    sub: None,
                     path: None,
                     api: false,
                     games: vec![],
@@ -1019,6 +1432,7 @@ mod tests {
                 no_manifest_update: false,
                 try_manifest_update: false,
                 sub: Some(Subcommand::Backups {
+                    sub: None,
                     path: Some(StrictPath::relative(s("tests/backup"), Some(repo_raw()))),
                     api: true,
                     games: vec![s("game1"), s("game2")],
@@ -1027,6 +1441,445 @@ mod tests {
         );
     }
 
+    #[test]
+    fn accepts_cli_backups_convert_with_minimal_arguments() {
+        check_args(
+            &["ludusavi", "backups", "convert"],
+            Cli {
+                config: None,
+                no_manifest_update: false,
+                try_manifest_update: false,
+                sub: Some(Subcommand::Backups {
+                    sub: Some(BackupsSubcommand::Convert {
+                        path: None,
+                        api: false,
+                        backup: None,
+                        format: None,
+                        compression: None,
+                        compression_level: None,
+                        encrypt: false,
+                        no_encrypt: false,
+                        games: vec![],
+                    }),
+                    path: None,
+                    api: false,
+                    games: vec![],
+                }),
+            },
+        );
+    }
+
+    #[test]
+    fn accepts_cli_backups_convert_with_all_arguments() {
+        check_args(
+            &[
+                "ludusavi",
+                "backups",
+                "convert",
+                "--path",
+                "tests/backup",
+                "--api",
+                "--backup",
+                ".",
+                "--format",
+                "zip",
+                "--compression",
+                "bzip2",
+                "--compression-level",
+                "5",
+                "--no-encrypt",
+                "game1",
+            ],
+            Cli {
+                config: None,
+                no_manifest_update: false,
+                try_manifest_update: false,
+                sub: Some(Subcommand::Backups {
+                    sub: Some(BackupsSubcommand::Convert {
+                        path: Some(StrictPath::relative(s("tests/backup"), Some(repo_raw()))),
+                        api: true,
+                        backup: Some(s(".")),
+                        format: Some(BackupFormat::Zip),
+                        compression: Some(ZipCompression::Bzip2),
+                        compression_level: Some(5),
+                        encrypt: false,
+                        no_encrypt: true,
+                        games: vec![s("game1")],
+                    }),
+                    path: None,
+                    api: false,
+                    games: vec![],
+                }),
+            },
+        );
+    }
+
+    #[test]
+    fn accepts_cli_backups_consolidate_with_minimal_arguments() {
+        check_args(
+            &["ludusavi", "backups", "consolidate"],
+            Cli {
+                config: None,
+                no_manifest_update: false,
+                try_manifest_update: false,
+                sub: Some(Subcommand::Backups {
+                    sub: Some(BackupsSubcommand::Consolidate {
+                        path: None,
+                        api: false,
+                        backup: None,
+                        games: vec![],
+                    }),
+                    path: None,
+                    api: false,
+                    games: vec![],
+                }),
+            },
+        );
+    }
+
+    #[test]
+    fn accepts_cli_backups_consolidate_with_all_arguments() {
+        check_args(
+            &[
+                "ludusavi",
+                "backups",
+                "consolidate",
+                "--path",
+                "tests/backup",
+                "--api",
+                "--backup",
+                ".",
+                "game1",
+            ],
+            Cli {
+                config: None,
+                no_manifest_update: false,
+                try_manifest_update: false,
+                sub: Some(Subcommand::Backups {
+                    sub: Some(BackupsSubcommand::Consolidate {
+                        path: Some(StrictPath::relative(s("tests/backup"), Some(repo_raw()))),
+                        api: true,
+                        backup: Some(s(".")),
+                        games: vec![s("game1")],
+                    }),
+                    path: None,
+                    api: false,
+                    games: vec![],
+                }),
+            },
+        );
+    }
+
+    #[test]
+    fn accepts_cli_backups_diff_with_minimal_arguments() {
+        check_args(
+            &["ludusavi", "backups", "diff", "game1"],
+            Cli {
+                config: None,
+                no_manifest_update: false,
+                try_manifest_update: false,
+                sub: Some(Subcommand::Backups {
+                    sub: Some(BackupsSubcommand::Diff {
+                        path: None,
+                        api: false,
+                        from: None,
+                        to: None,
+                        game: s("game1"),
+                    }),
+                    path: None,
+                    api: false,
+                    games: vec![],
+                }),
+            },
+        );
+    }
+
+    #[test]
+    fn accepts_cli_backups_diff_with_all_arguments() {
+        check_args(
+            &[
+                "ludusavi",
+                "backups",
+                "diff",
+                "--path",
+                "tests/backup",
+                "--api",
+                "--from",
+                "backup-1",
+                "--to",
+                "backup-2",
+                "game1",
+            ],
+            Cli {
+                config: None,
+                no_manifest_update: false,
+                try_manifest_update: false,
+                sub: Some(Subcommand::Backups {
+                    sub: Some(BackupsSubcommand::Diff {
+                        path: Some(StrictPath::relative(s("tests/backup"), Some(repo_raw()))),
+                        api: true,
+                        from: Some(s("backup-1")),
+                        to: Some(s("backup-2")),
+                        game: s("game1"),
+                    }),
+                    path: None,
+                    api: false,
+                    games: vec![],
+                }),
+            },
+        );
+    }
+
+    #[test]
+    fn accepts_cli_backups_history_with_all_arguments() {
+        check_args(
+            &[
+                "ludusavi",
+                "backups",
+                "history",
+                "--path",
+                "tests/backup",
+                "--api",
+                "game1",
+                "/file1.txt",
+            ],
+            Cli {
+                config: None,
+                no_manifest_update: false,
+                try_manifest_update: false,
+                sub: Some(Subcommand::Backups {
+                    sub: Some(BackupsSubcommand::History {
+                        path: Some(StrictPath::relative(s("tests/backup"), Some(repo_raw()))),
+                        api: true,
+                        game: s("game1"),
+                        file: StrictPath::relative(s("/file1.txt"), Some(repo_raw())),
+                    }),
+                    path: None,
+                    api: false,
+                    games: vec![],
+                }),
+            },
+        );
+    }
+
+    #[test]
+    fn accepts_cli_backups_validate_with_minimal_arguments() {
+        check_args(
+            &["ludusavi", "backups", "validate"],
+            Cli {
+                config: None,
+                no_manifest_update: false,
+                try_manifest_update: false,
+                sub: Some(Subcommand::Backups {
+                    sub: Some(BackupsSubcommand::Validate {
+                        path: None,
+                        api: false,
+                        deep: false,
+                        games: vec![],
+                    }),
+                    path: None,
+                    api: false,
+                    games: vec![],
+                }),
+            },
+        );
+    }
+
+    #[test]
+    fn accepts_cli_backups_validate_with_all_arguments() {
+        check_args(
+            &[
+                "ludusavi",
+                "backups",
+                "validate",
+                "--path",
+                "tests/backup",
+                "--api",
+                "--deep",
+                "game1",
+                "game2",
+            ],
+            Cli {
+                config: None,
+                no_manifest_update: false,
+                try_manifest_update: false,
+                sub: Some(Subcommand::Backups {
+                    sub: Some(BackupsSubcommand::Validate {
+                        path: Some(StrictPath::relative(s("tests/backup"), Some(repo_raw()))),
+                        api: true,
+                        deep: true,
+                        games: vec![s("game1"), s("game2")],
+                    }),
+                    path: None,
+                    api: false,
+                    games: vec![],
+                }),
+            },
+        );
+    }
+
+    #[test]
+    fn accepts_cli_backups_gc_with_minimal_arguments() {
+        check_args(
+            &["ludusavi", "backups", "gc"],
+            Cli {
+                config: None,
+                no_manifest_update: false,
+                try_manifest_update: false,
+                sub: Some(Subcommand::Backups {
+                    sub: Some(BackupsSubcommand::Gc {
+                        path: None,
+                        preview: false,
+                        include_invalid_mappings: false,
+                        force: false,
+                        api: false,
+                    }),
+                    path: None,
+                    api: false,
+                    games: vec![],
+                }),
+            },
+        );
+    }
+
+    #[test]
+    fn accepts_cli_backups_gc_with_all_arguments() {
+        check_args(
+            &[
+                "ludusavi",
+                "backups",
+                "gc",
+                "--path",
+                "tests/backup",
+                "--preview",
+                "--include-invalid-mappings",
+                "--force",
+                "--api",
+            ],
+            Cli {
+                config: None,
+                no_manifest_update: false,
+                try_manifest_update: false,
+                sub: Some(Subcommand::Backups {
+                    sub: Some(BackupsSubcommand::Gc {
+                        path: Some(StrictPath::relative(s("tests/backup"), Some(repo_raw()))),
+                        preview: true,
+                        include_invalid_mappings: true,
+                        force: true,
+                        api: true,
+                    }),
+                    path: None,
+                    api: false,
+                    games: vec![],
+                }),
+            },
+        );
+    }
+
+    #[test]
+    fn accepts_cli_backups_stats_with_all_arguments() {
+        check_args(
+            &[
+                "ludusavi",
+                "backups",
+                "stats",
+                "--path",
+                "tests/backup",
+                "--api",
+                "game1",
+                "game2",
+            ],
+            Cli {
+                config: None,
+                no_manifest_update: false,
+                try_manifest_update: false,
+                sub: Some(Subcommand::Backups {
+                    sub: Some(BackupsSubcommand::Stats {
+                        path: Some(StrictPath::relative(s("tests/backup"), Some(repo_raw()))),
+                        api: true,
+                        games: vec![s("game1"), s("game2")],
+                    }),
+                    path: None,
+                    api: false,
+                    games: vec![],
+                }),
+            },
+        );
+    }
+
+    #[test]
+    fn accepts_cli_backups_migrate_renames_with_all_arguments() {
+        check_args(
+            &[
+                "ludusavi",
+                "backups",
+                "migrate-renames",
+                "--path",
+                "tests/backup",
+                "--preview",
+                "--force",
+            ],
+            Cli {
+                config: None,
+                no_manifest_update: false,
+                try_manifest_update: false,
+                sub: Some(Subcommand::Backups {
+                    sub: Some(BackupsSubcommand::MigrateRenames {
+                        path: Some(StrictPath::relative(s("tests/backup"), Some(repo_raw()))),
+                        preview: true,
+                        force: true,
+                    }),
+                    path: None,
+                    api: false,
+                    games: vec![],
+                }),
+            },
+        );
+    }
+
+    #[test]
+    fn accepts_cli_watch_with_minimal_arguments() {
+        check_args(
+            &["ludusavi", "watch"],
+            Cli {
+                config: None,
+                no_manifest_update: false,
+                try_manifest_update: false,
+                sub: Some(Subcommand::Watch {
+                    path: None,
+                    delay: 10,
+                    games: vec![],
+                }),
+            },
+        );
+    }
+
+    #[test]
+    fn accepts_cli_watch_with_all_arguments() {
+        check_args(
+            &[
+                "ludusavi",
+                "watch",
+                "--path",
+                "tests/backup",
+                "--delay",
+                "30",
+                "game1",
+                "game2",
+            ],
+            Cli {
+                config: None,
+                no_manifest_update: false,
+                try_manifest_update: false,
+                sub: Some(Subcommand::Watch {
+                    path: Some(StrictPath::relative(s("tests/backup"), Some(repo_raw()))),
+                    delay: 30,
+                    games: vec![s("game1"), s("game2")],
+                }),
+            },
+        );
+    }
+
     #[test]
     fn accepts_cli_find_with_minimal_arguments() {
         check_args(
@@ -8,8 +8,11 @@ use crate::{
     prelude::StrictPath,
     resource::manifest::Os,
     scan::{
-        layout::Backup, BackupError, BackupInfo, DuplicateDetector, OperationStatus, OperationStepDecision, ScanChange,
-        ScanInfo,
+        layout::{
+            Backup, BackupComparison, BackupReference, BackupStats, FileComparison, FileHistoryEntry, OrphanKind,
+            OrphanedItem, ValidationIssue,
+        },
+        BackupError, BackupInfo, DuplicateDetector, OperationStatus, OperationStepDecision, ScanChange, ScanInfo,
     },
 };
 
@@ -98,6 +101,13 @@ struct ApiFile {
     /// Any other games that also have the same file path.
     #[serde(skip_serializing_if = "BTreeSet::is_empty")]
     duplicated_by: BTreeSet<String>,
+    /// When restoring, whether the local file is newer than the backup
+    /// or doesn't match any version in the backups.
+    #[serde(skip_serializing_if = "std::ops::Not::not")]
+    conflict: bool,
+    /// When backing up, the Steam account ID (SteamID3) that the file belongs to.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    steam_account: Option<u32>,
 }
 
 #[derive(Debug, Default, serde::Serialize, schemars::JsonSchema)]
@@ -157,6 +167,40 @@ enum ApiGame {
         backup_path: String,
         backups: Vec<ApiBackup>,
     },
+    /// Used by the `backups validate` command.
+    Validated {
+        #[serde(rename = "backupPath")]
+        backup_path: String,
+        /// Any problems found in the game's backups.
+        issues: Vec<ValidationIssue>,
+    },
+    /// Used by the `backups diff` command.
+    Compared {
+        #[serde(rename = "backupPath")]
+        backup_path: String,
+        /// The older backup, if there is one.
+        #[serde(skip_serializing_if = "Option::is_none")]
+        from: Option<BackupReference>,
+        /// The newer backup.
+        to: BackupReference,
+        /// Each key is a file path.
+        /// Files that are the same in both backups are omitted.
+        files: BTreeMap<String, FileComparison>,
+    },
+    /// Used by the `backups history` command.
+    History {
+        #[serde(rename = "backupPath")]
+        backup_path: String,
+        file: String,
+        /// Each backup where the file was added, changed, or removed, from oldest to newest.
+        history: Vec<FileHistoryEntry>,
+    },
+    /// Used by the `backups stats` command.
+    Statistics {
+        #[serde(rename = "backupPath")]
+        backup_path: String,
+        stats: BackupStats,
+    },
     /// Used by the `find` command.
     Found {},
 }
@@ -183,12 +227,19 @@ pub struct JsonOutput {
     /// Overall stats, populated by the `backup` and `restore` commands.
     #[serde(skip_serializing_if = "Option::is_none")]
     overall: Option<OperationStatus>,
+    /// Totals across all games, populated by the `backups stats` command.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    stats: Option<BackupStats>,
     /// Each key is the name of a game.
     games: BTreeMap<String, ApiGame>,
     /// Each key is the path of a file relative to the cloud folder.
     /// Populated by the `cloud` commands.
     #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
     cloud: BTreeMap<String, CloudEntry>,
+    /// Each key is the path of an unused file or folder in the backup directory.
+    /// Populated by the `backups gc` command.
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    orphans: BTreeMap<String, ApiOrphan>,
 }
 
 #[derive(Debug, Default, serde::Serialize, schemars::JsonSchema)]
@@ -199,6 +250,22 @@ struct CloudEntry {
     change: ScanChange,
 }
 
+#[derive(Debug, serde::Serialize, schemars::JsonSchema)]
+#[serde(rename_all = "camelCase")]
+struct ApiOrphan {
+    /// Why this item is unused.
+    kind: OrphanKind,
+    /// Size of the file, or the total size of the folder's contents.
+    bytes: u64,
+    /// Whether this item could not be removed.
+    #[serde(skip_serializing_if = "std::ops::Not::not")]
+    failed: bool,
+    /// Whether this item was left in place.
+    /// Folders with a mapping that can't be read are only removed with `--include-invalid-mappings`.
+    #[serde(skip_serializing_if = "std::ops::Not::not")]
+    ignored: bool,
+}
+
 #[derive(Debug)]
 pub enum Reporter {
     Standard {
@@ -225,8 +292,10 @@ impl Reporter {
             output: JsonOutput {
                 errors: Default::default(),
                 overall: Some(Default::default()),
+                stats: None,
                 games: Default::default(),
                 cloud: Default::default(),
+                orphans: Default::default(),
             },
         }
     }
@@ -246,7 +315,7 @@ impl Reporter {
         }
     }
 
-    fn trip_some_games_failed(&mut self) {
+    pub fn trip_some_games_failed(&mut self) {
         self.set_errors(|e| {
             e.some_games_failed = Some(true);
         });
@@ -333,6 +402,10 @@ impl Reporter {
                         }
                     }
 
+                    if entry.conflict {
+                        parts.push(TRANSLATOR.cli_game_line_item_conflict());
+                    }
+
                     if let Some(error) = backup_info.as_ref().and_then(|x| x.failed_files.get(scan_key)) {
                         parts.push(TRANSLATOR.cli_game_line_item_error(error));
                     }
@@ -398,6 +471,8 @@ impl Reporter {
                             .and_then(|x| x.failed_files.get(scan_key).map(SaveError::from)),
                         ignored: entry.ignored,
                         change: entry.change(),
+                        conflict: entry.conflict,
+                        steam_account: entry.steam_account,
                         ..Default::default()
                     };
                     if !duplicate_detector.is_file_duplicated(scan_key, entry).resolved() {
@@ -560,6 +635,177 @@ impl Reporter {
         }
     }
 
+    /// Returns whether the game's backups are valid.
+    pub fn add_validation(
+        &mut self,
+        name: &str,
+        display_title: &str,
+        backup_dir: StrictPath,
+        issues: &[ValidationIssue],
+    ) -> bool {
+        let valid = issues.is_empty();
+
+        match self {
+            Self::Standard { parts, .. } => {
+                if valid {
+                    return true;
+                }
+
+                parts.push(format!("{}:", display_title));
+                parts.push(format!("  {} {}", TRANSLATOR.folder_label(), backup_dir.render()));
+                for issue in issues {
+                    parts.push(format!("  - {}", TRANSLATOR.validation_issue(issue)));
+                }
+
+                // Blank line between games.
+                parts.push("".to_string());
+            }
+            Self::Json { output } => {
+                output.games.insert(
+                    name.to_string(),
+                    ApiGame::Validated {
+                        backup_path: backup_dir.render(),
+                        issues: issues.to_vec(),
+                    },
+                );
+            }
+        }
+
+        if !valid {
+            self.trip_some_games_failed();
+        }
+        valid
+    }
+
+    pub fn add_comparison(
+        &mut self,
+        name: &str,
+        display_title: &str,
+        backup_dir: StrictPath,
+        comparison: BackupComparison,
+    ) {
+        match self {
+            Self::Standard { parts, .. } => {
+                parts.push(format!("{}:", display_title));
+                parts.push(format!("  {} {}", TRANSLATOR.folder_label(), backup_dir.render()));
+                match &comparison.from {
+                    Some(from) => parts.push(format!(
+                        "  {} -> {}",
+                        render_backup_reference(from),
+                        render_backup_reference(&comparison.to)
+                    )),
+                    None => parts.push(format!("  {}", render_backup_reference(&comparison.to))),
+                }
+                for (file, entry) in &comparison.files {
+                    let versions = [&entry.from, &entry.to]
+                        .into_iter()
+                        .flatten()
+                        .map(|x| format!("{} ({})", x.hash, TRANSLATOR.adjusted_size(x.size)))
+                        .join(" -> ");
+                    parts.push(format!("  [{}] {}: {}", entry.change.symbol(), file, versions));
+                }
+
+                // Blank line between games.
+                parts.push("".to_string());
+            }
+            Self::Json { output } => {
+                let BackupComparison { from, to, files } = comparison;
+                output.games.insert(
+                    name.to_string(),
+                    ApiGame::Compared {
+                        backup_path: backup_dir.render(),
+                        from,
+                        to,
+                        files,
+                    },
+                );
+            }
+        }
+    }
+
+    pub fn add_file_history(
+        &mut self,
+        name: &str,
+        display_title: &str,
+        backup_dir: StrictPath,
+        file: &str,
+        history: Vec<FileHistoryEntry>,
+    ) {
+        match self {
+            Self::Standard { parts, .. } => {
+                parts.push(format!("{}:", display_title));
+                parts.push(format!("  {} {}", TRANSLATOR.folder_label(), backup_dir.render()));
+                parts.push(format!("  {file}"));
+                for entry in &history {
+                    let mut line = format!(
+                        "    [{}] {}",
+                        entry.change.symbol(),
+                        render_backup_reference(&BackupReference {
+                            name: entry.backup.clone(),
+                            when: entry.when,
+                        })
+                    );
+                    if let (Some(hash), Some(size)) = (&entry.hash, entry.size) {
+                        line += &format!(": {} ({})", hash, TRANSLATOR.adjusted_size(size));
+                    }
+                    parts.push(line);
+                }
+
+                // Blank line between games.
+                parts.push("".to_string());
+            }
+            Self::Json { output } => {
+                output.games.insert(
+                    name.to_string(),
+                    ApiGame::History {
+                        backup_path: backup_dir.render(),
+                        file: file.to_string(),
+                        history,
+                    },
+                );
+            }
+        }
+    }
+
+    pub fn add_backup_stats(&mut self, name: &str, display_title: &str, backup_dir: StrictPath, stats: BackupStats) {
+        match self {
+            Self::Standard { parts, .. } => {
+                parts.push(format!("{}:", display_title));
+                parts.push(format!("  {} {}", TRANSLATOR.folder_label(), backup_dir.render()));
+                for line in TRANSLATOR.backup_stats(&stats) {
+                    parts.push(format!("  {line}"));
+                }
+
+                // Blank line between games.
+                parts.push("".to_string());
+            }
+            Self::Json { output } => {
+                output.games.insert(
+                    name.to_string(),
+                    ApiGame::Statistics {
+                        backup_path: backup_dir.render(),
+                        stats,
+                    },
+                );
+            }
+        }
+    }
+
+    pub fn add_overall_backup_stats(&mut self, games: usize, stats: BackupStats) {
+        match self {
+            Self::Standard { parts, .. } => {
+                parts.push(format!("{}:", TRANSLATOR.overall()));
+                parts.push(format!("  {}: {}", TRANSLATOR.total_games(), games));
+                for line in TRANSLATOR.backup_stats(&stats) {
+                    parts.push(format!("  {line}"));
+                }
+            }
+            Self::Json { output } => {
+                output.stats = Some(stats);
+            }
+        }
+    }
+
     pub fn add_found_titles(&mut self, names: &BTreeSet<String>) {
         match self {
             Self::Standard { parts, .. } => {
@@ -604,13 +850,23 @@ impl Reporter {
     }
 }
 
+fn render_backup_reference(backup: &BackupReference) -> String {
+    format!(
+        "\"{}\" ({})",
+        backup.name,
+        chrono::DateTime::<chrono::Local>::from(backup.when).format("%Y-%m-%dT%H:%M:%S"),
+    )
+}
+
 pub fn report_cloud_changes(changes: &[CloudChange], api: bool) {
     if api {
         let mut output = JsonOutput {
             errors: None,
             overall: None,
+            stats: None,
             games: Default::default(),
             cloud: Default::default(),
+            orphans: Default::default(),
         };
 
         output.cloud = changes
@@ -630,6 +886,64 @@ pub fn report_cloud_changes(changes: &[CloudChange], api: bool) {
     }
 }
 
+pub fn report_orphans(orphans: &[OrphanedItem], failed: &[StrictPath], ignored: &[StrictPath], api: bool) {
+    print!("{}", render_orphans(orphans, failed, ignored, api));
+}
+
+fn render_orphans(orphans: &[OrphanedItem], failed: &[StrictPath], ignored: &[StrictPath], api: bool) -> String {
+    if api {
+        let output = JsonOutput {
+            errors: None,
+            overall: None,
+            stats: None,
+            games: Default::default(),
+            cloud: Default::default(),
+            orphans: orphans
+                .iter()
+                .map(|x| {
+                    (
+                        x.path.render(),
+                        ApiOrphan {
+                            kind: x.kind,
+                            bytes: x.size,
+                            failed: failed.contains(&x.path),
+                            ignored: ignored.contains(&x.path),
+                        },
+                    )
+                })
+                .collect(),
+        };
+        return format!("{}\n", serde_json::to_string_pretty(&output).unwrap());
+    }
+
+    if orphans.is_empty() {
+        return format!("{}\n", TRANSLATOR.no_orphans());
+    }
+
+    let mut out = String::new();
+    for orphan in orphans {
+        let badge = if failed.contains(&orphan.path) {
+            format!("[{}] ", TRANSLATOR.badge_failed())
+        } else if ignored.contains(&orphan.path) {
+            format!("[{}] ", TRANSLATOR.badge_ignored())
+        } else {
+            "".to_string()
+        };
+        out += &format!(
+            "{}{} [{}]: {}\n",
+            badge,
+            orphan.path.render(),
+            TRANSLATOR.adjusted_size(orphan.size),
+            TRANSLATOR.orphan_kind(orphan.kind)
+        );
+    }
+    out += &format!(
+        "\n{}\n",
+        TRANSLATOR.orphans_summary(orphans.len(), orphans.iter().map(|x| x.size).sum())
+    );
+    out
+}
+
 #[cfg(test)]
 mod tests {
     use pretty_assertions::assert_eq;
@@ -680,6 +994,9 @@ Overall:
                         change: Default::default(),
                         container: None,
                         redirected: None,
+                        conflict: false,
+                        filtered: false,
+                        steam_account: None,
                     },
                     "/file2".into(): ScannedFile {
                         size: 51_200,
@@ -689,6 +1006,9 @@ Overall:
                         change: Default::default(),
                         container: None,
                         redirected: None,
+                        conflict: false,
+                        filtered: false,
+                        steam_account: None,
                     },
                 },
                 found_registry_keys: hash_map! {
@@ -748,6 +1068,9 @@ Overall:
                         change: ScanChange::Same,
                         container: None,
                         redirected: None,
+                        conflict: false,
+                        filtered: false,
+                        steam_account: None,
                     },
                 },
                 found_registry_keys: hash_map! {},
@@ -770,6 +1093,9 @@ Overall:
                         change: Default::default(),
                         container: None,
                         redirected: None,
+                        conflict: false,
+                        filtered: false,
+                        steam_account: None,
                     },
                 },
                 found_registry_keys: hash_map! {},
@@ -797,6 +1123,166 @@ Overall:
         );
     }
 
+    #[test]
+    fn can_render_backup_stats_in_json_mode() {
+        let mut reporter = Reporter::json();
+        reporter.suppress_overall();
+
+        let when = chrono::DateTime::parse_from_rfc3339("2000-01-02T03:04:05Z")
+            .unwrap()
+            .to_utc();
+        let stats = BackupStats {
+            bytes: 150,
+            full_backups: 1,
+            full_bytes: 100,
+            differential_backups: 1,
+            differential_bytes: 50,
+            duplicate_bytes: 25,
+            oldest: Some(when),
+            newest: Some(when),
+            growth: BTreeMap::from([(s("2000-01"), 150)]),
+        };
+        reporter.add_backup_stats("foo", "foo", StrictPath::new(s("/backup/foo")), stats.clone());
+        reporter.add_overall_backup_stats(1, stats);
+
+        assert_eq!(
+            r#"
+{
+  "stats": {
+    "bytes": 150,
+    "fullBackups": 1,
+    "fullBytes": 100,
+    "differentialBackups": 1,
+    "differentialBytes": 50,
+    "duplicateBytes": 25,
+    "oldest": "2000-01-02T03:04:05Z",
+    "newest": "2000-01-02T03:04:05Z",
+    "growth": {
+      "2000-01": 150
+    }
+  },
+  "games": {
+    "foo": {
+      "backupPath": "/backup/foo",
+      "stats": {
+        "bytes": 150,
+        "fullBackups": 1,
+        "fullBytes": 100,
+        "differentialBackups": 1,
+        "differentialBytes": 50,
+        "duplicateBytes": 25,
+        "oldest": "2000-01-02T03:04:05Z",
+        "newest": "2000-01-02T03:04:05Z",
+        "growth": {
+          "2000-01": 150
+        }
+      }
+    }
+  }
+}
+            "#
+            .trim(),
+            reporter.render(&StrictPath::new(s("/dev/null")))
+        );
+    }
+
+    #[test]
+    fn can_render_orphans() {
+        let orphans = vec![
+            OrphanedItem {
+                path: StrictPath::new(s("/backup/foo/backup-1.zip")),
+                kind: OrphanKind::Archive,
+                size: 100,
+            },
+            OrphanedItem {
+                path: StrictPath::new(s("/backup/bar")),
+                kind: OrphanKind::GameFolder,
+                size: 50,
+            },
+        ];
+        let failed = vec![StrictPath::new(s("/backup/bar"))];
+
+        assert_eq!(
+            r#"
+/backup/foo/backup-1.zip [100 B]: Unreferenced archive
+[FAILED] /backup/bar [50 B]: Folder without a mapping
+
+2 unused items (150 B)
+            "#
+            .trim(),
+            render_orphans(&orphans, &failed, &[], false).trim(),
+        );
+        assert_eq!(
+            r#"
+{
+  "games": {},
+  "orphans": {
+    "/backup/bar": {
+      "kind": "gameFolder",
+      "bytes": 50,
+      "failed": true
+    },
+    "/backup/foo/backup-1.zip": {
+      "kind": "archive",
+      "bytes": 100
+    }
+  }
+}
+            "#
+            .trim(),
+            render_orphans(&orphans, &failed, &[], true).trim(),
+        );
+        assert_eq!("No unused files found.\n", render_orphans(&[], &[], &[], false));
+    }
+
+    #[test]
+    fn can_render_orphans_with_ignored_invalid_mappings() {
+        let orphans = vec![
+            OrphanedItem {
+                path: StrictPath::new(s("/backup/foo/backup-1.zip")),
+                kind: OrphanKind::Archive,
+                size: 100,
+            },
+            OrphanedItem {
+                path: StrictPath::new(s("/backup/bar")),
+                kind: OrphanKind::InvalidMapping,
+                size: 50,
+            },
+        ];
+        let ignored = vec![StrictPath::new(s("/backup/bar"))];
+
+        assert_eq!(
+            r#"
+/backup/foo/backup-1.zip [100 B]: Unreferenced archive
+[IGNORED] /backup/bar [50 B]: Folder with an invalid mapping
+
+2 unused items (150 B)
+            "#
+            .trim(),
+            render_orphans(&orphans, &[], &ignored, false).trim(),
+        );
+        assert_eq!(
+            r#"
+{
+  "games": {},
+  "orphans": {
+    "/backup/bar": {
+      "kind": "invalidMapping",
+      "bytes": 50,
+      "ignored": true
+    },
+    "/backup/foo/backup-1.zip": {
+      "kind": "archive",
+      "bytes": 100
+    }
+  }
+}
+            "#
+            .trim(),
+            render_orphans(&orphans, &[], &ignored, true).trim(),
+        );
+    }
+
     #[test]
     fn can_render_in_standard_mode_with_one_game_in_restore_mode() {
         let mut reporter = Reporter::standard();
@@ -814,6 +1300,9 @@ Overall:
                         change: Default::default(),
                         container: None,
                         redirected: None,
+                        conflict: false,
+                        filtered: false,
+                        steam_account: None,
                     },
                     "/backup/file2".into(): ScannedFile {
                         size: 51_200,
@@ -823,6 +1312,9 @@ Overall:
                         change: Default::default(),
                         container: None,
                         redirected: None,
+                        conflict: false,
+                        filtered: false,
+                        steam_account: None,
                     },
                 },
                 found_registry_keys: hash_map! {},
@@ -848,6 +1340,51 @@ Overall:
         );
     }
 
+    #[test]
+    fn can_render_in_standard_mode_with_conflict_in_restore_mode() {
+        let mut reporter = Reporter::standard();
+
+        reporter.add_game(
+            "foo",
+            &ScanInfo {
+                game_name: s("foo"),
+                found_files: hash_map! {
+                    "/backup/file1".into(): ScannedFile {
+                        size: 102_400,
+                        hash: "1".to_string(),
+                        original_path: Some(StrictPath::new(s("/original/file1"))),
+                        ignored: false,
+                        change: ScanChange::Different,
+                        container: None,
+                        redirected: None,
+                        conflict: true,
+                        filtered: false,
+                        steam_account: None,
+                    },
+                },
+                found_registry_keys: hash_map! {},
+                ..Default::default()
+            },
+            None,
+            &OperationStepDecision::Processed,
+            &DuplicateDetector::default(),
+        );
+        assert_eq!(
+            r#"
+foo [100.00 KiB] [Δ]:
+  - [Δ] /original/file1
+    - Conflict: The local file is newer than this backup or doesn't match any backed up version.
+
+Overall:
+  Games: 1 [Δ1]
+  Size: 100.00 KiB
+  Location: /dev/null
+            "#
+            .trim(),
+            reporter.render(&StrictPath::new(s("/dev/null")))
+        );
+    }
+
     #[test]
     fn can_render_in_standard_mode_with_duplicated_entries() {
         let mut reporter = Reporter::standard();
@@ -1101,6 +1638,9 @@ Overall:
                         change: Default::default(),
                         container: None,
                         redirected: None,
+                        conflict: false,
+                        filtered: false,
+                        steam_account: None,
                     },
                     "/backup/file2".into(): ScannedFile {
                         size: 50,
@@ -1110,6 +1650,9 @@ Overall:
                         change: Default::default(),
                         container: None,
                         redirected: None,
+                        conflict: false,
+                        filtered: false,
+                        steam_account: None,
                     },
                 },
                 found_registry_keys: hash_map! {},
@@ -1157,6 +1700,70 @@ Overall:
         );
     }
 
+    #[test]
+    fn can_render_in_json_mode_with_conflict_in_restore_mode() {
+        let mut reporter = Reporter::json();
+
+        reporter.add_game(
+            "foo",
+            &ScanInfo {
+                game_name: s("foo"),
+                found_files: hash_map! {
+                    "/backup/file1".into(): ScannedFile {
+                        size: 100,
+                        hash: "1".to_string(),
+                        original_path: Some(StrictPath::new(s("/original/file1"))),
+                        ignored: false,
+                        change: ScanChange::Different,
+                        container: None,
+                        redirected: None,
+                        conflict: true,
+                        filtered: false,
+                        steam_account: None,
+                    },
+                },
+                found_registry_keys: hash_map! {},
+                ..Default::default()
+            },
+            None,
+            &OperationStepDecision::Processed,
+            &DuplicateDetector::default(),
+        );
+        assert_eq!(
+            r#"
+{
+  "overall": {
+    "totalGames": 1,
+    "totalBytes": 100,
+    "processedGames": 1,
+    "processedBytes": 100,
+    "changedGames": {
+      "new": 0,
+      "different": 1,
+      "same": 0
+    }
+  },
+  "games": {
+    "foo": {
+      "decision": "Processed",
+      "change": "Different",
+      "files": {
+        "/original/file1": {
+          "change": "Different",
+          "bytes": 100,
+          "conflict": true
+        }
+      },
+      "registry": {}
+    }
+  }
+}
+            "#
+            .trim(),
+            reporter.render(&StrictPath::new(s("/dev/null")))
+        );
+    }
+
     #[test]
     fn can_render_in_json_mode_with_duplicated_entries() {
         let mut reporter = Reporter::json();
@@ -1298,6 +1905,145 @@ Overall:
       "registry": {}
     }
   }
+}
+            "#
+            .trim(),
+            reporter.render(&StrictPath::new(s("/dev/null")))
+        );
+    }
+
+    #[test]
+    fn can_render_validation_in_standard_mode() {
+        let mut reporter = Reporter::standard();
+        reporter.suppress_overall();
+
+        assert!(reporter.add_validation("foo", "foo", StrictPath::new(s("/backup/foo")), &[]));
+        assert!(!reporter.add_validation(
+            "bar",
+            "bar",
+            StrictPath::new(s("/backup/bar")),
+            &[
+                ValidationIssue::MissingFile {
+                    backup: s("."),
+                    file: s("/file1.txt"),
+                },
+                ValidationIssue::ChangedFile {
+                    backup: s("."),
+                    file: s("/file2.txt"),
+                    expected: s("old"),
+                    actual: s("new"),
+                },
+            ],
+        ));
+        assert_eq!(
+            r#"
+bar:
+  Folder: /backup/bar
+  - Missing file in backup .: /file1.txt
+  - Changed file in backup .: /file2.txt
+            "#
+            .trim(),
+            reporter.render(&StrictPath::new(s("/dev/null"))).trim(),
+        );
+    }
+
+    #[test]
+    fn can_render_validation_in_json_mode() {
+        let mut reporter = Reporter::json();
+        reporter.suppress_overall();
+
+        reporter.add_validation("foo", "foo", StrictPath::new(s("/backup/foo")), &[]);
+        reporter.add_validation(
+            "bar",
+            "bar",
+            StrictPath::new(s("/backup/bar")),
+            &[ValidationIssue::ChangedFile {
+                backup: s("."),
+                file: s("/file2.txt"),
+                expected: s("old"),
+                actual: s("new"),
+            }],
+        );
+        assert_eq!(
+            r#"
+{
+  "errors": {
+    "someGamesFailed": true
+  },
+  "games": {
+    "bar": {
+      "backupPath": "/backup/bar",
+      "issues": [
+        {
+          "kind": "changedFile",
+          "backup": ".",
+          "file": "/file2.txt",
+          "expected": "old",
+          "actual": "new"
+        }
+      ]
+    },
+    "foo": {
+      "backupPath": "/backup/foo",
+      "issues": []
+    }
+  }
+}
+            "#
+            .trim(),
+            reporter.render(&StrictPath::new(s("/dev/null")))
+        );
+    }
+
+    #[test]
+    fn can_render_comparison_in_json_mode() {
+        let mut reporter = Reporter::json();
+        reporter.suppress_overall();
+
+        reporter.add_comparison(
+            "foo",
+            "foo",
+            StrictPath::new(s("/backup/foo")),
+            BackupComparison {
+                from: None,
+                to: BackupReference {
+                    name: s("."),
+                    when: chrono::DateTime::default(),
+                },
+                files: BTreeMap::from([(
+                    s("/file1.txt"),
+                    FileComparison {
+                        change: ScanChange::New,
+                        from: None,
+                        to: Some(crate::scan::layout::IndividualMappingFile {
+                            hash: s("hash"),
+                            size: 1,
+                        }),
+                    },
+                )]),
+            },
+        );
+        assert_eq!(
+            r#"
+{
+  "games": {
+    "foo": {
+      "backupPath": "/backup/foo",
+      "to": {
+        "name": ".",
+        "when": "1970-01-01T00:00:00Z"
+      },
+      "files": {
+        "/file1.txt": {
+          "change": "New",
+          "to": {
+            "hash": "hash",
+            "size": 1
+          }
+        }
+      }
+    }
+  }
 }
             "#
             .trim(),
@@ -0,0 +1,425 @@
+use std::{
+    collections::{BTreeSet, HashMap, HashSet},
+    path::{Path, PathBuf},
+    sync::{atomic::Ordering, mpsc},
+    time::{Duration, Instant},
+};
+
+use notify::Watcher;
+use rayon::prelude::*;
+
+use crate::{
+    path::CommonPath,
+    prelude::{app_dir, register_sigint, unregister_sigint, Error, StrictPath},
+    resource::{
+        config::{Config, Root},
+        manifest::Manifest,
+        ResourceFile, SaveableResourceFile,
+    },
+    scan::{
+        encryption::Passphrase, find_save_candidates, layout::BackupLayout, scan_game_for_backup, BackupInfo,
+        HashCache, Launchers, ScanChange, ScanChangeCount, ScanInfo, ScanKind, SteamShortcuts, TitleFinder,
+    },
+};
+
+/// How often to check for games whose saves have settled down.
+const POLL_INTERVAL: Duration = Duration::from_millis(500);
+
+/// How often to resolve each game's save locations again,
+/// so that we notice folders that didn't exist yet.
+const RESOLVE_INTERVAL: Duration = Duration::from_secs(60);
+
+/// Folders to watch, and whether to watch each one recursively.
+type Folders = HashMap<PathBuf, bool>;
+
+/// Printed as one line of JSON after each backup made by the `watch` command.
+#[derive(Debug, serde::Serialize)]
+#[serde(rename_all = "camelCase")]
+struct WatchEvent {
+    /// When the backup finished.
+    time: chrono::DateTime<chrono::Utc>,
+    game: String,
+    /// How the game's saves compare to its previous backup.
+    change: ScanChange,
+    /// How many files are new, different, or the same as in the previous backup.
+    files: ScanChangeCount,
+    /// Whether a new backup was made.
+    backed_up: bool,
+    /// Size of the backed up data in bytes.
+    bytes: u64,
+    /// Files that could not be backed up.
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    failed_files: Vec<String>,
+}
+
+struct Context<'a> {
+    config: &'a Config,
+    manifest: &'a Manifest,
+    roots: Vec<Root>,
+    backup_dir: StrictPath,
+    passphrase: Option<Passphrase>,
+    launchers: Launchers,
+    steam_shortcuts: SteamShortcuts,
+    hash_cache: HashCache,
+    /// Folders that are too broad to watch recursively, like the roots themselves.
+    broad_folders: HashSet<PathBuf>,
+}
+
+impl Context<'_> {
+    fn layout(&self) -> BackupLayout {
+        // Reload each time so that we notice backups made since the last scan.
+        BackupLayout::new(self.backup_dir.clone()).with_passphrase(self.passphrase.clone())
+    }
+
+    fn scan(&self, layout: &BackupLayout, name: &str) -> ScanInfo {
+        let previous = layout.latest_backup(
+            name,
+            ScanKind::Backup,
+            &self.config.redirects,
+            self.config.restore.reverse_redirects,
+            &self.config.restore.toggled_paths,
+        );
+
+        scan_game_for_backup(
+            &self.manifest.0[name],
+            name,
+            &self.roots,
+            &app_dir(),
+            &self.launchers,
+            &self.config.backup.filter,
+            None,
+            &self.config.backup.toggled_paths,
+            &self.config.backup.toggled_registry,
+            previous,
+            &self.config.redirects,
+            self.config.restore.reverse_redirects,
+            &self.steam_shortcuts,
+            &self.hash_cache,
+        )
+    }
+
+    /// Folders where the game's saves could be, based on its manifest paths.
+    /// Folders are watched recursively, unless we only know the parent of a specific file.
+    fn resolve(&self, name: &str) -> Folders {
+        let candidates = find_save_candidates(
+            &self.manifest.0[name],
+            name,
+            &self.roots,
+            &app_dir(),
+            &self.launchers,
+            &self.config.backup.filter,
+            None,
+            &self.steam_shortcuts,
+        );
+
+        let mut folders = Folders::new();
+        for (candidate, _) in candidates.paths {
+            let Ok(candidate) = candidate.interpret() else {
+                continue;
+            };
+            let prefix = static_prefix(&candidate);
+            if prefix.is_dir() {
+                if !self.broad_folders.contains(&prefix) {
+                    folders.insert(prefix, true);
+                }
+            } else if prefix.is_file() {
+                if let Some(parent) = prefix.parent() {
+                    folders.entry(parent.to_path_buf()).or_insert(false);
+                }
+            }
+        }
+        folders
+    }
+
+    fn back_up(&self, name: &str) -> ScanInfo {
+        let layout = self.layout();
+        let scan_info = self.scan(&layout, name);
+
+        let backup_info = layout.back_up_game(
+            name,
+            &scan_info,
+            &chrono::Utc::now(),
+            &self.config.backup.format,
+            self.config.backup.retention,
+        );
+        layout.prune_blobs();
+        self.hash_cache.save();
+
+        report(&scan_info, backup_info.as_ref());
+        scan_info
+    }
+}
+
+fn report(scan_info: &ScanInfo, backup_info: Option<&BackupInfo>) {
+    let event = WatchEvent {
+        time: chrono::Utc::now(),
+        game: scan_info.game_name.clone(),
+        change: scan_info.overall_change(),
+        files: scan_info.count_changes(),
+        backed_up: backup_info.is_some_and(|x| x.successful()),
+        bytes: scan_info.sum_bytes(backup_info),
+        failed_files: backup_info
+            .map(|x| x.failed_files.keys().map(|x| x.render()).collect())
+            .unwrap_or_default(),
+    };
+
+    match backup_info {
+        Some(info) if !info.successful() => log::warn!("[{}] watch: backup failed: {:?}", event.game, &event),
+        Some(_) => log::info!("[{}] watch: backed up", event.game),
+        None => log::info!("[{}] watch: no need for new backup", event.game),
+    }
+
+    if let Ok(line) = serde_json::to_string(&event) {
+        println!("{line}");
+    }
+}
+
+/// Folders that contain the game's save files.
+fn folders(scan_info: &ScanInfo) -> Folders {
+    scan_info
+        .found_files
+        .iter()
+        .filter(|(_, file)| file.container.is_none() && !file.ignored)
+        .filter_map(|(scan_key, _)| {
+            scan_key
+                .as_std_path_buf()
+                .ok()?
+                .parent()
+                .map(|x| (x.to_path_buf(), false))
+        })
+        .collect()
+}
+
+/// The part of a glob before the first wildcard.
+fn static_prefix(glob: &str) -> PathBuf {
+    let parts: Vec<_> = glob
+        .split(['/', '\\'])
+        .take_while(|part| !part.contains(['*', '?', '[', '{']))
+        .collect();
+    match parts.as_slice() {
+        [""] => PathBuf::from("/"),
+        parts => PathBuf::from(parts.join("/")),
+    }
+}
+
+/// Folders that we shouldn't watch recursively because they would cover too much,
+/// namely the roots and common folders like home, along with their parents.
+fn broad_folders(roots: &[Root]) -> HashSet<PathBuf> {
+    let common = [
+        CommonPath::Config,
+        CommonPath::Data,
+        CommonPath::DataLocal,
+        CommonPath::DataLocalLow,
+        CommonPath::Document,
+        CommonPath::Home,
+        CommonPath::Public,
+        CommonPath::SavedGames,
+    ];
+
+    roots
+        .iter()
+        .filter_map(|root| root.path().interpret().ok())
+        .chain(common.iter().filter_map(|x| x.get().map(|x| x.to_string())))
+        .flat_map(|folder| {
+            Path::new(&folder)
+                .ancestors()
+                .map(|x| x.to_path_buf())
+                .collect::<Vec<_>>()
+        })
+        .collect()
+}
+
+/// Keeps track of which folders are being watched for which games.
+#[derive(Default)]
+struct Subscriptions {
+    /// Whether each folder is watched recursively, and for which games.
+    folders: HashMap<PathBuf, (bool, HashSet<String>)>,
+}
+
+impl Subscriptions {
+    fn add(&mut self, watcher: &mut impl Watcher, game: &str, folders: Folders) {
+        for (folder, recursive) in folders {
+            let (watched_recursively, games) = self.folders.entry(folder.clone()).or_default();
+            let upgrade = recursive && !*watched_recursively;
+            if games.is_empty() || upgrade {
+                if !games.is_empty() {
+                    let _ = watcher.unwatch(&folder);
+                }
+                let mode = if recursive {
+                    notify::RecursiveMode::Recursive
+                } else {
+                    notify::RecursiveMode::NonRecursive
+                };
+                log::debug!("watch: watching folder ({mode:?}): {folder:?}");
+                if let Err(e) = watcher.watch(&folder, mode) {
+                    log::warn!("watch: unable to watch folder: {folder:?} | {e:?}");
+                    continue;
+                }
+                *watched_recursively = recursive;
+            }
+            games.insert(game.to_string());
+        }
+    }
+
+    fn games<'a>(&'a self, path: &'a Path) -> BTreeSet<&'a String> {
+        path.ancestors()
+            .enumerate()
+            .filter_map(|(i, folder)| self.folders.get(folder).filter(|(recursive, _)| *recursive || i <= 1))
+            .flat_map(|(_, games)| games)
+            .collect()
+    }
+}
+
+/// Back up each game shortly after its save files stop changing.
+/// This runs until interrupted.
+pub fn watch(
+    config: &Config,
+    manifest: &Manifest,
+    backup_dir: StrictPath,
+    passphrase: Option<Passphrase>,
+    games: Vec<String>,
+    delay: Duration,
+) -> Result<(), Error> {
+    let roots = config.expanded_roots();
+    let broad_folders = broad_folders(&roots);
+    let layout = BackupLayout::new(backup_dir.clone());
+    let title_finder = TitleFinder::new(config, manifest, layout.restorable_game_set());
+
+    let context = Context {
+        config,
+        manifest,
+        launchers: Launchers::scan(&roots, manifest, &games, &title_finder, None),
+        roots,
+        backup_dir: backup_dir.clone(),
+        passphrase,
+        steam_shortcuts: SteamShortcuts::scan(),
+        hash_cache: HashCache::load().unwrap_or_default(),
+        broad_folders,
+    };
+
+    let (sender, receiver) = mpsc::channel();
+    let mut watcher = notify::recommended_watcher(sender).map_err(|e| {
+        log::error!("watch: unable to start watcher: {e:?}");
+        Error::UnableToWatchSaves { why: e.to_string() }
+    })?;
+
+    log::info!("watch: scanning {} games", games.len());
+    let scans: Vec<_> = games
+        .par_iter()
+        .map(|name| {
+            let mut folders = folders(&context.scan(&layout, name));
+            folders.extend(context.resolve(name));
+            (name.clone(), folders)
+        })
+        .collect();
+
+    let mut subscriptions = Subscriptions::default();
+    for (name, folders) in scans {
+        subscriptions.add(&mut watcher, &name, folders);
+    }
+    context.hash_cache.save();
+    log::info!("watch: watching {} folders", subscriptions.folders.len());
+
+    let backup_dir = backup_dir.as_std_path_buf().ok();
+    let interrupted = register_sigint();
+    let mut pending = HashMap::<String, Instant>::new();
+    let mut last_resolved = Instant::now();
+
+    loop {
+        let stop = interrupted.load(Ordering::Relaxed);
+
+        match receiver.recv_timeout(POLL_INTERVAL) {
+            Ok(Ok(event)) => {
+                if !matches!(event.kind, notify::EventKind::Access(_)) {
+                    for path in &event.paths {
+                        if backup_dir.as_ref().is_some_and(|x| path.starts_with(x)) {
+                            continue;
+                        }
+                        for game in subscriptions.games(path) {
+                            log::trace!("[{game}] watch: change detected: {path:?}");
+                            pending.insert(game.clone(), Instant::now());
+                        }
+                    }
+                }
+            }
+            Ok(Err(e)) => {
+                log::warn!("watch: error from watcher: {e:?}");
+            }
+            Err(mpsc::RecvTimeoutError::Timeout) => {}
+            Err(mpsc::RecvTimeoutError::Disconnected) => break,
+        }
+
+        // When interrupted, back up any pending changes right away before exiting.
+        let ready: Vec<_> = pending
+            .iter()
+            .filter(|(_, changed)| stop || changed.elapsed() >= delay)
+            .map(|(game, _)| game.clone())
+            .collect();
+
+        for game in ready {
+            pending.remove(&game);
+            let scan_info = context.back_up(&game);
+            // Pick up any new folders where the game has started saving.
+            subscriptions.add(&mut watcher, &game, folders(&scan_info));
+        }
+
+        if stop {
+            break;
+        }
+
+        if last_resolved.elapsed() >= RESOLVE_INTERVAL {
+            let resolved: Vec<_> = games.par_iter().map(|name| (name, context.resolve(name))).collect();
+            for (name, folders) in resolved {
+                subscriptions.add(&mut watcher, name, folders);
+            }
+            last_resolved = Instant::now();
+        }
+    }
+
+    unregister_sigint();
+    context.hash_cache.prune();
+    context.hash_cache.save();
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use pretty_assertions::assert_eq;
+
+    use super::*;
+
+    #[test]
+    fn can_find_games_for_changed_path() {
+        let subscriptions = Subscriptions {
+            folders: HashMap::from([
+                (PathBuf::from("/saves/a"), (false, HashSet::from(["game1".to_string()]))),
+                (
+                    PathBuf::from("/saves/b"),
+                    (false, HashSet::from(["game1".to_string(), "game2".to_string()])),
+                ),
+                (PathBuf::from("/saves/c"), (true, HashSet::from(["game3".to_string()]))),
+            ]),
+        };
+
+        let games = |path: &str| -> Vec<String> { subscriptions.games(Path::new(path)).into_iter().cloned().collect() };
+
+        assert_eq!(vec!["game1".to_string()], games("/saves/a/file.sav"));
+        assert_eq!(Vec::<String>::new(), games("/saves/a/nested/file.sav"));
+        assert_eq!(vec!["game1".to_string(), "game2".to_string()], games("/saves/b"));
+        assert_eq!(vec!["game3".to_string()], games("/saves/c/nested/deeper/file.sav"));
+        assert_eq!(Vec::<String>::new(), games("/saves/d/file.sav"));
+    }
+
+    #[test]
+    fn can_find_static_prefix_of_glob() {
+        assert_eq!(PathBuf::from("/saves/game"), static_prefix("/saves/game/*.sav"));
+        assert_eq!(PathBuf::from("/saves"), static_prefix("/saves/*/data"));
+        assert_eq!(
+            PathBuf::from("/saves/game/file.sav"),
+            static_prefix("/saves/game/file.sav")
+        );
+        assert_eq!(PathBuf::from("/"), static_prefix("/*"));
+        assert_eq!(PathBuf::from("C:/saves"), static_prefix("C:\\saves\\[Gg]ame"));
+    }
+}
@@ -24,13 +24,16 @@ use crate::{
     prelude::{app_dir, get_threads_from_env, initialize_rayon, Error, Finality, StrictPath, SyncDirection},
     resource::{
         cache::{self, Cache},
-        config::{self, Config, CustomGame, CustomGameKind, Root},
+        config::{self, Config, ConflictPolicy, CustomGame, CustomGameKind, Root},
         manifest::Manifest,
         ResourceFile, SaveableResourceFile,
     },
     scan::{
-        layout::BackupLayout, prepare_backup_target, registry::RegistryItem, scan_game_for_backup, BackupId, Launchers,
-        ScanKind, SteamShortcuts, TitleFinder,
+        encryption::Passphrase,
+        layout::{BackupLayout, FileSelection},
+        prepare_backup_target,
+        registry::RegistryItem,
+        scan_game_for_backup, BackupId, HashCache, Launchers, ScanKind, SteamShortcuts, TitleFinder,
     },
 };
 
@@ -79,6 +82,10 @@ pub struct App {
     config: Config,
     manifest: LoadedManifest,
     cache: Cache,
+    hash_cache: std::sync::Arc<HashCache>,
+    /// Passphrase entered in the GUI when none is configured.
+    /// This is only kept in memory for the current session.
+    passphrase: Option<Passphrase>,
     operation: Operation,
     screen: Screen,
     modal: Option<Modal>,
@@ -175,6 +182,15 @@ impl App {
         });
     }
 
+    fn passphrase(&self) -> Option<Passphrase> {
+        self.config
+            .backup
+            .format
+            .encryption
+            .passphrase()
+            .or_else(|| self.passphrase.clone())
+    }
+
     fn save_config(&mut self) {
         self.pending_save.insert(SaveKind::Config, Instant::now());
     }
@@ -245,8 +261,7 @@ impl App {
         let games = match games {
             Some(games) => {
                 let layout = BackupLayout::new(local.clone());
-                let games: Vec<_> = games.iter().filter_map(|x| layout.game_folder(x).leaf()).collect();
-                games
+                layout.cloud_folders(games)
             }
             None => vec![],
         };
@@ -276,7 +291,14 @@ impl App {
         const SCAN_KIND: ScanKind = ScanKind::Backup;
 
         match phase {
-            BackupPhase::Confirm { games } => self.show_modal(Modal::ConfirmBackup { games }),
+            BackupPhase::Confirm { games } => {
+                let format = &self.config.backup.format;
+                if self.passphrase().is_none() && format.encryption.applies_to(format.chosen) {
+                    self.text_histories.clear_modal_fields();
+                    return self.show_modal(Modal::EnterPassphrase { restore: false, games });
+                }
+                self.show_modal(Modal::ConfirmBackup { games })
+            }
             BackupPhase::Start {
                 preview,
                 repair,
@@ -376,6 +398,7 @@ impl App {
                 let mut manifest = self.manifest.primary.clone();
                 let config = self.config.clone();
                 let previewed_games = self.backup_screen.previewed_games.clone();
+                let passphrase = self.passphrase();
 
                 Task::perform(
                     async move {
@@ -397,7 +420,7 @@ impl App {
                         let subjects: Vec<_> = subjects.into_iter().collect();
 
                         let roots = config.expanded_roots();
-                        let layout = BackupLayout::new(config.backup.path.clone());
+                        let layout = BackupLayout::new(config.backup.path.clone()).with_passphrase(passphrase);
                         let title_finder = TitleFinder::new(&config, &manifest, layout.restorable_game_set());
                         let steam = SteamShortcuts::scan();
                         let launchers = Launchers::scan(&roots, &manifest, &subjects, &title_finder, None);
@@ -473,6 +496,7 @@ impl App {
                     let layout = layout.clone();
                     let filter = filter.clone();
                     let steam_shortcuts = steam_shortcuts.clone();
+                    let hash_cache = self.hash_cache.clone();
                     let cancel_flag = self.operation_should_cancel.clone();
                     self.operation_steps.push(Task::perform(
                         async move {
@@ -510,13 +534,15 @@ impl App {
                                 &config.redirects,
                                 config.restore.reverse_redirects,
                                 &steam_shortcuts,
+                                &hash_cache,
                             );
                             if !config.is_game_enabled_for_backup(&key) && full {
                                 return (Some(scan_info), None);
                             }
 
                             let backup_info = if !preview {
-                                layout.game_layout(&key).back_up(
+                                layout.back_up_game(
+                                    &key,
                                     &scan_info,
                                     &chrono::Utc::now(),
                                     &config.backup.format,
@@ -528,7 +554,10 @@ impl App {
                             (Some(scan_info), backup_info)
                         },
                         move |(scan_info, backup_info)| {
-                            Message::Backup(BackupPhase::GameScanned { scan_info, backup_info })
+                            Message::Backup(BackupPhase::GameScanned {
+                                scan_info: scan_info.map(Box::new),
+                                backup_info,
+                            })
                         },
                     ));
                 }
@@ -540,7 +569,7 @@ impl App {
                 self.progress.step();
                 let full = self.operation.full();
 
-                if let Some(mut scan_info) = scan_info {
+                if let Some(mut scan_info) = scan_info.map(|x| *x) {
                     log::trace!(
                         "step {} / {}: {}",
                         self.progress.current,
@@ -601,6 +630,10 @@ impl App {
                 }
             }
             BackupPhase::CloudSync => {
+                if !self.operation.preview() {
+                    BackupLayout::new(self.config.backup.path.clone()).prune_blobs();
+                }
+
                 if !self.operation.should_sync_cloud_after() {
                     return self.handle_backup(BackupPhase::Done);
                 }
@@ -670,6 +703,10 @@ impl App {
                 }
 
                 self.save_cache();
+                if !preview {
+                    self.hash_cache.prune();
+                    self.hash_cache.save();
+                }
 
                 if failed {
                     self.operation.push_error(Error::SomeEntriesFailed);
@@ -717,7 +754,17 @@ impl App {
         const SCAN_KIND: ScanKind = ScanKind::Restore;
 
         match phase {
-            RestorePhase::Confirm { games } => self.show_modal(Modal::ConfirmRestore { games }),
+            RestorePhase::Confirm { games } => {
+                let format = &self.config.backup.format;
+                if self.passphrase().is_none()
+                    && ((self.config.restore.snapshot && format.encryption.applies_to(format.chosen))
+                        || BackupLayout::new(self.config.restore.path.clone()).has_encrypted_backups())
+                {
+                    self.text_histories.clear_modal_fields();
+                    return self.show_modal(Modal::EnterPassphrase { restore: true, games });
+                }
+                self.show_modal(Modal::ConfirmRestore { games })
+            }
             RestorePhase::Start { preview, mut games } => {
                 if !self.operation.idle() {
                     return Task::none();
@@ -783,12 +830,13 @@ impl App {
             }
             RestorePhase::Load => {
                 let restore_path = self.config.restore.path.clone();
+                let passphrase = self.passphrase();
 
                 self.progress.start();
 
                 Task::perform(
                     async move {
-                        let layout = BackupLayout::new(restore_path);
+                        let layout = BackupLayout::new(restore_path).with_passphrase(passphrase);
                         let restorables = layout.restorable_games();
                         (layout, restorables)
                     },
@@ -841,6 +889,7 @@ impl App {
 
                 let config = std::sync::Arc::new(self.config.clone());
                 let layout = std::sync::Arc::new(layout);
+                let now = chrono::Utc::now();
 
                 for name in restorables {
                     let config = config.clone();
@@ -855,20 +904,37 @@ impl App {
                                 return (None, None, layout);
                             }
 
-                            let scan_info = layout.scan_for_restoration(
+                            let mut scan_info = layout.scan_for_restoration(
                                 &name,
                                 &backup_id,
                                 &config.redirects,
                                 config.restore.reverse_redirects,
                                 &config.restore.toggled_paths,
                                 &config.restore.toggled_registry,
+                                &config.restore.steam_accounts,
                             );
                             if !config.is_game_enabled_for_restore(&name) && full {
                                 return (Some(scan_info), None, layout);
                             }
 
                             let backup_info = if scan_info.backup.is_some() && !preview {
-                                Some(layout.restore(&scan_info, &config.restore.toggled_registry))
+                                // The GUI can't ask in the middle of a restore, so it skips those files instead.
+                                let unresolved =
+                                    layout.resolve_conflicts(&mut scan_info, config.restore.conflicts, &now, |_| {
+                                        ConflictPolicy::Skip
+                                    });
+                                let mut backup_info = if config.restore.snapshot {
+                                    layout.restore_with_snapshot(
+                                        &scan_info,
+                                        &config.restore.toggled_registry,
+                                        &now,
+                                        &config.backup.format,
+                                    )
+                                } else {
+                                    layout.restore(&scan_info, &config.restore.toggled_registry)
+                                };
+                                backup_info.failed_files.extend(unresolved);
+                                Some(backup_info)
                             } else {
                                 None
                             };
@@ -876,7 +942,7 @@ impl App {
                         },
                         move |(scan_info, backup_info, game_layout)| {
                             Message::Restore(RestorePhase::GameScanned {
-                                scan_info,
+                                scan_info: scan_info.map(Box::new),
                                 backup_info,
                                 game_layout: Box::new(game_layout),
                             })
@@ -895,7 +961,7 @@ impl App {
                 self.progress.step();
                 let full = self.operation.full();
 
-                if let Some(mut scan_info) = scan_info {
+                if let Some(mut scan_info) = scan_info.map(|x| *x) {
                     log::trace!(
                         "step {} / {}: {}",
                         self.progress.current,
@@ -987,6 +1053,8 @@ impl App {
 
                 if failed {
                     self.operation.push_error(Error::SomeEntriesFailed);
+                    // In case the entered passphrase was wrong, ask again next time.
+                    self.passphrase = None;
                 }
 
                 let errors = self.operation.errors().cloned();
@@ -1005,7 +1073,7 @@ impl App {
 
     fn handle_validation(&mut self, phase: ValidatePhase) -> Task<Message> {
         match phase {
-            ValidatePhase::Start => {
+            ValidatePhase::Start { deep } => {
                 if !self.operation.idle() {
                     return Task::none();
                 }
@@ -1017,7 +1085,7 @@ impl App {
                     });
                 }
 
-                self.operation = Operation::new_validate_backups();
+                self.operation = Operation::new_validate_backups(deep);
 
                 self.invalidate_path_caches();
                 self.timed_notification = None;
@@ -1026,12 +1094,13 @@ impl App {
             }
             ValidatePhase::Load => {
                 let restore_path = self.config.restore.path.clone();
+                let passphrase = self.passphrase();
 
                 self.progress.start();
 
                 Task::perform(
                     async move {
-                        let layout = BackupLayout::new(restore_path);
+                        let layout = BackupLayout::new(restore_path).with_passphrase(passphrase);
                         let subjects = layout.restorable_games();
                         (layout, subjects)
                     },
@@ -1056,6 +1125,7 @@ impl App {
                 self.progress.set_max(subjects.len() as f32);
 
                 let layout = std::sync::Arc::new(layout);
+                let deep = matches!(self.operation, Operation::ValidateBackups { deep: true, .. });
 
                 for name in subjects {
                     let layout = layout.clone();
@@ -1064,31 +1134,33 @@ impl App {
                     self.operation_steps.push(Task::perform(
                         async move {
                             if cancel_flag.load(std::sync::atomic::Ordering::Relaxed) {
-                                return (name, true);
+                                return (name, true, vec![]);
                             }
 
                             let Some(layout) = layout.try_game_layout(&name) else {
-                                return (name, false);
+                                return (name, false, vec![]);
                             };
 
                             // TODO: Add an option to validate all backups at once.
-                            let valid = layout.validate(backup_id);
-                            (name, valid)
+                            let issues = layout.validate(backup_id, deep);
+                            (name, issues.is_empty(), issues)
+                        },
+                        move |(game, valid, issues)| {
+                            Message::ValidateBackups(ValidatePhase::GameScanned { game, valid, issues })
                         },
-                        move |(game, valid)| Message::ValidateBackups(ValidatePhase::GameScanned { game, valid }),
                     ));
                 }
 
                 self.operation_steps_active = 100.min(self.operation_steps.len());
                 Task::batch(self.operation_steps.drain(..self.operation_steps_active))
             }
-            ValidatePhase::GameScanned { game, valid } => {
+            ValidatePhase::GameScanned { game, valid, issues } => {
                 self.progress.step();
                 log::trace!("step {} / {}: {}", self.progress.current, self.progress.max, &game);
 
                 if !valid {
                     if let Operation::ValidateBackups { faulty_games, .. } = &mut self.operation {
-                        faulty_games.insert(game);
+                        faulty_games.insert(game, issues);
                     }
                 }
 
@@ -1165,6 +1237,7 @@ impl App {
                 prefer_alias: false,
                 files: standard.files.keys().cloned().collect(),
                 registry: standard.registry.keys().cloned().collect(),
+                exclude: standard.exclude.clone(),
                 expanded: true,
             }
         } else {
@@ -1176,6 +1249,7 @@ impl App {
                 prefer_alias: false,
                 files: vec![],
                 registry: vec![],
+                exclude: vec![],
                 expanded: true,
             }
         };
@@ -1200,6 +1274,7 @@ impl App {
             prefer_alias: true,
             files: vec![],
             registry: vec![],
+            exclude: vec![],
             expanded: true,
         };
 
@@ -1382,6 +1457,7 @@ impl App {
                 config,
                 manifest,
                 cache,
+                hash_cache: std::sync::Arc::new(HashCache::load().unwrap_or_default()),
                 modal,
                 updating_manifest: flags.update_manifest,
                 text_histories,
@@ -1482,9 +1558,11 @@ impl App {
                 self.manifest_notification = None;
                 let mut errors = vec![];
 
+                let mut updates_renamed = false;
                 for update in updates {
                     match update {
                         Ok(Some(update)) => {
+                            updates_renamed |= !update.renames.is_empty();
                             self.cache.update_manifest(update);
                         }
                         Ok(None) => {}
@@ -1508,11 +1586,24 @@ impl App {
                     }
                 }
 
-                if errors.is_empty() {
-                    self.close_specific_modal(Modal::UpdatingManifest)
-                } else {
-                    self.show_modal(Modal::Errors { errors })
+                if !errors.is_empty() {
+                    return self.show_modal(Modal::Errors { errors });
+                }
+
+                let closed = self.close_specific_modal(Modal::UpdatingManifest);
+                if !updates_renamed {
+                    return closed;
                 }
+
+                let restore_path = self.config.restore.path.clone();
+                let renames = self.cache.renamed_games.clone();
+                Task::batch([
+                    closed,
+                    Task::perform(
+                        async move { BackupLayout::new(restore_path).pending_renames(&renames) },
+                        Message::FoundRenamedGames,
+                    ),
+                ])
             }
             Message::Backup(phase) => self.handle_backup(phase),
             Message::Restore(phase) => self.handle_restore(phase),
@@ -1784,6 +1875,31 @@ impl App {
                 self.save_config();
                 Task::none()
             }
+            Message::EditedCustomGameExclude(game_index, action) => {
+                match action {
+                    EditAction::Add => {
+                        self.text_histories.custom_games[game_index]
+                            .exclude
+                            .push(Default::default());
+                        self.config.custom_games[game_index].exclude.push("".to_string());
+                    }
+                    EditAction::Change(index, value) => {
+                        self.text_histories.custom_games[game_index].exclude[index].push(&value);
+                        self.config.custom_games[game_index].exclude[index] = value;
+                    }
+                    EditAction::Remove(index) => {
+                        self.text_histories.custom_games[game_index].exclude.remove(index);
+                        self.config.custom_games[game_index].exclude.remove(index);
+                    }
+                    EditAction::Move(index, direction) => {
+                        let offset = direction.shift(index);
+                        self.text_histories.custom_games[game_index].exclude.swap(index, offset);
+                        self.config.custom_games[game_index].exclude.swap(index, offset);
+                    }
+                }
+                self.save_config();
+                Task::none()
+            }
             Message::EditedExcludeStoreScreenshots(enabled) => {
                 self.config.backup.filter.exclude_store_screenshots = enabled;
                 self.save_config();
@@ -2232,6 +2348,138 @@ impl App {
                     }
                 })
             }
+            Message::ExtractFiles {
+                game,
+                path,
+                destination: None,
+            } => Task::future(async move {
+                let choice = async move { rfd::AsyncFileDialog::new().pick_folder().await }.await;
+
+                match choice {
+                    Some(choice) => Message::ExtractFiles {
+                        game,
+                        path,
+                        destination: Some(StrictPath::from(choice.path().to_path_buf())),
+                    },
+                    None => Message::Ignore,
+                }
+            }),
+            Message::ExtractFiles {
+                game,
+                path,
+                destination: Some(destination),
+            } => {
+                let restore_path = self.config.restore.path.clone();
+                let passphrase = self.passphrase();
+                let backup_id = self.backups_to_restore.get(&game).cloned().unwrap_or(BackupId::Latest);
+                let config = self.config.clone();
+                let target = destination.clone();
+
+                Task::perform(
+                    async move {
+                        let layout = BackupLayout::new(restore_path).with_passphrase(passphrase);
+                        let Some(mut layout) = layout.try_game_layout(&game) else {
+                            return false;
+                        };
+
+                        let mut scan_info = layout.scan_for_restoration(
+                            &game,
+                            &backup_id,
+                            &config.redirects,
+                            config.restore.reverse_redirects,
+                            &config.restore.toggled_paths,
+                            &config.restore.toggled_registry,
+                            &config.restore.steam_accounts,
+                        );
+                        layout.select_for_restoration(&mut scan_info, &FileSelection::path(&path), Some(&target));
+                        if scan_info.found_files.is_empty() {
+                            return false;
+                        }
+
+                        layout
+                            .restore(&scan_info, &config.restore.toggled_registry)
+                            .successful()
+                    },
+                    move |successful| Message::ExtractedFiles {
+                        destination: destination.clone(),
+                        successful,
+                    },
+                )
+            }
+            Message::UndoRestore { snapshots: None } => {
+                let restore_path = self.config.restore.path.clone();
+                Task::perform(
+                    async move { BackupLayout::new(restore_path).last_restore_snapshots() },
+                    |snapshots| Message::UndoRestore {
+                        snapshots: Some(snapshots),
+                    },
+                )
+            }
+            Message::UndoRestore {
+                snapshots: Some(snapshots),
+            } => {
+                if snapshots.is_empty() {
+                    return self.show_error(Error::NoRestoreToUndo);
+                }
+
+                let games: HashSet<_> = snapshots.keys().cloned().collect();
+                self.backups_to_restore.extend(snapshots);
+                self.handle_restore(RestorePhase::Confirm { games: Some(games) })
+            }
+            Message::ShowBackupStats { stats: None } => {
+                let restore_path = self.config.restore.path.clone();
+                Task::perform(
+                    async move {
+                        let layout = BackupLayout::new(restore_path);
+                        layout.stats(&layout.restorable_game_set())
+                    },
+                    |stats| Message::ShowBackupStats { stats: Some(stats) },
+                )
+            }
+            Message::ShowBackupStats { stats: Some(stats) } => self.show_modal(Modal::BackupStats { stats }),
+            Message::FoundRenamedGames(renames) => {
+                if renames.is_empty() {
+                    Task::none()
+                } else {
+                    self.show_modal(Modal::ConfirmMigrateRenames { renames })
+                }
+            }
+            Message::MigrateRenamedGames { renames, errors: None } => {
+                let restore_path = self.config.restore.path.clone();
+                Task::perform(
+                    async move {
+                        let layout = BackupLayout::new(restore_path);
+                        let errors: Vec<_> = renames
+                            .iter()
+                            .filter_map(|(old, new)| layout.migrate_renamed_game(old, new).err())
+                            .collect();
+                        (renames, errors)
+                    },
+                    |(renames, errors)| Message::MigrateRenamedGames {
+                        renames,
+                        errors: Some(errors),
+                    },
+                )
+            }
+            Message::MigrateRenamedGames {
+                errors: Some(errors), ..
+            } => {
+                if errors.is_empty() {
+                    self.close_modal()
+                } else {
+                    self.show_modal(Modal::Errors { errors })
+                }
+            }
+            Message::ExtractedFiles {
+                destination,
+                successful,
+            } => {
+                if successful {
+                    self.update(Message::OpenDir { path: destination })
+                } else {
+                    self.show_error(Error::SomeEntriesFailed)
+                }
+            }
             Message::OpenDirSubject(subject) => {
                 let path = match subject {
                     BrowseSubject::BackupTarget => self.config.backup.path.clone(),
@@ -2242,6 +2490,9 @@ impl App {
                     BrowseSubject::CustomGameFile(i, j) => {
                         StrictPath::new(self.config.custom_games[i].files[j].clone())
                     }
+                    BrowseSubject::CustomGameExclude(i, j) => {
+                        StrictPath::new(self.config.custom_games[i].exclude[j].clone())
+                    }
                     BrowseSubject::BackupFilterIgnoredPath(i) => self.config.backup.filter.ignored_paths[i].clone(),
                 };
 
@@ -2371,6 +2622,10 @@ impl App {
                         &mut self.config.custom_games[i].registry[j],
                         &mut self.text_histories.custom_games[i].registry[j],
                     ),
+                    UndoSubject::CustomGameExclude(i, j) => shortcut.apply_to_string_field(
+                        &mut self.config.custom_games[i].exclude[j],
+                        &mut self.text_histories.custom_games[i].exclude[j],
+                    ),
                     UndoSubject::BackupFilterIgnoredPath(i) => shortcut.apply_to_strict_path_field(
                         &mut self.config.backup.filter.ignored_paths[i],
                         &mut self.text_histories.backup_filter_ignored_paths[i],
@@ -2460,11 +2715,26 @@ impl App {
                 self.save_config();
                 Task::none()
             }
+            Message::SelectedConflictPolicy(policy) => {
+                self.config.restore.conflicts = policy;
+                self.save_config();
+                Task::none()
+            }
             Message::EditedCompressionLevel(value) => {
                 self.config.backup.format.set_level(value);
                 self.save_config();
                 Task::none()
             }
+            Message::ToggleBackupEncryption(enabled) => {
+                self.config.backup.format.encryption.enabled = enabled;
+                self.save_config();
+                Task::none()
+            }
+            Message::ToggleRestoreSnapshot(enabled) => {
+                self.config.restore.snapshot = enabled;
+                self.save_config();
+                Task::none()
+            }
             Message::ToggleCloudSynchronize => {
                 self.config.cloud.synchronize = !self.config.cloud.synchronize;
                 self.save_config();
@@ -2517,6 +2787,25 @@ impl App {
                     }
                     Task::none()
                 }
+                GameAction::Consolidate => {
+                    match self.restore_screen.log.consolidate(
+                        &game,
+                        &self.config.backup.format,
+                        self.config.backup.retention,
+                    ) {
+                        Ok(true) => {
+                            BackupLayout::new(self.config.restore.path.clone()).prune_blobs();
+                            // The selected backup may have been removed by the retention limits.
+                            self.backups_to_restore.remove(&game);
+                            self.handle_restore(RestorePhase::Start {
+                                preview: true,
+                                games: Some(HashSet::from([game])),
+                            })
+                        }
+                        Ok(false) => Task::none(),
+                        Err(e) => self.show_error(e),
+                    }
+                }
                 GameAction::MakeAlias => self.customize_game_as_alias(game),
             },
             Message::Scrolled { subject, position } => {
@@ -2779,6 +3068,19 @@ impl App {
                 }
                 Task::none()
             }
+            Message::EnteredPassphrase {
+                passphrase,
+                restore,
+                games,
+            } => {
+                self.passphrase = Some(passphrase);
+                self.text_histories.clear_modal_fields();
+                if restore {
+                    self.handle_restore(RestorePhase::Confirm { games })
+                } else {
+                    self.handle_backup(BackupPhase::Confirm { games })
+                }
+            }
             Message::FinalizeRemote(remote) => self.configure_remote(remote),
             Message::ModalChangePage(page) => {
                 if let Some(modal) = self.modal.as_mut() {
@@ -535,17 +535,20 @@ pub fn restore_preview<'a>(ongoing: &Operation, filtered: bool) -> Element<'a> {
     )
 }
 
-pub fn validate_backups<'a>(ongoing: &Operation) -> Element<'a> {
+pub fn validate_backups<'a>(ongoing: &Operation, modifiers: &keyboard::Modifiers) -> Element<'a> {
+    let deep = modifiers.shift();
+
     template(
         text(match ongoing {
             Operation::ValidateBackups { cancelling: false, .. } => TRANSLATOR.cancel_button(),
             Operation::ValidateBackups { cancelling: true, .. } => TRANSLATOR.cancelling_button(),
+            _ if deep => TRANSLATOR.validate_deep_button(),
             _ => TRANSLATOR.validate_button(),
         })
         .width(WIDTH)
         .align_x(alignment::Horizontal::Center),
         match ongoing {
-            Operation::Idle => Some(Message::ValidateBackups(ValidatePhase::Start)),
+            Operation::Idle => Some(Message::ValidateBackups(ValidatePhase::Start { deep })),
             Operation::ValidateBackups { cancelling: false, .. } => Some(Message::CancelOperation),
             _ => None,
         },
@@ -553,6 +556,26 @@ pub fn validate_backups<'a>(ongoing: &Operation) -> Element<'a> {
     )
 }
 
+pub fn undo_restore<'a>(ongoing: &Operation) -> Element<'a> {
+    template(
+        text(TRANSLATOR.undo_restore_button())
+            .width(WIDTH)
+            .align_x(alignment::Horizontal::Center),
+        matches!(ongoing, Operation::Idle).then_some(Message::UndoRestore { snapshots: None }),
+        None,
+    )
+}
+
+pub fn backup_stats<'a>(ongoing: &Operation) -> Element<'a> {
+    template(
+        text(TRANSLATOR.statistics_button())
+            .width(WIDTH)
+            .align_x(alignment::Horizontal::Center),
+        matches!(ongoing, Operation::Idle).then_some(Message::ShowBackupStats { stats: None }),
+        None,
+    )
+}
+
 pub fn show_game_notes<'a>(game: String, notes: Vec<manifest::Note>) -> Element<'a> {
     template_bare(
         Icon::Info.text_narrow(),
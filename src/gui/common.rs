@@ -1,4 +1,4 @@
-use std::collections::{BTreeSet, HashSet};
+use std::collections::{BTreeMap, HashSet};
 
 use iced::Length;
 
@@ -12,16 +12,17 @@ use crate::{
     prelude::{CommandError, Error, Finality, Privacy, StrictPath, SyncDirection},
     resource::{
         config::{
-            BackupFormat, CloudFilter, CustomGameKind, Integration, RedirectKind, Root, SecondaryManifestConfigKind,
-            SortKey, Theme, ZipCompression,
+            BackupFormat, CloudFilter, ConflictPolicy, CustomGameKind, Integration, RedirectKind, Root,
+            SecondaryManifestConfigKind, SortKey, Theme, ZipCompression,
         },
         manifest::{self, Manifest, ManifestUpdate, Store},
     },
     scan::{
+        encryption::Passphrase,
         game_filter,
-        layout::{Backup, BackupLayout, GameLayout},
+        layout::{Backup, BackupLayout, BackupLayoutStats, GameLayout, ValidationIssue},
         registry::RegistryItem,
-        BackupInfo, Launchers, ScanInfo, ScanKind, SteamShortcuts,
+        BackupId, BackupInfo, Launchers, ScanInfo, ScanKind, SteamShortcuts,
     },
 };
 
@@ -53,7 +54,7 @@ pub enum BackupPhase {
         launchers: Launchers,
     },
     GameScanned {
-        scan_info: Option<ScanInfo>,
+        scan_info: Option<Box<ScanInfo>>,
         backup_info: Option<BackupInfo>,
     },
     CloudSync,
@@ -76,7 +77,7 @@ pub enum RestorePhase {
         layout: BackupLayout,
     },
     GameScanned {
-        scan_info: Option<ScanInfo>,
+        scan_info: Option<Box<ScanInfo>>,
         backup_info: Option<BackupInfo>,
         game_layout: Box<GameLayout>,
     },
@@ -85,7 +86,9 @@ pub enum RestorePhase {
 
 #[derive(Debug, Clone)]
 pub enum ValidatePhase {
-    Start,
+    Start {
+        deep: bool,
+    },
     Load,
     RegisterCommands {
         subjects: Vec<String>,
@@ -94,6 +97,7 @@ pub enum ValidatePhase {
     GameScanned {
         game: String,
         valid: bool,
+        issues: Vec<ValidationIssue>,
     },
     Done,
 }
@@ -138,6 +142,7 @@ pub enum Message {
     EditedCustomGaleAliasDisplay(usize, bool),
     EditedCustomGameFile(usize, EditAction),
     EditedCustomGameRegistry(usize, EditAction),
+    EditedCustomGameExclude(usize, EditAction),
     EditedExcludeStoreScreenshots(bool),
     EditedCloudFilter(CloudFilter),
     EditedBackupFilterIgnoredPath(EditAction),
@@ -219,6 +224,32 @@ pub enum Message {
     OpenDirFailure {
         path: StrictPath,
     },
+    /// Restore a file or folder from a game's selected backup into another folder.
+    /// When the destination is unset, the user will be asked to choose one.
+    ExtractFiles {
+        game: String,
+        path: StrictPath,
+        destination: Option<StrictPath>,
+    },
+    ExtractedFiles {
+        destination: StrictPath,
+        successful: bool,
+    },
+    /// Restore the snapshots made before the last restore.
+    /// When unset, they still need to be looked up.
+    UndoRestore {
+        snapshots: Option<BTreeMap<String, BackupId>>,
+    },
+    ShowBackupStats {
+        stats: Option<BackupLayoutStats>,
+    },
+    /// Games renamed in the manifest that still have backups under their old names.
+    FoundRenamedGames(BTreeMap<String, String>),
+    /// When `errors` is unset, the backups still need to be moved.
+    MigrateRenamedGames {
+        renames: BTreeMap<String, String>,
+        errors: Option<Vec<Error>>,
+    },
     OpenUrlFailure {
         url: String,
     },
@@ -233,7 +264,10 @@ pub enum Message {
     SelectedTheme(Theme),
     SelectedBackupFormat(BackupFormat),
     SelectedBackupCompression(ZipCompression),
+    SelectedConflictPolicy(ConflictPolicy),
     EditedCompressionLevel(i32),
+    ToggleBackupEncryption(bool),
+    ToggleRestoreSnapshot(bool),
     ToggleCloudSynchronize,
     GameAction {
         action: GameAction,
@@ -282,6 +316,11 @@ pub enum Message {
         finality: Finality,
     },
     RcloneMonitor(rclone_monitor::Event),
+    EnteredPassphrase {
+        passphrase: Passphrase,
+        restore: bool,
+        games: Option<HashSet<String>>,
+    },
     FinalizeRemote(Remote),
     EditedModalField(ModalField),
     ModalChangePage(usize),
@@ -313,6 +352,10 @@ impl Message {
                     i,
                     EditAction::Change(j, globetter::Pattern::escape(&crate::path::render_pathbuf(&path))),
                 ),
+                BrowseSubject::CustomGameExclude(i, j) => Message::EditedCustomGameExclude(
+                    i,
+                    EditAction::Change(j, globetter::Pattern::escape(&crate::path::render_pathbuf(&path))),
+                ),
                 BrowseSubject::BackupFilterIgnoredPath(i) => {
                     Message::EditedBackupFilterIgnoredPath(EditAction::Change(i, crate::path::render_pathbuf(&path)))
                 }
@@ -354,7 +397,8 @@ pub enum Operation {
     },
     ValidateBackups {
         cancelling: bool,
-        faulty_games: BTreeSet<String>,
+        deep: bool,
+        faulty_games: BTreeMap<String, Vec<ValidationIssue>>,
     },
     Cloud {
         direction: SyncDirection,
@@ -395,9 +439,10 @@ impl Operation {
         }
     }
 
-    pub fn new_validate_backups() -> Self {
+    pub fn new_validate_backups(deep: bool) -> Self {
         Self::ValidateBackups {
             cancelling: false,
+            deep,
             faulty_games: Default::default(),
         }
     }
@@ -681,6 +726,7 @@ pub enum BrowseSubject {
     RedirectSource(usize),
     RedirectTarget(usize),
     CustomGameFile(usize, usize),
+    CustomGameExclude(usize, usize),
     BackupFilterIgnoredPath(usize),
 }
 
@@ -707,6 +753,7 @@ pub enum UndoSubject {
     CustomGameAlias(usize),
     CustomGameFile(usize, usize),
     CustomGameRegistry(usize, usize),
+    CustomGameExclude(usize, usize),
     BackupFilterIgnoredPath(usize),
     BackupFilterIgnoredRegistry(usize),
     RcloneExecutable,
@@ -734,6 +781,7 @@ impl UndoSubject {
             | UndoSubject::CustomGameAlias(_)
             | UndoSubject::CustomGameFile(_, _)
             | UndoSubject::CustomGameRegistry(_, _)
+            | UndoSubject::CustomGameExclude(_, _)
             | UndoSubject::BackupFilterIgnoredPath(_)
             | UndoSubject::BackupFilterIgnoredRegistry(_)
             | UndoSubject::RcloneExecutable
@@ -815,6 +863,7 @@ pub enum GameAction {
     Comment,
     Lock,
     Unlock,
+    Consolidate,
     MakeAlias,
 }
 
@@ -826,6 +875,7 @@ impl GameAction {
         invented: bool,
         has_backups: bool,
         locked: bool,
+        differential: bool,
     ) -> Vec<Self> {
         let mut options = vec![];
 
@@ -856,6 +906,10 @@ impl GameAction {
             } else {
                 options.push(Self::Lock);
             }
+
+            if differential && !operating {
+                options.push(Self::Consolidate);
+            }
         }
 
         if !invented {
@@ -880,6 +934,7 @@ impl GameAction {
             GameAction::Comment => Icon::Comment,
             GameAction::Lock => Icon::Lock,
             GameAction::Unlock => Icon::LockOpen,
+            GameAction::Consolidate => Icon::CallMerge,
             GameAction::MakeAlias => Icon::Edit,
         }
     }
@@ -908,6 +963,7 @@ impl ToString for GameAction {
             Self::Comment => TRANSLATOR.comment_button(),
             Self::Lock => TRANSLATOR.lock_button(),
             Self::Unlock => TRANSLATOR.unlock_button(),
+            Self::Consolidate => TRANSLATOR.consolidate_button(),
             Self::MakeAlias => TRANSLATOR.alias_label(),
         }
     }
@@ -459,6 +459,50 @@ pub fn custom_games<'a>(
                                         })
                                         .push(button::add_nested(Message::EditedCustomGameRegistry, i)),
                                 )
+                        })
+                        .push_if(config.custom_games[i].kind() == CustomGameKind::Game, || {
+                            Row::new()
+                                .spacing(10)
+                                .push(
+                                    Column::new()
+                                        .width(left_side)
+                                        .padding(padding::top(top_side))
+                                        .push(text(TRANSLATOR.custom_exclude_label())),
+                                )
+                                .push(
+                                    x.exclude
+                                        .iter()
+                                        .enumerate()
+                                        .fold(Column::new().spacing(4), |column, (ii, _)| {
+                                            column.push(
+                                                Row::new()
+                                                    .align_y(Alignment::Center)
+                                                    .spacing(20)
+                                                    .push(button::move_up_nested(
+                                                        Message::EditedCustomGameExclude,
+                                                        i,
+                                                        ii,
+                                                    ))
+                                                    .push(button::move_down_nested(
+                                                        Message::EditedCustomGameExclude,
+                                                        i,
+                                                        ii,
+                                                        x.exclude.len(),
+                                                    ))
+                                                    .push(histories.input(UndoSubject::CustomGameExclude(i, ii)))
+                                                    .push(button::choose_folder(
+                                                        BrowseSubject::CustomGameExclude(i, ii),
+                                                        modifiers,
+                                                    ))
+                                                    .push(button::remove_nested(
+                                                        Message::EditedCustomGameExclude,
+                                                        i,
+                                                        ii,
+                                                    )),
+                                            )
+                                        })
+                                        .push(button::add_nested(Message::EditedCustomGameExclude, i)),
+                                )
                         });
                 }
 
@@ -10,7 +10,7 @@ use crate::{
         common::{Message, TreeNodeKey},
         icon::Icon,
         style,
-        widget::{checkbox, text, Button, Column, Container, IcedParentExt, Row},
+        widget::{checkbox, text, Button, Column, Container, IcedParentExt, Row, Tooltip},
     },
     lang::TRANSLATOR,
     path::StrictPath,
@@ -140,6 +140,27 @@ impl FileTreeNode {
             )
         };
 
+        let make_extractor = || match (&self.path, scan_kind) {
+            (FileTreeNodePath::File(path), ScanKind::Restore) => Some(
+                Tooltip::new(
+                    Button::new(Icon::Download.text_small())
+                        .on_press(Message::ExtractFiles {
+                            game: game_name.to_string(),
+                            path: path.clone(),
+                            destination: None,
+                        })
+                        .class(style::Button::Primary)
+                        .padding(5)
+                        .height(25),
+                    text(TRANSLATOR.extract_button()).size(16),
+                    iced::widget::tooltip::Position::Top,
+                )
+                .gap(5)
+                .class(style::Container::Tooltip),
+            ),
+            _ => None,
+        };
+
         if self.nodes.is_empty() {
             return Container::new(
                 Row::new()
@@ -173,6 +194,12 @@ impl FileTreeNode {
                             .faded(self.duplicated.resolved())
                             .view()
                     })
+                    .push_if(self.scanned_file.as_ref().is_some_and(|(_, f)| f.conflict), || {
+                        Badge::new(&TRANSLATOR.badge_conflict()).view()
+                    })
+                    .push_if(self.scanned_file.as_ref().is_some_and(|(_, f)| f.filtered), || {
+                        Badge::new(&TRANSLATOR.badge_filtered()).view()
+                    })
                     .push_maybe(
                         self.error
                             .as_ref()
@@ -195,7 +222,8 @@ impl FileTreeNode {
                             let size = TRANSLATOR.adjusted_size(f.size);
                             Badge::new(&size).faded(f.ignored).view()
                         })
-                    }),
+                    })
+                    .push_maybe(make_extractor()),
             );
         } else if self.nodes.len() == 1 {
             let keys: Vec<_> = self.nodes.keys().cloned().collect();
@@ -247,6 +275,7 @@ impl FileTreeNode {
                             ),
                             FileTreeNodePath::RegistryKey(..) | FileTreeNodePath::RegistryValue(..) => None,
                         })
+                        .push_maybe(make_extractor())
                         .push_maybe({
                             let total_bytes = self.calculate_directory_size(true);
                             let total_size = total_bytes.map(|bytes| TRANSLATOR.adjusted_size(bytes));
@@ -325,6 +354,9 @@ impl FileTreeNode {
         node.error = error.map(|x| x.message());
         node.duplicated = duplicated;
         node.change = change;
+        if scanned_file.as_ref().is_some_and(|(_, f)| f.filtered) {
+            node.ignored = true;
+        }
         node.scanned_file = scanned_file;
 
         if let Some(registry_values) = registry_values {
@@ -21,9 +21,10 @@ use crate::{
         widget::{checkbox, pick_list, text, Button, Column, Container, IcedButtonExt, IcedParentExt, Row, Tooltip},
     },
     lang::TRANSLATOR,
+    prelude::Error,
     resource::{
         cache::Cache,
-        config::{Config, Sort},
+        config::{BackupFormats, Config, Retention, Sort},
         manifest::{self, Manifest, Os},
     },
     scan::{
@@ -296,6 +297,11 @@ impl GameListEntry {
                                                 .as_ref()
                                                 .map(|backup| backup.locked())
                                                 .unwrap_or_default(),
+                                            self.scan_info
+                                                .backup
+                                                .as_ref()
+                                                .map(|backup| !backup.full())
+                                                .unwrap_or_default(),
                                         );
                                         let game_name = self.scan_info.game_name.clone();
 
@@ -836,6 +842,23 @@ impl GameList {
         true
     }
 
+    /// Returns whether a new backup was made.
+    pub fn consolidate(&mut self, game: &str, format: &BackupFormats, retention: Retention) -> Result<bool, Error> {
+        let Some(index) = self.find_game(game) else {
+            return Ok(false);
+        };
+        let entry = &mut self.entries[index];
+        let Some(backup) = &entry.scan_info.backup else {
+            return Ok(false);
+        };
+        let Some(layout) = &mut entry.game_layout else {
+            return Ok(false);
+        };
+
+        let consolidated = layout.consolidate(&backup.id(), &chrono::Utc::now(), format, retention)?;
+        Ok(consolidated.is_some())
+    }
+
     pub fn save_layout(&mut self, game: &str) {
         let Some(index) = self.find_game(game) else { return };
         let entry = &mut self.entries[index];
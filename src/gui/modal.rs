@@ -1,4 +1,4 @@
-use std::collections::{BTreeSet, HashSet};
+use std::collections::{BTreeMap, HashSet};
 
 use iced::{
     padding,
@@ -24,6 +24,10 @@ use crate::{
         config::{Config, Root},
         manifest,
     },
+    scan::{
+        encryption::Passphrase,
+        layout::{BackupLayoutStats, ValidationIssue},
+    },
 };
 
 const CHANGES_PER_PAGE: usize = 500;
@@ -134,7 +138,7 @@ pub enum Modal {
     NoMissingRoots,
     ConfirmAddMissingRoots(Vec<Root>),
     BackupValidation {
-        games: BTreeSet<String>,
+        games: BTreeMap<String, Vec<ValidationIssue>>,
     },
     AppUpdate {
         release: crate::metadata::Release,
@@ -157,22 +161,37 @@ pub enum Modal {
         game: String,
         notes: Vec<manifest::Note>,
     },
+    BackupStats {
+        stats: BackupLayoutStats,
+    },
+    ConfirmMigrateRenames {
+        renames: BTreeMap<String, String>,
+    },
+    /// Shown before a backup or restore that needs a passphrase we don't have yet.
+    EnterPassphrase {
+        restore: bool,
+        games: Option<HashSet<String>>,
+    },
 }
 
 impl Modal {
     pub fn variant(&self) -> ModalVariant {
         match self {
             Self::Exiting | Self::UpdatingManifest => ModalVariant::Loading,
-            Self::Error { .. } | Self::Errors { .. } | Self::NoMissingRoots | Self::GameNotes { .. } => {
-                ModalVariant::Info
-            }
+            Self::Error { .. }
+            | Self::Errors { .. }
+            | Self::NoMissingRoots
+            | Self::GameNotes { .. }
+            | Self::BackupStats { .. } => ModalVariant::Info,
             Self::ConfirmBackup { .. }
             | Self::ConfirmRestore { .. }
             | Self::ConfirmAddMissingRoots(..)
             | Self::ConfigureFtpRemote { .. }
             | Self::ConfigureSmbRemote { .. }
             | Self::ConfigureWebDavRemote { .. }
-            | Self::AppUpdate { .. } => ModalVariant::Confirm,
+            | Self::AppUpdate { .. }
+            | Self::ConfirmMigrateRenames { .. }
+            | Self::EnterPassphrase { .. } => ModalVariant::Confirm,
             Self::BackupValidation { games } => {
                 if games.is_empty() {
                     ModalVariant::Info
@@ -230,6 +249,9 @@ impl Modal {
             Self::ConfigureSmbRemote { .. } => RemoteChoice::Smb.to_string(),
             Self::ConfigureWebDavRemote { .. } => RemoteChoice::WebDav.to_string(),
             Self::GameNotes { game, .. } => game.clone(),
+            Self::BackupStats { .. } => TRANSLATOR.statistics_button(),
+            Self::ConfirmMigrateRenames { .. } => TRANSLATOR.confirm_migrate_renames(),
+            Self::EnterPassphrase { .. } => TRANSLATOR.enter_passphrase(),
         }
     }
 
@@ -239,7 +261,8 @@ impl Modal {
             | Self::Errors { .. }
             | Self::NoMissingRoots
             | Self::BackupValidation { .. }
-            | Self::GameNotes { .. } => Some(Message::CloseModal),
+            | Self::GameNotes { .. }
+            | Self::BackupStats { .. } => Some(Message::CloseModal),
             Self::Exiting => None,
             Self::ConfirmBackup { games } => Some(Message::Backup(BackupPhase::Start {
                 preview: false,
@@ -254,6 +277,10 @@ impl Modal {
             Self::ConfirmAddMissingRoots(missing) => Some(Message::ConfirmAddMissingRoots(missing.clone())),
             Self::AppUpdate { release } => Some(Message::OpenUrlAndCloseModal(release.url.clone())),
             Self::UpdatingManifest => None,
+            Self::ConfirmMigrateRenames { renames } => Some(Message::MigrateRenamedGames {
+                renames: renames.clone(),
+                errors: None,
+            }),
             Self::ConfirmCloudSync { direction, state, .. } => {
                 if state.done() {
                     Some(Message::CloseModal)
@@ -264,6 +291,13 @@ impl Modal {
                     })
                 }
             }
+            Self::EnterPassphrase { restore, games } => {
+                Passphrase::new(histories.modal.password.current()).map(|passphrase| Message::EnteredPassphrase {
+                    passphrase,
+                    restore: *restore,
+                    games: games.clone(),
+                })
+            }
             Self::ConfigureFtpRemote => {
                 let host = histories.modal.host.current();
                 let port = histories.modal.port.current();
@@ -347,7 +381,7 @@ impl Modal {
                             preview: false,
                             repair: true,
                             jump: false,
-                            games: Some(games.iter().cloned().collect()),
+                            games: Some(games.keys().cloned().collect()),
                         })),
                     )]
                 }
@@ -364,7 +398,10 @@ impl Modal {
             | Self::ConfigureFtpRemote { .. }
             | Self::ConfigureSmbRemote { .. }
             | Self::ConfigureWebDavRemote { .. }
-            | Self::GameNotes { .. } => vec![],
+            | Self::GameNotes { .. }
+            | Self::BackupStats { .. }
+            | Self::ConfirmMigrateRenames { .. }
+            | Self::EnterPassphrase { .. } => vec![],
         }
     }
 
@@ -387,8 +424,11 @@ impl Modal {
             | Self::AppUpdate { .. }
             | Self::UpdatingManifest => (),
             Self::BackupValidation { games } => {
-                for game in games.iter().sorted() {
-                    col = col.push(text(game))
+                for (game, issues) in games {
+                    col = col.push(text(game));
+                    for issue in issues {
+                        col = col.push(text(TRANSLATOR.validation_issue(issue)).size(14));
+                    }
                 }
             }
             Self::ConfirmCloudSync {
@@ -438,6 +478,11 @@ impl Modal {
                     .push(ModalField::view(ModalInputKind::Username, histories))
                     .push(ModalField::view(ModalInputKind::Password, histories));
             }
+            Self::EnterPassphrase { .. } => {
+                col = col
+                    .width(500)
+                    .push(ModalField::view(ModalInputKind::Password, histories));
+            }
             Self::ConfigureWebDavRemote { provider, .. } => {
                 col = col
                     .width(500)
@@ -451,6 +496,30 @@ impl Modal {
                         ModalField::WebDavProvider,
                     ));
             }
+            Self::BackupStats { stats } => {
+                col = col.push(text(TRANSLATOR.overall())).push(
+                    TRANSLATOR
+                        .backup_stats(&stats.overall)
+                        .into_iter()
+                        .fold(Column::new().width(Length::Fill), |parent, line| {
+                            parent.push(text(line).size(14))
+                        }),
+                );
+                for (game, stats) in stats.games.iter().sorted_by_key(|(_, x)| std::cmp::Reverse(x.bytes)) {
+                    col = col.push(
+                        Row::new()
+                            .width(Length::Fill)
+                            .spacing(20)
+                            .push(text(game).size(14).width(Length::Fill))
+                            .push(text(TRANSLATOR.adjusted_size(stats.bytes)).size(14)),
+                    );
+                }
+            }
+            Self::ConfirmMigrateRenames { renames } => {
+                for (old, new) in renames {
+                    col = col.push(text(TRANSLATOR.renamed_game(old, new)).size(14));
+                }
+            }
             Self::GameNotes { notes, .. } => {
                 col = notes.iter().fold(col, |parent, note| {
                     parent.push(
@@ -533,7 +602,10 @@ impl Modal {
             | Self::ConfigureFtpRemote { .. }
             | Self::ConfigureSmbRemote { .. }
             | Self::ConfigureWebDavRemote { .. }
-            | Self::GameNotes { .. } => (),
+            | Self::GameNotes { .. }
+            | Self::BackupStats { .. }
+            | Self::ConfirmMigrateRenames { .. }
+            | Self::EnterPassphrase { .. } => (),
         }
     }
 
@@ -571,7 +643,10 @@ impl Modal {
             | Self::ConfigureFtpRemote { .. }
             | Self::ConfigureSmbRemote { .. }
             | Self::ConfigureWebDavRemote { .. }
-            | Self::GameNotes { .. } => (),
+            | Self::GameNotes { .. }
+            | Self::BackupStats { .. }
+            | Self::ConfirmMigrateRenames { .. }
+            | Self::EnterPassphrase { .. } => (),
         }
     }
 
@@ -593,7 +668,10 @@ impl Modal {
             | Self::ConfigureFtpRemote { .. }
             | Self::ConfigureSmbRemote { .. }
             | Self::ConfigureWebDavRemote { .. }
-            | Self::GameNotes { .. } => (),
+            | Self::GameNotes { .. }
+            | Self::BackupStats { .. }
+            | Self::ConfirmMigrateRenames { .. }
+            | Self::EnterPassphrase { .. } => (),
         }
     }
 
@@ -613,7 +691,10 @@ impl Modal {
             | Self::ConfigureFtpRemote { .. }
             | Self::ConfigureSmbRemote { .. }
             | Self::ConfigureWebDavRemote { .. }
-            | Self::GameNotes { .. } => false,
+            | Self::GameNotes { .. }
+            | Self::BackupStats { .. }
+            | Self::ConfirmMigrateRenames { .. }
+            | Self::EnterPassphrase { .. } => false,
         }
     }
 
@@ -633,7 +714,10 @@ impl Modal {
             | Self::ConfigureFtpRemote { .. }
             | Self::ConfigureSmbRemote { .. }
             | Self::ConfigureWebDavRemote { .. }
-            | Self::GameNotes { .. } => 2,
+            | Self::GameNotes { .. }
+            | Self::BackupStats { .. }
+            | Self::ConfirmMigrateRenames { .. }
+            | Self::EnterPassphrase { .. } => 2,
         }
     }
 
@@ -20,7 +20,7 @@ use crate::{
     prelude::{AVAILABLE_PARALELLISM, STEAM_DECK},
     resource::{
         cache::Cache,
-        config::{BackupFormat, CloudFilter, Config, SortKey, Theme, ZipCompression},
+        config::{BackupFormat, CloudFilter, Config, ConflictPolicy, Encryption, SortKey, Theme, ZipCompression},
         manifest::{Manifest, Store},
     },
     scan::{DuplicateDetector, Duplication, OperationStatus, ScanKind},
@@ -174,7 +174,9 @@ impl Restore {
                     .push(button::toggle_all_scanned_games(
                         self.log.all_entries_selected(config, Self::SCAN_KIND),
                     ))
-                    .push(button::validate_backups(operation))
+                    .push(button::validate_backups(operation, modifiers))
+                    .push(button::undo_restore(operation))
+                    .push(button::backup_stats(operation))
                     .push(button::filter(Screen::Restore, self.log.search.show)),
             )
             .push(make_status_row(
@@ -524,7 +526,38 @@ pub fn other<'a>(
                                                 )),
                                                 _ => None,
                                             },
-                                        ),
+                                        )
+                                        .push_if(Encryption::can_apply_to(config.backup.format.chosen), || {
+                                            checkbox(
+                                                TRANSLATOR.backup_encryption_field(),
+                                                config.backup.format.encryption.enabled,
+                                                Message::ToggleBackupEncryption,
+                                            )
+                                        }),
+                                )
+                                .push(
+                                    Row::new()
+                                        .spacing(20)
+                                        .align_y(Alignment::Center)
+                                        .push(
+                                            Row::new()
+                                                .spacing(5)
+                                                .align_y(Alignment::Center)
+                                                .push(text(TRANSLATOR.conflict_policy_field()))
+                                                .push(
+                                                    pick_list(
+                                                        ConflictPolicy::ALL,
+                                                        Some(config.restore.conflicts),
+                                                        Message::SelectedConflictPolicy,
+                                                    )
+                                                    .class(style::PickList::Primary),
+                                                ),
+                                        )
+                                        .push(checkbox(
+                                            TRANSLATOR.snapshot_before_restore(),
+                                            config.restore.snapshot,
+                                            Message::ToggleRestoreSnapshot,
+                                        )),
                                 ),
                         )
                         .class(style::Container::GameListEntry),
@@ -208,6 +208,7 @@ pub struct CustomGameHistory {
     pub alias: TextHistory,
     pub files: Vec<TextHistory>,
     pub registry: Vec<TextHistory>,
+    pub exclude: Vec<TextHistory>,
 }
 
 #[derive(Default)]
@@ -297,6 +298,7 @@ impl TextHistories {
             alias: TextHistory::raw(&game.alias.clone().unwrap_or_default()),
             files: game.files.iter().map(|x| TextHistory::raw(x)).collect(),
             registry: game.registry.iter().map(|x| TextHistory::raw(x)).collect(),
+            exclude: game.exclude.iter().map(|x| TextHistory::raw(x)).collect(),
         };
         self.custom_games.push(history);
     }
@@ -341,6 +343,11 @@ impl TextHistories {
                 .get(*i)
                 .and_then(|x| x.registry.get(*j).map(|y| y.current()))
                 .unwrap_or_default(),
+            UndoSubject::CustomGameExclude(i, j) => self
+                .custom_games
+                .get(*i)
+                .and_then(|x| x.exclude.get(*j).map(|y| y.current()))
+                .unwrap_or_default(),
             UndoSubject::BackupFilterIgnoredPath(i) => self
                 .backup_filter_ignored_paths
                 .get(*i)
@@ -401,6 +408,9 @@ impl TextHistories {
             UndoSubject::CustomGameRegistry(i, j) => {
                 Box::new(move |value| Message::EditedCustomGameRegistry(i, EditAction::Change(j, value)))
             }
+            UndoSubject::CustomGameExclude(i, j) => {
+                Box::new(move |value| Message::EditedCustomGameExclude(i, EditAction::Change(j, value)))
+            }
             UndoSubject::BackupFilterIgnoredPath(i) => {
                 Box::new(move |value| Message::EditedBackupFilterIgnoredPath(EditAction::Change(i, value)))
             }
@@ -441,6 +451,7 @@ impl TextHistories {
             UndoSubject::CustomGameAlias(_) => TRANSLATOR.custom_game_name_placeholder(),
             UndoSubject::CustomGameFile(_, _) => "".to_string(),
             UndoSubject::CustomGameRegistry(_, _) => "".to_string(),
+            UndoSubject::CustomGameExclude(_, _) => "".to_string(),
             UndoSubject::BackupFilterIgnoredPath(_) => "".to_string(),
             UndoSubject::BackupFilterIgnoredRegistry(_) => "".to_string(),
             UndoSubject::RcloneExecutable => TRANSLATOR.executable_label(),
@@ -459,6 +470,7 @@ impl TextHistories {
             | UndoSubject::RedirectSource(_)
             | UndoSubject::RedirectTarget(_)
             | UndoSubject::CustomGameFile(_, _)
+            | UndoSubject::CustomGameExclude(_, _)
             | UndoSubject::BackupFilterIgnoredPath(_)
             | UndoSubject::RcloneExecutable => (!path_appears_valid(&current)).then_some(text_input::Icon {
                 font: crate::gui::font::ICONS,
@@ -10,10 +10,14 @@ use unic_langid::LanguageIdentifier;
 use crate::{
     prelude::{CommandError, Error, StrictPath, VARIANT},
     resource::{
-        config::{BackupFormat, CustomGameKind, RedirectKind, Root, SortKey, Theme, ZipCompression},
+        config::{BackupFormat, ConflictPolicy, CustomGameKind, RedirectKind, Root, SortKey, Theme, ZipCompression},
         manifest::Store,
     },
-    scan::{game_filter, BackupError, OperationStatus, OperationStepDecision, ScanChange},
+    scan::{
+        game_filter,
+        layout::{BackupStats, OrphanKind, ValidationIssue},
+        BackupError, OperationStatus, OperationStepDecision, ScanChange,
+    },
 };
 
 const PATH: &str = "path";
@@ -30,6 +34,10 @@ const CODE: &str = "code";
 const MESSAGE: &str = "message";
 const APP: &str = "app";
 const GAME: &str = "game";
+const NAME: &str = "name";
+const BACKUP: &str = "backup";
+const QUOTA: &str = "quota";
+const FORMAT: &str = "format";
 const VERSION: &str = "version";
 
 pub const TRANSLATOR: Translator = Translator {};
@@ -369,6 +377,14 @@ impl Translator {
         translate("button-unlock")
     }
 
+    pub fn consolidate_button(&self) -> String {
+        translate("button-consolidate")
+    }
+
+    pub fn extract_button(&self) -> String {
+        translate("button-extract")
+    }
+
     pub fn handle_error(&self, error: &Error) -> String {
         match error {
             Error::ConfigInvalid { why } => self.config_is_invalid(why),
@@ -378,9 +394,19 @@ impl Translator {
             Error::CliUnableToRequestConfirmation => self.cli_unable_to_request_confirmation(),
             Error::CliBackupIdWithMultipleGames => self.cli_backup_id_with_multiple_games(),
             Error::CliInvalidBackupId => self.cli_invalid_backup_id(),
+            Error::CliInvalidGlob { glob } => self.cli_invalid_glob(glob),
             Error::SomeEntriesFailed => self.some_entries_failed(),
             Error::CannotPrepareBackupTarget { path } => self.cannot_prepare_backup_target(path),
             Error::RestorationSourceInvalid { path } => self.restoration_source_is_invalid(path),
+            Error::BackupPassphraseRequired => self.backup_passphrase_required(),
+            Error::BackupPassphraseInvalid => self.backup_passphrase_invalid(),
+            Error::BackupEncryptionFailed => self.backup_encryption_failed(),
+            Error::BackupQuotaExceeded { size, quota } => self.backup_quota_exceeded(*size, *quota),
+            Error::InvalidBackups => self.invalid_backups(),
+            Error::RestoreSnapshotFailed => self.restore_snapshot_failed(),
+            Error::NoRestoreToUndo => self.no_restore_to_undo(),
+            Error::UnsupportedBackupFormat { format } => self.unsupported_backup_format(format),
+            Error::NewerBackupsDependOnChain => self.newer_backups_depend_on_chain(),
             Error::RegistryIssue => self.registry_issue(),
             Error::UnableToOpenDir(path) => self.unable_to_open_dir(path),
             Error::UnableToOpenUrl(url) => self.unable_to_open_url(url),
@@ -403,6 +429,9 @@ impl Translator {
             }
             Error::CloudConflict => TRANSLATOR.prefix_error(&TRANSLATOR.cloud_synchronize_conflict()),
             Error::GameDidNotLaunch { why } => format!("{}\n\n{}", self.game_did_not_launch(), self.prefix_error(why)),
+            Error::UnableToWatchSaves { why } => {
+                format!("{}\n\n{}", self.unable_to_watch_saves(), self.prefix_error(why))
+            }
         }
     }
 
@@ -459,6 +488,12 @@ impl Translator {
         translate("cli-invalid-backup-id")
     }
 
+    pub fn cli_invalid_glob(&self, glob: &str) -> String {
+        let mut args = FluentArgs::new();
+        args.set(PATH, glob);
+        translate_args("cli-invalid-glob", &args)
+    }
+
     pub fn cloud_not_configured(&self) -> String {
         translate("cloud-not-configured")
     }
@@ -723,6 +758,120 @@ impl Translator {
         translate("backups-are-invalid")
     }
 
+    pub fn overall(&self) -> String {
+        translate("overall")
+    }
+
+    /// Each line is a separate field, and the growth entries are indented under their heading.
+    pub fn backup_stats(&self, stats: &BackupStats) -> Vec<String> {
+        let date = |x: &chrono::DateTime<chrono::Utc>| {
+            chrono::DateTime::<chrono::Local>::from(*x)
+                .format("%Y-%m-%dT%H:%M:%S")
+                .to_string()
+        };
+
+        let mut lines = vec![
+            format!("{}: {}", translate("file-size"), self.adjusted_size(stats.bytes)),
+            format!(
+                "{}: {} ({})",
+                translate("stats-full-backups"),
+                stats.full_backups,
+                self.adjusted_size(stats.full_bytes)
+            ),
+            format!(
+                "{}: {} ({})",
+                translate("stats-differential-backups"),
+                stats.differential_backups,
+                self.adjusted_size(stats.differential_bytes)
+            ),
+            format!(
+                "{}: {}",
+                translate("stats-duplicate-data"),
+                self.adjusted_size(stats.duplicate_bytes)
+            ),
+        ];
+        if let Some(oldest) = &stats.oldest {
+            lines.push(format!("{}: {}", translate("stats-oldest-backup"), date(oldest)));
+        }
+        if let Some(newest) = &stats.newest {
+            lines.push(format!("{}: {}", translate("stats-newest-backup"), date(newest)));
+        }
+        if !stats.growth.is_empty() {
+            lines.push(format!("{}:", translate("stats-growth")));
+            for (month, bytes) in &stats.growth {
+                lines.push(format!("  {month}: {}{}", ADD_SYMBOL, self.adjusted_size(*bytes)));
+            }
+        }
+
+        lines
+    }
+
+    pub fn no_renamed_games(&self) -> String {
+        translate("no-renamed-games")
+    }
+
+    pub fn renamed_game(&self, old: &str, new: &str) -> String {
+        let mut args = FluentArgs::new();
+        args.set(GAME, old);
+        args.set(NAME, new);
+        translate_args("renamed-game", &args)
+    }
+
+    pub fn confirm_migrate_renames(&self) -> String {
+        translate("confirm-migrate-renames")
+    }
+
+    pub fn no_orphans(&self) -> String {
+        translate("no-orphans")
+    }
+
+    pub fn orphans_summary(&self, total: usize, size: u64) -> String {
+        let mut args = FluentArgs::new();
+        args.set(TOTAL, total);
+        args.set(TOTAL_SIZE, self.adjusted_size(size));
+        translate_args("orphans-summary", &args)
+    }
+
+    pub fn confirm_remove_orphans(&self, total: usize, size: u64, path: &StrictPath) -> String {
+        let mut args = FluentArgs::new();
+        args.set(TOTAL, total);
+        args.set(TOTAL_SIZE, self.adjusted_size(size));
+        args.set(PATH, path.render());
+        translate_args("confirm-remove-orphans", &args)
+    }
+
+    pub fn orphan_kind(&self, kind: OrphanKind) -> String {
+        translate(match kind {
+            OrphanKind::File => "orphan-kind-file",
+            OrphanKind::Archive => "orphan-kind-archive",
+            OrphanKind::GameFolder => "orphan-kind-game-folder",
+            OrphanKind::InvalidMapping => "orphan-kind-invalid-mapping",
+            OrphanKind::Blob => "orphan-kind-blob",
+        })
+    }
+
+    pub fn validation_issue(&self, issue: &ValidationIssue) -> String {
+        let mut args = FluentArgs::new();
+        args.set(BACKUP, issue.backup());
+
+        match issue {
+            ValidationIssue::UnreadableBackup { .. } => translate_args("validation-unreadable-backup", &args),
+            ValidationIssue::MissingFile { file, .. } => {
+                args.set(PATH, file);
+                translate_args("validation-missing-file", &args)
+            }
+            ValidationIssue::UnreadableFile { file, .. } => {
+                args.set(PATH, file);
+                translate_args("validation-unreadable-file", &args)
+            }
+            ValidationIssue::ChangedFile { file, .. } => {
+                args.set(PATH, file);
+                translate_args("validation-changed-file", &args)
+            }
+            ValidationIssue::ChangedRegistry { .. } => translate_args("validation-changed-registry", &args),
+        }
+    }
+
     pub fn confirm_add_missing_roots(&self, roots: &[Root]) -> String {
         use std::fmt::Write;
         let mut msg = translate("confirm-add-missing-roots") + "\n";
@@ -800,6 +949,18 @@ impl Translator {
         translate("button-validate")
     }
 
+    pub fn validate_deep_button(&self) -> String {
+        translate("button-validate-deep")
+    }
+
+    pub fn undo_restore_button(&self) -> String {
+        translate("button-undo-restore")
+    }
+
+    pub fn statistics_button(&self) -> String {
+        translate("button-statistics")
+    }
+
     pub fn override_manifest_button(&self) -> String {
         translate("button-override-manifest")
     }
@@ -848,6 +1009,51 @@ impl Translator {
         translate("registry-issue")
     }
 
+    pub fn backup_passphrase_required(&self) -> String {
+        translate("backup-passphrase-required")
+    }
+
+    pub fn backup_passphrase_invalid(&self) -> String {
+        translate("backup-passphrase-invalid")
+    }
+
+    pub fn backup_quota_exceeded(&self, size: u64, quota: u64) -> String {
+        let mut args = FluentArgs::new();
+        args.set(TOTAL_SIZE, self.adjusted_size(size));
+        args.set(QUOTA, self.adjusted_size(quota));
+        translate_args("backup-quota-exceeded", &args)
+    }
+
+    pub fn invalid_backups(&self) -> String {
+        translate("invalid-backups")
+    }
+
+    pub fn restore_snapshot_failed(&self) -> String {
+        translate("restore-snapshot-failed")
+    }
+
+    pub fn no_restore_to_undo(&self) -> String {
+        translate("no-restore-to-undo")
+    }
+
+    pub fn unsupported_backup_format(&self, format: &BackupFormat) -> String {
+        let mut args = FluentArgs::new();
+        args.set(FORMAT, self.backup_format(format));
+        translate_args("unsupported-backup-format", &args)
+    }
+
+    pub fn newer_backups_depend_on_chain(&self) -> String {
+        translate("newer-backups-depend-on-chain")
+    }
+
+    pub fn backup_encryption_failed(&self) -> String {
+        translate("backup-encryption-failed")
+    }
+
+    pub fn enter_passphrase(&self) -> String {
+        translate("cli-enter-passphrase")
+    }
+
     #[allow(unused)]
     pub fn unable_to_browse_file_system(&self) -> String {
         translate("unable-to-browse-file-system")
@@ -925,22 +1131,30 @@ impl Translator {
         translate("field-custom-registry")
     }
 
+    pub fn custom_exclude_label(&self) -> String {
+        translate("field-custom-exclude")
+    }
+
     pub fn sort_label(&self) -> String {
         translate("field-sort")
     }
 
     pub fn store(&self, store: &Store) -> String {
         translate(match store {
+            Store::Bottles => "store-bottles",
             Store::Ea => "store-ea",
             Store::Epic => "store-epic",
             Store::Gog => "store-gog",
             Store::GogGalaxy => "store-gog-galaxy",
             Store::Heroic => "store-heroic",
+            Store::Itch => "store-itch",
             Store::Legendary => "store-legendary",
             Store::Lutris => "store-lutris",
             Store::Microsoft => "store-microsoft",
+            Store::Minecraft => "store-minecraft",
             Store::Origin => "store-origin",
             Store::Prime => "store-prime",
+            Store::RetroArch => "store-retroarch",
             Store::Steam => "store-steam",
             Store::Uplay => "store-uplay",
             Store::OtherHome => "store-other-home",
@@ -994,6 +1208,8 @@ impl Translator {
         translate(match key {
             BackupFormat::Simple => "backup-format-simple",
             BackupFormat::Zip => "backup-format-zip",
+            BackupFormat::TarZstd => "backup-format-tar-zstd",
+            BackupFormat::Dedup => "backup-format-dedup",
         })
     }
 
@@ -1006,6 +1222,15 @@ impl Translator {
         })
     }
 
+    pub fn conflict_policy(&self, policy: &ConflictPolicy) -> String {
+        translate(match policy {
+            ConflictPolicy::Overwrite => "conflict-policy-overwrite",
+            ConflictPolicy::Skip => "conflict-policy-skip",
+            ConflictPolicy::KeepBoth => "conflict-policy-keep-both",
+            ConflictPolicy::Prompt => "conflict-policy-prompt",
+        })
+    }
+
     pub fn theme_name(&self, theme: &Theme) -> String {
         translate(match theme {
             Theme::Light => "theme-light",
@@ -1136,10 +1361,36 @@ impl Translator {
         translate("field-backup-compression")
     }
 
+    pub fn conflict_policy_field(&self) -> String {
+        translate("field-conflict-policy")
+    }
+
+    pub fn badge_conflict(&self) -> String {
+        translate("badge-conflict")
+    }
+
+    pub fn badge_filtered(&self) -> String {
+        translate("badge-filtered")
+    }
+
+    pub fn cli_game_line_item_conflict(&self) -> String {
+        format!("    - {}", translate("cli-game-line-item-conflict"))
+    }
+
+    pub fn prompt_conflict(&self, path: &str) -> String {
+        let mut args = FluentArgs::new();
+        args.set(PATH, path);
+        translate_args("prompt-conflict", &args)
+    }
+
     pub fn backup_compression_level_field(&self) -> String {
         translate("field-backup-compression-level")
     }
 
+    pub fn backup_encryption_field(&self) -> String {
+        translate("field-backup-encryption")
+    }
+
     pub fn manifest_label(&self) -> String {
         self.field(&translate("label-manifest"))
     }
@@ -1262,6 +1513,10 @@ impl Translator {
         translate("synchronize-automatically")
     }
 
+    pub fn snapshot_before_restore(&self) -> String {
+        translate("snapshot-before-restore")
+    }
+
     pub fn prefer_alias_display(&self) -> String {
         translate("prefer-alias-display")
     }
@@ -1394,6 +1649,10 @@ impl Translator {
         translate("game-did-not-launch")
     }
 
+    pub fn unable_to_watch_saves(&self) -> String {
+        translate("unable-to-watch-saves")
+    }
+
     pub fn back_up_one_game_confirm(&self, game: &str) -> String {
         let mut args = FluentArgs::new();
         args.set(GAME, game);
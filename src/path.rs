@@ -622,6 +622,16 @@ impl StrictPath {
         std::fs::File::create(self.as_std_path_buf()?)
     }
 
+    /// Like `create`, but the new file can also be read back.
+    pub fn create_rw(&self) -> std::io::Result<std::fs::File> {
+        std::fs::OpenOptions::new()
+            .read(true)
+            .write(true)
+            .create(true)
+            .truncate(true)
+            .open(self.as_std_path_buf()?)
+    }
+
     pub fn open(&self) -> std::io::Result<std::fs::File> {
         std::fs::File::open(self.as_std_path_buf()?)
     }
@@ -630,6 +640,14 @@ impl StrictPath {
         std::fs::write(self.as_std_path_buf()?, content.as_bytes())
     }
 
+    pub fn write_bytes(&self, content: &[u8]) -> std::io::Result<()> {
+        std::fs::write(self.as_std_path_buf()?, content)
+    }
+
+    pub fn read_bytes(&self) -> std::io::Result<Vec<u8>> {
+        std::fs::read(self.as_std_path_buf()?)
+    }
+
     pub fn move_to(&self, new_path: &StrictPath) -> std::io::Result<()> {
         std::fs::rename(self.as_std_path_buf()?, new_path.as_std_path_buf()?)
     }
@@ -95,6 +95,9 @@ pub enum Error {
     CliUnableToRequestConfirmation,
     CliBackupIdWithMultipleGames,
     CliInvalidBackupId,
+    CliInvalidGlob {
+        glob: String,
+    },
     SomeEntriesFailed,
     CannotPrepareBackupTarget {
         path: StrictPath,
@@ -102,7 +105,20 @@ pub enum Error {
     RestorationSourceInvalid {
         path: StrictPath,
     },
-    #[cfg_attr(not(target_os = "windows"), allow(unused))]
+    BackupPassphraseRequired,
+    BackupPassphraseInvalid,
+    BackupEncryptionFailed,
+    BackupQuotaExceeded {
+        size: u64,
+        quota: u64,
+    },
+    InvalidBackups,
+    RestoreSnapshotFailed,
+    NoRestoreToUndo,
+    UnsupportedBackupFormat {
+        format: crate::resource::config::BackupFormat,
+    },
+    NewerBackupsDependOnChain,
     RegistryIssue,
     UnableToOpenDir(StrictPath),
     UnableToOpenUrl(String),
@@ -115,6 +131,9 @@ pub enum Error {
     GameDidNotLaunch {
         why: String,
     },
+    UnableToWatchSaves {
+        why: String,
+    },
 }
 #[derive(Clone, Debug, PartialEq, Eq)]
 pub enum CommandError {
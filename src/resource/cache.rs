@@ -20,6 +20,9 @@ pub struct Cache {
     pub roots: BTreeSet<Root>,
     pub backup: Backup,
     pub restore: Restore,
+    /// Games that were renamed in the primary manifest (old name -> new name),
+    /// so that their backups can be moved to the new name.
+    pub renamed_games: BTreeMap<String, String>,
 }
 
 #[derive(Clone, Debug, Default, PartialEq, Eq, serde::Serialize, serde::Deserialize)]
@@ -121,6 +124,18 @@ impl Cache {
         if update.modified {
             cached.updated = Some(update.timestamp);
         }
+
+        for (old, new) in update.renames {
+            // Follow chains of renames so that each old name points to the latest one.
+            for target in self.renamed_games.values_mut() {
+                if *target == old {
+                    target.clone_from(&new);
+                }
+            }
+            self.renamed_games.retain(|k, v| k != v);
+            self.renamed_games.remove(&new);
+            self.renamed_games.insert(old, new);
+        }
     }
 
     pub fn add_roots(&mut self, roots: &Vec<Root>) {
@@ -15,7 +15,7 @@ use crate::{
         manifest::{self, CloudMetadata, Manifest, Store},
         ResourceFile, SaveableResourceFile,
     },
-    scan::{registry::RegistryItem, ScanKind},
+    scan::{encryption::Passphrase, registry::RegistryItem, ScanKind},
 };
 
 pub const MANIFEST_URL: &str =
@@ -269,16 +269,20 @@ impl ToString for Theme {
 )]
 #[serde(tag = "store", rename_all = "camelCase")]
 pub enum Root {
+    Bottles(root::Bottles),
     Ea(root::Ea),
     Epic(root::Epic),
     Gog(root::Gog),
     GogGalaxy(root::GogGalaxy),
     Heroic(root::Heroic),
+    Itch(root::Itch),
     Legendary(root::Legendary),
     Lutris(root::Lutris),
     Microsoft(root::Microsoft),
+    Minecraft(root::Minecraft),
     Origin(root::Origin),
     Prime(root::Prime),
+    RetroArch(root::RetroArch),
     Steam(root::Steam),
     Uplay(root::Uplay),
     OtherHome(root::OtherHome),
@@ -298,19 +302,23 @@ impl Default for Root {
 impl Root {
     pub fn new(path: impl Into<StrictPath>, store: Store) -> Self {
         match store {
+            Store::Bottles => Self::Bottles(root::Bottles { path: path.into() }),
             Store::Ea => Self::Ea(root::Ea { path: path.into() }),
             Store::Epic => Self::Epic(root::Epic { path: path.into() }),
             Store::Gog => Self::Gog(root::Gog { path: path.into() }),
             Store::GogGalaxy => Self::GogGalaxy(root::GogGalaxy { path: path.into() }),
             Store::Heroic => Self::Heroic(root::Heroic { path: path.into() }),
+            Store::Itch => Self::Itch(root::Itch { path: path.into() }),
             Store::Legendary => Self::Legendary(root::Legendary { path: path.into() }),
             Store::Lutris => Self::Lutris(root::Lutris {
                 path: path.into(),
                 database: None,
             }),
             Store::Microsoft => Self::Microsoft(root::Microsoft { path: path.into() }),
+            Store::Minecraft => Self::Minecraft(root::Minecraft { path: path.into() }),
             Store::Origin => Self::Origin(root::Origin { path: path.into() }),
             Store::Prime => Self::Prime(root::Prime { path: path.into() }),
+            Store::RetroArch => Self::RetroArch(root::RetroArch { path: path.into() }),
             Store::Steam => Self::Steam(root::Steam { path: path.into() }),
             Store::Uplay => Self::Uplay(root::Uplay { path: path.into() }),
             Store::OtherHome => Self::OtherHome(root::OtherHome { path: path.into() }),
@@ -324,16 +332,20 @@ impl Root {
 
     pub fn store(&self) -> Store {
         match self {
+            Self::Bottles(_) => Store::Bottles,
             Self::Ea(_) => Store::Ea,
             Self::Epic(_) => Store::Epic,
             Self::Gog(_) => Store::Gog,
             Self::GogGalaxy(_) => Store::GogGalaxy,
             Self::Heroic(_) => Store::Heroic,
+            Self::Itch(_) => Store::Itch,
             Self::Legendary(_) => Store::Legendary,
             Self::Lutris(_) => Store::Lutris,
             Self::Microsoft(_) => Store::Microsoft,
+            Self::Minecraft(_) => Store::Minecraft,
             Self::Origin(_) => Store::Origin,
             Self::Prime(_) => Store::Prime,
+            Self::RetroArch(_) => Store::RetroArch,
             Self::Steam(_) => Store::Steam,
             Self::Uplay(_) => Store::Uplay,
             Self::OtherHome(_) => Store::OtherHome,
@@ -347,16 +359,20 @@ impl Root {
 
     pub fn path(&self) -> &StrictPath {
         match self {
+            Self::Bottles(root::Bottles { path }) => path,
             Self::Ea(root::Ea { path }) => path,
             Self::Epic(root::Epic { path }) => path,
             Self::Gog(root::Gog { path }) => path,
             Self::GogGalaxy(root::GogGalaxy { path }) => path,
             Self::Heroic(root::Heroic { path }) => path,
+            Self::Itch(root::Itch { path }) => path,
             Self::Legendary(root::Legendary { path }) => path,
             Self::Lutris(root::Lutris { path, .. }) => path,
             Self::Microsoft(root::Microsoft { path }) => path,
+            Self::Minecraft(root::Minecraft { path }) => path,
             Self::Origin(root::Origin { path }) => path,
             Self::Prime(root::Prime { path }) => path,
+            Self::RetroArch(root::RetroArch { path }) => path,
             Self::Steam(root::Steam { path }) => path,
             Self::Uplay(root::Uplay { path }) => path,
             Self::OtherHome(root::OtherHome { path }) => path,
@@ -370,16 +386,20 @@ impl Root {
 
     pub fn path_mut(&mut self) -> &mut StrictPath {
         match self {
+            Self::Bottles(root::Bottles { path }) => path,
             Self::Ea(root::Ea { path }) => path,
             Self::Epic(root::Epic { path }) => path,
             Self::Gog(root::Gog { path }) => path,
             Self::GogGalaxy(root::GogGalaxy { path }) => path,
             Self::Heroic(root::Heroic { path }) => path,
+            Self::Itch(root::Itch { path }) => path,
             Self::Legendary(root::Legendary { path }) => path,
             Self::Lutris(root::Lutris { path, .. }) => path,
             Self::Microsoft(root::Microsoft { path }) => path,
+            Self::Minecraft(root::Minecraft { path }) => path,
             Self::Origin(root::Origin { path }) => path,
             Self::Prime(root::Prime { path }) => path,
+            Self::RetroArch(root::RetroArch { path }) => path,
             Self::Steam(root::Steam { path }) => path,
             Self::Uplay(root::Uplay { path }) => path,
             Self::OtherHome(root::OtherHome { path }) => path,
@@ -475,6 +495,16 @@ impl ToString for RedirectKind {
     }
 }
 
+#[derive(Clone, Debug, Default, PartialEq, Eq, serde::Serialize, serde::Deserialize, schemars::JsonSchema)]
+#[serde(default, rename_all = "camelCase")]
+pub struct SteamAccountRedirect {
+    /// The Steam account that the backup was made from,
+    /// as either an account ID (SteamID3) or a SteamID64.
+    pub source: u64,
+    /// The Steam account to restore into, in either format.
+    pub target: u64,
+}
+
 #[derive(Debug, Default, Clone, serde::Serialize, serde::Deserialize, schemars::JsonSchema)]
 #[serde(default, rename_all = "camelCase")]
 pub struct CloudFilter {
@@ -526,8 +556,15 @@ pub struct BackupFilter {
     pub ignored_paths: Vec<StrictPath>,
     /// Globally ignored registry keys.
     pub ignored_registry: Vec<RegistryItem>,
+    /// Rules for ignoring files across all games.
+    pub file_rules: FileRules,
+    /// Rules for ignoring files for specific games, in addition to the global rules.
+    /// Each key is a game name.
+    pub game_file_rules: BTreeMap<String, FileRules>,
     #[serde(skip)]
     pub path_globs: Arc<Mutex<Option<globset::GlobSet>>>,
+    #[serde(skip)]
+    pub file_rule_patterns: Arc<Mutex<HashMap<String, regex::Regex>>>,
 }
 
 impl std::fmt::Debug for BackupFilter {
@@ -537,6 +574,8 @@ impl std::fmt::Debug for BackupFilter {
             .field("cloud", &self.cloud)
             .field("ignored_paths", &self.ignored_paths)
             .field("ignored_registry", &self.ignored_registry)
+            .field("file_rules", &self.file_rules)
+            .field("game_file_rules", &self.game_file_rules)
             .finish()
     }
 }
@@ -548,11 +587,15 @@ impl PartialEq for BackupFilter {
         self.exclude_store_screenshots == other.exclude_store_screenshots
             && self.ignored_paths == other.ignored_paths
             && self.ignored_registry == other.ignored_registry
+            && self.file_rules == other.file_rules
+            && self.game_file_rules == other.game_file_rules
     }
 }
 
 impl BackupFilter {
     pub fn build_globs(&mut self) {
+        self.build_file_rule_patterns();
+
         let mut path_globs = self.path_globs.lock().unwrap();
         if self.ignored_paths.is_empty() {
             *path_globs = None;
@@ -584,6 +627,45 @@ impl BackupFilter {
         *path_globs = builder.build().ok();
     }
 
+    fn build_file_rule_patterns(&mut self) {
+        let mut patterns = self.file_rule_patterns.lock().unwrap();
+        patterns.clear();
+
+        for rules in std::iter::once(&self.file_rules).chain(self.game_file_rules.values()) {
+            for pattern in &rules.ignored_patterns {
+                if patterns.contains_key(pattern) {
+                    continue;
+                }
+                match regex::Regex::new(pattern) {
+                    Ok(regex) => {
+                        patterns.insert(pattern.clone(), regex);
+                    }
+                    Err(e) => {
+                        log::warn!("Invalid file rule pattern: {pattern:?} | {e:?}");
+                    }
+                }
+            }
+        }
+    }
+
+    /// Whether a file should be ignored because of the global or game-specific file rules.
+    /// Unlike `is_path_ignored`, the file is still reported, but marked as ignored.
+    pub fn is_file_filtered(&self, game: &str, path: &StrictPath, size: u64) -> bool {
+        let game_rules = self.game_file_rules.get(game).filter(|x| !x.is_empty());
+        if self.file_rules.is_empty() && game_rules.is_none() {
+            return false;
+        }
+
+        let patterns = self.file_rule_patterns.lock().unwrap();
+        let rendered = path.render();
+        let mut modified = None;
+        let mut get_modified = || *modified.get_or_insert_with(|| path.get_mtime().ok().map(chrono::DateTime::from));
+
+        std::iter::once(&self.file_rules)
+            .chain(game_rules)
+            .any(|rules| rules.excludes(&rendered, size, &mut get_modified, &patterns))
+    }
+
     pub fn is_path_ignored(&self, item: &StrictPath) -> bool {
         if self.ignored_paths.is_empty() {
             return false;
@@ -612,6 +694,70 @@ impl BackupFilter {
     }
 }
 
+/// Rules for ignoring files based on their size, age, path, or extension.
+/// A file is ignored if any rule applies to it.
+#[derive(Clone, Debug, Default, PartialEq, Eq, serde::Serialize, serde::Deserialize, schemars::JsonSchema)]
+#[serde(default, rename_all = "camelCase")]
+pub struct FileRules {
+    /// Ignore files larger than this many megabytes (1 MB = 1,000,000 bytes).
+    pub max_size_mb: Option<u64>,
+    /// Ignore files last modified before this date (YYYY-MM-DD).
+    pub modified_after: Option<chrono::NaiveDate>,
+    /// Ignore files whose full path matches any of these regular expressions.
+    pub ignored_patterns: Vec<String>,
+    /// If not empty, only back up files with one of these extensions (e.g., `sav`).
+    pub included_extensions: Vec<String>,
+}
+
+impl FileRules {
+    pub fn is_empty(&self) -> bool {
+        self.max_size_mb.is_none()
+            && self.modified_after.is_none()
+            && self.ignored_patterns.is_empty()
+            && self.included_extensions.is_empty()
+    }
+
+    fn excludes(
+        &self,
+        path: &str,
+        size: u64,
+        modified: &mut impl FnMut() -> Option<chrono::DateTime<chrono::Utc>>,
+        patterns: &HashMap<String, regex::Regex>,
+    ) -> bool {
+        if self.max_size_mb.is_some_and(|max| size > max.saturating_mul(1_000_000)) {
+            return true;
+        }
+
+        if let Some(date) = self.modified_after {
+            if modified().is_some_and(|modified| modified.date_naive() < date) {
+                return true;
+            }
+        }
+
+        if self
+            .ignored_patterns
+            .iter()
+            .any(|x| patterns.get(x).is_some_and(|regex| regex.is_match(path)))
+        {
+            return true;
+        }
+
+        if !self.included_extensions.is_empty() {
+            let extension = std::path::Path::new(path).extension().map(|x| x.to_string_lossy());
+            let included = extension.is_some_and(|extension| {
+                self.included_extensions
+                    .iter()
+                    .any(|x| x.trim_start_matches('.').eq_ignore_ascii_case(&extension))
+            });
+            if !included {
+                return true;
+            }
+        }
+
+        false
+    }
+}
+
 /// Allows including/excluding specific file paths.
 /// Each outer key is a game name,
 /// and each nested key is a file path.
@@ -773,6 +919,14 @@ pub struct Retention {
     pub full: u8,
     /// Differential backups to keep. Range: 0-255.
     pub differential: u8,
+    /// Additional backups to keep based on when they were made.
+    pub calendar: CalendarRetention,
+    /// Maximum space in bytes for each game's backups.
+    /// When a new backup would exceed this, the oldest unlocked backups are removed.
+    pub game_quota: Option<u64>,
+    /// Maximum space in bytes for all games' backups in the backup folder.
+    /// When a new backup would exceed this, the game's oldest unlocked backups are removed.
+    pub total_quota: Option<u64>,
     #[serde(skip)]
     pub force_new_full: bool,
 }
@@ -795,6 +949,24 @@ impl Retention {
         }
     }
 
+    pub fn with_calendar(
+        self,
+        hours: Option<u16>,
+        daily: Option<u16>,
+        weekly: Option<u16>,
+        monthly: Option<u16>,
+    ) -> Self {
+        Self {
+            calendar: CalendarRetention {
+                hours: hours.unwrap_or(self.calendar.hours),
+                daily: daily.unwrap_or(self.calendar.daily),
+                weekly: weekly.unwrap_or(self.calendar.weekly),
+                monthly: monthly.unwrap_or(self.calendar.monthly),
+            },
+            ..self
+        }
+    }
+
     pub fn with_force_new_full(self, force: bool) -> Self {
         Self {
             force_new_full: force,
@@ -808,22 +980,107 @@ impl Default for Retention {
         Self {
             full: 1,
             differential: 0,
+            calendar: CalendarRetention::default(),
+            game_quota: None,
+            total_quota: None,
             force_new_full: false,
         }
     }
 }
 
+/// Grandfather-father-son retention.
+/// A backup is kept if either this policy or the count limits would keep it,
+/// so these settings can only extend how long backups are kept.
+/// Periods are based on UTC.
+#[derive(Clone, Debug, Copy, Default, Eq, PartialEq, serde::Serialize, serde::Deserialize, schemars::JsonSchema)]
+#[serde(default, rename_all = "camelCase")]
+pub struct CalendarRetention {
+    /// Keep every backup from this many recent hours. 0 to disable.
+    pub hours: u16,
+    /// Keep the newest backup from each of this many recent days. 0 to disable.
+    pub daily: u16,
+    /// Keep the newest backup from each of this many recent weeks. 0 to disable.
+    pub weekly: u16,
+    /// Keep the newest backup from each of this many recent months. 0 to disable.
+    pub monthly: u16,
+}
+
+impl CalendarRetention {
+    pub fn enabled(&self) -> bool {
+        self.hours > 0 || self.daily > 0 || self.weekly > 0 || self.monthly > 0
+    }
+
+    /// Returns the indices of the times that this policy would keep.
+    pub fn keep(&self, now: &chrono::DateTime<chrono::Utc>, times: &[chrono::DateTime<chrono::Utc>]) -> HashSet<usize> {
+        use chrono::Datelike;
+
+        let mut keep = HashSet::new();
+
+        if self.hours > 0 {
+            let cutoff = *now - chrono::TimeDelta::hours(self.hours as i64);
+            keep.extend(
+                times
+                    .iter()
+                    .enumerate()
+                    .filter(|(_, when)| **when >= cutoff)
+                    .map(|(i, _)| i),
+            );
+        }
+
+        // Each period is numbered so that consecutive periods differ by 1.
+        // Day 1 of the common era was a Monday, so weeks start on Monday.
+        type PeriodOf = fn(&chrono::DateTime<chrono::Utc>) -> i64;
+        let periods: [(u16, PeriodOf); 3] = [
+            (self.daily, |x| x.num_days_from_ce() as i64),
+            (self.weekly, |x| (x.num_days_from_ce() as i64 - 1).div_euclid(7)),
+            (self.monthly, |x| x.year() as i64 * 12 + x.month0() as i64),
+        ];
+
+        for (count, period) in periods {
+            if count == 0 {
+                continue;
+            }
+
+            let current = period(now);
+            let mut newest = HashMap::<i64, usize>::new();
+            for (i, when) in times.iter().enumerate() {
+                let candidate = period(when);
+                if current - candidate >= count as i64 {
+                    continue;
+                }
+                newest
+                    .entry(candidate)
+                    .and_modify(|kept| {
+                        if times[*kept] < *when {
+                            *kept = i;
+                        }
+                    })
+                    .or_insert(i);
+            }
+            keep.extend(newest.into_values());
+        }
+
+        keep
+    }
+}
+
 #[derive(Copy, Clone, Debug, Default, Eq, PartialEq, serde::Serialize, serde::Deserialize, schemars::JsonSchema)]
 #[serde(rename_all = "camelCase")]
 pub enum BackupFormat {
     #[default]
     Simple,
     Zip,
+    /// A tar archive compressed with zstd as a single stream.
+    #[serde(rename = "tar.zst", alias = "tarZstd")]
+    TarZstd,
+    /// File contents are stored once in a blob store shared by all games,
+    /// and each backup only records which blobs it references.
+    Dedup,
 }
 
 impl BackupFormat {
-    pub const ALL: &'static [Self] = &[Self::Simple, Self::Zip];
-    pub const ALL_NAMES: &'static [&'static str] = &["simple", "zip"];
+    pub const ALL: &'static [Self] = &[Self::Simple, Self::Zip, Self::TarZstd, Self::Dedup];
+    pub const ALL_NAMES: &'static [&'static str] = &["simple", "zip", "tar.zst", "dedup"];
 }
 
 impl std::str::FromStr for BackupFormat {
@@ -833,6 +1090,8 @@ impl std::str::FromStr for BackupFormat {
         match s {
             "simple" => Ok(Self::Simple),
             "zip" => Ok(Self::Zip),
+            "tar.zst" => Ok(Self::TarZstd),
+            "dedup" => Ok(Self::Dedup),
             _ => Err(format!("invalid backup format: {}", s)),
         }
     }
@@ -854,24 +1113,27 @@ pub struct BackupFormats {
     /// Settings for specific compression methods.
     /// In compression levels, higher numbers are slower, but save more space.
     pub compression: Compression,
+    /// Settings for encrypting backups at rest.
+    pub encryption: Encryption,
 }
 
 impl BackupFormats {
     pub fn level(&self) -> Option<i32> {
         match self.chosen {
-            BackupFormat::Simple => None,
+            BackupFormat::Simple | BackupFormat::Dedup => None,
             BackupFormat::Zip => match self.zip.compression {
                 ZipCompression::None => None,
                 ZipCompression::Deflate => Some(self.compression.deflate.level),
                 ZipCompression::Bzip2 => Some(self.compression.bzip2.level),
                 ZipCompression::Zstd => Some(self.compression.zstd.level),
             },
+            BackupFormat::TarZstd => Some(self.compression.zstd.level),
         }
     }
 
     pub fn set_level(&mut self, value: i32) {
         match self.chosen {
-            BackupFormat::Simple => {}
+            BackupFormat::Simple | BackupFormat::Dedup => {}
             BackupFormat::Zip => match self.zip.compression {
                 ZipCompression::None => {}
                 ZipCompression::Deflate => {
@@ -884,22 +1146,58 @@ impl BackupFormats {
                     self.compression.zstd.level = value;
                 }
             },
+            BackupFormat::TarZstd => {
+                self.compression.zstd.level = value;
+            }
         }
     }
 
     pub fn range(&self) -> Option<std::ops::RangeInclusive<i32>> {
         match self.chosen {
-            BackupFormat::Simple => None,
+            BackupFormat::Simple | BackupFormat::Dedup => None,
             BackupFormat::Zip => match self.zip.compression {
                 ZipCompression::None => None,
                 ZipCompression::Deflate => Some(DeflateCompression::RANGE),
                 ZipCompression::Bzip2 => Some(Bzip2Compression::RANGE),
                 ZipCompression::Zstd => Some(ZstdCompression::RANGE),
             },
+            BackupFormat::TarZstd => Some(ZstdCompression::RANGE),
         }
     }
 }
 
+#[derive(Clone, Debug, Default, Eq, PartialEq, serde::Serialize, serde::Deserialize, schemars::JsonSchema)]
+#[serde(default, rename_all = "camelCase")]
+pub struct Encryption {
+    /// Whether to encrypt new backups.
+    /// This only applies to the simple and zip formats.
+    pub enabled: bool,
+    /// Passphrase from which the encryption key is derived.
+    /// If not set, then the `LUDUSAVI_PASSPHRASE` environment variable will be used,
+    /// and the CLI will prompt for it when needed.
+    pub passphrase: Option<String>,
+}
+
+impl Encryption {
+    pub const PASSPHRASE_ENV_VAR: &'static str = "LUDUSAVI_PASSPHRASE";
+
+    pub fn can_apply_to(format: BackupFormat) -> bool {
+        matches!(format, BackupFormat::Simple | BackupFormat::Zip)
+    }
+
+    pub fn applies_to(&self, format: BackupFormat) -> bool {
+        self.enabled && Self::can_apply_to(format)
+    }
+
+    /// Passphrase from the config file or environment, if available.
+    pub fn passphrase(&self) -> Option<Passphrase> {
+        self.passphrase
+            .clone()
+            .and_then(Passphrase::new)
+            .or_else(|| std::env::var(Self::PASSPHRASE_ENV_VAR).ok().and_then(Passphrase::new))
+    }
+}
+
 #[derive(Clone, Debug, Default, Eq, PartialEq, serde::Serialize, serde::Deserialize, schemars::JsonSchema)]
 #[serde(default, rename_all = "camelCase")]
 pub struct ZipConfig {
@@ -1036,6 +1334,48 @@ pub struct BackupConfig {
     pub format: BackupFormats,
 }
 
+#[derive(Copy, Clone, Debug, Default, Eq, PartialEq, serde::Serialize, serde::Deserialize, schemars::JsonSchema)]
+#[serde(rename_all = "camelCase")]
+pub enum ConflictPolicy {
+    /// Replace the local file with the one from the backup.
+    #[default]
+    Overwrite,
+    /// Leave the local file as-is.
+    Skip,
+    /// Copy the local file aside, then replace it with the one from the backup.
+    #[serde(rename = "keep-both", alias = "keepBoth")]
+    KeepBoth,
+    /// Ask what to do for each file.
+    /// This is only possible in an interactive CLI session;
+    /// otherwise, the file is skipped.
+    Prompt,
+}
+
+impl ConflictPolicy {
+    pub const ALL: &'static [Self] = &[Self::Overwrite, Self::Skip, Self::KeepBoth, Self::Prompt];
+    pub const ALL_NAMES: &'static [&'static str] = &["overwrite", "skip", "keep-both", "prompt"];
+}
+
+impl std::str::FromStr for ConflictPolicy {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "overwrite" => Ok(Self::Overwrite),
+            "skip" => Ok(Self::Skip),
+            "keep-both" => Ok(Self::KeepBoth),
+            "prompt" => Ok(Self::Prompt),
+            _ => Err(format!("invalid conflict policy: {}", s)),
+        }
+    }
+}
+
+impl std::fmt::Display for ConflictPolicy {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(&TRANSLATOR.conflict_policy(self))
+    }
+}
+
 #[derive(Clone, Debug, PartialEq, Eq, serde::Serialize, serde::Deserialize, schemars::JsonSchema)]
 #[serde(default, rename_all = "camelCase")]
 pub struct RestoreConfig {
@@ -1047,6 +1387,15 @@ pub struct RestoreConfig {
     pub toggled_registry: ToggledRegistry,
     pub sort: Sort,
     pub reverse_redirects: bool,
+    /// What to do with local save files that were changed after the backup was made,
+    /// or that don't match any version in the backups.
+    pub conflicts: ConflictPolicy,
+    /// Before overwriting any save files,
+    /// back them up as a locked backup so that the restore can be undone.
+    pub snapshot: bool,
+    /// Restore saves from one Steam account into another.
+    /// This applies to files whose paths contain the source account's ID.
+    pub steam_accounts: Vec<SteamAccountRedirect>,
 }
 
 #[derive(Clone, Debug, PartialEq, Eq, serde::Serialize, serde::Deserialize, schemars::JsonSchema)]
@@ -1151,6 +1500,9 @@ pub struct CustomGame {
     pub files: Vec<String>,
     /// Any registry keys you want to back up.
     pub registry: Vec<String>,
+    /// Any files or directories within `files` that you want to skip.
+    /// These support the same placeholders and globs as `files`.
+    pub exclude: Vec<String>,
     #[serde(skip)]
     pub expanded: bool,
 }
@@ -1255,6 +1607,9 @@ impl Default for RestoreConfig {
             toggled_registry: Default::default(),
             sort: Default::default(),
             reverse_redirects: false,
+            conflicts: ConflictPolicy::Overwrite,
+            snapshot: false,
+            steam_accounts: vec![],
         }
     }
 }
@@ -1349,6 +1704,14 @@ impl Config {
                 "~/.var/app/com.heroicgameslauncher.hgl/config/heroic".to_string(),
                 Store::Heroic,
             ),
+            // Bottles:
+            ("~/.local/share/bottles".to_string(), Store::Bottles),
+            (
+                "~/.var/app/com.usebottles.bottles/data/bottles".to_string(),
+                Store::Bottles,
+            ),
+            // itch:
+            ("~/.var/app/io.itch.itch/config/itch".to_string(), Store::Itch),
             // Uplay:
             (format!("{}/Ubisoft/Ubisoft Game Launcher", pf32), Store::Uplay),
             (format!("{}/Ubisoft/Ubisoft Game Launcher", pf64), Store::Uplay),
@@ -1358,8 +1721,22 @@ impl Config {
             // Microsoft:
             (format!("{}/WindowsApps", pf32), Store::Microsoft),
             (format!("{}/WindowsApps", pf64), Store::Microsoft),
+            // Minecraft:
+            // The vanilla launcher's folder is already covered by the manifest.
+            (
+                "~/.var/app/org.prismlauncher.PrismLauncher/data/PrismLauncher".to_string(),
+                Store::Minecraft,
+            ),
             // Prime Gaming:
             ("C:/Amazon Games/Library".to_string(), Store::Prime),
+            // RetroArch:
+            ("C:/RetroArch-Win64".to_string(), Store::RetroArch),
+            ("~/.config/retroarch".to_string(), Store::RetroArch),
+            (
+                "~/.var/app/org.libretro.RetroArch/config/retroarch".to_string(),
+                Store::RetroArch,
+            ),
+            ("~/Library/Application Support/RetroArch".to_string(), Store::RetroArch),
             // EA app:
             (format!("{}/EA Games", pf32), Store::Ea),
             (format!("{}/EA Games", pf64), Store::Ea),
@@ -1367,6 +1744,10 @@ impl Config {
 
         if let Some(data_dir) = CommonPath::Data.get() {
             candidates.push((format!("{}/heroic", data_dir), Store::Heroic));
+            candidates.push((format!("{}/PrismLauncher", data_dir), Store::Minecraft));
+        }
+        if let Some(config_dir) = CommonPath::Config.get() {
+            candidates.push((format!("{}/itch", config_dir), Store::Itch));
         }
 
         let detected_steam = match steamlocate::SteamDir::locate() {
@@ -1995,6 +2376,9 @@ mod tests {
                     toggled_registry: Default::default(),
                     sort: Default::default(),
                     reverse_redirects: false,
+                    conflicts: ConflictPolicy::Overwrite,
+                    snapshot: false,
+                    steam_accounts: vec![],
                 },
                 scan: Default::default(),
                 apps: Apps {
@@ -2119,6 +2503,9 @@ mod tests {
                     toggled_registry: Default::default(),
                     sort: Default::default(),
                     reverse_redirects: false,
+                    conflicts: ConflictPolicy::Overwrite,
+                    snapshot: false,
+                    steam_accounts: vec![],
                 },
                 scan: Scan {
                     show_deselected_games: false,
@@ -2147,6 +2534,7 @@ mod tests {
                         prefer_alias: false,
                         files: vec![],
                         registry: vec![],
+                        exclude: vec![],
                         expanded: false,
                     },
                     CustomGame {
@@ -2157,6 +2545,7 @@ mod tests {
                         prefer_alias: false,
                         files: vec![s("Custom File 1"), s("Custom File 2"), s("Custom File 2"),],
                         registry: vec![s("Custom Registry 1"), s("Custom Registry 2"), s("Custom Registry 2"),],
+                        exclude: vec![],
                         expanded: false,
                     },
                 ],
@@ -2205,6 +2594,12 @@ backup:
       uplay: false
     ignoredPaths: []
     ignoredRegistry: []
+    fileRules:
+      maxSizeMb: ~
+      modifiedAfter: ~
+      ignoredPatterns: []
+      includedExtensions: []
+    gameFileRules: {}
   toggledPaths: {}
   toggledRegistry: {}
   sort:
@@ -2213,6 +2608,13 @@ backup:
   retention:
     full: 1
     differential: 0
+    calendar:
+      hours: 0
+      daily: 0
+      weekly: 0
+      monthly: 0
+    gameQuota: ~
+    totalQuota: ~
   format:
     chosen: simple
     zip:
@@ -2224,6 +2626,9 @@ backup:
         level: 6
       zstd:
         level: 10
+    encryption:
+      enabled: false
+      passphrase: ~
 restore:
   path: ~/restore
   ignoredGames:
@@ -2236,6 +2641,9 @@ restore:
     key: status
     reversed: false
   reverseRedirects: false
+  conflicts: overwrite
+  snapshot: false
+  steamAccounts: []
 scan:
   showDeselectedGames: false
   showUnchangedGames: false
@@ -2255,6 +2663,7 @@ customGames:
     integration: override
     files: []
     registry: []
+    exclude: []
   - name: Custom Game 2
     integration: extend
     files:
@@ -2265,11 +2674,13 @@ customGames:
       - Custom Registry 1
       - Custom Registry 2
       - Custom Registry 2
+    exclude: []
   - name: Alias
     integration: override
     alias: Other
     files: []
     registry: []
+    exclude: []
 "#
             .trim(),
             serde_yaml::to_string(&Config {
@@ -2316,6 +2727,9 @@ customGames:
                     toggled_registry: Default::default(),
                     sort: Default::default(),
                     reverse_redirects: false,
+                    conflicts: ConflictPolicy::Overwrite,
+                    snapshot: false,
+                    steam_accounts: vec![],
                 },
                 scan: Scan {
                     show_deselected_games: false,
@@ -2344,6 +2758,7 @@ customGames:
                         prefer_alias: false,
                         files: vec![],
                         registry: vec![],
+                        exclude: vec![],
                         expanded: false,
                     },
                     CustomGame {
@@ -2354,6 +2769,7 @@ customGames:
                         prefer_alias: false,
                         files: vec![s("Custom File 1"), s("Custom File 2"), s("Custom File 2"),],
                         registry: vec![s("Custom Registry 1"), s("Custom Registry 2"), s("Custom Registry 2"),],
+                        exclude: vec![],
                         expanded: false,
                     },
                     CustomGame {
@@ -2364,6 +2780,7 @@ customGames:
                         prefer_alias: false,
                         files: vec![],
                         registry: vec![],
+                        exclude: vec![],
                         expanded: false,
                     },
                 ],
@@ -2373,6 +2790,84 @@ customGames:
         );
     }
 
+    #[test]
+    fn uses_cli_names_for_backup_formats() {
+        for (format, name) in BackupFormat::ALL.iter().zip(BackupFormat::ALL_NAMES) {
+            assert_eq!(format!("\"{name}\""), serde_json::to_string(format).unwrap());
+            assert_eq!(*format, serde_json::from_str(&format!("\"{name}\"")).unwrap());
+        }
+        assert_eq!(BackupFormat::TarZstd, serde_json::from_str("\"tarZstd\"").unwrap());
+    }
+
+    #[test]
+    fn uses_cli_names_for_conflict_policies() {
+        for (policy, name) in ConflictPolicy::ALL.iter().zip(ConflictPolicy::ALL_NAMES) {
+            assert_eq!(format!("\"{name}\""), serde_json::to_string(policy).unwrap());
+            assert_eq!(*policy, serde_json::from_str(&format!("\"{name}\"")).unwrap());
+        }
+        assert_eq!(ConflictPolicy::KeepBoth, serde_json::from_str("\"keepBoth\"").unwrap());
+    }
+
+    mod file_rules {
+        use pretty_assertions::assert_eq;
+
+        use super::*;
+
+        fn excludes(rules: &FileRules, path: &str, size: u64, modified: Option<&str>) -> bool {
+            let mut filter = BackupFilter {
+                file_rules: rules.clone(),
+                ..Default::default()
+            };
+            filter.build_globs();
+            let patterns = filter.file_rule_patterns.lock().unwrap();
+            let modified = modified.map(|x| x.parse().unwrap());
+            rules.excludes(path, size, &mut || modified, &patterns)
+        }
+
+        #[test]
+        fn can_exclude_by_size() {
+            let rules = FileRules {
+                max_size_mb: Some(1),
+                ..Default::default()
+            };
+            assert_eq!(false, excludes(&rules, "/a.sav", 1_000_000, None));
+            assert_eq!(true, excludes(&rules, "/a.sav", 1_000_001, None));
+        }
+
+        #[test]
+        fn can_exclude_by_age() {
+            let rules = FileRules {
+                modified_after: Some(chrono::NaiveDate::from_ymd_opt(2024, 5, 1).unwrap()),
+                ..Default::default()
+            };
+            assert_eq!(true, excludes(&rules, "/a.sav", 1, Some("2024-04-30T23:59:59Z")));
+            assert_eq!(false, excludes(&rules, "/a.sav", 1, Some("2024-05-01T00:00:00Z")));
+            assert_eq!(false, excludes(&rules, "/a.sav", 1, None));
+        }
+
+        #[test]
+        fn can_exclude_by_pattern() {
+            let rules = FileRules {
+                ignored_patterns: vec![s(r"/cache/"), s("(invalid")],
+                ..Default::default()
+            };
+            assert_eq!(true, excludes(&rules, "/game/cache/a.sav", 1, None));
+            assert_eq!(false, excludes(&rules, "/game/saves/a.sav", 1, None));
+        }
+
+        #[test]
+        fn can_include_only_certain_extensions() {
+            let rules = FileRules {
+                included_extensions: vec![s("sav"), s(".DAT")],
+                ..Default::default()
+            };
+            assert_eq!(false, excludes(&rules, "/game/a.SAV", 1, None));
+            assert_eq!(false, excludes(&rules, "/game/b.dat", 1, None));
+            assert_eq!(true, excludes(&rules, "/game/c.log", 1, None));
+            assert_eq!(true, excludes(&rules, "/game.sav/noextension", 1, None));
+        }
+    }
+
     mod ignored_paths {
         use pretty_assertions::assert_eq;
 
@@ -2787,4 +3282,130 @@ customGames:
             );
         }
     }
+
+    mod calendar_retention {
+        use pretty_assertions::assert_eq;
+
+        use super::*;
+
+        fn at(year: i32, month: u32, day: u32, hour: u32) -> chrono::DateTime<chrono::Utc> {
+            chrono::NaiveDate::from_ymd_opt(year, month, day)
+                .unwrap()
+                .and_hms_opt(hour, 0, 0)
+                .unwrap()
+                .and_local_timezone(chrono::Utc)
+                .unwrap()
+        }
+
+        #[test]
+        fn keeps_nothing_when_disabled() {
+            let policy = CalendarRetention::default();
+            assert!(!policy.enabled());
+            assert_eq!(
+                HashSet::new(),
+                policy.keep(&at(2000, 1, 10, 12), &[at(2000, 1, 10, 11), at(2000, 1, 9, 11)])
+            );
+        }
+
+        #[test]
+        fn keeps_everything_within_hours() {
+            let policy = CalendarRetention {
+                hours: 24,
+                ..Default::default()
+            };
+            assert_eq!(
+                HashSet::from([1, 2]),
+                policy.keep(
+                    &at(2000, 1, 10, 12),
+                    &[at(2000, 1, 9, 11), at(2000, 1, 9, 12), at(2000, 1, 10, 11)]
+                )
+            );
+        }
+
+        #[test]
+        fn keeps_newest_per_day() {
+            let policy = CalendarRetention {
+                daily: 2,
+                ..Default::default()
+            };
+            assert_eq!(
+                HashSet::from([1, 3]),
+                policy.keep(
+                    &at(2000, 1, 10, 12),
+                    &[
+                        at(2000, 1, 9, 1),
+                        at(2000, 1, 9, 23),
+                        at(2000, 1, 10, 1),
+                        at(2000, 1, 10, 2),
+                        at(2000, 1, 8, 23),
+                    ]
+                )
+            );
+        }
+
+        #[test]
+        fn keeps_newest_per_week() {
+            let policy = CalendarRetention {
+                weekly: 2,
+                ..Default::default()
+            };
+            // 2000-01-10 was a Monday.
+            assert_eq!(
+                HashSet::from([1, 2]),
+                policy.keep(
+                    &at(2000, 1, 11, 12),
+                    &[
+                        at(2000, 1, 3, 1),
+                        at(2000, 1, 9, 1),
+                        at(2000, 1, 10, 1),
+                        at(2000, 1, 2, 1),
+                    ]
+                )
+            );
+        }
+
+        #[test]
+        fn keeps_newest_per_month() {
+            let policy = CalendarRetention {
+                monthly: 12,
+                ..Default::default()
+            };
+            assert_eq!(
+                HashSet::from([0, 2, 3]),
+                policy.keep(
+                    &at(2000, 6, 15, 12),
+                    &[
+                        at(1999, 7, 31, 1),
+                        at(1999, 6, 30, 1),
+                        at(2000, 5, 2, 1),
+                        at(2000, 6, 1, 1),
+                        at(2000, 5, 1, 1),
+                    ]
+                )
+            );
+        }
+
+        #[test]
+        fn combines_periods() {
+            let policy = CalendarRetention {
+                hours: 1,
+                daily: 1,
+                monthly: 2,
+                ..Default::default()
+            };
+            assert_eq!(
+                HashSet::from([0, 1, 2, 3]),
+                policy.keep(
+                    &at(2000, 6, 15, 12),
+                    &[
+                        at(2000, 6, 15, 12),
+                        at(2000, 6, 15, 11),
+                        at(2000, 5, 20, 1),
+                        at(2000, 6, 15, 12) + chrono::TimeDelta::minutes(-30),
+                        at(2000, 6, 15, 1),
+                    ]
+                )
+            );
+        }
+    }
 }
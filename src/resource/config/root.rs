@@ -1,5 +1,24 @@
 use crate::path::StrictPath;
 
+#[derive(
+    Clone,
+    Debug,
+    Default,
+    Eq,
+    PartialEq,
+    Ord,
+    PartialOrd,
+    Hash,
+    serde::Serialize,
+    serde::Deserialize,
+    schemars::JsonSchema,
+)]
+#[serde(default, rename_all = "camelCase")]
+pub struct Bottles {
+    /// Where the root is located on your system.
+    pub path: StrictPath,
+}
+
 #[derive(
     Clone,
     Debug,
@@ -95,6 +114,25 @@ pub struct Heroic {
     pub path: StrictPath,
 }
 
+#[derive(
+    Clone,
+    Debug,
+    Default,
+    Eq,
+    PartialEq,
+    Ord,
+    PartialOrd,
+    Hash,
+    serde::Serialize,
+    serde::Deserialize,
+    schemars::JsonSchema,
+)]
+#[serde(default, rename_all = "camelCase")]
+pub struct Itch {
+    /// Where the root is located on your system.
+    pub path: StrictPath,
+}
+
 #[derive(
     Clone,
     Debug,
@@ -154,6 +192,25 @@ pub struct Microsoft {
     pub path: StrictPath,
 }
 
+#[derive(
+    Clone,
+    Debug,
+    Default,
+    Eq,
+    PartialEq,
+    Ord,
+    PartialOrd,
+    Hash,
+    serde::Serialize,
+    serde::Deserialize,
+    schemars::JsonSchema,
+)]
+#[serde(default, rename_all = "camelCase")]
+pub struct Minecraft {
+    /// Where the root is located on your system.
+    pub path: StrictPath,
+}
+
 #[derive(
     Clone,
     Debug,
@@ -192,6 +249,25 @@ pub struct Prime {
     pub path: StrictPath,
 }
 
+#[derive(
+    Clone,
+    Debug,
+    Default,
+    Eq,
+    PartialEq,
+    Ord,
+    PartialOrd,
+    Hash,
+    serde::Serialize,
+    serde::Deserialize,
+    schemars::JsonSchema,
+)]
+#[serde(default, rename_all = "camelCase")]
+pub struct RetroArch {
+    /// Where the root is located on your system.
+    pub path: StrictPath,
+}
+
 #[derive(
     Clone,
     Debug,
@@ -5,10 +5,10 @@ use crate::{
     prelude::{app_dir, Error, StrictPath},
     resource::{
         cache::{self, Cache},
-        config::{Config, CustomGame, ManifestConfig},
+        config::{Config, CustomGame, ManifestConfig, Root},
         ResourceFile, SaveableResourceFile,
     },
-    scan::layout::escape_folder_name,
+    scan::{launchers, layout::escape_folder_name},
 };
 
 pub mod placeholder {
@@ -103,16 +103,20 @@ impl From<&str> for Os {
 )]
 #[serde(rename_all = "camelCase")]
 pub enum Store {
+    Bottles,
     Ea,
     Epic,
     Gog,
     GogGalaxy,
     Heroic,
+    Itch,
     Legendary,
     Lutris,
     Microsoft,
+    Minecraft,
     Origin,
     Prime,
+    RetroArch,
     Steam,
     Uplay,
     OtherHome,
@@ -127,16 +131,20 @@ pub enum Store {
 
 impl Store {
     pub const ALL: &'static [Self] = &[
+        Store::Bottles,
         Store::Ea,
         Store::Epic,
         Store::Gog,
         Store::GogGalaxy,
         Store::Heroic,
+        Store::Itch,
         Store::Legendary,
         Store::Lutris,
         Store::Microsoft,
+        Store::Minecraft,
         Store::Origin,
         Store::Prime,
+        Store::RetroArch,
         Store::Steam,
         Store::Uplay,
         Store::OtherHome,
@@ -177,6 +185,9 @@ pub enum Source {
     Primary,
     Custom,
     Secondary(String),
+    /// Games built from a root's own data, such as RetroArch playlists.
+    /// This is the path of the file that listed the game.
+    Generated(String),
 }
 
 #[derive(Clone, Debug, Default, PartialEq, Eq, serde::Serialize, serde::Deserialize)]
@@ -203,6 +214,9 @@ pub struct Game {
     pub cloud: CloudMetadata,
     #[serde(skip_serializing_if = "Vec::is_empty")]
     pub notes: Vec<Note>,
+    /// Paths to skip within `files`. This is only set by custom games.
+    #[serde(skip)]
+    pub exclude: Vec<String>,
     #[serde(skip)]
     pub sources: BTreeSet<Source>,
 }
@@ -247,6 +261,7 @@ impl Game {
             Source::Primary => true,
             Source::Custom => false,
             Source::Secondary(_) => true,
+            Source::Generated(_) => true,
         })
     }
 }
@@ -410,6 +425,9 @@ pub struct ManifestUpdate {
     pub etag: Option<String>,
     pub timestamp: chrono::DateTime<chrono::Utc>,
     pub modified: bool,
+    /// Games that were renamed in this update (old name -> new name).
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub renames: BTreeMap<String, String>,
 }
 
 impl ResourceFile for Manifest {
@@ -519,12 +537,22 @@ impl Manifest {
                 let mut manifest_bytes = vec![];
                 res.copy_to(&mut manifest_bytes).map_err(|_| cannot_update())?;
                 let manifest_string = String::from_utf8(manifest_bytes).map_err(|_| cannot_update())?;
-                if let Err(e) = Self::load_from_string(&manifest_string) {
-                    return Err(Error::ManifestInvalid {
-                        why: e.to_string(),
-                        identifier: identifier.clone(),
-                    });
-                }
+                let new = match Self::load_from_string(&manifest_string) {
+                    Ok(new) => new,
+                    Err(e) => {
+                        return Err(Error::ManifestInvalid {
+                            why: e.to_string(),
+                            identifier: identifier.clone(),
+                        });
+                    }
+                };
+
+                // Backups are stored by the primary manifest's titles, so we only track its renames.
+                let renames = if primary {
+                    Self::load().map(|old| old.find_renames(&new)).unwrap_or_default()
+                } else {
+                    BTreeMap::new()
+                };
 
                 path.write_with_content(&manifest_string).map_err(|_| cannot_update())?;
 
@@ -538,6 +566,7 @@ impl Manifest {
                     etag: new_etag,
                     timestamp: chrono::offset::Utc::now(),
                     modified: true,
+                    renames,
                 }))
             }
             reqwest::StatusCode::NOT_MODIFIED => Ok(Some(ManifestUpdate {
@@ -545,6 +574,7 @@ impl Manifest {
                 etag: old_etag,
                 timestamp: chrono::offset::Utc::now(),
                 modified: false,
+                renames: BTreeMap::new(),
             })),
             _ => Err(cannot_update()),
         }
@@ -605,6 +635,50 @@ impl Manifest {
         out
     }
 
+    /// Find games that were renamed between this manifest and a newer one.
+    /// A game is considered renamed if the newer manifest turns its title into an alias,
+    /// or if its Steam or GOG ID now belongs to a title that didn't exist before.
+    pub fn find_renames(&self, new: &Manifest) -> BTreeMap<String, String> {
+        let mut renames = BTreeMap::new();
+
+        let steam_ids = new.map_steam_ids_to_names();
+        let gog_ids = new.map_gog_ids_to_names();
+
+        for (name, game) in &self.0 {
+            if game.alias.is_some() {
+                continue;
+            }
+
+            let renamed = match new.0.get(name) {
+                Some(Game { alias: Some(alias), .. }) => Some(alias.clone()),
+                Some(_) => None,
+                None => game
+                    .steam
+                    .id
+                    .iter()
+                    .chain(&game.id.steam_extra)
+                    .find_map(|id| steam_ids.get(id))
+                    .or_else(|| {
+                        game.gog
+                            .id
+                            .iter()
+                            .chain(&game.id.gog_extra)
+                            .find_map(|id| gog_ids.get(id))
+                    })
+                    .filter(|x| !self.0.contains_key(*x))
+                    .cloned(),
+            };
+
+            if let Some(renamed) = renamed {
+                if renamed != *name && new.0.get(&renamed).is_some_and(|x| x.alias.is_none()) {
+                    renames.insert(name.clone(), renamed);
+                }
+            }
+        }
+
+        renames
+    }
+
     pub fn map_lutris_ids_to_names(&self) -> HashMap<String, String> {
         self.0
             .iter()
@@ -618,6 +692,7 @@ impl Manifest {
         }
 
         self.load_secondary_manifests(config);
+        self.add_generated_games(config);
         self.add_custom_games(config);
     }
 
@@ -655,6 +730,7 @@ impl Manifest {
                     // We intentionally don't carry over the cloud info for custom games.
                     // If you choose not to back up games with cloud support,
                     // you probably still want to back up your customized versions of such games.
+                    stored.exclude = custom.exclude;
                     stored.cloud = CloudMetadata::default();
                     stored.sources.insert(Source::Custom);
                 }
@@ -666,6 +742,7 @@ impl Manifest {
                     for item in custom.registry {
                         stored.registry.entry(item).or_default();
                     }
+                    stored.exclude.extend(custom.exclude);
                     stored.cloud = CloudMetadata::default();
                     stored.sources.insert(Source::Custom);
                 }
@@ -683,6 +760,7 @@ impl Manifest {
                     .into_iter()
                     .map(|x| (x, GameRegistryEntry::default()))
                     .collect(),
+                exclude: custom.exclude,
                 sources: BTreeSet::from_iter([Source::Custom]),
                 ..Default::default()
             };
@@ -707,6 +785,36 @@ impl Manifest {
         }
     }
 
+    /// Some roots list their own content (like emulator playlists or Minecraft worlds)
+    /// that we treat as separate games.
+    fn add_generated_games(&mut self, config: &Config) {
+        for root in &config.roots {
+            let generated = match root {
+                Root::Minecraft(root) => launchers::minecraft::find_games(root),
+                Root::RetroArch(root) => launchers::retroarch::find_games(root),
+                _ => continue,
+            };
+
+            for (path, manifest) in generated {
+                let source = Source::Generated(path.render());
+                log::debug!("adding generated games from: {}", path.render());
+
+                for (name, mut game) in manifest.0 {
+                    // Merging into an unrelated game would back up both sets of files under one name.
+                    if self.0.contains_key(&name) {
+                        log::warn!(
+                            "skipping generated game with conflicting name: {name} | {}",
+                            path.render()
+                        );
+                        continue;
+                    }
+                    game.sources.insert(source.clone());
+                    self.0.insert(name, game);
+                }
+            }
+        }
+    }
+
     fn incorporate_secondary_manifest(&mut self, secondary: Secondary) {
         log::debug!("incorporating secondary manifest: {}", &secondary.id);
         let manifest = secondary.data.0;
@@ -779,6 +887,7 @@ impl Manifest {
                 id,
                 cloud: _,
                 notes: _,
+                exclude: _,
                 sources: _,
             } = &v;
             alias.is_none()
@@ -854,6 +963,7 @@ mod tests {
                 id: Default::default(),
                 cloud: Default::default(),
                 notes: Default::default(),
+                exclude: Default::default(),
                 sources: Default::default(),
             },
             manifest.0["game"],
@@ -943,6 +1053,7 @@ mod tests {
                     uplay: true
                 },
                 notes: Default::default(),
+                exclude: Default::default(),
                 sources: Default::default(),
             },
             manifest.0["game"],
@@ -1112,6 +1223,39 @@ mod tests {
         assert_eq!(GogMetadata { id: None }, manifest.0["game"].gog);
     }
 
+    #[test]
+    fn generated_games_do_not_merge_into_existing_games() {
+        let mut manifest = Manifest::load_from_string(
+            r#"
+            Game 1:
+              files:
+                foo: {}
+            "#,
+        )
+        .unwrap();
+        let config = Config {
+            roots: vec![Root::RetroArch(crate::resource::config::root::RetroArch {
+                path: format!("{}/tests/launchers/retroarch", crate::testing::repo()).into(),
+            })],
+            ..Default::default()
+        };
+
+        manifest.add_generated_games(&config);
+
+        assert_eq!(
+            btree_map! { s("foo"): GameFileEntry::default() },
+            manifest.0["Game 1"].files,
+        );
+        assert!(manifest.0["Game 1"].sources.is_empty());
+        assert_eq!(
+            btree_set![Source::Generated(format!(
+                "{}/tests/launchers/retroarch/playlists/Nintendo - SNES.lpl",
+                crate::testing::repo()
+            ))],
+            manifest.0["game-2 [!]"].sources,
+        );
+    }
+
     #[test]
     fn can_get_aliases() {
         let manifest = Manifest::load_from_string(
@@ -1133,4 +1277,69 @@ mod tests {
             manifest.aliases(),
         );
     }
+
+    #[test]
+    fn can_find_renames() {
+        let old = Manifest::load_from_string(
+            r#"
+            aliased: {}
+            by-steam:
+              steam:
+                id: 1
+            by-steam-extra:
+              id:
+                steamExtra: [2]
+            by-gog:
+              gog:
+                id: 3
+            unchanged:
+              steam:
+                id: 4
+            removed:
+              steam:
+                id: 5
+            existing:
+              steam:
+                id: 6
+            old-alias:
+              alias: unchanged
+            "#,
+        )
+        .unwrap();
+        let new = Manifest::load_from_string(
+            r#"
+            aliased:
+              alias: aliased-new
+            aliased-new: {}
+            by-steam-new:
+              steam:
+                id: 1
+            by-steam-extra-new:
+              steam:
+                id: 2
+            by-gog-new:
+              id:
+                gogExtra: [3]
+            unchanged:
+              steam:
+                id: 4
+            existing:
+              id:
+                steamExtra: [6]
+            old-alias:
+              alias: by-steam-new
+            "#,
+        )
+        .unwrap();
+
+        assert_eq!(
+            btree_map! {
+                s("aliased"): s("aliased-new"),
+                s("by-steam"): s("by-steam-new"),
+                s("by-steam-extra"): s("by-steam-extra-new"),
+                s("by-gog"): s("by-gog-new"),
+            },
+            old.find_renames(&new),
+        );
+    }
 }
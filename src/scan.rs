@@ -1,7 +1,9 @@
 mod backup;
 mod change;
 mod duplicate;
+pub mod encryption;
 pub mod game_filter;
+mod hash_cache;
 pub mod launchers;
 pub mod layout;
 mod preview;
@@ -19,10 +21,11 @@ pub use self::{
     backup::{BackupError, BackupId, BackupInfo, OperationStatus, OperationStepDecision},
     change::{ScanChange, ScanChangeCount},
     duplicate::{DuplicateDetector, Duplication},
+    hash_cache::HashCache,
     launchers::{LauncherGame, Launchers},
     preview::ScanInfo,
     saves::{ScannedFile, ScannedRegistry, ScannedRegistryValue, ScannedRegistryValues},
-    steam::{SteamShortcut, SteamShortcuts},
+    steam::{redirect_steam_account, steam_account_id, SteamAccounts, SteamShortcut, SteamShortcuts},
     title::{TitleFinder, TitleQuery},
 };
 
@@ -33,12 +36,9 @@ use crate::{
         config::{BackupFilter, RedirectConfig, RedirectKind, Root, SortKey, ToggledPaths, ToggledRegistry},
         manifest::{Game, GameFileEntry, IdSet, Os, Store},
     },
-    scan::layout::LatestBackup,
+    scan::{layout::LatestBackup, registry::RegistryItem},
 };
 
-#[cfg(target_os = "windows")]
-use crate::scan::registry::RegistryItem;
-
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub enum ScanKind {
     Backup,
@@ -96,6 +96,24 @@ pub fn game_file_target(
     (original != &redirected).then_some(redirected)
 }
 
+/// For Steam roots, checks each known account for `<storeUserId>`.
+/// The original path is kept as well so that any other folder still matches,
+/// since an account may be missing from `loginusers.vdf` or use an ID format that we don't recognize.
+fn expand_store_user_id(raw_path: &str, root: &Root, accounts: &SteamAccounts) -> Vec<String> {
+    use crate::resource::manifest::placeholder as p;
+
+    if root.store() != Store::Steam || accounts.is_empty() || !raw_path.contains(p::STORE_USER_ID) {
+        return vec![raw_path.to_string()];
+    }
+
+    accounts
+        .iter()
+        .flat_map(|account| account.ids())
+        .map(|id| raw_path.replace(p::STORE_USER_ID, &id))
+        .chain(std::iter::once(raw_path.to_string()))
+        .collect()
+}
+
 fn check_windows_path(path: &str) -> &str {
     match Os::HOST {
         Os::Windows => path,
@@ -162,7 +180,7 @@ pub fn parse_paths(
             p::BASE,
             &match root.store() {
                 Store::Steam => format!("{}/steamapps/common/{}", &root_interpreted, install_dir),
-                Store::Heroic | Store::Legendary | Store::Lutris => full_install_dir
+                Store::Bottles | Store::Heroic | Store::Itch | Store::Legendary | Store::Lutris => full_install_dir
                     .and_then(|x| x.interpret().ok())
                     .unwrap_or_else(|| SKIP.to_string()),
                 Store::Ea
@@ -170,8 +188,10 @@ pub fn parse_paths(
                 | Store::Gog
                 | Store::GogGalaxy
                 | Store::Microsoft
+                | Store::Minecraft
                 | Store::Origin
                 | Store::Prime
+                | Store::RetroArch
                 | Store::Uplay
                 | Store::OtherHome
                 | Store::OtherWine
@@ -375,14 +395,18 @@ pub fn parse_paths(
                     &format!("{}/Users/*/Library/Preferences", &root_interpreted),
                 ));
         }
-        Store::Ea
+        Store::Bottles
+        | Store::Ea
         | Store::Epic
         | Store::GogGalaxy
+        | Store::Itch
         | Store::Legendary
         | Store::Lutris
         | Store::Microsoft
+        | Store::Minecraft
         | Store::Origin
         | Store::Prime
+        | Store::RetroArch
         | Store::Uplay
         | Store::Other => {}
     }
@@ -480,7 +504,19 @@ pub fn parse_paths(
         .collect()
 }
 
-pub fn scan_game_for_backup(
+/// Where a game's saves could be, before checking what actually exists.
+#[derive(Debug, Default)]
+pub struct SaveCandidates {
+    /// Paths and globs to check, along with whether they're case-sensitive, if known.
+    pub paths: HashSet<(StrictPath, Option<bool>)>,
+    /// Paths and globs that a custom game excludes from its own files.
+    pub exclusions: HashSet<(StrictPath, bool)>,
+    /// Wine prefixes that may hold the game's registry data.
+    pub wine_prefixes: Vec<StrictPath>,
+}
+
+/// Resolve a game's manifest paths against each root.
+pub fn find_save_candidates(
     game: &Game,
     name: &str,
     roots: &[Root],
@@ -488,21 +524,11 @@ pub fn scan_game_for_backup(
     launchers: &Launchers,
     filter: &BackupFilter,
     wine_prefix: Option<&StrictPath>,
-    ignored_paths: &ToggledPaths,
-    #[cfg_attr(not(target_os = "windows"), allow(unused))] ignored_registry: &ToggledRegistry,
-    previous: Option<LatestBackup>,
-    redirects: &[RedirectConfig],
-    reverse_redirects_on_restore: bool,
     steam_shortcuts: &SteamShortcuts,
-) -> ScanInfo {
-    log::trace!("[{name}] beginning scan for backup");
-
-    let mut found_files = HashMap::new();
-    #[cfg_attr(not(target_os = "windows"), allow(unused))]
-    let mut found_registry_keys = HashMap::new();
-    let has_backups = previous.is_some();
-
+) -> SaveCandidates {
     let mut paths_to_check = HashSet::<(StrictPath, Option<bool>)>::new();
+    let mut wine_prefixes = Vec::<StrictPath>::new();
+    let mut paths_to_exclude = HashSet::<(StrictPath, bool)>::new();
 
     // Add a dummy root for checking paths without `<root>`.
     let mut roots_to_check: Vec<Root> = vec![Root::new(SKIP, Store::Other)];
@@ -542,68 +568,98 @@ pub fn scan_game_for_backup(
             log::error!("Invalid root path: {:?}", &root);
             continue;
         };
+        if root.store() == Store::OtherWine {
+            wine_prefixes.push(StrictPath::new(root_interpreted.clone()));
+        }
 
-        for (raw_path, path_data) in &game.files {
-            log::trace!("[{name}] parsing candidates from: {}", raw_path);
-            if raw_path.trim().is_empty() {
-                continue;
-            }
-
+        let parse_candidates = |raw_path: &str, path_data: &GameFileEntry| {
             let mut candidates = HashSet::new();
-            let mut launcher_entries = launchers.get_game(&root, name).peekable();
-
-            if launcher_entries.peek().is_none() {
-                let platform = Os::HOST;
-                let install_dir = None;
-                let full_install_dir = None;
-
-                candidates.extend(parse_paths(
-                    raw_path,
-                    path_data,
-                    &root,
-                    install_dir,
-                    full_install_dir,
-                    &all_ids,
-                    manifest_dir,
-                    steam_shortcut,
-                    platform,
-                ));
-            } else {
-                for launcher_entry in launcher_entries {
-                    log::trace!("[{name}] parsing candidates with launcher info: {:?}", &launcher_entry);
-                    let platform = launcher_entry.platform.unwrap_or(Os::HOST);
-                    let install_dir = launcher_entry.install_dir.as_ref().and_then(|x| x.leaf());
-                    let full_install_dir = launcher_entry.install_dir.as_ref();
+
+            for raw_path in expand_store_user_id(raw_path, &root, launchers.get_steam_accounts(&root)) {
+                let mut launcher_entries = launchers.get_game(&root, name).peekable();
+
+                if launcher_entries.peek().is_none() {
+                    let platform = Os::HOST;
+                    let install_dir = None;
+                    let full_install_dir = None;
 
                     candidates.extend(parse_paths(
-                        raw_path,
+                        &raw_path,
                         path_data,
                         &root,
-                        install_dir.as_ref(),
+                        install_dir,
                         full_install_dir,
                         &all_ids,
                         manifest_dir,
                         steam_shortcut,
                         platform,
                     ));
+                } else {
+                    for launcher_entry in launcher_entries {
+                        log::trace!("[{name}] parsing candidates with launcher info: {:?}", &launcher_entry);
+                        let platform = launcher_entry.platform.unwrap_or(Os::HOST);
+                        let install_dir = launcher_entry.install_dir.as_ref().and_then(|x| x.leaf());
+                        let full_install_dir = launcher_entry.install_dir.as_ref();
+
+                        candidates.extend(parse_paths(
+                            &raw_path,
+                            path_data,
+                            &root,
+                            install_dir.as_ref(),
+                            full_install_dir,
+                            &all_ids,
+                            manifest_dir,
+                            steam_shortcut,
+                            platform,
+                        ));
+                    }
                 }
             }
 
-            for (candidate, case_sensitive) in candidates {
+            candidates
+        };
+
+        for (raw_path, path_data) in &game.files {
+            log::trace!("[{name}] parsing candidates from: {}", raw_path);
+            if raw_path.trim().is_empty() {
+                continue;
+            }
+
+            for (candidate, case_sensitive) in parse_candidates(raw_path, path_data) {
                 log::trace!("[{name}] parsed candidate: {candidate:?}");
                 paths_to_check.insert((candidate, Some(case_sensitive)));
             }
         }
+        for raw_path in &game.exclude {
+            log::trace!("[{name}] parsing exclusions from: {}", raw_path);
+            if raw_path.trim().is_empty() {
+                continue;
+            }
+
+            for (candidate, case_sensitive) in parse_candidates(raw_path, &GameFileEntry::default()) {
+                log::trace!("[{name}] parsed exclusion: {candidate:?}");
+                paths_to_exclude.insert((candidate, case_sensitive));
+            }
+        }
         if root.store() == Store::Steam {
+            let account_ids: Vec<_> = launchers
+                .get_steam_accounts(&root)
+                .iter()
+                .map(|x| x.id.to_string())
+                .chain(std::iter::once("*".to_string()))
+                .collect();
+
             for id in all_ids.steam(steam_shortcut.map(|x| x.id)) {
                 // Cloud saves:
-                paths_to_check.insert((
-                    StrictPath::relative(
-                        format!("{}/userdata/*/{}/remote/", root_interpreted.clone(), id),
-                        Some(manifest_dir_interpreted.clone()),
-                    ),
-                    None,
-                ));
+                for account_id in &account_ids {
+                    paths_to_check.insert((
+                        StrictPath::relative(
+                            format!("{}/userdata/{}/{}/remote/", root_interpreted.clone(), account_id, id),
+                            Some(manifest_dir_interpreted.clone()),
+                        ),
+                        None,
+                    ));
+                }
 
                 // Screenshots:
                 if !filter.exclude_store_screenshots {
@@ -623,11 +679,61 @@ pub fn scan_game_for_backup(
                         StrictPath::relative(format!("{}/*.reg", prefix), Some(manifest_dir_interpreted.clone())),
                         None,
                     ));
+                    wine_prefixes.push(StrictPath::relative(prefix, Some(manifest_dir_interpreted.clone())));
                 }
             }
         }
     }
 
+    SaveCandidates {
+        paths: paths_to_check,
+        exclusions: paths_to_exclude,
+        wine_prefixes,
+    }
+}
+
+pub fn scan_game_for_backup(
+    game: &Game,
+    name: &str,
+    roots: &[Root],
+    manifest_dir: &StrictPath,
+    launchers: &Launchers,
+    filter: &BackupFilter,
+    wine_prefix: Option<&StrictPath>,
+    ignored_paths: &ToggledPaths,
+    ignored_registry: &ToggledRegistry,
+    previous: Option<LatestBackup>,
+    redirects: &[RedirectConfig],
+    reverse_redirects_on_restore: bool,
+    steam_shortcuts: &SteamShortcuts,
+    hash_cache: &HashCache,
+) -> ScanInfo {
+    log::trace!("[{name}] beginning scan for backup");
+
+    let mut found_files = HashMap::new();
+    let mut found_registry_keys = HashMap::new();
+    let has_backups = previous.is_some();
+
+    let SaveCandidates {
+        paths: paths_to_check,
+        exclusions: paths_to_exclude,
+        wine_prefixes,
+    } = find_save_candidates(
+        game,
+        name,
+        roots,
+        manifest_dir,
+        launchers,
+        filter,
+        wine_prefix,
+        steam_shortcuts,
+    );
+
+    let mut steam_accounts = SteamAccounts::default();
+    for root in roots {
+        steam_accounts.extend(launchers.get_steam_accounts(root));
+    }
+
     let previous_files: HashMap<&StrictPath, &String> = previous
         .as_ref()
         .map(|previous| {
@@ -640,9 +746,14 @@ pub fn scan_game_for_backup(
         })
         .unwrap_or_default();
 
+    let exclusions = build_exclusion_globs(name, &paths_to_exclude);
+    let is_excluded = |path: &StrictPath| {
+        filter.is_path_ignored(path) || exclusions.as_ref().is_some_and(|x| x.is_match(path.render()))
+    };
+
     for (path, case_sensitive) in paths_to_check {
         log::trace!("[{name}] checking: {path:?}");
-        if filter.is_path_ignored(&path) {
+        if is_excluded(&path) {
             log::debug!("[{name}] excluded: {path:?}");
             continue;
         }
@@ -655,14 +766,21 @@ pub fn scan_game_for_backup(
                 let Ok(scan_key) = p.interpreted().map(|x| x.rendered()) else {
                     continue;
                 };
-                if filter.is_path_ignored(&scan_key) {
+                if is_excluded(&scan_key) {
                     log::debug!("[{name}] excluded: {scan_key:?}");
                     continue;
                 }
-                let ignored = ignored_paths.is_ignored(name, &scan_key);
                 log::debug!("[{name}] found: {scan_key:?}");
                 let size = scan_key.size();
-                let hash = scan_key.sha1();
+                let filtered = filter.is_file_filtered(name, &scan_key, size);
+                let ignored = filtered || ignored_paths.is_ignored(name, &scan_key);
+                // Filtered files are never backed up, so there's no need to read them.
+                let hash = if filtered {
+                    String::new()
+                } else {
+                    hash_cache.sha1(&scan_key, size)
+                };
+                let steam_account = steam_accounts.find_in(&scan_key);
                 let redirected = game_file_target(&scan_key, redirects, reverse_redirects_on_restore, ScanKind::Backup);
                 let change =
                     ScanChange::evaluate_backup(&hash, previous_files.get(redirected.as_ref().unwrap_or(&scan_key)));
@@ -676,6 +794,9 @@ pub fn scan_game_for_backup(
                         original_path: None,
                         ignored,
                         container: None,
+                        conflict: false,
+                        filtered,
+                        steam_account,
                     },
                 );
             } else if p.is_dir() {
@@ -697,14 +818,20 @@ pub fn scan_game_for_backup(
                             continue;
                         };
 
-                        if filter.is_path_ignored(&scan_key) {
+                        if is_excluded(&scan_key) {
                             log::debug!("[{name}] excluded: {scan_key:?}");
                             continue;
                         }
-                        let ignored = ignored_paths.is_ignored(name, &scan_key);
                         log::debug!("[{name}] found: {scan_key:?}");
                         let size = scan_key.size();
-                        let hash = scan_key.sha1();
+                        let filtered = filter.is_file_filtered(name, &scan_key, size);
+                        let ignored = filtered || ignored_paths.is_ignored(name, &scan_key);
+                        let hash = if filtered {
+                            String::new()
+                        } else {
+                            hash_cache.sha1(&scan_key, size)
+                        };
+                        let steam_account = steam_accounts.find_in(&scan_key);
                         let redirected =
                             game_file_target(&scan_key, redirects, reverse_redirects_on_restore, ScanKind::Backup);
                         let change = ScanChange::evaluate_backup(
@@ -721,6 +848,9 @@ pub fn scan_game_for_backup(
                                 original_path: None,
                                 ignored,
                                 container: None,
+                                conflict: false,
+                                filtered,
+                                steam_account,
                             },
                         );
                     }
@@ -757,12 +887,21 @@ pub fn scan_game_for_backup(
                     original_path: None,
                     ignored: ignored_paths.is_ignored(name, previous_file),
                     container: None,
+                    conflict: false,
+                    filtered: false,
+                    steam_account: None,
                 },
             );
         }
     }
 
-    #[cfg(target_os = "windows")]
+    #[cfg(not(target_os = "windows"))]
+    let wine = if game.registry.is_empty() {
+        registry::wine::Prefixes::default()
+    } else {
+        registry::wine::Prefixes::load(&wine_prefixes)
+    };
+
     {
         let previous_registry = previous.and_then(|x| x.registry_content);
 
@@ -789,10 +928,20 @@ pub fn scan_game_for_backup(
 
             for candidate in candidates {
                 log::trace!("[{name}] checking registry: {candidate}");
-                for (scan_key, mut scanned) in
-                    registry::win::scan_registry(name, &candidate, filter, ignored_registry, previous_registry.as_ref())
-                        .unwrap_or_default()
-                {
+                #[cfg(target_os = "windows")]
+                let scanned_keys = registry::win::scan_registry(
+                    name,
+                    &candidate,
+                    filter,
+                    ignored_registry,
+                    previous_registry.as_ref(),
+                )
+                .unwrap_or_default();
+                #[cfg(not(target_os = "windows"))]
+                let scanned_keys =
+                    wine.scan_registry(name, &candidate, filter, ignored_registry, previous_registry.as_ref());
+
+                for (scan_key, mut scanned) in scanned_keys {
                     log::debug!("[{name}] found registry: {}", scan_key.raw());
 
                     // Mark removed registry values.
@@ -843,16 +992,58 @@ pub fn scan_game_for_backup(
 
     log::trace!("[{name}] completed scan for backup");
 
+    #[cfg(target_os = "windows")]
+    let wine_prefixes = vec![];
+    #[cfg(not(target_os = "windows"))]
+    let wine_prefixes = wine.containing(&found_registry_keys);
+
     ScanInfo {
         game_name: name.to_string(),
         found_files,
         found_registry_keys,
+        wine_prefixes,
         available_backups: vec![],
         backup: None,
         has_backups,
     }
 }
 
+/// Builds a matcher for the paths that a custom game excludes from its own files.
+fn build_exclusion_globs(name: &str, paths: &HashSet<(StrictPath, bool)>) -> Option<globset::GlobSet> {
+    if paths.is_empty() {
+        return None;
+    }
+
+    let mut builder = globset::GlobSetBuilder::new();
+    for (path, case_sensitive) in paths {
+        let normalized = path.globbable();
+
+        let variants = vec![
+            normalized.to_string(),
+            // If the user has specified a plain folder, we also want to exclude its children.
+            format!("{}/**", &normalized),
+        ];
+
+        for variant in variants {
+            match globset::GlobBuilder::new(&variant)
+                .literal_separator(true)
+                .backslash_escape(false)
+                .case_insensitive(!case_sensitive)
+                .build()
+            {
+                Ok(glob) => {
+                    builder.add(glob);
+                }
+                Err(e) => {
+                    log::warn!("[{name}] unable to parse exclusion: {variant} | {e:?}");
+                }
+            }
+        }
+    }
+
+    builder.build().ok()
+}
+
 fn scan_game_for_backup_add_prefix(
     roots_to_check: &mut Vec<Root>,
     paths_to_check: &mut HashSet<(StrictPath, Option<bool>)>,
@@ -927,7 +1118,11 @@ mod tests {
     #[cfg(target_os = "windows")]
     use crate::resource::config::ToggledRegistryEntry;
     use crate::{
-        resource::{config::Config, manifest::Manifest, ResourceFile},
+        resource::{
+            config::{Config, FileRules},
+            manifest::Manifest,
+            ResourceFile,
+        },
         testing::{repo, s, EMPTY_HASH},
     };
 
@@ -1121,6 +1316,7 @@ mod tests {
                 &[],
                 false,
                 &Default::default(),
+                &Default::default(),
             ),
         );
 
@@ -1147,6 +1343,7 @@ mod tests {
                 &[],
                 false,
                 &Default::default(),
+                &Default::default(),
             ),
         );
     }
@@ -1177,6 +1374,7 @@ mod tests {
                 &[],
                 false,
                 &Default::default(),
+                &Default::default(),
             ),
         );
     }
@@ -1196,6 +1394,9 @@ mod tests {
                         change: ScanChange::New,
                         container: None,
                         redirected: Some(StrictPath::new(format!("{}/tests/root3/game5/data-symlink/file1.txt", repo()))),
+                        conflict: false,
+                        filtered: false,
+                        steam_account: None,
                     },
                 },
                 found_registry_keys: hash_map! {},
@@ -1219,6 +1420,7 @@ mod tests {
                 }],
                 false,
                 &Default::default(),
+                &Default::default(),
             ),
         );
     }
@@ -1249,6 +1451,7 @@ mod tests {
                 &[],
                 false,
                 &Default::default(),
+                &Default::default(),
             ),
         );
     }
@@ -1283,6 +1486,7 @@ mod tests {
                 &[],
                 false,
                 &Default::default(),
+                &Default::default(),
             ),
         );
     }
@@ -1316,6 +1520,7 @@ mod tests {
                 &[],
                 false,
                 &Default::default(),
+                &Default::default(),
             ),
         );
     }
@@ -1345,6 +1550,7 @@ mod tests {
                 &[],
                 false,
                 &Default::default(),
+                &Default::default(),
             ),
         );
     }
@@ -1374,6 +1580,193 @@ mod tests {
                 &[],
                 false,
                 &Default::default(),
+                &Default::default(),
+            ),
+        );
+    }
+
+    #[test]
+    #[cfg(not(target_os = "windows"))]
+    fn can_scan_game_for_backup_with_registry_keys_in_wine_prefix() {
+        let prefix = format!("{}/tests/wine-registry", repo());
+
+        assert_eq!(
+            ScanInfo {
+                game_name: s("game3"),
+                found_files: hash_map! {
+                    format!("{prefix}/system.reg").into(): ScannedFile::new(161, "6bf5791d115f218ed618c869be980179cade6983").change_new(),
+                    format!("{prefix}/user.reg").into(): ScannedFile::new(328, "796d726d6b7ec2a19d19482d52abab2845c185f7").change_new(),
+                },
+                found_registry_keys: hash_map! {
+                    "HKEY_CURRENT_USER/Software/Ludusavi/game3".into(): ScannedRegistry::new().change_as(ScanChange::New)
+                        .with_value_new("dword")
+                        .with_value_new("sz"),
+                    "HKEY_CURRENT_USER/Software/Ludusavi/game3/sub".into(): ScannedRegistry::new().change_as(ScanChange::New),
+                },
+                wine_prefixes: vec![StrictPath::new(prefix.clone())],
+                ..Default::default()
+            },
+            scan_game_for_backup(
+                &manifest().0["game3"],
+                "game3",
+                &config().roots,
+                &StrictPath::new(repo()),
+                &Launchers::scan_dirs(&config().roots, &manifest(), &["game3".to_string()]),
+                &BackupFilter::default(),
+                Some(&StrictPath::new(prefix.clone())),
+                &ToggledPaths::default(),
+                &ToggledRegistry::default(),
+                None,
+                &[],
+                false,
+                &Default::default(),
+                &Default::default(),
+            ),
+        );
+    }
+
+    #[test]
+    fn can_scan_game_for_backup_with_steam_accounts() {
+        let manifest = Manifest::load_from_string(
+            r#"
+            steam-game:
+              files:
+                <root>/userdata/<storeUserId>/<storeGameId>/local.dat: {}
+              steam:
+                id: 101
+            "#,
+        )
+        .unwrap();
+        let roots = vec![Root::new(format!("{}/tests/launchers/steam", repo()), Store::Steam)];
+
+        assert_eq!(
+            ScanInfo {
+                game_name: s("steam-game"),
+                found_files: hash_map! {
+                    format!("{}/tests/launchers/steam/userdata/1/101/local.dat", repo()).into(): ScannedFile::new(1, "356a192b7913b04c54574d18c28d46e6395428ab").change_new().steam_account(1),
+                    format!("{}/tests/launchers/steam/userdata/1/101/remote/save.dat", repo()).into(): ScannedFile::new(1, "356a192b7913b04c54574d18c28d46e6395428ab").change_new().steam_account(1),
+                    format!("{}/tests/launchers/steam/userdata/2/101/remote/save.dat", repo()).into(): ScannedFile::new(1, "da4b9237bacccdf19c0760cab7aec4a8359010b0").change_new().steam_account(2),
+                    format!("{}/tests/launchers/steam/userdata/other/101/local.dat", repo()).into(): ScannedFile::new(1, "b6589fc6ab0dc82cf12099d1c2d40ab994e8410c").change_new(),
+                },
+                ..Default::default()
+            },
+            scan_game_for_backup(
+                &manifest.0["steam-game"],
+                "steam-game",
+                &roots,
+                &StrictPath::new(repo()),
+                &Launchers::scan_dirs(&roots, &manifest, &["steam-game".to_string()]),
+                &BackupFilter::default(),
+                None,
+                &ToggledPaths::default(),
+                &ToggledRegistry::default(),
+                None,
+                &[],
+                false,
+                &Default::default(),
+                &Default::default(),
+            ),
+        );
+    }
+
+    #[test]
+    fn can_scan_game_for_backup_with_retroarch_content() {
+        let roots = vec![Root::new(
+            format!("{}/tests/launchers/retroarch", repo()),
+            Store::RetroArch,
+        )];
+        let config = Config {
+            roots: roots.clone(),
+            ..Default::default()
+        };
+        let manifest = Manifest::default().with_extensions(&config);
+
+        assert_eq!(
+            ScanInfo {
+                game_name: s("Game 1"),
+                found_files: hash_map! {
+                    format!("{}/tests/launchers/retroarch/saves/game-1.srm", repo()).into(): ScannedFile::new(1, "356a192b7913b04c54574d18c28d46e6395428ab").change_new(),
+                    format!("{}/tests/launchers/retroarch/states/Snes9x/game-1.state1", repo()).into(): ScannedFile::new(1, "356a192b7913b04c54574d18c28d46e6395428ab").change_new(),
+                },
+                ..Default::default()
+            },
+            scan_game_for_backup(
+                &manifest.0["Game 1"],
+                "Game 1",
+                &roots,
+                &StrictPath::new(repo()),
+                &Launchers::scan_dirs(&roots, &manifest, &["Game 1".to_string()]),
+                &BackupFilter::default(),
+                None,
+                &ToggledPaths::default(),
+                &ToggledRegistry::default(),
+                None,
+                &[],
+                false,
+                &Default::default(),
+                &Default::default(),
+            ),
+        );
+    }
+
+    #[test]
+    fn can_scan_game_for_backup_with_file_matches_and_excluded_paths() {
+        let mut game = manifest().0["game1"].clone();
+        game.exclude = vec![s("<base>/subdir")];
+
+        assert_eq!(
+            ScanInfo {
+                game_name: s("game1"),
+                found_files: hash_map! {
+                    format!("{}/tests/root2/game1/file1.txt", repo()).into(): ScannedFile::new(1, "3a52ce780950d4d969792a2559cd519d7ee8c727").change_new(),
+                },
+                found_registry_keys: hash_map! {},
+                ..Default::default()
+            },
+            scan_game_for_backup(
+                &game,
+                "game1",
+                &config().roots,
+                &StrictPath::new(repo()),
+                &Launchers::scan_dirs(&config().roots, &manifest(), &["game1".to_string()]),
+                &BackupFilter::default(),
+                None,
+                &ToggledPaths::default(),
+                &ToggledRegistry::default(),
+                None,
+                &[],
+                false,
+                &Default::default(),
+                &Default::default(),
+            ),
+        );
+
+        game.exclude = vec![s("<base>/*.txt")];
+
+        assert_eq!(
+            ScanInfo {
+                game_name: s("game1"),
+                found_files: hash_map! {
+                    format!("{}/tests/root1/game1/subdir/file2.txt", repo()).into(): ScannedFile::new(2, "9d891e731f75deae56884d79e9816736b7488080").change_new(),
+                },
+                found_registry_keys: hash_map! {},
+                ..Default::default()
+            },
+            scan_game_for_backup(
+                &game,
+                "game1",
+                &config().roots,
+                &StrictPath::new(repo()),
+                &Launchers::scan_dirs(&config().roots, &manifest(), &["game1".to_string()]),
+                &BackupFilter::default(),
+                None,
+                &ToggledPaths::default(),
+                &ToggledRegistry::default(),
+                None,
+                &[],
+                false,
+                &Default::default(),
+                &Default::default(),
             ),
         );
     }
@@ -1411,6 +1804,58 @@ mod tests {
                 &[],
                 false,
                 &Default::default(),
+                &Default::default(),
+            ),
+        );
+    }
+
+    #[test]
+    fn can_scan_game_for_backup_with_file_rules() {
+        let mut filter = BackupFilter {
+            file_rules: FileRules {
+                included_extensions: vec![s("TXT")],
+                ..Default::default()
+            },
+            game_file_rules: btree_map! {
+                s("game1"): FileRules {
+                    ignored_patterns: vec![s(r"subdir/file2\.txt$")],
+                    ..Default::default()
+                },
+                s("game2"): FileRules {
+                    max_size_mb: Some(0),
+                    ..Default::default()
+                },
+            },
+            ..Default::default()
+        };
+        let found = hash_map! {
+            format!("{}/tests/root1/game1/subdir/file2.txt", repo()).into(): ScannedFile::new(2, "").change_new().filtered(),
+            format!("{}/tests/root2/game1/file1.txt", repo()).into(): ScannedFile::new(1, "3a52ce780950d4d969792a2559cd519d7ee8c727").change_new(),
+        };
+
+        filter.build_globs();
+        assert_eq!(
+            ScanInfo {
+                game_name: s("game1"),
+                found_files: found,
+                found_registry_keys: hash_map! {},
+                ..Default::default()
+            },
+            scan_game_for_backup(
+                &manifest().0["game1"],
+                "game1",
+                &config().roots,
+                &StrictPath::new(repo()),
+                &Launchers::scan_dirs(&config().roots, &manifest(), &["game1".to_string()]),
+                &filter,
+                None,
+                &ToggledPaths::default(),
+                &ToggledRegistry::default(),
+                None,
+                &[],
+                false,
+                &Default::default(),
+                &Default::default(),
             ),
         );
     }
@@ -1450,6 +1895,7 @@ mod tests {
                 &[],
                 false,
                 &Default::default(),
+                &Default::default(),
             ),
         );
     }
@@ -1489,6 +1935,7 @@ mod tests {
                 &[],
                 false,
                 &Default::default(),
+                &Default::default(),
             ),
         );
     }
@@ -1525,6 +1972,7 @@ mod tests {
                 &[],
                 false,
                 &Default::default(),
+                &Default::default(),
             ),
         );
     }
@@ -1565,6 +2013,7 @@ mod tests {
                 &[],
                 false,
                 &Default::default(),
+                &Default::default(),
             ),
         );
     }
@@ -1665,6 +2114,7 @@ mod tests {
                     &[],
                     false,
                     &Default::default(),
+                    &Default::default(),
                 ),
             );
         }
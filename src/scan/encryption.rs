@@ -0,0 +1,443 @@
+use std::io::{Read, Seek, SeekFrom, Write};
+
+use base64::prelude::*;
+use chacha20poly1305::{
+    aead::{
+        generic_array::GenericArray,
+        rand_core::RngCore,
+        stream::{NewStream, StreamBE32, StreamPrimitive},
+        KeyInit, OsRng,
+    },
+    XChaCha20Poly1305,
+};
+
+use crate::prelude::Error;
+
+/// Known plaintext that we encrypt with each new key,
+/// so that we can tell a wrong passphrase apart from corrupted data.
+const CHECK: &[u8] = b"ludusavi";
+const SALT_LENGTH: usize = 16;
+/// The XChaCha20 nonce is 24 bytes, and the STREAM construction uses the last 5 for its own counter.
+const NONCE_PREFIX_LENGTH: usize = 19;
+const TAG_LENGTH: usize = 16;
+/// Content is encrypted in chunks of this size,
+/// so that large archives never need to be held in memory.
+const CHUNK_LENGTH: usize = 64 * 1024;
+const SEALED_CHUNK_LENGTH: u64 = (CHUNK_LENGTH + TAG_LENGTH) as u64;
+
+/// A user-provided secret from which backup keys are derived.
+/// This is kept opaque so that it doesn't end up in logs.
+#[derive(Clone, Default, PartialEq, Eq)]
+pub struct Passphrase(String);
+
+impl Passphrase {
+    pub fn new(raw: String) -> Option<Self> {
+        (!raw.is_empty()).then_some(Self(raw))
+    }
+}
+
+impl std::fmt::Debug for Passphrase {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str("Passphrase(***)")
+    }
+}
+
+/// Stored alongside each encrypted backup in the mapping file.
+#[derive(Clone, Debug, Default, Eq, PartialEq, serde::Serialize, serde::Deserialize)]
+#[serde(default, rename_all = "camelCase")]
+pub struct EncryptionInfo {
+    /// Base64-encoded salt for deriving the key from the passphrase.
+    pub salt: String,
+    /// Base64-encoded `CHECK`, encrypted with the derived key.
+    pub check: String,
+}
+
+#[derive(Clone)]
+pub struct Cipher {
+    inner: XChaCha20Poly1305,
+}
+
+impl Cipher {
+    fn derive(passphrase: &Passphrase, salt: &[u8]) -> Option<Self> {
+        let mut key = [0u8; 32];
+        argon2::Argon2::default()
+            .hash_password_into(passphrase.0.as_bytes(), salt, &mut key)
+            .ok()?;
+        Some(Self {
+            inner: XChaCha20Poly1305::new(&key.into()),
+        })
+    }
+
+    /// Derive a new key with a fresh salt, for use in a new backup.
+    pub fn create(passphrase: &Passphrase) -> Result<(Self, EncryptionInfo), Error> {
+        let mut salt = [0u8; SALT_LENGTH];
+        OsRng.fill_bytes(&mut salt);
+
+        let cipher = Self::derive(passphrase, &salt).ok_or(Error::BackupEncryptionFailed)?;
+        let check = cipher.encrypt(CHECK).map_err(|_| Error::BackupEncryptionFailed)?;
+
+        let info = EncryptionInfo {
+            salt: BASE64_STANDARD.encode(salt),
+            check: BASE64_STANDARD.encode(check),
+        };
+
+        Ok((cipher, info))
+    }
+
+    /// Derive the key for an existing backup,
+    /// making sure that the passphrase is the same one used to create it.
+    pub fn unlock(passphrase: Option<&Passphrase>, info: &EncryptionInfo) -> Result<Self, Error> {
+        let passphrase = passphrase.ok_or(Error::BackupPassphraseRequired)?;
+        let salt = BASE64_STANDARD
+            .decode(&info.salt)
+            .map_err(|_| Error::BackupPassphraseInvalid)?;
+        let check = BASE64_STANDARD
+            .decode(&info.check)
+            .map_err(|_| Error::BackupPassphraseInvalid)?;
+
+        let cipher = Self::derive(passphrase, &salt).ok_or(Error::BackupPassphraseInvalid)?;
+        match cipher.decrypt(&check) {
+            Ok(x) if x == CHECK => Ok(cipher),
+            _ => Err(Error::BackupPassphraseInvalid),
+        }
+    }
+
+    fn stream(&self, nonce_prefix: &[u8]) -> StreamBE32<XChaCha20Poly1305> {
+        StreamBE32::from_aead(self.inner.clone(), GenericArray::from_slice(nonce_prefix))
+    }
+
+    /// Encrypt everything written to `inner`.
+    /// The output is a random nonce prefix followed by the encrypted chunks.
+    /// `inner` needs to be readable so that earlier chunks can be revised after seeking back.
+    pub fn writer<W: Read + Write + Seek>(&self, mut inner: W) -> std::io::Result<EncryptedWriter<W>> {
+        let mut nonce_prefix = [0u8; NONCE_PREFIX_LENGTH];
+        OsRng.fill_bytes(&mut nonce_prefix);
+        inner.write_all(&nonce_prefix)?;
+
+        Ok(EncryptedWriter {
+            stream: self.stream(&nonce_prefix),
+            inner,
+            chunk: vec![],
+            index: 0,
+            dirty: false,
+            position: 0,
+            len: 0,
+        })
+    }
+
+    /// Decrypt content produced by `writer`, with random access to the plaintext.
+    pub fn reader<R: Read + Seek>(&self, mut inner: R) -> std::io::Result<EncryptedReader<R>> {
+        let mut nonce_prefix = [0u8; NONCE_PREFIX_LENGTH];
+        inner.read_exact(&mut nonce_prefix).map_err(|_| invalid_data())?;
+
+        let sealed = inner.seek(SeekFrom::End(0))? - NONCE_PREFIX_LENGTH as u64;
+        let chunks = sealed.div_ceil(SEALED_CHUNK_LENGTH).max(1);
+        let Some(len) = sealed.checked_sub(chunks * TAG_LENGTH as u64) else {
+            return Err(invalid_data());
+        };
+
+        Ok(EncryptedReader {
+            stream: self.stream(&nonce_prefix),
+            inner,
+            chunk: None,
+            chunks,
+            position: 0,
+            len,
+        })
+    }
+
+    pub fn encrypt(&self, data: &[u8]) -> std::io::Result<Vec<u8>> {
+        let mut writer = self.writer(std::io::Cursor::new(vec![]))?;
+        writer.write_all(data)?;
+        Ok(writer.finish()?.into_inner())
+    }
+
+    pub fn decrypt(&self, data: &[u8]) -> std::io::Result<Vec<u8>> {
+        let mut out = vec![];
+        self.reader(std::io::Cursor::new(data))?.read_to_end(&mut out)?;
+        Ok(out)
+    }
+}
+
+fn invalid_data() -> std::io::Error {
+    std::io::Error::new(std::io::ErrorKind::InvalidData, "Unable to decrypt data")
+}
+
+fn chunk_counter(index: u64) -> std::io::Result<u32> {
+    u32::try_from(index).map_err(|_| std::io::Error::other("Too much data to encrypt"))
+}
+
+fn chunk_offset(index: u64) -> u64 {
+    NONCE_PREFIX_LENGTH as u64 + index * SEALED_CHUNK_LENGTH
+}
+
+/// Encrypts content one chunk at a time.
+/// Only the current chunk is held in memory.
+/// Writers like `zip::ZipWriter` seek back to fill in headers,
+/// so a previous chunk is read back and decrypted if it needs to change.
+/// Call `finish` once done, since the last chunk is sealed differently.
+pub struct EncryptedWriter<W: Read + Write + Seek> {
+    stream: StreamBE32<XChaCha20Poly1305>,
+    inner: W,
+    /// Plaintext of the chunk at `index`.
+    chunk: Vec<u8>,
+    index: u64,
+    /// Whether `chunk` has changed since it was last written to `inner`.
+    dirty: bool,
+    position: u64,
+    len: u64,
+}
+
+impl<W: Read + Write + Seek> EncryptedWriter<W> {
+    /// Write out the current chunk. Every chunk is sealed as non-final until `finish`.
+    fn store_chunk(&mut self, last: bool) -> std::io::Result<()> {
+        let mut sealed = self.chunk.clone();
+        self.stream
+            .encrypt_in_place(chunk_counter(self.index)?, last, &[], &mut sealed)
+            .map_err(|_| std::io::Error::other("Unable to encrypt data"))?;
+        self.inner.seek(SeekFrom::Start(chunk_offset(self.index)))?;
+        self.inner.write_all(&sealed)?;
+        self.dirty = false;
+        Ok(())
+    }
+
+    fn load_chunk(&mut self, index: u64) -> std::io::Result<()> {
+        if index == self.index {
+            return Ok(());
+        }
+        if self.dirty {
+            self.store_chunk(false)?;
+        }
+
+        let start = index * CHUNK_LENGTH as u64;
+        self.chunk.clear();
+        if start < self.len {
+            let plain = (self.len - start).min(CHUNK_LENGTH as u64) as usize;
+            let mut sealed = vec![0u8; plain + TAG_LENGTH];
+            self.inner.seek(SeekFrom::Start(chunk_offset(index)))?;
+            self.inner.read_exact(&mut sealed)?;
+            self.stream
+                .decrypt_in_place(chunk_counter(index)?, false, &[], &mut sealed)
+                .map_err(|_| invalid_data())?;
+            self.chunk = sealed;
+        }
+        self.index = index;
+        Ok(())
+    }
+
+    /// Seal the final chunk and return the inner writer.
+    pub fn finish(mut self) -> std::io::Result<W> {
+        let last = self.len.saturating_sub(1) / CHUNK_LENGTH as u64;
+        self.load_chunk(last)?;
+        self.store_chunk(true)?;
+        self.inner.flush()?;
+        Ok(self.inner)
+    }
+}
+
+impl<W: Read + Write + Seek> Write for EncryptedWriter<W> {
+    fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+        if buf.is_empty() {
+            return Ok(0);
+        }
+
+        self.load_chunk(self.position / CHUNK_LENGTH as u64)?;
+        let offset = (self.position % CHUNK_LENGTH as u64) as usize;
+        let count = buf.len().min(CHUNK_LENGTH - offset);
+        let end = offset + count;
+
+        if self.chunk.len() < end {
+            self.chunk.resize(end, 0);
+        }
+        self.chunk[offset..end].copy_from_slice(&buf[..count]);
+        self.dirty = true;
+        self.position += count as u64;
+        self.len = self.len.max(self.position);
+
+        Ok(count)
+    }
+
+    fn flush(&mut self) -> std::io::Result<()> {
+        self.inner.flush()
+    }
+}
+
+impl<W: Read + Write + Seek> Seek for EncryptedWriter<W> {
+    fn seek(&mut self, pos: SeekFrom) -> std::io::Result<u64> {
+        let position = resolve_seek(pos, self.position, self.len)?;
+        if position > self.len {
+            // Skipped chunks would never be written.
+            return Err(std::io::Error::new(
+                std::io::ErrorKind::InvalidInput,
+                "Unable to seek past the end of encrypted data",
+            ));
+        }
+        self.position = position;
+        Ok(position)
+    }
+}
+
+/// Decrypts content one chunk at a time, as it's read.
+pub struct EncryptedReader<R: Read + Seek> {
+    stream: StreamBE32<XChaCha20Poly1305>,
+    inner: R,
+    /// Index and plaintext of the most recently decrypted chunk.
+    chunk: Option<(u64, Vec<u8>)>,
+    chunks: u64,
+    position: u64,
+    len: u64,
+}
+
+impl<R: Read + Seek> Read for EncryptedReader<R> {
+    fn read(&mut self, buf: &mut [u8]) -> std::io::Result<usize> {
+        if buf.is_empty() || self.position >= self.len {
+            return Ok(0);
+        }
+
+        let index = self.position / CHUNK_LENGTH as u64;
+        if self.chunk.as_ref().map(|(i, _)| *i) != Some(index) {
+            let last = index + 1 == self.chunks;
+            let plain = (self.len - index * CHUNK_LENGTH as u64).min(CHUNK_LENGTH as u64) as usize;
+            let mut sealed = vec![0u8; plain + TAG_LENGTH];
+            self.inner.seek(SeekFrom::Start(chunk_offset(index)))?;
+            self.inner.read_exact(&mut sealed).map_err(|_| invalid_data())?;
+            self.stream
+                .decrypt_in_place(chunk_counter(index)?, last, &[], &mut sealed)
+                .map_err(|_| invalid_data())?;
+            self.chunk = Some((index, sealed));
+        }
+
+        let Some((_, chunk)) = &self.chunk else {
+            return Ok(0);
+        };
+        let offset = (self.position % CHUNK_LENGTH as u64) as usize;
+        let count = buf.len().min(chunk.len() - offset);
+        buf[..count].copy_from_slice(&chunk[offset..offset + count]);
+        self.position += count as u64;
+
+        Ok(count)
+    }
+}
+
+impl<R: Read + Seek> Seek for EncryptedReader<R> {
+    fn seek(&mut self, pos: SeekFrom) -> std::io::Result<u64> {
+        self.position = resolve_seek(pos, self.position, self.len)?;
+        Ok(self.position)
+    }
+}
+
+fn resolve_seek(pos: SeekFrom, current: u64, len: u64) -> std::io::Result<u64> {
+    let resolved = match pos {
+        SeekFrom::Start(x) => Some(x),
+        SeekFrom::Current(x) => current.checked_add_signed(x),
+        SeekFrom::End(x) => len.checked_add_signed(x),
+    };
+    resolved.ok_or_else(|| {
+        std::io::Error::new(
+            std::io::ErrorKind::InvalidInput,
+            "Unable to seek before the start of encrypted data",
+        )
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use pretty_assertions::assert_eq;
+
+    fn passphrase(raw: &str) -> Passphrase {
+        Passphrase::new(raw.to_string()).unwrap()
+    }
+
+    #[test]
+    fn can_round_trip_data() {
+        let (cipher, info) = Cipher::create(&passphrase("foo")).unwrap();
+        let encrypted = cipher.encrypt(b"hello").unwrap();
+        assert_ne!(b"hello".to_vec(), encrypted);
+
+        let cipher = Cipher::unlock(Some(&passphrase("foo")), &info).unwrap();
+        assert_eq!(b"hello".to_vec(), cipher.decrypt(&encrypted).unwrap());
+    }
+
+    #[test]
+    fn can_round_trip_data_across_chunks() {
+        let (cipher, _) = Cipher::create(&passphrase("foo")).unwrap();
+
+        for len in [0, 1, CHUNK_LENGTH, CHUNK_LENGTH + 1, 3 * CHUNK_LENGTH] {
+            let data: Vec<u8> = (0..len).map(|i| (i % 251) as u8).collect();
+            let encrypted = cipher.encrypt(&data).unwrap();
+            assert_eq!(data, cipher.decrypt(&encrypted).unwrap());
+        }
+    }
+
+    #[test]
+    fn can_revise_earlier_chunks_after_seeking() {
+        let (cipher, _) = Cipher::create(&passphrase("foo")).unwrap();
+        let mut data = vec![1u8; 2 * CHUNK_LENGTH + 10];
+
+        let mut writer = cipher.writer(std::io::Cursor::new(vec![])).unwrap();
+        writer.write_all(&data).unwrap();
+        writer.seek(SeekFrom::Start(5)).unwrap();
+        writer.write_all(&[2, 2]).unwrap();
+        writer.seek(SeekFrom::End(0)).unwrap();
+        writer.write_all(&[3]).unwrap();
+        let encrypted = writer.finish().unwrap().into_inner();
+
+        data[5] = 2;
+        data[6] = 2;
+        data.push(3);
+        assert_eq!(data, cipher.decrypt(&encrypted).unwrap());
+
+        let mut reader = cipher.reader(std::io::Cursor::new(&encrypted)).unwrap();
+        let mut buffer = [0u8; 3];
+        reader.seek(SeekFrom::Start(4)).unwrap();
+        reader.read_exact(&mut buffer).unwrap();
+        assert_eq!([1, 2, 2], buffer);
+    }
+
+    #[test]
+    fn can_round_trip_zip_archive() {
+        let (cipher, _) = Cipher::create(&passphrase("foo")).unwrap();
+        let big = vec![7u8; 3 * CHUNK_LENGTH];
+
+        let mut zip = zip::ZipWriter::new(cipher.writer(std::io::Cursor::new(vec![])).unwrap());
+        let options = zip::write::FileOptions::default().compression_method(zip::CompressionMethod::Stored);
+        zip.start_file("small.txt", options).unwrap();
+        zip.write_all(b"hello").unwrap();
+        zip.start_file("big.bin", options).unwrap();
+        zip.write_all(&big).unwrap();
+        let encrypted = zip.finish().unwrap().finish().unwrap().into_inner();
+
+        let mut zip = zip::ZipArchive::new(cipher.reader(std::io::Cursor::new(encrypted)).unwrap()).unwrap();
+        let mut content = vec![];
+        zip.by_name("big.bin").unwrap().read_to_end(&mut content).unwrap();
+        assert_eq!(big, content);
+        content.clear();
+        zip.by_name("small.txt").unwrap().read_to_end(&mut content).unwrap();
+        assert_eq!(b"hello".to_vec(), content);
+    }
+
+    #[test]
+    fn rejects_truncated_data() {
+        let (cipher, _) = Cipher::create(&passphrase("foo")).unwrap();
+        let encrypted = cipher.encrypt(&vec![1u8; 2 * CHUNK_LENGTH]).unwrap();
+
+        assert!(cipher.decrypt(&encrypted[..chunk_offset(1) as usize]).is_err());
+        assert!(cipher.decrypt(&encrypted[..NONCE_PREFIX_LENGTH]).is_err());
+    }
+
+    #[test]
+    fn rejects_wrong_passphrase() {
+        let (_, info) = Cipher::create(&passphrase("foo")).unwrap();
+        assert_eq!(
+            Some(Error::BackupPassphraseInvalid),
+            Cipher::unlock(Some(&passphrase("bar")), &info).err(),
+        );
+    }
+
+    #[test]
+    fn requires_passphrase() {
+        let (_, info) = Cipher::create(&passphrase("foo")).unwrap();
+        assert_eq!(Some(Error::BackupPassphraseRequired), Cipher::unlock(None, &info).err());
+    }
+}
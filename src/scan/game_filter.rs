@@ -132,6 +132,7 @@ impl ToString for Manifest {
             manifest::Source::Primary => TRANSLATOR.primary_manifest_label(),
             manifest::Source::Custom => TRANSLATOR.custom_games_label(),
             manifest::Source::Secondary(id) => id.to_string(),
+            manifest::Source::Generated(id) => id.to_string(),
         }
     }
 }
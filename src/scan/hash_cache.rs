@@ -0,0 +1,154 @@
+use std::{collections::BTreeMap, sync::Mutex};
+
+use chrono::{DateTime, Utc};
+
+use crate::{
+    path::StrictPath,
+    resource::{ResourceFile, SaveableResourceFile},
+};
+
+/// Files modified more recently than this are always hashed in full,
+/// since another write within the file system's timestamp resolution
+/// would not change the modification time.
+const MIN_AGE: chrono::TimeDelta = chrono::TimeDelta::seconds(2);
+
+/// Hashes of save files from previous scans,
+/// so that unchanged files don't need to be read again.
+#[derive(Debug, Default, serde::Serialize, serde::Deserialize)]
+#[serde(default)]
+pub struct HashCache {
+    files: Mutex<BTreeMap<String, CachedHash>>,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq, serde::Serialize, serde::Deserialize)]
+struct CachedHash {
+    size: u64,
+    modified: DateTime<Utc>,
+    hash: String,
+}
+
+impl ResourceFile for HashCache {
+    const FILE_NAME: &'static str = "hash-cache.yaml";
+}
+
+impl SaveableResourceFile for HashCache {}
+
+impl HashCache {
+    /// Get the file's hash, reusing the cached one if the size and modification time haven't changed.
+    /// If the file's metadata can't be read, this falls back to hashing the file without caching it.
+    pub fn sha1(&self, path: &StrictPath, size: u64) -> String {
+        let Ok(key) = path.interpret() else {
+            return path.sha1();
+        };
+        let Ok(modified) = path.get_mtime().map(DateTime::<Utc>::from) else {
+            return path.sha1();
+        };
+
+        if let Some(cached) = self.files.lock().unwrap_or_else(|e| e.into_inner()).get(&key) {
+            if cached.size == size && cached.modified == modified {
+                return cached.hash.clone();
+            }
+        }
+
+        let Ok(hash) = path.try_sha1() else {
+            return String::new();
+        };
+
+        if Utc::now() - modified >= MIN_AGE {
+            self.files.lock().unwrap_or_else(|e| e.into_inner()).insert(
+                key,
+                CachedHash {
+                    size,
+                    modified,
+                    hash: hash.clone(),
+                },
+            );
+        }
+
+        hash
+    }
+
+    /// Forget all cached hashes, so that every file is hashed again.
+    pub fn clear(&self) {
+        self.files.lock().unwrap_or_else(|e| e.into_inner()).clear();
+    }
+
+    /// Forget the hashes of files that no longer exist.
+    pub fn prune(&self) {
+        self.files
+            .lock()
+            .unwrap_or_else(|e| e.into_inner())
+            .retain(|path, _| StrictPath::new(path.clone()).is_file());
+    }
+
+    #[cfg(test)]
+    fn len(&self) -> usize {
+        self.files.lock().unwrap_or_else(|e| e.into_inner()).len()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use pretty_assertions::assert_eq;
+
+    use super::*;
+    use crate::testing::repo_path;
+
+    #[test]
+    fn can_reuse_cached_hash() {
+        let cache = HashCache::default();
+        let path = repo_path("tests/root1/game1/subdir/file2.txt");
+        let size = path.size();
+
+        assert_eq!("9d891e731f75deae56884d79e9816736b7488080", cache.sha1(&path, size));
+        assert_eq!(1, cache.len());
+
+        let key = path.interpret().unwrap();
+        cache.files.lock().unwrap().get_mut(&key).unwrap().hash = "cached".to_string();
+        assert_eq!("cached", cache.sha1(&path, size));
+    }
+
+    #[test]
+    fn rehashes_file_when_size_changes() {
+        let cache = HashCache::default();
+        let path = repo_path("tests/root1/game1/subdir/file2.txt");
+        let size = path.size();
+
+        cache.sha1(&path, size);
+        let key = path.interpret().unwrap();
+        cache.files.lock().unwrap().get_mut(&key).unwrap().hash = "cached".to_string();
+
+        assert_eq!("9d891e731f75deae56884d79e9816736b7488080", cache.sha1(&path, size + 1));
+    }
+
+    #[test]
+    fn falls_back_to_full_hash_for_missing_file() {
+        let cache = HashCache::default();
+        let path = repo_path("tests/root1/game1/nonexistent.txt");
+
+        assert_eq!("", cache.sha1(&path, 0));
+        assert_eq!(0, cache.len());
+    }
+
+    #[test]
+    fn can_clear_and_prune() {
+        let cache = HashCache::default();
+        let path = repo_path("tests/root1/game1/subdir/file2.txt");
+        cache.sha1(&path, path.size());
+        cache.files.lock().unwrap().insert(
+            repo_path("tests/root1/game1/nonexistent.txt").interpret().unwrap(),
+            CachedHash {
+                size: 0,
+                modified: DateTime::default(),
+                hash: "x".to_string(),
+            },
+        );
+        assert_eq!(2, cache.len());
+
+        cache.prune();
+        assert_eq!(1, cache.len());
+
+        cache.clear();
+        assert_eq!(0, cache.len());
+    }
+}
@@ -1,7 +1,11 @@
+mod bottles;
 mod generic;
 pub mod heroic;
+mod itch;
 mod legendary;
 mod lutris;
+pub mod minecraft;
+pub mod retroarch;
 
 use std::collections::{HashMap, HashSet};
 
@@ -11,13 +15,15 @@ use crate::{
         config::Root,
         manifest::{Manifest, Os},
     },
-    scan::TitleFinder,
+    scan::{SteamAccounts, TitleFinder},
 };
 
 #[derive(Clone, Default, Debug)]
 pub struct Launchers {
     games: HashMap<Root, HashMap<String, HashSet<LauncherGame>>>,
     empty: HashSet<LauncherGame>,
+    steam_accounts: HashMap<Root, SteamAccounts>,
+    no_steam_accounts: SteamAccounts,
 }
 
 #[derive(Debug, Clone, Eq, PartialEq, Hash)]
@@ -42,6 +48,10 @@ impl Launchers {
             .iter()
     }
 
+    pub fn get_steam_accounts(&self, root: &Root) -> &SteamAccounts {
+        self.steam_accounts.get(root).unwrap_or(&self.no_steam_accounts)
+    }
+
     pub fn scan(
         roots: &[Root],
         manifest: &Manifest,
@@ -54,9 +64,13 @@ impl Launchers {
         for root in roots {
             log::debug!("Scanning launcher info: {:?}", &root);
             let mut found = match root {
+                Root::Bottles(root) => bottles::scan(root, title_finder),
                 Root::Heroic(root) => heroic::scan(root, title_finder, legendary.as_ref()),
+                Root::Itch(root) => itch::scan(root, title_finder),
                 Root::Legendary(root) => legendary::scan(root, title_finder),
                 Root::Lutris(root) => lutris::scan(root, title_finder),
+                // These roots' content is turned into games of its own when loading the manifest.
+                Root::Minecraft(_) | Root::RetroArch(_) => HashMap::new(),
                 _ => generic::scan(root, manifest, subjects),
             };
             found.retain(|_k, v| {
@@ -67,6 +81,14 @@ impl Launchers {
             if !found.is_empty() {
                 instance.games.entry(root.clone()).or_default().extend(found);
             }
+
+            if let Root::Steam(steam) = root {
+                let accounts = SteamAccounts::scan(&steam.path);
+                log::debug!("Steam accounts found ({:?}): {:?}", &root, &accounts);
+                if !accounts.is_empty() {
+                    instance.steam_accounts.insert(root.clone(), accounts);
+                }
+            }
         }
 
         instance
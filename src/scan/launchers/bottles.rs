@@ -0,0 +1,185 @@
+use std::collections::{HashMap, HashSet};
+
+use crate::{
+    prelude::{StrictPath, ENV_DEBUG},
+    resource::{config::root, manifest::Os},
+    scan::{launchers::LauncherGame, TitleFinder},
+};
+
+/// https://github.com/bottlesdevs/Bottles/blob/main/bottles/backend/models/config.py
+mod bottle {
+    use std::collections::HashMap;
+
+    pub const PATH: &str = "bottle.yml";
+
+    #[derive(serde::Deserialize)]
+    pub struct Data {
+        #[serde(rename = "Name")]
+        pub name: Option<String>,
+        #[serde(rename = "External_Programs", default)]
+        pub external_programs: HashMap<String, Program>,
+    }
+
+    #[derive(Clone, serde::Deserialize)]
+    pub struct Program {
+        pub name: Option<String>,
+        pub path: Option<String>,
+        pub folder: Option<String>,
+        #[serde(default)]
+        pub removed: bool,
+    }
+}
+
+pub fn scan(root: &root::Bottles, title_finder: &TitleFinder) -> HashMap<String, HashSet<LauncherGame>> {
+    log::trace!("Scanning Bottles root for games: {:?}", &root);
+
+    let mut out = HashMap::<String, HashSet<LauncherGame>>::new();
+
+    for spec_path in root.path.joined(&format!("bottles/*/{}", bottle::PATH)).glob() {
+        let Some(prefix) = spec_path.parent() else {
+            continue;
+        };
+        let Some(data) = read_bottle(&spec_path) else {
+            continue;
+        };
+        let bottle_name = data.name.unwrap_or_else(|| prefix.leaf().unwrap_or_default());
+
+        for program in data.external_programs.into_values() {
+            if program.removed {
+                continue;
+            }
+            let Some(name) = program.name else {
+                continue;
+            };
+
+            let Some(official_title) = title_finder.find_one_by_normalized_name(&name) else {
+                log::trace!("Ignoring unrecognized game: {}", &name);
+                if std::env::var(ENV_DEBUG).is_ok() {
+                    eprintln!(
+                        "Ignoring unrecognized game from Bottles: {} (bottle = {})",
+                        &name, &bottle_name
+                    );
+                }
+                continue;
+            };
+
+            let install_dir = program
+                .folder
+                .map(|folder| in_bottle(&prefix, &folder))
+                .or_else(|| program.path.and_then(|path| in_bottle(&prefix, &path).parent()));
+
+            log::trace!(
+                "Detected game: {} | bottle: {}, raw title: {}",
+                &official_title,
+                &bottle_name,
+                &name
+            );
+            out.entry(official_title).or_default().insert(LauncherGame {
+                install_dir,
+                prefix: Some(prefix.clone()),
+                platform: Some(Os::Windows),
+            });
+        }
+    }
+
+    out
+}
+
+fn read_bottle(path: &StrictPath) -> Option<bottle::Data> {
+    let content = match path.try_read() {
+        Ok(content) => content,
+        Err(e) => {
+            log::debug!("Unable to read Bottles config '{:?}': {:?}", &path, e);
+            return None;
+        }
+    };
+
+    match serde_yaml::from_str::<bottle::Data>(&content) {
+        Ok(data) => Some(data),
+        Err(e) => {
+            log::info!("Unable to parse Bottles config '{:?}': {:?}", &path, e);
+            None
+        }
+    }
+}
+
+/// Programs may be recorded with a Windows path inside the bottle (e.g., `C:\Games\foo.exe`),
+/// so we map those onto the bottle's drive folders.
+fn in_bottle(prefix: &StrictPath, raw: &str) -> StrictPath {
+    let normalized = raw.replace('\\', "/");
+    let mut chars = normalized.chars();
+    match (chars.next(), chars.next(), chars.next()) {
+        (Some(drive), Some(':'), Some('/') | None) if drive.is_ascii_alphabetic() => prefix.joined(&format!(
+            "drive_{}/{}",
+            drive.to_ascii_lowercase(),
+            normalized[2..].trim_start_matches('/')
+        )),
+        _ => StrictPath::new(normalized),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use pretty_assertions::assert_eq;
+    use velcro::{hash_map, hash_set};
+
+    use super::*;
+    use crate::{
+        resource::{manifest::Manifest, ResourceFile},
+        testing::repo,
+    };
+
+    fn manifest() -> Manifest {
+        Manifest::load_from_string(
+            r#"
+            game-1:
+              files:
+                <base>/file1.txt: {}
+            game-2:
+              files:
+                <base>/file2.txt: {}
+            game-3:
+              files:
+                <base>/file3.txt: {}
+            "#,
+        )
+        .unwrap()
+    }
+
+    fn title_finder() -> TitleFinder {
+        TitleFinder::new(&Default::default(), &manifest(), Default::default())
+    }
+
+    #[test]
+    fn scan_finds_nothing_when_folder_does_not_exist() {
+        let root = root::Bottles {
+            path: format!("{}/tests/nonexistent", repo()).into(),
+        };
+        let games = scan(&root, &title_finder());
+        assert_eq!(HashMap::new(), games);
+    }
+
+    #[test]
+    fn scan_finds_all_games() {
+        let root = root::Bottles {
+            path: format!("{}/tests/launchers/bottles", repo()).into(),
+        };
+        let games = scan(&root, &title_finder());
+        let prefix = StrictPath::new(format!("{}/tests/launchers/bottles/bottles/gaming", repo()));
+        assert_eq!(
+            hash_map! {
+                "game-1".to_string(): hash_set![LauncherGame {
+                    install_dir: Some(StrictPath::new("/games/game-1".to_string())),
+                    prefix: Some(prefix.clone()),
+                    platform: Some(Os::Windows),
+                }],
+                "game-2".to_string(): hash_set![LauncherGame {
+                    install_dir: Some(prefix.joined("drive_c/Games/game-2")),
+                    prefix: Some(prefix.clone()),
+                    platform: Some(Os::Windows),
+                }],
+            },
+            games,
+        );
+    }
+}
@@ -0,0 +1,241 @@
+use std::collections::{HashMap, HashSet};
+
+use crate::{
+    prelude::{StrictPath, ENV_DEBUG},
+    resource::{config::root, manifest::Os},
+    scan::{launchers::LauncherGame, TitleFinder},
+};
+
+pub const DATABASE: &str = "db/butler.db";
+
+#[derive(Debug)]
+enum Error {
+    NoDatabase,
+    #[allow(unused)]
+    Sql(rusqlite::Error),
+}
+
+impl From<rusqlite::Error> for Error {
+    fn from(value: rusqlite::Error) -> Self {
+        Self::Sql(value)
+    }
+}
+
+/// https://github.com/itchio/butler/blob/master/butlerd/types.go
+mod verdict {
+    #[derive(Debug, Default, serde::Deserialize)]
+    #[serde(default, rename_all = "camelCase")]
+    pub struct Data {
+        pub base_path: Option<String>,
+        pub candidates: Vec<Candidate>,
+    }
+
+    #[derive(Debug, Default, serde::Deserialize)]
+    #[serde(default)]
+    pub struct Candidate {
+        pub flavor: Option<String>,
+    }
+}
+
+#[derive(Debug)]
+struct Cave {
+    title: String,
+    install_dir: Option<StrictPath>,
+    platform: Option<Os>,
+}
+
+pub fn scan(root: &root::Itch, title_finder: &TitleFinder) -> HashMap<String, HashSet<LauncherGame>> {
+    log::trace!("Scanning itch root for games: {:?}", &root);
+
+    let mut out = HashMap::<String, HashSet<LauncherGame>>::new();
+
+    let caves = match scan_db(root) {
+        Ok(caves) => caves,
+        Err(e) => {
+            log::error!("Failed to read database: {e:?}");
+            return out;
+        }
+    };
+
+    for cave in caves {
+        let Some(official_title) = title_finder.find_one_by_normalized_name(&cave.title) else {
+            log::trace!("Ignoring unrecognized game: {}", &cave.title);
+            if std::env::var(ENV_DEBUG).is_ok() {
+                eprintln!("Ignoring unrecognized game from itch: {}", &cave.title);
+            }
+            continue;
+        };
+
+        log::trace!("Detected game: {} | raw title: {}", &official_title, &cave.title);
+        out.entry(official_title).or_default().insert(LauncherGame {
+            install_dir: cave.install_dir,
+            prefix: None,
+            platform: cave.platform,
+        });
+    }
+
+    log::trace!("Finished scanning itch root for games: {:?}", &root.path);
+
+    out
+}
+
+fn scan_db(root: &root::Itch) -> Result<Vec<Cave>, Error> {
+    #[derive(Debug)]
+    struct Row {
+        title: Option<String>,
+        install_folder_name: Option<String>,
+        custom_install_folder: Option<String>,
+        verdict: Option<String>,
+        location: Option<String>,
+    }
+
+    let db_file = root.path.joined(DATABASE);
+    if !db_file.is_file() {
+        return Err(Error::NoDatabase);
+    }
+
+    let mut caves = vec![];
+
+    let Ok(file) = db_file.as_std_path_buf() else {
+        return Ok(caves);
+    };
+    let conn = rusqlite::Connection::open_with_flags(file, rusqlite::OpenFlags::SQLITE_OPEN_READ_ONLY)?;
+
+    let mut stmt = conn.prepare(
+        "SELECT games.title, caves.install_folder_name, caves.custom_install_folder, caves.verdict, install_locations.path
+        FROM caves
+        INNER JOIN games ON games.id = caves.game_id
+        LEFT JOIN install_locations ON install_locations.id = caves.install_location_id",
+    )?;
+    let rows = stmt.query_map([], |row| {
+        Ok(Row {
+            title: row.get(0)?,
+            install_folder_name: row.get(1)?,
+            custom_install_folder: row.get(2)?,
+            verdict: row.get(3)?,
+            location: row.get(4)?,
+        })
+    })?;
+
+    for row in rows {
+        match row {
+            Ok(row) => {
+                log::trace!("Row = {row:?}");
+
+                let Some(title) = row.title.filter(|x| !x.trim().is_empty()) else {
+                    log::warn!("Ignoring row without `title`");
+                    continue;
+                };
+
+                let verdict = row
+                    .verdict
+                    .and_then(|x| serde_json::from_str::<verdict::Data>(&x).ok())
+                    .unwrap_or_default();
+
+                let install_dir = match (row.custom_install_folder, row.location, row.install_folder_name) {
+                    (Some(custom), _, _) if !custom.trim().is_empty() => Some(StrictPath::new(custom)),
+                    (_, Some(location), Some(folder)) if !location.trim().is_empty() && !folder.trim().is_empty() => {
+                        Some(StrictPath::new(location).joined(&folder))
+                    }
+                    _ => verdict.base_path.filter(|x| !x.trim().is_empty()).map(StrictPath::new),
+                };
+
+                let platform = verdict
+                    .candidates
+                    .iter()
+                    .find_map(|candidate| candidate.flavor.as_deref().and_then(flavor_platform));
+
+                caves.push(Cave {
+                    title,
+                    install_dir,
+                    platform,
+                });
+            }
+            Err(e) => {
+                log::warn!("Row error: {e:?}");
+            }
+        }
+    }
+
+    Ok(caves)
+}
+
+/// https://github.com/itchio/butler/blob/master/butlerd/types.go
+fn flavor_platform(flavor: &str) -> Option<Os> {
+    match flavor {
+        "windows" | "windows-script" | "msi" => Some(Os::Windows),
+        "linux" => Some(Os::Linux),
+        "macos" | "app-macos" => Some(Os::Mac),
+        _ => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use pretty_assertions::assert_eq;
+    use velcro::{hash_map, hash_set};
+
+    use super::*;
+    use crate::{
+        resource::{manifest::Manifest, ResourceFile},
+        testing::repo,
+    };
+
+    fn manifest() -> Manifest {
+        Manifest::load_from_string(
+            r#"
+            game-1:
+              files:
+                <base>/file1.txt: {}
+            game-2:
+              files:
+                <base>/file2.txt: {}
+            game-3:
+              files:
+                <base>/file3.txt: {}
+            "#,
+        )
+        .unwrap()
+    }
+
+    fn title_finder() -> TitleFinder {
+        TitleFinder::new(&Default::default(), &manifest(), Default::default())
+    }
+
+    #[test]
+    fn scan_finds_nothing_when_folder_does_not_exist() {
+        let root = root::Itch {
+            path: format!("{}/tests/nonexistent", repo()).into(),
+        };
+        let games = scan(&root, &title_finder());
+        assert_eq!(HashMap::new(), games);
+    }
+
+    #[test]
+    fn scan_finds_all_games() {
+        let root = root::Itch {
+            path: format!("{}/tests/launchers/itch", repo()).into(),
+        };
+        let games = scan(&root, &title_finder());
+        assert_eq!(
+            hash_map! {
+                "game-1".to_string(): hash_set![LauncherGame {
+                    install_dir: Some(StrictPath::new("/itch/apps/game-1".to_string())),
+                    prefix: None,
+                    platform: Some(Os::Windows),
+                }],
+                "game-2".to_string(): hash_set![LauncherGame {
+                    install_dir: Some(StrictPath::new("/games/game-2".to_string())),
+                    prefix: None,
+                    platform: Some(Os::Linux),
+                }],
+                "game-3".to_string(): hash_set![LauncherGame {
+                    install_dir: Some(StrictPath::new("/other/game-3".to_string())),
+                    prefix: None,
+                    platform: None,
+                }],
+            },
+            games,
+        );
+    }
+}
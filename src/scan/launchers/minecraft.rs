@@ -0,0 +1,232 @@
+use std::collections::BTreeMap;
+
+use crate::{
+    prelude::StrictPath,
+    resource::{
+        config::root,
+        manifest::{Game, GameFileEntry, Manifest, Tag},
+    },
+};
+
+/// Used by MultiMC and its forks, like Prism Launcher.
+pub const INSTANCE_CONFIG: &str = "instance.cfg";
+/// Used by the vanilla launcher.
+pub const LAUNCHER_PROFILES: &str = "launcher_profiles.json";
+
+/// https://minecraft.wiki/w/Launcher_profiles.json
+mod profiles {
+    use std::collections::HashMap;
+
+    #[derive(serde::Deserialize)]
+    pub struct Data {
+        #[serde(default)]
+        pub profiles: HashMap<String, Profile>,
+    }
+
+    #[derive(serde::Deserialize)]
+    #[serde(rename_all = "camelCase")]
+    pub struct Profile {
+        pub name: Option<String>,
+        pub game_dir: Option<String>,
+    }
+}
+
+#[derive(Debug)]
+struct Instance {
+    /// The file that defined the instance.
+    source: StrictPath,
+    /// Unset for the vanilla launcher's default game folder.
+    name: Option<String>,
+    game_dir: StrictPath,
+}
+
+/// Build a virtual game for each world in the root's instances.
+/// The result is grouped by the file that defined each instance.
+pub fn find_games(root: &root::Minecraft) -> BTreeMap<StrictPath, Manifest> {
+    log::trace!("Scanning Minecraft root for instances: {:?}", &root);
+
+    let mut out = BTreeMap::<StrictPath, Manifest>::new();
+
+    for instance in find_instances(root) {
+        log::trace!("Found Minecraft instance: {:?}", &instance);
+
+        for level in instance.game_dir.joined("saves/*/level.dat").glob() {
+            let Some(world_dir) = level.parent() else {
+                continue;
+            };
+            let Some(world) = world_dir.leaf() else {
+                continue;
+            };
+
+            let name = match &instance.name {
+                Some(instance) => format!("Minecraft ({instance}) - {world}"),
+                None => format!("Minecraft - {world}"),
+            };
+
+            out.entry(instance.source.clone()).or_default().0.insert(
+                name,
+                Game {
+                    files: BTreeMap::from_iter([(
+                        globetter::Pattern::escape(&world_dir.render()),
+                        GameFileEntry {
+                            tags: [Tag::Save].into(),
+                            ..Default::default()
+                        },
+                    )]),
+                    ..Default::default()
+                },
+            );
+        }
+    }
+
+    out
+}
+
+fn find_instances(root: &root::Minecraft) -> Vec<Instance> {
+    let mut instances = vec![];
+
+    for source in root.path.joined(&format!("instances/*/{INSTANCE_CONFIG}")).glob() {
+        let Some(instance_dir) = source.parent() else {
+            continue;
+        };
+        let Some(folder) = instance_dir.leaf() else {
+            continue;
+        };
+
+        let name = source
+            .read()
+            .and_then(|content| {
+                content.lines().find_map(|line| {
+                    let (key, value) = line.split_once('=')?;
+                    (key.trim() == "name" && !value.trim().is_empty()).then(|| value.trim().to_string())
+                })
+            })
+            .unwrap_or(folder);
+
+        // Older instances use `minecraft`, while newer ones use `.minecraft`.
+        let Some(game_dir) = [".minecraft", "minecraft"]
+            .into_iter()
+            .map(|x| instance_dir.joined(x))
+            .find(|x| x.is_dir())
+        else {
+            continue;
+        };
+
+        instances.push(Instance {
+            source,
+            name: Some(name),
+            game_dir,
+        });
+    }
+
+    let source = root.path.joined(LAUNCHER_PROFILES);
+    if let Some(content) = source.read() {
+        match serde_json::from_str::<profiles::Data>(&content) {
+            Ok(data) => {
+                instances.push(Instance {
+                    source: source.clone(),
+                    name: None,
+                    game_dir: root.path.clone(),
+                });
+
+                let mut profiles: Vec<_> = data.profiles.into_values().collect();
+                profiles.sort_by(|x, y| x.name.cmp(&y.name));
+                for profile in profiles {
+                    let Some(game_dir) = profile.game_dir.filter(|x| !x.trim().is_empty()) else {
+                        continue;
+                    };
+                    let game_dir = StrictPath::relative(game_dir, Some(root.path.render()));
+                    if instances.iter().any(|x| x.game_dir.equivalent(&game_dir)) {
+                        continue;
+                    }
+                    let Some(name) = profile
+                        .name
+                        .filter(|x| !x.trim().is_empty())
+                        .or_else(|| game_dir.leaf())
+                    else {
+                        continue;
+                    };
+
+                    instances.push(Instance {
+                        source: source.clone(),
+                        name: Some(name),
+                        game_dir,
+                    });
+                }
+            }
+            Err(e) => {
+                log::info!("Unable to parse Minecraft launcher profiles: {:?} | {e:?}", &source);
+            }
+        }
+    }
+
+    instances
+}
+
+#[cfg(test)]
+mod tests {
+    use pretty_assertions::assert_eq;
+    use velcro::btree_map;
+
+    use super::*;
+    use crate::testing::repo;
+
+    fn world(path: String) -> Game {
+        Game {
+            files: btree_map! {
+                path: GameFileEntry {
+                    tags: [Tag::Save].into(),
+                    ..Default::default()
+                },
+            },
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn find_games_finds_nothing_when_folder_does_not_exist() {
+        let root = root::Minecraft {
+            path: format!("{}/tests/nonexistent", repo()).into(),
+        };
+        assert_eq!(BTreeMap::new(), find_games(&root));
+    }
+
+    #[test]
+    fn find_games_finds_all_instance_worlds() {
+        let root = root::Minecraft {
+            path: format!("{}/tests/launchers/minecraft-prism", repo()).into(),
+        };
+        let instances = format!("{}/tests/launchers/minecraft-prism/instances", repo());
+
+        assert_eq!(
+            btree_map! {
+                StrictPath::new(format!("{instances}/modded/instance.cfg")): Manifest(btree_map! {
+                    "Minecraft (Modded [1.20]) - New World".to_string(): world(format!("{instances}/modded/.minecraft/saves/New World")),
+                }),
+                StrictPath::new(format!("{instances}/vanilla/instance.cfg")): Manifest(btree_map! {
+                    "Minecraft (vanilla) - world-1".to_string(): world(format!("{instances}/vanilla/minecraft/saves/world-1")),
+                    "Minecraft (vanilla) - world-2".to_string(): world(format!("{instances}/vanilla/minecraft/saves/world-2")),
+                }),
+            },
+            find_games(&root),
+        );
+    }
+
+    #[test]
+    fn find_games_finds_all_profile_worlds() {
+        let root = root::Minecraft {
+            path: format!("{}/tests/launchers/minecraft-vanilla", repo()).into(),
+        };
+        let base = format!("{}/tests/launchers/minecraft-vanilla", repo());
+
+        assert_eq!(
+            btree_map! {
+                StrictPath::new(format!("{base}/launcher_profiles.json")): Manifest(btree_map! {
+                    "Minecraft - world-1".to_string(): world(format!("{base}/saves/world-1")),
+                    "Minecraft (Snapshots) - world-2".to_string(): world(format!("{base}/snapshots/saves/world-2")),
+                }),
+            },
+            find_games(&root),
+        );
+    }
+}
@@ -0,0 +1,240 @@
+use std::collections::{BTreeMap, BTreeSet, HashMap};
+
+use crate::{
+    prelude::StrictPath,
+    resource::{
+        config::root,
+        manifest::{Game, GameFileEntry, Manifest, Tag},
+    },
+};
+
+pub const CONFIG: &str = "retroarch.cfg";
+
+/// Playlists that RetroArch keeps for its media players rather than for games.
+const MEDIA_PLAYLISTS: &[&str] = &[
+    "content_image_history",
+    "content_music_history",
+    "content_video_history",
+];
+
+/// https://github.com/libretro/RetroArch/blob/master/retroarch.cfg
+#[derive(Debug, Default)]
+struct Settings(HashMap<String, String>);
+
+impl Settings {
+    fn load(root: &root::RetroArch) -> Self {
+        let file = root.path.joined(CONFIG);
+        let Some(content) = file.read() else {
+            log::debug!("Unable to read RetroArch config: {:?}", &file);
+            return Self::default();
+        };
+
+        Self(
+            content
+                .lines()
+                .filter_map(|line| {
+                    let (key, value) = line.split_once('=')?;
+                    let key = key.trim();
+                    if key.starts_with('#') {
+                        return None;
+                    }
+                    Some((key.to_string(), value.trim().trim_matches('"').to_string()))
+                })
+                .collect(),
+        )
+    }
+
+    fn flag(&self, key: &str) -> bool {
+        self.0.get(key).is_some_and(|x| x == "true")
+    }
+
+    /// RetroArch uses `default` (or an empty value) for its own fallback folder,
+    /// and a leading `:` for paths relative to the RetroArch folder.
+    fn directory(&self, root: &root::RetroArch, key: &str, fallback: &str) -> StrictPath {
+        match self.0.get(key).map(|x| x.as_str()) {
+            None | Some("" | "default") => root.path.joined(fallback),
+            Some(raw) => match raw.strip_prefix(':') {
+                Some(relative) => root.path.joined(relative.trim_start_matches(['/', '\\'])),
+                None => StrictPath::new(raw.to_string()),
+            },
+        }
+    }
+}
+
+/// https://docs.libretro.com/guides/roms-playlists-thumbnails/
+mod playlist {
+    #[derive(serde::Deserialize)]
+    pub struct Data {
+        #[serde(default)]
+        pub items: Vec<Item>,
+    }
+
+    #[derive(serde::Deserialize)]
+    pub struct Item {
+        pub path: Option<String>,
+        pub label: Option<String>,
+    }
+}
+
+/// Build a virtual game for each piece of content in the root's playlists.
+/// The result is grouped by the playlist that listed the content.
+pub fn find_games(root: &root::RetroArch) -> BTreeMap<StrictPath, Manifest> {
+    log::trace!("Scanning RetroArch root for content: {:?}", &root);
+
+    let mut out = BTreeMap::new();
+
+    let settings = Settings::load(root);
+    let saves = globetter::Pattern::escape(&settings.directory(root, "savefile_directory", "saves").render());
+    let states = globetter::Pattern::escape(&settings.directory(root, "savestate_directory", "states").render());
+    let playlists = settings.directory(root, "playlist_directory", "playlists");
+
+    for playlist_path in playlists.joined("*.lpl").glob() {
+        if playlist_path
+            .file_stem()
+            .is_some_and(|stem| MEDIA_PLAYLISTS.contains(&stem.as_str()))
+        {
+            continue;
+        }
+
+        let Some(content) = playlist_path.read() else {
+            log::warn!("Unable to read RetroArch playlist: {:?}", &playlist_path);
+            continue;
+        };
+        let data = match serde_json::from_str::<playlist::Data>(&content) {
+            Ok(data) => data,
+            Err(e) => {
+                // Older versions of RetroArch used a line-based format, which we don't support.
+                log::info!("Unable to parse RetroArch playlist: {:?} | {e:?}", &playlist_path);
+                continue;
+            }
+        };
+
+        let mut manifest = Manifest::default();
+        for item in data.items {
+            let Some(content_path) = item.path.filter(|x| !x.trim().is_empty()) else {
+                continue;
+            };
+            let Some(stem) = content_stem(&content_path) else {
+                continue;
+            };
+            let name = item
+                .label
+                .filter(|x| !x.trim().is_empty())
+                .unwrap_or_else(|| stem.clone());
+
+            let stem = globetter::Pattern::escape(&stem);
+            let mut files = vec![
+                format!("{saves}/{stem}.*"),
+                format!("{saves}/*/{stem}.*"),
+                format!("{states}/{stem}.state*"),
+                format!("{states}/*/{stem}.state*"),
+            ];
+            if let Some(content_dir) = content_dir(&content_path) {
+                let content_dir = globetter::Pattern::escape(&content_dir.render());
+                if settings.flag("savefiles_in_content_dir") {
+                    files.push(format!("{content_dir}/{stem}.srm"));
+                }
+                if settings.flag("savestates_in_content_dir") {
+                    files.push(format!("{content_dir}/{stem}.state*"));
+                }
+            }
+
+            log::trace!("Found RetroArch content: {name} | {content_path}");
+            manifest
+                .0
+                .entry(name)
+                .or_insert_with(Game::default)
+                .files
+                .extend(files.into_iter().map(|file| {
+                    (
+                        file,
+                        GameFileEntry {
+                            tags: BTreeSet::from_iter([Tag::Save]),
+                            ..Default::default()
+                        },
+                    )
+                }));
+        }
+
+        if !manifest.0.is_empty() {
+            out.insert(playlist_path, manifest);
+        }
+    }
+
+    out
+}
+
+/// Content inside of an archive is written as `foo.zip#foo.sfc`,
+/// and RetroArch names the saves after the inner file.
+fn content_stem(content_path: &str) -> Option<String> {
+    let inner = content_path.rsplit('#').next()?.replace('\\', "/");
+    std::path::Path::new(&inner)
+        .file_stem()
+        .map(|x| x.to_string_lossy().to_string())
+}
+
+fn content_dir(content_path: &str) -> Option<StrictPath> {
+    let outer = content_path.split('#').next()?;
+    StrictPath::new(outer.replace('\\', "/")).parent_raw()
+}
+
+#[cfg(test)]
+mod tests {
+    use pretty_assertions::assert_eq;
+    use velcro::btree_map;
+
+    use super::*;
+    use crate::testing::repo;
+
+    fn save() -> GameFileEntry {
+        GameFileEntry {
+            tags: BTreeSet::from_iter([Tag::Save]),
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn find_games_finds_nothing_when_folder_does_not_exist() {
+        let root = root::RetroArch {
+            path: format!("{}/tests/nonexistent", repo()).into(),
+        };
+        assert_eq!(BTreeMap::new(), find_games(&root));
+    }
+
+    #[test]
+    fn find_games_finds_all_content() {
+        let root = root::RetroArch {
+            path: format!("{}/tests/launchers/retroarch", repo()).into(),
+        };
+        let saves = format!("{}/tests/launchers/retroarch/saves", repo());
+        let states = format!("{}/tests/launchers/retroarch/states", repo());
+
+        assert_eq!(
+            btree_map! {
+                StrictPath::new(format!("{}/tests/launchers/retroarch/playlists/Nintendo - SNES.lpl", repo())): Manifest(btree_map! {
+                    "Game 1".to_string(): Game {
+                        files: btree_map! {
+                            format!("{saves}/game-1.*"): save(),
+                            format!("{saves}/*/game-1.*"): save(),
+                            format!("{states}/game-1.state*"): save(),
+                            format!("{states}/*/game-1.state*"): save(),
+                            "/roms/snes/game-1.state*".to_string(): save(),
+                        },
+                        ..Default::default()
+                    },
+                    "game-2 [!]".to_string(): Game {
+                        files: btree_map! {
+                            format!("{saves}/game-2 [[]![]].*"): save(),
+                            format!("{saves}/*/game-2 [[]![]].*"): save(),
+                            format!("{states}/game-2 [[]![]].state*"): save(),
+                            format!("{states}/*/game-2 [[]![]].state*"): save(),
+                            "/roms/snes/zipped/game-2 [[]![]].state*".to_string(): save(),
+                        },
+                        ..Default::default()
+                    },
+                }),
+            },
+            find_games(&root),
+        );
+    }
+}
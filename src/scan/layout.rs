@@ -1,22 +1,25 @@
 use std::{
-    collections::{BTreeMap, BTreeSet, HashMap, VecDeque},
+    collections::{BTreeMap, BTreeSet, HashMap, HashSet, VecDeque},
     io::Write,
+    sync::{Arc, Mutex},
 };
 
 use chrono::{Datelike, Timelike};
 
 use crate::{
     path::StrictPath,
-    prelude::{AnyError, INVALID_FILE_CHARS},
+    prelude::{AnyError, Error, INVALID_FILE_CHARS},
     resource::{
         config::{
-            BackupFormat, BackupFormats, RedirectConfig, Retention, ToggledPaths, ToggledRegistry, ZipCompression,
+            BackupFormat, BackupFormats, ConflictPolicy, RedirectConfig, Retention, SteamAccountRedirect, ToggledPaths,
+            ToggledRegistry, ZipCompression,
         },
         manifest::Os,
     },
     scan::{
-        game_file_target, prepare_backup_target, registry, BackupError, BackupId, BackupInfo, ScanChange, ScanInfo,
-        ScanKind, ScannedFile,
+        encryption::{Cipher, EncryptedWriter, EncryptionInfo, Passphrase},
+        game_file_target, prepare_backup_target, redirect_steam_account, registry, steam_account_id, BackupError,
+        BackupId, BackupInfo, ScanChange, ScanInfo, ScanKind, ScannedFile,
     },
 };
 
@@ -25,6 +28,38 @@ use crate::scan::ScannedRegistry;
 
 const SAFE: &str = "_";
 
+/// Folder within the backup directory where the deduplicated format stores file contents.
+/// Game folders never start with a dot (see `escape_folder_name`), so this can't collide with one.
+pub const BLOB_FOLDER: &str = ".blobs";
+
+/// Comment prefix for backups made automatically right before a restore.
+const PRE_RESTORE_COMMENT: &str = "pre-restore";
+
+/// Lets us read plain and decrypted zip archives the same way.
+trait ReadSeek: std::io::Read + std::io::Seek {}
+impl<T: std::io::Read + std::io::Seek> ReadSeek for T {}
+
+type ZipReader = zip::ZipArchive<Box<dyn ReadSeek>>;
+
+/// A file's content as read back from an existing backup, so that it can be rewritten into a new one.
+struct StoredFile {
+    data: IndividualMappingFile,
+    content: Vec<u8>,
+    mtime: Option<chrono::DateTime<chrono::Utc>>,
+}
+
+/// Each stored file's original path, mapped to the backup that stores it and its mapping data.
+type StoredFileSources = BTreeMap<String, (String, IndividualMappingFile)>;
+
+/// Destination for `GameLayout::write_stored_files`.
+enum StoredFileWriter {
+    Simple { relevant_files: Vec<StrictPath> },
+    Zip(zip::ZipWriter<std::fs::File>),
+    EncryptedZip(zip::ZipWriter<EncryptedWriter<std::fs::File>>),
+    TarZstd(tar::Builder<zstd::Encoder<'static, std::fs::File>>),
+    Dedup,
+}
+
 macro_rules! some_or_continue {
     ($maybe:expr) => {
         match $maybe {
@@ -40,6 +75,114 @@ fn encode_base64_for_folder(name: &str) -> String {
     BASE64_STANDARD.encode(name).replace('/', SAFE)
 }
 
+fn sha1_reader(mut reader: impl std::io::Read) -> Option<String> {
+    use sha1::Digest;
+
+    let mut hasher = sha1::Sha1::new();
+    std::io::copy(&mut reader, &mut hasher).ok()?;
+    Some(format!("{:x}", hasher.finalize()))
+}
+
+fn format_from_backup_name(name: &str) -> BackupFormat {
+    if name.ends_with(".zip") {
+        BackupFormat::Zip
+    } else if name.ends_with(".tar.zst") {
+        BackupFormat::TarZstd
+    } else if name.ends_with(".dedup") {
+        BackupFormat::Dedup
+    } else {
+        BackupFormat::Simple
+    }
+}
+
+fn name_with_format_extension(name: String, format: BackupFormat) -> String {
+    match format {
+        BackupFormat::Simple => name,
+        BackupFormat::Zip => format!("{name}.zip"),
+        BackupFormat::TarZstd => format!("{name}.tar.zst"),
+        BackupFormat::Dedup => format!("{name}.dedup"),
+    }
+}
+
+/// Zips don't store time zones, so we treat their times as UTC.
+fn zip_time_to_utc(mtime: zip::DateTime) -> Option<chrono::DateTime<chrono::Utc>> {
+    chrono::NaiveDate::from_ymd_opt(mtime.year() as i32, mtime.month() as u32, mtime.day() as u32)?
+        .and_hms_opt(mtime.hour() as u32, mtime.minute() as u32, mtime.second() as u32)
+        .map(|x| x.and_utc())
+}
+
+/// Zip doesn't support years before 1980, so those fall back to the default.
+fn utc_to_zip_time(mtime: &chrono::DateTime<chrono::Utc>) -> zip::DateTime {
+    zip::DateTime::from_date_and_time(
+        mtime.year() as u16,
+        mtime.month() as u8,
+        mtime.day() as u8,
+        mtime.hour() as u8,
+        mtime.minute() as u8,
+        mtime.second() as u8,
+    )
+    .unwrap_or_default()
+}
+
+fn append_to_zip<W: std::io::Write + std::io::Seek>(
+    zip: &mut zip::ZipWriter<W>,
+    name: &str,
+    content: &[u8],
+    mtime: Option<&chrono::DateTime<chrono::Utc>>,
+    options: zip::write::FileOptions,
+) -> Result<(), AnyError> {
+    let options = match mtime {
+        Some(mtime) => options.last_modified_time(utc_to_zip_time(mtime)),
+        None => options,
+    };
+    zip.start_file(name, options)?;
+    zip.write_all(content)?;
+    Ok(())
+}
+
+fn append_to_tar<W: std::io::Write>(
+    tar: &mut tar::Builder<W>,
+    name: &str,
+    content: &[u8],
+    mtime: Option<&chrono::DateTime<chrono::Utc>>,
+) -> std::io::Result<()> {
+    let mut header = tar::Header::new_gnu();
+    header.set_size(content.len() as u64);
+    header.set_mode(0o644);
+    header.set_mtime(mtime.map(|x| x.timestamp().max(0) as u64).unwrap_or_default());
+    header.set_cksum();
+    tar.append_data(&mut header, name, content)
+}
+
+/// Blobs are sharded by the first two characters of their hash
+/// so that no single folder ends up with too many entries.
+fn blob_file(store: &StrictPath, hash: &str) -> StrictPath {
+    let shard = hash.get(..2).unwrap_or(hash);
+    store.joined(&format!("{shard}/{hash}"))
+}
+
+/// Blobs are named after their content, so an existing one can be reused as long as its size matches.
+/// Rehashing every blob would make each backup read all of the unchanged content again,
+/// so full hash checks are left to `backups validate`.
+fn blob_present(blob: &StrictPath, size: u64) -> bool {
+    blob.is_file() && blob.size() == size
+}
+
+/// Write to a temporary file first so that an interrupted backup can't leave a truncated blob behind.
+/// Games are backed up in parallel and may share a blob, so each write gets its own temporary file.
+fn write_blob(blob: &StrictPath, write: impl FnOnce(&StrictPath) -> std::io::Result<()>) -> std::io::Result<()> {
+    static COUNTER: std::sync::atomic::AtomicU64 = std::sync::atomic::AtomicU64::new(0);
+    let unique = COUNTER.fetch_add(1, std::sync::atomic::Ordering::Relaxed);
+    let temp = StrictPath::new(format!("{}.{}-{unique}.tmp", blob.render(), std::process::id()));
+    blob.create_parent_dir()?;
+
+    let written = write(&temp).and_then(|_| temp.move_to(blob));
+    if written.is_err() {
+        let _ = temp.remove();
+    }
+    written
+}
+
 pub fn escape_folder_name(name: &str) -> String {
     let mut escaped = String::from(name);
 
@@ -115,6 +258,14 @@ impl Backup {
         }
     }
 
+    /// Whether this backup was made automatically right before a restore.
+    pub fn pre_restore(&self) -> bool {
+        match self {
+            Self::Full(x) => x.pre_restore,
+            Self::Differential(_) => false,
+        }
+    }
+
     pub fn locked(&self) -> bool {
         match self {
             Self::Full(x) => x.locked,
@@ -129,6 +280,13 @@ impl Backup {
         }
     }
 
+    pub fn set_encryption(&mut self, encryption: Option<EncryptionInfo>) {
+        match self {
+            Self::Full(x) => x.encryption = encryption,
+            Self::Differential(x) => x.encryption = encryption,
+        }
+    }
+
     pub fn label(&self) -> String {
         match self {
             Self::Full(x) => x.label(),
@@ -243,6 +401,14 @@ pub struct FullBackup {
     /// Locked backups do not count toward retention limits and are never deleted.
     #[serde(skip_serializing_if = "std::ops::Not::not")]
     pub locked: bool,
+    /// Set when the backup's content is encrypted.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub encryption: Option<EncryptionInfo>,
+    /// Snapshots taken right before a restore are kept apart from the regular backups:
+    /// they are never the latest backup, never a differential parent,
+    /// and they don't count toward retention limits.
+    #[serde(skip_serializing_if = "std::ops::Not::not")]
+    pub pre_restore: bool,
     pub files: BTreeMap<String, IndividualMappingFile>,
     pub registry: IndividualMappingRegistry,
     pub children: VecDeque<DifferentialBackup>,
@@ -256,11 +422,12 @@ impl FullBackup {
     }
 
     pub fn format(&self) -> BackupFormat {
-        if self.name.ends_with(".zip") {
-            BackupFormat::Zip
-        } else {
-            BackupFormat::Simple
-        }
+        format_from_backup_name(&self.name)
+    }
+
+    /// Total size of the files stored in this backup, not including its children.
+    pub fn size(&self) -> u64 {
+        self.files.values().map(|x| x.size).sum()
     }
 }
 
@@ -283,11 +450,19 @@ pub struct DifferentialBackup {
     /// Locked backups do not count toward retention limits and are never deleted.
     #[serde(skip_serializing_if = "std::ops::Not::not")]
     pub locked: bool,
+    /// Set when the backup's content is encrypted.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub encryption: Option<EncryptionInfo>,
     pub files: BTreeMap<String, Option<IndividualMappingFile>>,
     pub registry: Option<IndividualMappingRegistry>,
 }
 
 impl DifferentialBackup {
+    /// Total size of the files stored in this backup, not including inherited ones.
+    pub fn size(&self) -> u64 {
+        self.files.values().flatten().map(|x| x.size).sum()
+    }
+
     /// File path must be in rendered form.
     pub fn file(&self, file: String) -> BackupInclusion {
         match self.files.get(&file) {
@@ -310,15 +485,23 @@ impl DifferentialBackup {
     }
 
     pub fn format(&self) -> BackupFormat {
-        if self.name.ends_with(".zip") {
-            BackupFormat::Zip
-        } else {
-            BackupFormat::Simple
-        }
+        format_from_backup_name(&self.name)
     }
 }
 
-#[derive(Clone, Default, Debug, Eq, PartialEq, Ord, PartialOrd, Hash, serde::Serialize, serde::Deserialize)]
+#[derive(
+    Clone,
+    Default,
+    Debug,
+    Eq,
+    PartialEq,
+    Ord,
+    PartialOrd,
+    Hash,
+    serde::Serialize,
+    serde::Deserialize,
+    schemars::JsonSchema,
+)]
 #[serde(default, rename_all = "camelCase")]
 pub struct IndividualMappingFile {
     pub hash: String,
@@ -329,6 +512,9 @@ pub struct IndividualMappingFile {
 #[serde(default, rename_all = "camelCase")]
 pub struct IndividualMappingRegistry {
     pub hash: Option<String>,
+    /// Wine prefixes that the registry content was read from.
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub wine_prefixes: Vec<String>,
 }
 
 #[derive(Clone, Debug, Default, Eq, PartialEq, serde::Serialize, serde::Deserialize)]
@@ -347,6 +533,23 @@ impl IndividualMapping {
         }
     }
 
+    /// Total size of the files stored in all backups.
+    /// Deduplicated backups may refer to the same blob more than once, but each blob is only counted once.
+    pub fn size(&self) -> u64 {
+        self.usage().total()
+    }
+
+    pub fn usage(&self) -> StorageUsage {
+        let mut usage = StorageUsage::default();
+        for full in &self.backups {
+            usage.add(full.format(), full.files.values());
+            for diff in &full.children {
+                usage.add(diff.format(), diff.files.values().flatten());
+            }
+        }
+        usage
+    }
+
     fn reversed_drives(&self) -> HashMap<String, String> {
         self.drives.iter().map(|(k, v)| (v.to_owned(), k.to_owned())).collect()
     }
@@ -410,8 +613,16 @@ impl IndividualMapping {
         format!("{}/{}", drive_folder, plain_path).replace('\\', "/")
     }
 
+    /// Index of the latest full backup that isn't a pre-restore snapshot.
+    fn latest_regular_backup(&self) -> Option<usize> {
+        self.backups.iter().rposition(|x| !x.pre_restore)
+    }
+
     fn latest_backup(&self) -> Option<(&FullBackup, Option<&DifferentialBackup>)> {
-        let full = self.backups.back();
+        let full = match self.latest_regular_backup() {
+            Some(i) => self.backups.get(i),
+            None => self.backups.back(),
+        };
         full.map(|x| (x, x.children.back()))
     }
 
@@ -519,12 +730,18 @@ impl IndividualMapping {
 pub struct GameLayout {
     pub path: StrictPath,
     mapping: IndividualMapping,
+    /// For encrypting new backups and decrypting existing ones.
+    passphrase: Option<Passphrase>,
 }
 
 impl GameLayout {
     #[cfg(test)]
     pub fn new(path: StrictPath, mapping: IndividualMapping) -> Self {
-        Self { path, mapping }
+        Self {
+            path,
+            mapping,
+            passphrase: None,
+        }
     }
 
     pub fn load(path: StrictPath) -> Result<Self, AnyError> {
@@ -532,9 +749,15 @@ impl GameLayout {
         Ok(Self {
             path,
             mapping: IndividualMapping::load(&mapping)?,
+            passphrase: None,
         })
     }
 
+    pub fn with_passphrase(mut self, passphrase: Option<Passphrase>) -> Self {
+        self.passphrase = passphrase;
+        self
+    }
+
     pub fn save(&self) {
         self.mapping.save(&Self::mapping_file(&self.path))
     }
@@ -619,6 +842,7 @@ impl GameLayout {
                 ),
                 // Registry is handled separately.
                 found_registry_keys: Default::default(),
+                wine_prefixes: vec![],
                 available_backups: vec![],
                 backup: None,
                 has_backups: true,
@@ -727,10 +951,13 @@ impl GameLayout {
                             redirected,
                             original_path: Some(original_path),
                             container: None,
+                            conflict: false,
+                            filtered: false,
+                            steam_account: None,
                         },
                     );
                 }
-                BackupFormat::Zip => {
+                BackupFormat::Zip | BackupFormat::TarZstd => {
                     let scan_key = StrictPath::new(self.mapping.game_file_for_zip_immutable(&original_path));
 
                     restorables.insert(
@@ -748,6 +975,33 @@ impl GameLayout {
                             redirected,
                             original_path: Some(original_path),
                             container: Some(self.path.joined(&backup.name)),
+                            conflict: false,
+                            filtered: false,
+                            steam_account: None,
+                        },
+                    );
+                }
+                BackupFormat::Dedup => {
+                    let scan_key = StrictPath::new(self.mapping.game_file_for_zip_immutable(&original_path));
+
+                    restorables.insert(
+                        scan_key,
+                        ScannedFile {
+                            change: match scan_kind {
+                                ScanKind::Backup => ScanChange::Unknown,
+                                ScanKind::Restore => {
+                                    ScanChange::evaluate_restore(redirected.as_ref().unwrap_or(&original_path), &v.hash)
+                                }
+                            },
+                            size: v.size,
+                            hash: v.hash.clone(),
+                            ignored: toggled_paths.is_ignored(&self.mapping.name, ignorable_path),
+                            redirected,
+                            original_path: Some(original_path),
+                            container: Some(self.blob_file(&v.hash)),
+                            conflict: false,
+                            filtered: false,
+                            steam_account: None,
                         },
                     );
                 }
@@ -798,10 +1052,13 @@ impl GameLayout {
                             redirected,
                             original_path: Some(original_path),
                             container: None,
+                            conflict: false,
+                            filtered: false,
+                            steam_account: None,
                         },
                     );
                 }
-                BackupFormat::Zip => {
+                BackupFormat::Zip | BackupFormat::TarZstd => {
                     let scan_key = StrictPath::new(self.mapping.game_file_for_zip_immutable(&original_path));
 
                     restorables.insert(
@@ -819,6 +1076,33 @@ impl GameLayout {
                             redirected,
                             original_path: Some(original_path),
                             container: Some(self.path.joined(&backup.name)),
+                            conflict: false,
+                            filtered: false,
+                            steam_account: None,
+                        },
+                    );
+                }
+                BackupFormat::Dedup => {
+                    let scan_key = StrictPath::new(self.mapping.game_file_for_zip_immutable(&original_path));
+
+                    restorables.insert(
+                        scan_key,
+                        ScannedFile {
+                            change: match scan_kind {
+                                ScanKind::Backup => ScanChange::Unknown,
+                                ScanKind::Restore => {
+                                    ScanChange::evaluate_restore(redirected.as_ref().unwrap_or(&original_path), &v.hash)
+                                }
+                            },
+                            size: v.size,
+                            hash: v.hash.clone(),
+                            ignored: toggled_paths.is_ignored(&self.mapping.name, ignorable_path),
+                            redirected,
+                            original_path: Some(original_path),
+                            container: Some(self.blob_file(&v.hash)),
+                            conflict: false,
+                            filtered: false,
+                            steam_account: None,
                         },
                     );
                 }
@@ -868,6 +1152,9 @@ impl GameLayout {
                         ignored: false,
                         container: None,
                         redirected: None,
+                        conflict: false,
+                        filtered: false,
+                        steam_account: None,
                     },
                 );
             }
@@ -875,17 +1162,44 @@ impl GameLayout {
         files
     }
 
+    fn blob_store(&self) -> StrictPath {
+        self.path.popped().joined(BLOB_FOLDER)
+    }
+
+    fn blob_file(&self, hash: &str) -> StrictPath {
+        blob_file(&self.blob_store(), hash)
+    }
+
+    fn registry_hash(&self, backup: &str) -> Option<String> {
+        for full in &self.mapping.backups {
+            if full.name == backup {
+                return full.registry.hash.clone();
+            }
+            for diff in &full.children {
+                if diff.name == backup {
+                    return diff.registry.as_ref().and_then(|x| x.hash.clone());
+                }
+            }
+        }
+        None
+    }
+
     #[cfg_attr(not(target_os = "windows"), allow(unused))]
     pub fn registry_content(&self, id: &BackupId) -> Option<registry::Hives> {
+        self.try_registry_content(id).ok().flatten()
+    }
+
+    /// Like `registry_content`, but reporting when an encrypted registry can't be decrypted.
+    pub fn try_registry_content(&self, id: &BackupId) -> Result<Option<registry::Hives>, Error> {
         match self.find_by_id(id) {
-            None => None,
+            None => Ok(None),
             Some((full, None)) => self.registry_content_in(&full.name, &full.format()),
             Some((full, Some(diff))) => {
-                let diff_reg = self.registry_content_in(&diff.name, &diff.format());
+                let diff_reg = self.registry_content_in(&diff.name, &diff.format())?;
                 if diff_reg.is_some() {
-                    diff_reg
+                    Ok(diff_reg)
                 } else if diff.omits_registry() {
-                    None
+                    Ok(None)
                 } else {
                     self.registry_content_in(&full.name, &full.format())
                 }
@@ -893,12 +1207,74 @@ impl GameLayout {
         }
     }
 
-    fn registry_content_in(&self, backup: &str, format: &BackupFormat) -> Option<registry::Hives> {
+    /// Wine prefixes to restore the registry content into, following the same inheritance as `registry_content`.
+    #[cfg_attr(target_os = "windows", allow(unused))]
+    pub fn registry_wine_prefixes(&self, id: &BackupId) -> Vec<StrictPath> {
+        let registry = match self.find_by_id(id) {
+            None => None,
+            Some((full, None)) => Some(&full.registry),
+            Some((full, Some(diff))) => match &diff.registry {
+                Some(registry) if registry.hash.is_some() => Some(registry),
+                _ => Some(&full.registry),
+            },
+        };
+
+        registry
+            .map(|x| x.wine_prefixes.iter().map(|x| StrictPath::new(x.clone())).collect())
+            .unwrap_or_default()
+    }
+
+    /// Like `registry_wine_prefixes`, but with the same redirects applied as for files.
+    #[cfg_attr(target_os = "windows", allow(unused))]
+    pub fn restorable_wine_prefixes(
+        &self,
+        id: &BackupId,
+        redirects: &[RedirectConfig],
+        reverse_redirects_on_restore: bool,
+    ) -> Vec<StrictPath> {
+        self.registry_wine_prefixes(id)
+            .into_iter()
+            .map(|prefix| {
+                game_file_target(&prefix, redirects, reverse_redirects_on_restore, ScanKind::Restore).unwrap_or(prefix)
+            })
+            .collect()
+    }
+
+    fn registry_content_in(&self, backup: &str, format: &BackupFormat) -> Result<Option<registry::Hives>, Error> {
+        let cipher = match self.encryption_for(&self.path.joined(backup)) {
+            Some(encryption) if self.registry_hash(backup).is_some() => {
+                Some(Cipher::unlock(self.passphrase.as_ref(), encryption)?)
+            }
+            _ => None,
+        };
+
+        let hives = self.read_registry_in(backup, format, cipher.as_ref());
+        if hives.is_none() && cipher.is_some() {
+            // We know there's a registry, so it must have failed to decrypt.
+            return Err(Error::RegistryIssue);
+        }
+        Ok(hives)
+    }
+
+    fn read_registry_in(
+        &self,
+        backup: &str,
+        format: &BackupFormat,
+        cipher: Option<&Cipher>,
+    ) -> Option<registry::Hives> {
         match format {
             BackupFormat::Simple => {
                 for format in registry::Format::ALL {
                     let candidate = self.path.joined(backup).joined(format.filename());
-                    let hives = registry::Hives::load(&candidate);
+                    let hives = match cipher {
+                        None => registry::Hives::load(&candidate),
+                        Some(cipher) => candidate
+                            .read_bytes()
+                            .ok()
+                            .and_then(|content| cipher.decrypt(&content).ok())
+                            .and_then(|content| String::from_utf8(content).ok())
+                            .and_then(|content| registry::Hives::deserialize(&content, *format)),
+                    };
                     if hives.is_some() {
                         return hives;
                     }
@@ -907,8 +1283,7 @@ impl GameLayout {
                 None
             }
             BackupFormat::Zip => {
-                let handle = self.path.joined(backup).open().ok()?;
-                let mut archive = zip::ZipArchive::new(handle).ok()?;
+                let mut archive = self.open_zip(&self.path.joined(backup), cipher).ok()?;
 
                 for format in registry::Format::ALL {
                     if let Ok(mut file) = archive.by_name(format.filename()) {
@@ -922,6 +1297,32 @@ impl GameLayout {
 
                 None
             }
+            BackupFormat::TarZstd => {
+                let handle = self.path.joined(backup).open().ok()?;
+                let decoder = zstd::Decoder::new(handle).ok()?;
+                let mut archive = tar::Archive::new(decoder);
+
+                for entry in archive.entries().ok()? {
+                    let mut entry = entry.ok()?;
+                    let name = entry.path().ok()?.to_string_lossy().to_string();
+                    let Some(format) = registry::Format::ALL.iter().find(|x| x.filename() == name) else {
+                        continue;
+                    };
+
+                    let mut buffer = vec![];
+                    std::io::copy(&mut entry, &mut buffer).ok()?;
+                    let content = String::from_utf8(buffer).ok()?;
+
+                    return registry::Hives::deserialize(&content, *format);
+                }
+
+                None
+            }
+            BackupFormat::Dedup => {
+                let hash = self.registry_hash(backup)?;
+                let content = self.blob_file(&hash).read()?;
+                registry::Hives::deserialize(&content, registry::Format::Reg)
+            }
         }
     }
 
@@ -946,6 +1347,7 @@ impl GameLayout {
     ) -> String {
         if *kind == BackupKind::Full
             && retention.full == 1
+            && !retention.calendar.enabled()
             && format.chosen == BackupFormat::Simple
             && self.mapping.backups.iter().all(|x| !x.locked)
         {
@@ -956,10 +1358,7 @@ impl GameLayout {
                 BackupKind::Full => format!("backup-{}", timestamp),
                 BackupKind::Differential => format!("backup-{}-diff", timestamp),
             };
-            match format.chosen {
-                BackupFormat::Simple => name,
-                BackupFormat::Zip => format!("{name}.zip"),
-            }
+            name_with_format_extension(name, format.chosen)
         }
     }
 
@@ -991,12 +1390,22 @@ impl GameLayout {
             return BackupKind::Full;
         }
 
-        let fulls = self.mapping.backups.iter().filter(|full| !full.locked).count() as u8;
-        let diffs = self
+        let fulls = self
             .mapping
             .backups
-            .back()
-            .map(|x| x.children.iter().filter(|diff| !diff.locked).count())
+            .iter()
+            .filter(|full| !full.locked && !full.pre_restore)
+            .count() as u8;
+        let diffs = self
+            .mapping
+            .latest_regular_backup()
+            .map(|i| {
+                self.mapping.backups[i]
+                    .children
+                    .iter()
+                    .filter(|diff| !diff.locked)
+                    .count()
+            })
             .unwrap_or(0) as u8;
 
         if fulls > 0 && (diffs < retention.differential || (retention.full == 1 && retention.differential > 0)) {
@@ -1014,7 +1423,6 @@ impl GameLayout {
         retention: Retention,
     ) -> FullBackup {
         let mut files = BTreeMap::new();
-        #[cfg_attr(not(target_os = "windows"), allow(unused_mut))]
         let mut registry = IndividualMappingRegistry::default();
 
         for (scan_key, file) in scan.found_files.iter().filter(|(_, x)| !x.ignored) {
@@ -1032,12 +1440,13 @@ impl GameLayout {
             }
         }
 
-        #[cfg(target_os = "windows")]
         {
-            use crate::scan::registry::Hives;
-            let mut hives = Hives::default();
-            let _ = hives.back_up(&scan.game_name, &scan.found_registry_keys);
+            let mut hives = registry::Hives::default();
+            let _ = hives.back_up_scan(scan);
             registry.hash = hives.sha1(registry::Format::Reg);
+            if registry.hash.is_some() {
+                registry.wine_prefixes = scan.wine_prefixes.iter().map(|x| x.render()).collect();
+            }
         }
 
         FullBackup {
@@ -1046,6 +1455,8 @@ impl GameLayout {
             os: Some(Os::HOST),
             comment: None,
             locked: false,
+            encryption: None,
+            pre_restore: false,
             files,
             registry,
             children: VecDeque::new(),
@@ -1060,7 +1471,6 @@ impl GameLayout {
         retention: Retention,
     ) -> DifferentialBackup {
         let mut files = BTreeMap::new();
-        #[cfg_attr(not(target_os = "windows"), allow(unused_mut))]
         let mut registry = Some(IndividualMappingRegistry::default());
 
         for (scan_key, file) in &scan.found_files {
@@ -1081,14 +1491,13 @@ impl GameLayout {
             };
         }
 
-        #[cfg(target_os = "windows")]
         {
-            use crate::scan::registry::Hives;
-            let mut hives = Hives::default();
-            let _ = hives.back_up(&scan.game_name, &scan.found_registry_keys);
+            let mut hives = registry::Hives::default();
+            let _ = hives.back_up_scan(scan);
             if !hives.is_empty() {
                 registry = Some(IndividualMappingRegistry {
                     hash: hives.sha1(registry::Format::Reg),
+                    wine_prefixes: scan.wine_prefixes.iter().map(|x| x.render()).collect(),
                 });
             }
         }
@@ -1119,12 +1528,13 @@ impl GameLayout {
             os: Some(Os::HOST),
             comment: None,
             locked: false,
+            encryption: None,
             files,
             registry,
         }
     }
 
-    fn execute_backup_as_simple(&mut self, backup: &Backup, scan: &ScanInfo) -> BackupInfo {
+    fn execute_backup_as_simple(&mut self, backup: &Backup, scan: &ScanInfo, cipher: Option<&Cipher>) -> BackupInfo {
         let mut backup_info = BackupInfo::default();
 
         let mut relevant_files = vec![];
@@ -1137,6 +1547,23 @@ impl GameLayout {
             let target_file = self
                 .mapping
                 .game_file(&self.path, file.effective(scan_key), backup.name());
+            if let Some(cipher) = cipher {
+                if let Err(e) = Self::copy_encrypted(scan_key, &target_file, cipher) {
+                    log::error!(
+                        "[{}] unable to encrypt: {:?} -> {:?} | {e}",
+                        self.mapping.name,
+                        scan_key,
+                        &target_file
+                    );
+                    backup_info
+                        .failed_files
+                        .insert(scan_key.clone(), BackupError::Raw(e.to_string()));
+                    continue;
+                }
+                log::info!("[{}] backed up: {:?} -> {:?}", self.mapping.name, scan_key, target_file);
+                relevant_files.push(target_file);
+                continue;
+            }
             if scan_key.same_content(&target_file) {
                 log::info!(
                     "[{}] already matches: {:?} -> {:?}",
@@ -1157,15 +1584,23 @@ impl GameLayout {
             relevant_files.push(target_file);
         }
 
-        #[cfg(target_os = "windows")]
         {
             if backup.includes_registry() {
                 let target_registry_file = self.path.joined(backup.name()).joined(registry::Format::Reg.filename());
                 let mut hives = registry::Hives::default();
-                if let Err(failed) = hives.back_up(&scan.game_name, &scan.found_registry_keys) {
+                if let Err(failed) = hives.back_up_scan(scan) {
                     backup_info.failed_registry.extend(failed);
                 }
-                hives.save(&target_registry_file);
+                match cipher {
+                    None => hives.save(&target_registry_file),
+                    Some(cipher) => {
+                        let content = hives.serialize(registry::Format::Reg);
+                        if let Ok(content) = cipher.encrypt(content.as_bytes()) {
+                            let _ = target_registry_file.create_parent_dir();
+                            let _ = target_registry_file.write_bytes(&content);
+                        }
+                    }
+                }
                 relevant_files.push(target_registry_file);
             }
         }
@@ -1177,12 +1612,15 @@ impl GameLayout {
         backup_info
     }
 
-    fn execute_backup_as_zip(&mut self, backup: &Backup, scan: &ScanInfo, format: &BackupFormats) -> BackupInfo {
+    fn execute_backup_as_zip(
+        &mut self,
+        backup: &Backup,
+        scan: &ScanInfo,
+        format: &BackupFormats,
+        cipher: Option<&Cipher>,
+    ) -> BackupInfo {
         let mut backup_info = BackupInfo::default();
 
-        let fail_file = |file: &StrictPath, backup_info: &mut BackupInfo, error: String| {
-            backup_info.failed_files.insert(file.clone(), BackupError::Raw(error))
-        };
         let fail_all = |backup_info: &mut BackupInfo, error: String| {
             for file in scan.found_files.keys() {
                 backup_info
@@ -1192,7 +1630,12 @@ impl GameLayout {
         };
 
         let archive_path = self.path.joined(backup.name());
-        let archive_file = match archive_path.create() {
+        let archive_file = match cipher {
+            None => archive_path.create(),
+            // The archive is encrypted as it's written, so we may need to read back earlier parts.
+            Some(_) => archive_path.create_rw(),
+        };
+        let archive_file = match archive_file {
             Ok(x) => x,
             Err(e) => {
                 log::error!(
@@ -1204,8 +1647,40 @@ impl GameLayout {
                 return backup_info;
             }
         };
-        let mut zip = zip::ZipWriter::new(archive_file);
-        let options = zip::write::FileOptions::default()
+        let options = Self::zip_options(format);
+
+        match cipher {
+            None => {
+                let mut zip = zip::ZipWriter::new(archive_file);
+                self.write_zip(&mut zip, backup, scan, options, &mut backup_info);
+                if let Err(e) = zip.finish() {
+                    fail_all(&mut backup_info, e.to_string());
+                }
+            }
+            Some(cipher) => {
+                let mut zip = match cipher.writer(archive_file) {
+                    Ok(x) => zip::ZipWriter::new(x),
+                    Err(e) => {
+                        fail_all(&mut backup_info, e.to_string());
+                        return backup_info;
+                    }
+                };
+                self.write_zip(&mut zip, backup, scan, options, &mut backup_info);
+                let outcome = zip
+                    .finish()
+                    .map_err(|e| e.to_string())
+                    .and_then(|writer| writer.finish().map_err(|e| e.to_string()));
+                if let Err(e) = outcome {
+                    fail_all(&mut backup_info, e);
+                }
+            }
+        }
+
+        backup_info
+    }
+
+    fn zip_options(format: &BackupFormats) -> zip::write::FileOptions {
+        zip::write::FileOptions::default()
             .compression_method(match format.zip.compression {
                 ZipCompression::None => zip::CompressionMethod::Stored,
                 ZipCompression::Deflate => zip::CompressionMethod::Deflated,
@@ -1213,7 +1688,20 @@ impl GameLayout {
                 ZipCompression::Zstd => zip::CompressionMethod::Zstd,
             })
             .compression_level(format.level())
-            .large_file(true);
+            .large_file(true)
+    }
+
+    fn write_zip<W: Write + std::io::Seek>(
+        &mut self,
+        zip: &mut zip::ZipWriter<W>,
+        backup: &Backup,
+        scan: &ScanInfo,
+        options: zip::write::FileOptions,
+        backup_info: &mut BackupInfo,
+    ) {
+        let fail_file = |file: &StrictPath, backup_info: &mut BackupInfo, error: String| {
+            backup_info.failed_files.insert(file.clone(), BackupError::Raw(error))
+        };
 
         'item: for (scan_key, file) in &scan.found_files {
             if !backup.includes_file(file.mapping_key(scan_key)) {
@@ -1232,7 +1720,7 @@ impl GameLayout {
                         &scan_key,
                         &target_file_id
                     );
-                    fail_file(scan_key, &mut backup_info, e.to_string());
+                    fail_file(scan_key, backup_info, e.to_string());
                     continue;
                 }
             };
@@ -1257,7 +1745,7 @@ impl GameLayout {
                     &scan_key,
                     &target_file_id
                 );
-                fail_file(scan_key, &mut backup_info, e.to_string());
+                fail_file(scan_key, backup_info, e.to_string());
                 continue;
             }
 
@@ -1266,7 +1754,7 @@ impl GameLayout {
                 Ok(x) => x,
                 Err(e) => {
                     log::error!("[{}] unable to open source: {:?} | {e}", self.mapping.name, &scan_key);
-                    fail_file(scan_key, &mut backup_info, e.to_string());
+                    fail_file(scan_key, backup_info, e.to_string());
                     continue;
                 }
             };
@@ -1278,7 +1766,7 @@ impl GameLayout {
                     Ok(x) => x,
                     Err(e) => {
                         log::error!("[{}] unable to read source: {:?} | {e}", self.mapping.name, &scan_key);
-                        fail_file(scan_key, &mut backup_info, e.to_string());
+                        fail_file(scan_key, backup_info, e.to_string());
                         continue 'item;
                     }
                 };
@@ -1298,19 +1786,16 @@ impl GameLayout {
                         &scan_key,
                         &target_file_id
                     );
-                    fail_file(scan_key, &mut backup_info, e.to_string());
+                    fail_file(scan_key, backup_info, e.to_string());
                     continue 'item;
                 }
             }
         }
 
-        #[cfg(target_os = "windows")]
         {
-            use crate::scan::registry::Hives;
-
             if backup.includes_registry() {
-                let mut hives = Hives::default();
-                if let Err(failed) = hives.back_up(&scan.game_name, &scan.found_registry_keys) {
+                let mut hives = registry::Hives::default();
+                if let Err(failed) = hives.back_up_scan(scan) {
                     backup_info.failed_registry.extend(failed);
                 }
                 let format = registry::Format::Reg;
@@ -1319,55 +1804,268 @@ impl GameLayout {
                 }
             }
         }
-
-        if let Err(e) = zip.finish() {
-            fail_all(&mut backup_info, e.to_string());
-        }
-
-        backup_info
-    }
-
-    fn insert_backup(&mut self, backup: Backup) {
-        match backup {
-            Backup::Full(backup) => {
-                self.mapping.backups.push_back(backup);
-            }
-            Backup::Differential(backup) => {
-                if let Some(parent) = self.mapping.backups.back_mut() {
-                    parent.children.push_back(backup);
-                }
-            }
-        }
     }
 
-    fn forget_excess_backups(&mut self, retention: Retention) {
-        // We need to track by index rather than by ID.
-        // If we're merging into a single existing backup (like the special ID `.`),
-        // then we may have two of them before pruning the older one.
-        let mut excess = vec![];
-
-        let unlocked_fulls = self
-            .mapping
-            .backups
-            .iter()
-            .filter(|full| !full.locked && full.children.iter().all(|diff| !diff.locked))
-            .count();
-        let mut excess_fulls = unlocked_fulls.saturating_sub(retention.full as usize);
+    fn execute_backup_as_tar(&mut self, backup: &Backup, scan: &ScanInfo, format: &BackupFormats) -> BackupInfo {
+        let mut backup_info = BackupInfo::default();
 
-        for (i, full) in self.mapping.backups.iter_mut().enumerate() {
-            let locked = full.locked || full.children.iter().any(|diff| diff.locked);
-            if !locked && excess_fulls > 0 {
-                excess.push((i, None));
-                excess_fulls -= 1;
+        let fail_all = |backup_info: &mut BackupInfo, error: String| {
+            for file in scan.found_files.keys() {
+                backup_info
+                    .failed_files
+                    .insert(file.clone(), BackupError::Raw(error.clone()));
             }
+        };
 
-            let unlocked_diffs = full.children.iter().filter(|diff| !diff.locked).count();
-            let mut excess_diffs = unlocked_diffs.saturating_sub(retention.differential as usize);
+        let archive_path = self.path.joined(backup.name());
+        let archive_file = match archive_path.create() {
+            Ok(x) => x,
+            Err(e) => {
+                log::error!(
+                    "[{}] unable to create tar file: {:?} | {e}",
+                    self.mapping.name,
+                    &archive_path
+                );
+                fail_all(&mut backup_info, e.to_string());
+                return backup_info;
+            }
+        };
+        let encoder = match zstd::Encoder::new(archive_file, format.level().unwrap_or_default()) {
+            Ok(x) => x,
+            Err(e) => {
+                log::error!(
+                    "[{}] unable to start compression: {:?} | {e}",
+                    self.mapping.name,
+                    &archive_path
+                );
+                fail_all(&mut backup_info, e.to_string());
+                return backup_info;
+            }
+        };
+        // The default header mode keeps each file's permissions and modification time.
+        let mut tar = tar::Builder::new(encoder);
+
+        for (scan_key, file) in &scan.found_files {
+            if !backup.includes_file(file.mapping_key(scan_key)) {
+                log::debug!("[{}] skipped: {:?}", self.mapping.name, &scan_key);
+                continue;
+            }
+
+            let target_file_id = self.mapping.game_file_for_zip(file.effective(scan_key));
+
+            let source = match scan_key.as_std_path_buf() {
+                Ok(x) => x,
+                Err(e) => {
+                    log::error!("[{}] unable to open source: {:?} | {e}", self.mapping.name, &scan_key);
+                    backup_info
+                        .failed_files
+                        .insert(scan_key.clone(), BackupError::Raw(e.to_string()));
+                    continue;
+                }
+            };
+
+            if let Err(e) = tar.append_path_with_name(source, &target_file_id) {
+                log::error!(
+                    "[{}] unable to write target: {:?} -> {} | {e}",
+                    self.mapping.name,
+                    &scan_key,
+                    &target_file_id
+                );
+                backup_info
+                    .failed_files
+                    .insert(scan_key.clone(), BackupError::Raw(e.to_string()));
+                continue;
+            }
+
+            log::info!(
+                "[{}] backed up: {:?} -> {}",
+                self.mapping.name,
+                &scan_key,
+                &target_file_id
+            );
+        }
+
+        {
+            if backup.includes_registry() {
+                let mut hives = registry::Hives::default();
+                if let Err(failed) = hives.back_up_scan(scan) {
+                    backup_info.failed_registry.extend(failed);
+                }
+                let format = registry::Format::Reg;
+                let content = hives.serialize(format);
+
+                let mut header = tar::Header::new_gnu();
+                header.set_size(content.len() as u64);
+                header.set_mode(0o644);
+                header.set_mtime(chrono::Utc::now().timestamp().max(0) as u64);
+                header.set_cksum();
+                let _ = tar.append_data(&mut header, format.filename(), content.as_bytes());
+            }
+        }
+
+        match tar.into_inner().and_then(|encoder| encoder.finish()) {
+            Ok(_) => {}
+            Err(e) => {
+                fail_all(&mut backup_info, e.to_string());
+            }
+        }
+
+        backup_info
+    }
+
+    fn execute_backup_as_dedup(&mut self, backup: &Backup, scan: &ScanInfo) -> BackupInfo {
+        let mut backup_info = BackupInfo::default();
+
+        for (scan_key, file) in &scan.found_files {
+            if !backup.includes_file(file.mapping_key(scan_key)) {
+                log::debug!("[{}] skipped: {}", self.mapping.name, scan_key.raw());
+                continue;
+            }
+
+            let (drive, _) = file.effective(scan_key).split_drive();
+            self.mapping.drive_folder_name(&drive);
+
+            let blob = self.blob_file(&file.hash);
+            if blob_present(&blob, file.size) {
+                log::info!("[{}] already stored: {:?} -> {:?}", self.mapping.name, &scan_key, &blob);
+                continue;
+            }
+            let written = write_blob(&blob, |temp| {
+                scan_key.copy_to_path(&self.mapping.name, temp)?;
+                // The file may have changed since the scan, in which case it doesn't belong under this hash.
+                match temp.try_sha1() {
+                    Ok(hash) if hash == file.hash => Ok(()),
+                    Ok(_) => Err(std::io::Error::other("File changed since it was scanned")),
+                    Err(e) => Err(std::io::Error::other(e.to_string())),
+                }
+            });
+            if let Err(e) = written {
+                backup_info
+                    .failed_files
+                    .insert(scan_key.clone(), BackupError::Raw(e.to_string()));
+                continue;
+            }
+            log::info!("[{}] backed up: {:?} -> {:?}", self.mapping.name, scan_key, blob);
+        }
+
+        {
+            if backup.includes_registry() {
+                let mut hives = registry::Hives::default();
+                if let Err(failed) = hives.back_up_scan(scan) {
+                    backup_info.failed_registry.extend(failed);
+                }
+                let format = registry::Format::Reg;
+                if let Some(hash) = hives.sha1(format) {
+                    let blob = self.blob_file(&hash);
+                    let content = hives.serialize(format);
+                    if !blob_present(&blob, content.len() as u64) {
+                        let _ = write_blob(&blob, |temp| temp.write_with_content(&content));
+                    }
+                }
+            }
+        }
+
+        backup_info
+    }
+
+    fn insert_backup(&mut self, backup: Backup) {
+        match backup {
+            Backup::Full(backup) => {
+                self.mapping.backups.push_back(backup);
+            }
+            Backup::Differential(backup) => {
+                if let Some(parent) = self
+                    .mapping
+                    .latest_regular_backup()
+                    .and_then(|i| self.mapping.backups.get_mut(i))
+                {
+                    parent.children.push_back(backup);
+                }
+            }
+        }
+    }
+
+    /// Insert a full backup before any newer ones,
+    /// so that it doesn't become the latest backup if it has older content.
+    fn insert_full_backup_chronologically(&mut self, backup: FullBackup) {
+        match self.mapping.backups.iter().position(|x| x.when > backup.when) {
+            Some(i) => self.mapping.backups.insert(i, backup),
+            None => self.mapping.backups.push_back(backup),
+        }
+    }
+
+    /// Only the latest pre-restore snapshot is kept, unless older ones have been locked.
+    fn forget_excess_snapshots(&mut self) {
+        let Some(latest) = self.mapping.backups.iter().rposition(|x| x.pre_restore) else {
+            return;
+        };
+
+        let mut i = 0;
+        self.mapping.backups.retain(|full| {
+            let keep = !full.pre_restore || full.locked || i == latest;
+            i += 1;
+            keep
+        });
+    }
+
+    fn forget_excess_backups(&mut self, retention: Retention, now: &chrono::DateTime<chrono::Utc>) {
+        // We need to track by index rather than by ID.
+        // If we're merging into a single existing backup (like the special ID `.`),
+        // then we may have two of them before pruning the older one.
+        let mut excess = vec![];
+
+        // Backups that would otherwise be excess can still be kept by the calendar policy.
+        // A full backup is also kept if any of its children are.
+        let mut indices = vec![];
+        let mut times = vec![];
+        for (i, full) in self.mapping.backups.iter().enumerate() {
+            if full.pre_restore {
+                continue;
+            }
+            indices.push((i, None));
+            times.push(full.when);
+            for (j, diff) in full.children.iter().enumerate() {
+                indices.push((i, Some(j)));
+                times.push(diff.when);
+            }
+        }
+        let calendar: HashSet<_> = retention
+            .calendar
+            .keep(now, &times)
+            .into_iter()
+            .map(|k| indices[k])
+            .collect();
+
+        let unlocked_fulls = self
+            .mapping
+            .backups
+            .iter()
+            .filter(|full| !full.pre_restore && !full.locked && full.children.iter().all(|diff| !diff.locked))
+            .count();
+        let mut excess_fulls = unlocked_fulls.saturating_sub(retention.full as usize);
+
+        for (i, full) in self.mapping.backups.iter_mut().enumerate() {
+            if full.pre_restore {
+                continue;
+            }
+
+            let locked = full.locked || full.children.iter().any(|diff| diff.locked);
+            if !locked && excess_fulls > 0 {
+                if !calendar.iter().any(|(x, _)| *x == i) {
+                    excess.push((i, None));
+                }
+                excess_fulls -= 1;
+            }
+
+            let unlocked_diffs = full.children.iter().filter(|diff| !diff.locked).count();
+            let mut excess_diffs = unlocked_diffs.saturating_sub(retention.differential as usize);
 
             for (j, diff) in full.children.iter_mut().enumerate() {
                 let locked = diff.locked;
                 if !locked && excess_diffs > 0 {
-                    excess.push((i, Some(j)));
+                    if !calendar.contains(&(i, Some(j))) {
+                        excess.push((i, Some(j)));
+                    }
                     excess_diffs -= 1;
                 }
             }
@@ -1390,13 +2088,21 @@ impl GameLayout {
         }
     }
 
-    fn execute_backup(&mut self, backup: &Backup, scan: &ScanInfo, format: &BackupFormats) -> BackupInfo {
+    fn execute_backup(
+        &mut self,
+        backup: &Backup,
+        scan: &ScanInfo,
+        format: &BackupFormats,
+        cipher: Option<&Cipher>,
+    ) -> BackupInfo {
         if backup.only_inherits_and_overrides() {
             BackupInfo::default()
         } else {
             match format.chosen {
-                BackupFormat::Simple => self.execute_backup_as_simple(backup, scan),
-                BackupFormat::Zip => self.execute_backup_as_zip(backup, scan, format),
+                BackupFormat::Simple => self.execute_backup_as_simple(backup, scan, cipher),
+                BackupFormat::Zip => self.execute_backup_as_zip(backup, scan, format, cipher),
+                BackupFormat::TarZstd => self.execute_backup_as_tar(backup, scan, format),
+                BackupFormat::Dedup => self.execute_backup_as_dedup(backup, scan),
             }
         }
     }
@@ -1444,9 +2150,10 @@ impl GameLayout {
         }
         #[cfg(target_os = "windows")]
         {
-            if let Some(hives) = self.registry_content_in(&backup.name, &BackupFormat::Simple) {
+            if let Ok(Some(hives)) = self.registry_content_in(&backup.name, &BackupFormat::Simple) {
                 backup.registry = IndividualMappingRegistry {
                     hash: hives.sha1(registry::Format::Yaml),
+                    wine_prefixes: vec![],
                 };
             }
         }
@@ -1481,6 +2188,7 @@ impl GameLayout {
             os,
             comment,
             locked,
+            encryption,
             files,
             registry,
         } = initial.children.pop_front()?;
@@ -1490,6 +2198,7 @@ impl GameLayout {
         initial.os = os;
         initial.comment = comment;
         initial.locked = initial.locked || locked;
+        initial.encryption = encryption;
         initial.files = files.into_iter().filter_map(|(k, v)| Some((k, v?))).collect();
         if let Some(registry) = registry {
             initial.registry = registry;
@@ -1502,12 +2211,83 @@ impl GameLayout {
         Some(())
     }
 
+    /// Decide which existing backups to forget so that the new backup fits within `quota`
+    /// once the normal retention limits have also been applied.
+    /// Backups are identified by name and time, since the name alone may be reused (e.g., `.`).
+    fn plan_quota(
+        &self,
+        backup: &Backup,
+        now: &chrono::DateTime<chrono::Utc>,
+        retention: Retention,
+        quota: u64,
+    ) -> Result<Vec<(String, chrono::DateTime<chrono::Utc>)>, Error> {
+        let mut planned = self.clone();
+        planned.insert_backup(backup.clone());
+        planned.forget_excess_backups(retention, now);
+
+        let mut forgotten = vec![];
+
+        // Deduplicated backups may share blobs, so forgetting one doesn't necessarily free up its whole size.
+        'outer: while planned.mapping.size() > quota {
+            // The new backup is always in the latest regular chain, either as the full backup or its latest child.
+            let last = planned
+                .mapping
+                .latest_regular_backup()
+                .unwrap_or_else(|| planned.mapping.backups.len().saturating_sub(1));
+
+            for (i, full) in planned.mapping.backups.iter_mut().enumerate() {
+                let locked = full.locked || full.children.iter().any(|diff| diff.locked);
+                if i != last && !locked {
+                    forgotten.push((full.name.clone(), full.when));
+                    for diff in &full.children {
+                        forgotten.push((diff.name.clone(), diff.when));
+                    }
+                    planned.mapping.backups.remove(i);
+                    continue 'outer;
+                }
+
+                let protected = if i == last && backup.kind() == BackupKind::Differential {
+                    full.children.len().checked_sub(1)
+                } else {
+                    None
+                };
+                for (j, diff) in full.children.iter().enumerate() {
+                    if !diff.locked && Some(j) != protected {
+                        forgotten.push((diff.name.clone(), diff.when));
+                        full.children.remove(j);
+                        continue 'outer;
+                    }
+                }
+            }
+
+            return Err(Error::BackupQuotaExceeded {
+                size: planned.mapping.size(),
+                quota,
+            });
+        }
+
+        Ok(forgotten)
+    }
+
+    fn forget_backups(&mut self, forgotten: &[(String, chrono::DateTime<chrono::Utc>)]) {
+        let forget =
+            |name: &String, when: &chrono::DateTime<chrono::Utc>| forgotten.iter().any(|(x, y)| x == name && y == when);
+
+        self.mapping.backups.retain(|full| !forget(&full.name, &full.when));
+        for full in &mut self.mapping.backups {
+            full.children.retain(|diff| !forget(&diff.name, &diff.when));
+        }
+    }
+
+    /// The `space` is how much of a total quota is left over for this game,
+    /// after accounting for the other games in the backup directory.
     pub fn back_up(
         &mut self,
         scan: &ScanInfo,
         now: &chrono::DateTime<chrono::Utc>,
         format: &BackupFormats,
         retention: Retention,
+        space: Option<u64>,
     ) -> Option<BackupInfo> {
         if !scan.found_anything() {
             log::trace!("[{}] nothing to back up", &scan.game_name);
@@ -1531,17 +2311,51 @@ impl GameLayout {
                 None
             }
             Some(mut backup) => {
+                if let Some(quota) = [retention.game_quota, space].into_iter().flatten().min() {
+                    match self.plan_quota(&backup, now, retention, quota) {
+                        Ok(forgotten) => {
+                            if !forgotten.is_empty() {
+                                // Free up the space before writing the new backup.
+                                log::info!(
+                                    "[{}] forgetting backups to stay within quota: {:?}",
+                                    &scan.game_name,
+                                    &forgotten
+                                );
+                                self.forget_backups(&forgotten);
+                                self.save();
+                                self.prune_irrelevant_parents();
+                            }
+                        }
+                        Err(e) => {
+                            log::error!("[{}] backup would exceed quota: {e:?}", scan.game_name);
+                            return Some(BackupInfo::total_failure(scan, BackupError::App(e)));
+                        }
+                    }
+                }
+
+                let cipher = match self.prepare_cipher(format) {
+                    Ok(Some((cipher, info))) => {
+                        backup.set_encryption(Some(info));
+                        Some(cipher)
+                    }
+                    Ok(None) => None,
+                    Err(e) => {
+                        log::error!("[{}] unable to prepare encryption: {e:?}", scan.game_name);
+                        return Some(BackupInfo::total_failure(scan, BackupError::App(e)));
+                    }
+                };
+
                 log::info!(
                     "[{}] creating a {:?} backup: {}",
                     &scan.game_name,
                     backup.kind(),
                     backup.name()
                 );
-                let backup_info = self.execute_backup(&backup, scan, format);
+                let backup_info = self.execute_backup(&backup, scan, format, cipher.as_ref());
                 backup.prune_failures(&backup_info);
                 if backup.needed() {
                     self.insert_backup(backup.clone());
-                    self.forget_excess_backups(retention);
+                    self.forget_excess_backups(retention, now);
                     self.save();
                 }
                 self.prune_irrelevant_parents();
@@ -1550,780 +2364,4303 @@ impl GameLayout {
         }
     }
 
-    pub fn get_backups(&mut self) -> Vec<Backup> {
-        let mut available_backups = vec![];
-
-        if self.path.is_dir() {
-            self.migrate_backups(true);
-            available_backups = self.restorable_backups_flattened();
+    /// Set up encryption for a new backup, if the format calls for it.
+    fn prepare_cipher(&self, format: &BackupFormats) -> Result<Option<(Cipher, EncryptionInfo)>, Error> {
+        if !format.encryption.applies_to(format.chosen) {
+            return Ok(None);
         }
 
-        available_backups
-    }
-
-    pub fn has_backups(&self) -> bool {
-        !self.mapping.backups.is_empty()
+        let passphrase = self.passphrase.as_ref().ok_or(Error::BackupPassphraseRequired)?;
+        Cipher::create(passphrase).map(Some)
     }
 
-    pub fn scan_for_restoration(
+    /// Build a new full backup from the stored content of an existing differential backup,
+    /// so that restoring it no longer depends on the old chain.
+    /// This does not rescan the live save files.
+    /// The new backup keeps the original backup's time, so it takes the old chain's place in the history.
+    /// Afterward, the old chain is subject to the usual retention limits and locks.
+    /// Only the latest differential backup in a chain can be consolidated,
+    /// since pruning the old chain would otherwise remove the newer ones.
+    ///
+    /// Returns `None` if the backup doesn't exist or is already a full backup.
+    pub fn consolidate(
         &mut self,
-        name: &str,
         id: &BackupId,
-        redirects: &[RedirectConfig],
-        reverse_redirects_on_restore: bool,
-        toggled_paths: &ToggledPaths,
-        #[cfg_attr(not(target_os = "windows"), allow(unused))] toggled_registry: &ToggledRegistry,
-    ) -> ScanInfo {
-        log::trace!("[{name}] beginning scan for restore");
+        now: &chrono::DateTime<chrono::Utc>,
+        format: &BackupFormats,
+        retention: Retention,
+    ) -> Result<Option<Backup>, Error> {
+        let Some((full, Some(diff))) = self.find_by_id(id) else {
+            return Ok(None);
+        };
+        if full.children.back().map(|x| &x.name) != Some(&diff.name) {
+            return Err(Error::NewerBackupsDependOnChain);
+        }
 
-        let mut found_files = HashMap::new();
-        #[cfg_attr(not(target_os = "windows"), allow(unused_mut))]
-        let mut found_registry_keys = HashMap::new();
-        #[cfg_attr(not(target_os = "windows"), allow(unused_mut))]
-        let mut available_backups = vec![];
-        let mut backup = None;
+        for format in [full.format(), diff.format(), format.chosen] {
+            if !matches!(format, BackupFormat::Simple | BackupFormat::Zip) {
+                return Err(Error::UnsupportedBackupFormat { format });
+            }
+        }
 
-        let id = self.verify_id(id);
+        let when = diff.when;
+        let os = diff.os.or(full.os);
+        let files = Self::stored_file_sources(full, Some(diff));
+        let registry = self.try_registry_content(id)?;
 
-        if self.path.is_dir() {
-            self.migrate_backups(true);
-            found_files = self.restorable_files(
-                &id,
-                ScanKind::Restore,
-                redirects,
-                reverse_redirects_on_restore,
-                toggled_paths,
-            );
-            available_backups = self.restorable_backups_flattened();
-            backup = self.find_by_id_flattened(&id);
+        let mut backup = Backup::Full(FullBackup {
+            name: self.generate_backup_name(&BackupKind::Full, &when, format, retention),
+            when,
+            os,
+            comment: None,
+            locked: false,
+            encryption: None,
+            pre_restore: false,
+            files: files.iter().map(|(k, (_, data))| (k.clone(), data.clone())).collect(),
+            registry: IndividualMappingRegistry {
+                hash: registry.as_ref().and_then(|x| x.sha1(registry::Format::Reg)),
+                wine_prefixes: self.registry_wine_prefixes(id).iter().map(|x| x.render()).collect(),
+            },
+            children: VecDeque::new(),
+        });
+        let cipher = match self.prepare_cipher(format)? {
+            Some((cipher, info)) => {
+                backup.set_encryption(Some(info));
+                Some(cipher)
+            }
+            None => None,
+        };
+
+        log::info!("[{}] consolidating {:?} into {}", &self.mapping.name, id, backup.name());
+        if let Err(e) = self.write_stored_files(backup.name(), files, registry.as_ref(), format, cipher.as_ref()) {
+            log::error!("[{}] unable to consolidate backup: {e:?}", &self.mapping.name);
+            self.prune_irrelevant_parents();
+            return Err(e);
         }
 
-        #[cfg(target_os = "windows")]
-        {
-            use crate::scan::{registry, RegistryItem, ScannedRegistryValue, ScannedRegistryValues};
+        if let Backup::Full(full) = &backup {
+            self.insert_full_backup_chronologically(full.clone());
+        }
+        self.forget_excess_backups(retention, now);
+        self.save();
+        self.prune_irrelevant_parents();
 
-            if let Some(hives) = self.registry_content(&id) {
-                for (hive_name, keys) in hives.0.iter() {
-                    for (key_name, entries) in keys.0.iter() {
-                        let live_entries = registry::win::try_read_registry_key(hive_name, key_name);
-                        let mut live_values = ScannedRegistryValues::new();
-
-                        let path = RegistryItem::from_hive_and_key(hive_name, key_name);
+        Ok(Some(backup))
+    }
 
-                        for (entry_name, entry) in entries.0.iter() {
-                            live_values.insert(
-                                entry_name.clone(),
-                                ScannedRegistryValue {
-                                    ignored: toggled_registry.is_ignored(name, &path, Some(entry_name)),
-                                    change: live_entries
-                                        .as_ref()
-                                        .and_then(|x| x.0.get(entry_name))
-                                        .map(|live_entry| {
-                                            if entry == live_entry {
-                                                ScanChange::Same
-                                            } else {
-                                                ScanChange::Different
-                                            }
-                                        })
-                                        .unwrap_or(ScanChange::New),
-                                },
-                            );
-                        }
+    /// Rewrite existing backups in the chosen format,
+    /// such as to switch from simple to zip or to recompress zip backups with different settings.
+    /// This converts the specified backup, or every backup if `id` is `None`.
+    /// Each converted backup is validated before it replaces the original.
+    ///
+    /// Returns the names of the converted backups.
+    pub fn convert(&mut self, id: Option<&BackupId>, format: &BackupFormats) -> Result<Vec<String>, Error> {
+        let mut targets = vec![];
+        for (i, full) in self.mapping.backups.iter().enumerate() {
+            targets.push((i, None));
+            for j in 0..full.children.len() {
+                targets.push((i, Some(j)));
+            }
+        }
 
-                        let ignored = toggled_registry.is_ignored(name, &path, None)
-                            && entries
-                                .0
-                                .keys()
-                                .all(|x| toggled_registry.is_ignored(name, &path, Some(x)));
+        if let Some(id) = id {
+            let Some(wanted) = self.find_by_id_flattened(id) else {
+                return Ok(vec![]);
+            };
+            targets.retain(|(i, j)| {
+                let full = &self.mapping.backups[*i];
+                match j {
+                    None => wanted == Backup::Full(full.clone()),
+                    Some(j) => wanted == Backup::Differential(full.children[*j].clone()),
+                }
+            });
+            targets.truncate(1);
+        }
 
-                        found_registry_keys.insert(
-                            path,
-                            ScannedRegistry {
-                                ignored,
-                                change: match &live_entries {
-                                    None => ScanChange::New,
-                                    Some(_) => ScanChange::Same,
-                                },
-                                values: live_values,
-                            },
-                        );
-                    }
+        let mut converted = vec![];
+        let mut outcome = Ok(());
+        for (i, j) in targets {
+            match self.convert_backup(i, j, format) {
+                Ok(Some(name)) => converted.push(name),
+                Ok(None) => {}
+                Err(e) => {
+                    outcome = Err(e);
+                    break;
                 }
             }
         }
 
-        let has_backups = !available_backups.is_empty();
+        self.save();
+        self.prune_irrelevant_parents();
 
-        log::trace!("[{name}] completed scan for restore");
+        outcome.map(|_| converted)
+    }
 
-        ScanInfo {
-            game_name: name.to_string(),
-            found_files,
-            found_registry_keys,
-            available_backups,
-            backup,
-            has_backups,
+    fn convert_backup(&mut self, i: usize, j: Option<usize>, format: &BackupFormats) -> Result<Option<String>, Error> {
+        let original = self.mapping.backups[i].clone();
+        let (old_name, when, encrypted, files) = match j {
+            None => (
+                original.name.clone(),
+                original.when,
+                original.encryption.is_some(),
+                Self::stored_file_sources(&original, None),
+            ),
+            Some(j) => {
+                let diff = &original.children[j];
+                (
+                    diff.name.clone(),
+                    diff.when,
+                    diff.encryption.is_some(),
+                    Self::own_stored_file_sources(diff),
+                )
+            }
+        };
+
+        let source = format_from_backup_name(&old_name);
+        let unchanged = match source {
+            BackupFormat::Simple => encrypted == format.encryption.applies_to(BackupFormat::Simple),
+            BackupFormat::Dedup => true,
+            BackupFormat::Zip | BackupFormat::TarZstd => false,
+        };
+        if source == format.chosen && unchanged {
+            log::debug!("[{}] no need to convert: {}", &self.mapping.name, &old_name);
+            return Ok(None);
         }
-    }
 
-    pub fn restore(
-        &self,
-        scan: &ScanInfo,
-        #[cfg_attr(not(target_os = "windows"), allow(unused))] toggled: &ToggledRegistry,
-    ) -> BackupInfo {
-        log::trace!("[{}] beginning restore", &scan.game_name);
+        let base = match old_name.as_str() {
+            "." => format!("backup-{}", Self::generate_file_friendly_timestamp(&when)),
+            name => [".zip", ".tar.zst", ".dedup"]
+                .iter()
+                .find_map(|ext| name.strip_suffix(ext))
+                .unwrap_or(name)
+                .to_string(),
+        };
+        let new_name = name_with_format_extension(base.clone(), format.chosen);
+        if new_name != old_name && self.mapping.has_backup(&new_name) {
+            return Err(Error::CannotPrepareBackupTarget {
+                path: self.path.joined(&new_name),
+            });
+        }
+        // Write to a scratch location first so that the original is intact until the new one is validated.
+        // Deduplicated content lives in the shared blob store, so it doesn't need this.
+        let scratch = match format.chosen {
+            BackupFormat::Dedup => new_name.clone(),
+            _ => name_with_format_extension(format!("{base}.converting"), format.chosen),
+        };
 
-        let mut failed_files = HashMap::new();
-        #[cfg_attr(not(target_os = "windows"), allow(unused_mut))]
-        let mut failed_registry = HashMap::new();
+        let registry = self.registry_content_in(&old_name, &source)?;
+        let (cipher, encryption) = match self.prepare_cipher(format)? {
+            Some((cipher, info)) => (Some(cipher), Some(info)),
+            None => (None, None),
+        };
 
-        let mut containers: HashMap<StrictPath, zip::ZipArchive<std::fs::File>> = HashMap::new();
-        let mut failed_containers: HashMap<StrictPath, BackupError> = HashMap::new();
+        log::info!("[{}] converting {} into {}", &self.mapping.name, &old_name, &new_name);
+        if let Err(e) = self.write_stored_files(&scratch, files, registry.as_ref(), format, cipher.as_ref()) {
+            log::error!("[{}] unable to convert backup: {e:?}", &self.mapping.name);
+            if scratch != new_name {
+                let _ = self.path.joined(&scratch).remove();
+            }
+            return Err(e);
+        }
 
-        for (scan_key, file) in &scan.found_files {
-            let target = file.effective(scan_key);
+        let registry_hash = registry.as_ref().and_then(|x| x.sha1(registry::Format::Reg));
+        self.set_backup_storage(i, j, &scratch, encryption, registry_hash);
 
-            if !file.change().is_changed() || file.ignored {
-                log::info!(
-                    "[{}] skipping file; change={:?}, ignored={}: {:?} -> {:?}",
-                    self.mapping.name,
-                    file.change,
-                    file.ignored,
-                    scan_key,
-                    &target
+        let mut issues = vec![];
+        let full = &self.mapping.backups[i];
+        match j {
+            None => self.validate_full_backup(full, true, &mut issues),
+            Some(j) => self.validate_differential_backup(&full.children[j], true, &mut issues),
+        }
+        if !issues.is_empty() {
+            log::error!("[{}] converted backup is invalid: {:?}", &self.mapping.name, &issues);
+            self.mapping.backups[i] = original;
+            if scratch != new_name {
+                let _ = self.path.joined(&scratch).remove();
+            }
+            return Err(Error::InvalidBackups);
+        }
+
+        if scratch != new_name {
+            let target = self.path.joined(&new_name);
+            if new_name == old_name {
+                let _ = target.remove();
+            }
+            if let Err(e) = self.path.joined(&scratch).move_to(&target) {
+                // The scratch copy is valid, so we can keep using it.
+                log::error!(
+                    "[{}] unable to rename converted backup: {scratch} -> {new_name} | {e}",
+                    &self.mapping.name
                 );
-                continue;
+                return Ok(Some(scratch));
+            }
+            match j {
+                None => self.mapping.backups[i].name = new_name.clone(),
+                Some(j) => self.mapping.backups[i].children[j].name = new_name.clone(),
             }
+        }
 
-            if let Some(container) = file.container.as_ref() {
-                if let Some(e) = failed_containers.get(container) {
-                    log::warn!(
-                        "[{}] skipping file because container had failed to load: {:?} -> {:?} -> {:?}",
-                        self.mapping.name,
-                        &container,
-                        scan_key,
-                        &target,
-                    );
-                    failed_files.insert(scan_key.clone(), e.clone());
-                    continue;
-                }
+        Ok(Some(new_name))
+    }
 
-                if !containers.contains_key(container) {
-                    log::debug!("[{}] loading zip archive: {:?}", &self.mapping.name, &container);
-                    let handle = match container.open() {
-                        Ok(handle) => handle,
-                        Err(e) => {
-                            log::error!(
-                                "[{}] failed to open zip archive: {:?} | {e:?}",
-                                &self.mapping.name,
-                                &container
-                            );
-                            failed_containers.insert(container.clone(), BackupError::Raw(e.to_string()));
-                            failed_files.insert(scan_key.clone(), BackupError::Raw(e.to_string()));
-                            continue;
-                        }
-                    };
-                    let archive = match zip::ZipArchive::new(handle) {
-                        Ok(archive) => archive,
-                        Err(e) => {
-                            log::error!(
-                                "[{}] failed to parse zip archive: {:?} | {e:?}",
-                                &self.mapping.name,
-                                &container
-                            );
-                            failed_containers.insert(container.clone(), BackupError::Raw(e.to_string()));
-                            failed_files.insert(scan_key.clone(), BackupError::Raw(e.to_string()));
-                            continue;
-                        }
-                    };
-                    log::debug!("[{}] loaded zip archive: {:?}", &self.mapping.name, &container);
-                    containers.insert(container.clone(), archive);
+    /// Point a backup at its converted storage.
+    /// The registry hash is only updated if the backup had registry data.
+    fn set_backup_storage(
+        &mut self,
+        i: usize,
+        j: Option<usize>,
+        name: &str,
+        encryption: Option<EncryptionInfo>,
+        registry_hash: Option<String>,
+    ) {
+        let full = &mut self.mapping.backups[i];
+        match j {
+            None => {
+                full.name = name.to_string();
+                full.encryption = encryption;
+                if registry_hash.is_some() {
+                    full.registry.hash = registry_hash;
                 }
             }
-
-            let outcome = match &file.container {
-                None => self.restore_file_from_simple(target, scan_key),
-                Some(container) => {
-                    let Some(archive) = containers.get_mut(container) else {
-                        continue;
-                    };
-                    self.restore_file_from_zip(target, scan_key, archive)
+            Some(j) => {
+                let diff = &mut full.children[j];
+                diff.name = name.to_string();
+                diff.encryption = encryption;
+                if registry_hash.is_some() {
+                    diff.registry.get_or_insert_with(Default::default).hash = registry_hash;
                 }
-            };
+            }
+        }
+    }
 
-            match outcome {
-                Ok(_) => {
-                    log::info!("[{}] restored: {:?} -> {:?}", &self.mapping.name, scan_key, &target);
-                }
-                Err(e) => {
-                    log::error!(
-                        "[{}] failed to restore: {:?} -> {:?} | {e}",
-                        self.mapping.name,
-                        scan_key,
-                        &target
-                    );
-                    failed_files.insert(scan_key.clone(), BackupError::Raw(e.to_string()));
+    /// Each file in a backup, including any that it inherits from its full backup,
+    /// along with the name of the backup that actually stores it.
+    fn stored_file_sources(full: &FullBackup, diff: Option<&DifferentialBackup>) -> StoredFileSources {
+        let mut sources = BTreeMap::new();
+        for (file, data) in &full.files {
+            if !diff.is_some_and(|diff| diff.file(file.clone()) != BackupInclusion::Inherited) {
+                sources.insert(file.clone(), (full.name.clone(), data.clone()));
+            }
+        }
+        if let Some(diff) = diff {
+            for (file, data) in &diff.files {
+                if let Some(data) = data {
+                    sources.insert(file.clone(), (diff.name.clone(), data.clone()));
                 }
             }
         }
+        sources
+    }
 
-        #[cfg(target_os = "windows")]
-        {
-            if let Some(backup) = scan.backup.as_ref() {
-                if let Some(hives) = self.registry_content(&backup.id()) {
-                    if let Err(failed) = hives.restore(&scan.game_name, toggled) {
-                        failed_registry.extend(failed);
+    /// Only the files stored in a differential backup itself.
+    fn own_stored_file_sources(diff: &DifferentialBackup) -> StoredFileSources {
+        diff.files
+            .iter()
+            .filter_map(|(file, data)| {
+                data.as_ref()
+                    .map(|data| (file.clone(), (diff.name.clone(), data.clone())))
+            })
+            .collect()
+    }
+
+    /// Read the content of each file and pass it along one at a time.
+    /// The content is checked against the recorded hash so that corruption doesn't spread to a new backup.
+    fn read_stored_files(
+        &self,
+        sources: StoredFileSources,
+        mut visit: impl FnMut(String, StoredFile) -> Result<(), Error>,
+    ) -> Result<(), Error> {
+        let mut ciphers = HashMap::new();
+        let mut archives = HashMap::<String, ZipReader>::new();
+        // Tar archives can't be read out of order,
+        // so we gather what we need from each one and read it in a single pass afterward.
+        let mut tars = BTreeMap::<String, HashMap<String, (String, IndividualMappingFile)>>::new();
+
+        for (file, (backup, data)) in sources {
+            let original_path = StrictPath::new(file.clone());
+
+            let (content, mtime) = match format_from_backup_name(&backup) {
+                BackupFormat::Simple => {
+                    let path = self.mapping.game_file_immutable(&self.path, &original_path, &backup);
+                    let mut content = path.read_bytes().map_err(|_| Error::InvalidBackups)?;
+                    if let Some(encryption) = self.encryption_for(&path) {
+                        let cipher = self.cipher_for(encryption, &mut ciphers)?;
+                        content = cipher.decrypt(&content).map_err(|_| Error::InvalidBackups)?;
                     }
+                    (content, path.get_mtime().ok().map(chrono::DateTime::from))
                 }
-            }
-        }
+                BackupFormat::Zip => {
+                    let archive = match archives.entry(backup.clone()) {
+                        std::collections::hash_map::Entry::Occupied(x) => x.into_mut(),
+                        std::collections::hash_map::Entry::Vacant(x) => {
+                            let path = self.path.joined(&backup);
+                            let cipher = match self.encryption_for(&path) {
+                                None => None,
+                                Some(encryption) => Some(self.cipher_for(encryption, &mut ciphers)?),
+                            };
+                            x.insert(
+                                self.open_zip(&path, cipher.as_ref())
+                                    .map_err(|_| Error::InvalidBackups)?,
+                            )
+                        }
+                    };
 
-        log::trace!("[{}] completed restore", &scan.game_name);
+                    let mut entry = archive
+                        .by_name(&self.mapping.game_file_for_zip_immutable(&original_path))
+                        .map_err(|_| Error::InvalidBackups)?;
+                    let mut content = vec![];
+                    std::io::copy(&mut entry, &mut content).map_err(|_| Error::InvalidBackups)?;
+                    (content, zip_time_to_utc(entry.last_modified()))
+                }
+                BackupFormat::TarZstd => {
+                    tars.entry(backup)
+                        .or_default()
+                        .insert(self.mapping.game_file_for_zip_immutable(&original_path), (file, data));
+                    continue;
+                }
+                BackupFormat::Dedup => {
+                    let content = self
+                        .blob_file(&data.hash)
+                        .read_bytes()
+                        .map_err(|_| Error::InvalidBackups)?;
+                    (content, None)
+                }
+            };
 
-        BackupInfo {
-            failed_files,
-            failed_registry,
+            self.check_stored_file(&backup, &file, &data, &content)?;
+            visit(file, StoredFile { data, content, mtime })?;
         }
-    }
 
-    fn restore_file_from_simple(&self, target: &StrictPath, scan_key: &StrictPath) -> Result<(), AnyError> {
-        log::trace!(
-            "[{}] about to restore (simple): {:?} -> {:?}",
-            self.mapping.name,
-            scan_key,
-            &target
-        );
+        for (backup, mut wanted) in tars {
+            let handle = self.path.joined(&backup).open().map_err(|_| Error::InvalidBackups)?;
+            let decoder = zstd::Decoder::new(handle).map_err(|_| Error::InvalidBackups)?;
+            let mut archive = tar::Archive::new(decoder);
 
-        Ok(scan_key.copy_to_path(&self.mapping.name, target)?)
+            for entry in archive.entries().map_err(|_| Error::InvalidBackups)? {
+                let mut entry = entry.map_err(|_| Error::InvalidBackups)?;
+                let name = entry
+                    .path()
+                    .map_err(|_| Error::InvalidBackups)?
+                    .to_string_lossy()
+                    .to_string();
+                let Some((file, data)) = wanted.remove(&name) else {
+                    continue;
+                };
+                let mtime = entry
+                    .header()
+                    .mtime()
+                    .ok()
+                    .and_then(|x| chrono::DateTime::from_timestamp(x as i64, 0));
+                let mut content = vec![];
+                std::io::copy(&mut entry, &mut content).map_err(|_| Error::InvalidBackups)?;
+
+                self.check_stored_file(&backup, &file, &data, &content)?;
+                visit(file, StoredFile { data, content, mtime })?;
+            }
+
+            if !wanted.is_empty() {
+                log::error!(
+                    "[{}] stored files are missing: {backup} / {:?}",
+                    &self.mapping.name,
+                    wanted.values().map(|(file, _)| file).collect::<Vec<_>>()
+                );
+                return Err(Error::InvalidBackups);
+            }
+        }
+
+        Ok(())
     }
 
-    fn restore_file_from_zip(
+    fn check_stored_file(
         &self,
-        target: &StrictPath,
-        scan_key: &StrictPath,
-        archive: &mut zip::ZipArchive<std::fs::File>,
-    ) -> Result<(), AnyError> {
-        log::debug!(
-            "[{}] about to restore (zip): {:?} -> {:?}",
-            self.mapping.name,
-            scan_key,
-            &target
-        );
-
-        if let Err(e) = target.create_parent_dir() {
+        backup: &str,
+        file: &str,
+        data: &IndividualMappingFile,
+        content: &[u8],
+    ) -> Result<(), Error> {
+        if sha1_reader(content).as_ref() != Some(&data.hash) {
             log::error!(
-                "[{}] unable to create parent directories: {:?} | {e}",
-                self.mapping.name,
-                &target
+                "[{}] stored file does not match hash: {backup} / {file}",
+                &self.mapping.name
             );
-            return Err(Box::new(e));
+            return Err(Error::InvalidBackups);
         }
-        if let Err(e) = target.unset_readonly() {
-            log::warn!(
-                "[{}] failed to unset read-only on target: {:?} | {e}",
-                self.mapping.name,
-                &target
-            );
-            return Err(e);
+        Ok(())
+    }
+
+    /// Copy stored files into a new backup, writing each one as soon as it's been read.
+    fn write_stored_files(
+        &mut self,
+        backup: &str,
+        sources: StoredFileSources,
+        registry: Option<&registry::Hives>,
+        format: &BackupFormats,
+        cipher: Option<&Cipher>,
+    ) -> Result<(), Error> {
+        // Settle the drive folders up front so that we only need to read the mapping while copying.
+        for file in sources.keys() {
+            let (drive, _) = StrictPath::new(file.clone()).split_drive();
+            self.mapping.drive_folder_name(&drive);
         }
-        let mut target_handle = match target.create() {
-            Ok(x) => x,
-            Err(e) => {
-                log::warn!(
-                    "[{}] failed to get handle: {:?} -> {:?} | {e}",
-                    self.mapping.name,
-                    scan_key,
-                    &target
-                );
-                return Err(Box::new(e));
+
+        let archive_path = self.path.joined(backup);
+        let fail = || Error::CannotPrepareBackupTarget {
+            path: archive_path.clone(),
+        };
+        let encrypt = |content: Vec<u8>| -> std::io::Result<Vec<u8>> {
+            match cipher {
+                None => Ok(content),
+                Some(cipher) => cipher.encrypt(&content),
             }
         };
-        let mut source_file = archive.by_name(&scan_key.raw())?;
-        if let Err(e) = std::io::copy(&mut source_file, &mut target_handle) {
-            log::warn!(
-                "[{}] failed to copy to target: {:?} -> {:?} | {e}",
-                self.mapping.name,
-                &scan_key,
-                &target,
-            );
-            return Err(Box::new(e));
-        }
+        let options = Self::zip_options(format);
 
-        let mtime = source_file.last_modified();
-        if let Err(e) = target.set_mtime_zip(mtime) {
-            log::error!(
-                "[{}] unable to set modification time: {:?} -> {:?} to {:#?} | {e:?}",
-                self.mapping.name,
-                scan_key,
-                &target,
-                mtime
-            );
-            return Err("unable to set modification time".into());
+        let mut writer = match (format.chosen, cipher) {
+            (BackupFormat::Simple, _) => StoredFileWriter::Simple { relevant_files: vec![] },
+            (BackupFormat::Zip, None) => {
+                StoredFileWriter::Zip(zip::ZipWriter::new(archive_path.create().map_err(|_| fail())?))
+            }
+            (BackupFormat::Zip, Some(cipher)) => {
+                let archive_file = archive_path.create_rw().map_err(|_| fail())?;
+                StoredFileWriter::EncryptedZip(zip::ZipWriter::new(cipher.writer(archive_file).map_err(|_| fail())?))
+            }
+            (BackupFormat::TarZstd, _) => {
+                let archive_file = archive_path.create().map_err(|_| fail())?;
+                let encoder =
+                    zstd::Encoder::new(archive_file, format.level().unwrap_or_default()).map_err(|_| fail())?;
+                StoredFileWriter::TarZstd(tar::Builder::new(encoder))
+            }
+            (BackupFormat::Dedup, _) => StoredFileWriter::Dedup,
+        };
+
+        self.read_stored_files(sources, |file, stored| {
+            let original_path = StrictPath::new(file.clone());
+            match &mut writer {
+                StoredFileWriter::Simple { relevant_files } => {
+                    let target = self.mapping.game_file_immutable(&self.path, &original_path, backup);
+                    let written = target
+                        .create_parent_dir()
+                        .and_then(|_| encrypt(stored.content))
+                        .and_then(|content| target.write_bytes(&content));
+                    if written.is_err() {
+                        return Err(Error::CannotPrepareBackupTarget { path: target });
+                    }
+                    if let Some(mtime) = stored.mtime {
+                        let _ = target.set_mtime(mtime.into());
+                    }
+                    log::info!("[{}] rewrote: {file} -> {:?}", self.mapping.name, &target);
+                    relevant_files.push(target);
+                }
+                StoredFileWriter::Zip(zip) => {
+                    let target_file_id = self.mapping.game_file_for_zip_immutable(&original_path);
+                    append_to_zip(zip, &target_file_id, &stored.content, stored.mtime.as_ref(), options)
+                        .map_err(|_| fail())?;
+                    log::info!("[{}] rewrote: {file} -> {}", self.mapping.name, &target_file_id);
+                }
+                StoredFileWriter::EncryptedZip(zip) => {
+                    let target_file_id = self.mapping.game_file_for_zip_immutable(&original_path);
+                    append_to_zip(zip, &target_file_id, &stored.content, stored.mtime.as_ref(), options)
+                        .map_err(|_| fail())?;
+                    log::info!("[{}] rewrote: {file} -> {}", self.mapping.name, &target_file_id);
+                }
+                StoredFileWriter::TarZstd(tar) => {
+                    let target_file_id = self.mapping.game_file_for_zip_immutable(&original_path);
+                    append_to_tar(tar, &target_file_id, &stored.content, stored.mtime.as_ref()).map_err(|_| fail())?;
+                    log::info!("[{}] rewrote: {file} -> {}", self.mapping.name, &target_file_id);
+                }
+                StoredFileWriter::Dedup => {
+                    let blob = self.blob_file(&stored.data.hash);
+                    if blob_present(&blob, stored.data.size) {
+                        return Ok(());
+                    }
+                    let written = write_blob(&blob, |temp| temp.write_bytes(&stored.content));
+                    if written.is_err() {
+                        return Err(Error::CannotPrepareBackupTarget { path: blob });
+                    }
+                    log::info!("[{}] rewrote: {file} -> {:?}", self.mapping.name, &blob);
+                }
+            }
+            Ok(())
+        })?;
+
+        let registry_format = registry::Format::Reg;
+        match writer {
+            StoredFileWriter::Simple { mut relevant_files } => {
+                if let Some(hives) = registry {
+                    let target = self.path.joined(backup).joined(registry_format.filename());
+                    let written = target
+                        .create_parent_dir()
+                        .and_then(|_| encrypt(hives.serialize(registry_format).into_bytes()))
+                        .and_then(|content| target.write_bytes(&content));
+                    if written.is_err() {
+                        return Err(Error::CannotPrepareBackupTarget { path: target });
+                    }
+                    relevant_files.push(target);
+                }
+
+                self.remove_irrelevant_backup_files(backup, &relevant_files);
+            }
+            StoredFileWriter::Zip(mut zip) => {
+                if let Some(hives) = registry {
+                    let content = hives.serialize(registry_format);
+                    append_to_zip(&mut zip, registry_format.filename(), content.as_bytes(), None, options)
+                        .map_err(|_| fail())?;
+                }
+                zip.finish().map_err(|_| fail())?;
+            }
+            StoredFileWriter::EncryptedZip(mut zip) => {
+                if let Some(hives) = registry {
+                    let content = hives.serialize(registry_format);
+                    append_to_zip(&mut zip, registry_format.filename(), content.as_bytes(), None, options)
+                        .map_err(|_| fail())?;
+                }
+                zip.finish()
+                    .and_then(|writer| Ok(writer.finish()?))
+                    .map_err(|_| fail())?;
+            }
+            StoredFileWriter::TarZstd(mut tar) => {
+                if let Some(hives) = registry {
+                    append_to_tar(
+                        &mut tar,
+                        registry_format.filename(),
+                        hives.serialize(registry_format).as_bytes(),
+                        Some(&chrono::Utc::now()),
+                    )
+                    .map_err(|_| fail())?;
+                }
+                tar.into_inner()
+                    .and_then(|encoder| encoder.finish())
+                    .map_err(|_| fail())?;
+            }
+            StoredFileWriter::Dedup => {
+                if let Some(hives) = registry {
+                    if let Some(hash) = hives.sha1(registry_format) {
+                        let blob = self.blob_file(&hash);
+                        let content = hives.serialize(registry_format);
+                        if !blob_present(&blob, content.len() as u64) {
+                            let written = write_blob(&blob, |temp| temp.write_with_content(&content));
+                            if written.is_err() {
+                                return Err(Error::CannotPrepareBackupTarget { path: blob });
+                            }
+                        }
+                    }
+                }
+            }
         }
 
         Ok(())
     }
 
-    fn mapping_file(path: &StrictPath) -> StrictPath {
-        path.joined("mapping.yaml")
+    pub fn get_backups(&mut self) -> Vec<Backup> {
+        let mut available_backups = vec![];
+
+        if self.path.is_dir() {
+            self.migrate_backups(true);
+            available_backups = self.restorable_backups_flattened();
+        }
+
+        available_backups
     }
 
-    fn find_irrelevant_backup_files(&self, backup: &str, relevant_files: &[StrictPath]) -> Vec<StrictPath> {
-        #[allow(clippy::needless_collect)]
-        let relevant_files: Vec<_> = relevant_files.iter().filter_map(|x| x.interpret().ok()).collect();
-        let mut irrelevant_files = vec![];
+    pub fn has_backups(&self) -> bool {
+        !self.mapping.backups.is_empty()
+    }
 
-        let Ok(walk_path) = self.path.joined(backup).interpret() else {
-            return vec![];
-        };
+    /// Where a file would be extracted under `destination`,
+    /// mirroring the structure of a simple backup.
+    fn extraction_target(&self, destination: &StrictPath, original_path: &StrictPath) -> StrictPath {
+        let (drive, plain_path) = original_path.split_drive();
+        destination
+            .joined(&escape_folder_name(&self.mapping.name))
+            .joined(&self.mapping.drive_folder_name_immutable(&drive))
+            .joined(&plain_path)
+    }
 
-        for format in registry::Format::ALL {
-            let Ok(path) = self.path.joined(format.filename()).interpreted() else {
-                continue;
-            };
-            if !relevant_files.contains(&path.raw()) && path.is_file() {
-                irrelevant_files.push(path);
+    /// Narrow down a restoration scan to the selected files,
+    /// based on where they would normally be restored.
+    /// With a destination, the files are redirected there instead of their original locations,
+    /// so that live saves are left alone.
+    /// Registry data is dropped when only some files are selected or when extracting to a destination.
+    pub fn select_for_restoration(
+        &self,
+        scan: &mut ScanInfo,
+        selection: &FileSelection,
+        destination: Option<&StrictPath>,
+    ) {
+        scan.found_files
+            .retain(|scan_key, file| selection.matches(file.effective(scan_key)));
+
+        if let Some(destination) = destination {
+            for (scan_key, file) in scan.found_files.iter_mut() {
+                let target = self.extraction_target(destination, file.original_path(scan_key));
+                file.change = ScanChange::evaluate_restore(&target, &file.hash);
+                file.redirected = Some(target);
+                file.conflict = false;
             }
         }
 
-        for drive_dir in walkdir::WalkDir::new(walk_path)
-            .max_depth(1)
-            .follow_links(false)
-            .into_iter()
-            .filter_map(crate::scan::filter_map_walkdir)
-            .filter(|x| x.file_name().to_string_lossy().starts_with("drive-"))
+        if !selection.is_all() || destination.is_some() {
+            scan.found_registry_keys.clear();
+        }
+    }
+
+    /// A local file is in conflict with the backup if it was modified after the backup was made,
+    /// or if it doesn't match any version of the file in the backups.
+    fn is_restore_conflict(
+        &self,
+        scan_key: &StrictPath,
+        file: &ScannedFile,
+        when: &chrono::DateTime<chrono::Utc>,
+    ) -> bool {
+        if file.change != ScanChange::Different {
+            return false;
+        }
+
+        let live = file.effective(scan_key);
+        if live
+            .get_mtime()
+            .is_ok_and(|mtime| chrono::DateTime::<chrono::Utc>::from(mtime) > *when)
         {
-            for file in walkdir::WalkDir::new(drive_dir.path())
-                .max_depth(100)
-                .follow_links(false)
-                .into_iter()
-                .filter_map(crate::scan::filter_map_walkdir)
-                .filter(|x| x.file_type().is_file())
-            {
-                let backup_file = StrictPath::new(file.path().display().to_string());
-                let Ok(backup_path) = backup_file.interpret() else {
-                    continue;
-                };
-                if !relevant_files.contains(&backup_path) {
-                    irrelevant_files.push(backup_file);
+            return true;
+        }
+
+        let Ok(hash) = live.try_sha1() else {
+            return false;
+        };
+        let key = file.original_path(scan_key).render();
+        let known = self.mapping.backups.iter().any(|full| {
+            full.files.get(&key).is_some_and(|x| x.hash == hash)
+                || full.children.iter().any(|diff| {
+                    diff.files
+                        .get(&key)
+                        .is_some_and(|x| x.as_ref().is_some_and(|x| x.hash == hash))
+                })
+        });
+        !known
+    }
+
+    /// Apply a conflict policy before restoring.
+    /// Skipped files are marked as ignored,
+    /// and files to keep are copied aside with a `.local-<timestamp>` suffix.
+    /// The `prompt` callback is only used for `ConflictPolicy::Prompt`;
+    /// if it also returns `Prompt`, then the file is skipped.
+    ///
+    /// Returns the files that couldn't be copied aside, which are skipped as well.
+    pub fn resolve_conflicts(
+        &self,
+        scan: &mut ScanInfo,
+        policy: ConflictPolicy,
+        now: &chrono::DateTime<chrono::Utc>,
+        mut prompt: impl FnMut(&StrictPath) -> ConflictPolicy,
+    ) -> HashMap<StrictPath, BackupError> {
+        let mut failed = HashMap::new();
+
+        for (scan_key, file) in scan.found_files.iter_mut() {
+            if !file.conflict || file.ignored {
+                continue;
+            }
+
+            let live = file.effective(scan_key).clone();
+            let resolution = match policy {
+                ConflictPolicy::Prompt => prompt(&live),
+                policy => policy,
+            };
+
+            match resolution {
+                ConflictPolicy::Overwrite => {}
+                ConflictPolicy::Skip | ConflictPolicy::Prompt => {
+                    log::info!("[{}] skipping conflict: {:?}", self.mapping.name, &live);
+                    file.ignored = true;
+                }
+                ConflictPolicy::KeepBoth => {
+                    let aside = StrictPath::new(format!(
+                        "{}.local-{}",
+                        live.raw(),
+                        Self::generate_file_friendly_timestamp(now)
+                    ));
+                    if let Err(e) = live.copy_to_path(&self.mapping.name, &aside) {
+                        log::error!(
+                            "[{}] unable to keep conflicting file: {:?} -> {:?} | {e:?}",
+                            self.mapping.name,
+                            &live,
+                            &aside
+                        );
+                        file.ignored = true;
+                        failed.insert(scan_key.clone(), BackupError::Raw(e.to_string()));
+                    } else {
+                        log::info!(
+                            "[{}] kept conflicting file: {:?} -> {:?}",
+                            self.mapping.name,
+                            &live,
+                            &aside
+                        );
+                    }
                 }
             }
         }
 
-        irrelevant_files
+        failed
     }
 
-    pub fn remove_irrelevant_backup_files(&self, backup: &str, relevant_files: &[StrictPath]) {
-        // TODO: Remove empty directories as well.
-        log::trace!(
-            "[{}] looking for irrelevant backup files in {}",
-            self.mapping.name,
-            backup
-        );
-        for file in self.find_irrelevant_backup_files(backup, relevant_files) {
-            log::debug!("[{}] removing irrelevant backup file: {:?}", self.mapping.name, &file);
-            let _ = file.remove();
+    /// Back up the live files that a restore would overwrite, so that the restore can be undone.
+    /// This is saved as a full backup marked as `pre_restore`, with a `pre-restore` comment.
+    /// Snapshots are kept apart from the regular chain and its retention limits,
+    /// and only the latest one is kept, unless the user has locked older ones.
+    ///
+    /// Returns `None` if the restore wouldn't overwrite anything.
+    pub fn snapshot_before_restore(
+        &mut self,
+        scan: &ScanInfo,
+        now: &chrono::DateTime<chrono::Utc>,
+        format: &BackupFormats,
+    ) -> Option<BackupInfo> {
+        let mut found_files = HashMap::new();
+        for (scan_key, file) in &scan.found_files {
+            if file.ignored || file.change() != ScanChange::Different {
+                continue;
+            }
+
+            let live = file.effective(scan_key);
+            let Ok(hash) = live.try_sha1() else {
+                continue;
+            };
+            // Keep the original path in the mapping so that redirects apply the same way on undo.
+            let original = file.original_path(scan_key);
+            found_files.insert(
+                live.clone(),
+                ScannedFile {
+                    size: live.size(),
+                    hash,
+                    original_path: None,
+                    ignored: false,
+                    change: ScanChange::New,
+                    container: None,
+                    redirected: (original != live).then(|| original.clone()),
+                    conflict: false,
+                    filtered: false,
+                    steam_account: None,
+                },
+            );
         }
-        log::trace!("[{}] done removing irrelevant backup files", self.mapping.name);
-    }
 
-    pub fn set_backup_comment(&mut self, backup_name: &str, comment: &str) {
-        let comment = if comment.is_empty() {
-            None
-        } else {
-            Some(comment.to_string())
+        if found_files.is_empty() {
+            log::trace!("[{}] nothing to snapshot before restore", &scan.game_name);
+            return None;
+        }
+
+        let snapshot = ScanInfo {
+            game_name: scan.game_name.clone(),
+            found_files,
+            ..Default::default()
         };
 
-        'outer: for backup in &mut self.mapping.backups {
-            if backup.name == backup_name {
-                backup.comment = comment;
-                break 'outer;
+        self.migrate_backups(true);
+
+        let mut full = self.plan_full_backup(&snapshot, now, format, Retention::default());
+        // Don't reuse a regular backup's name, like the special ID `.`.
+        full.name = name_with_format_extension(
+            format!(
+                "backup-{}-{PRE_RESTORE_COMMENT}",
+                Self::generate_file_friendly_timestamp(now)
+            ),
+            format.chosen,
+        );
+        full.comment = Some(format!(
+            "{PRE_RESTORE_COMMENT} {}",
+            chrono::DateTime::<chrono::Local>::from(*now).format("%Y-%m-%d")
+        ));
+        full.pre_restore = true;
+        let mut backup = Backup::Full(full);
+
+        let cipher = match self.prepare_cipher(format) {
+            Ok(Some((cipher, info))) => {
+                backup.set_encryption(Some(info));
+                Some(cipher)
             }
-            for child in &mut backup.children {
-                if child.name == backup_name {
-                    child.comment = comment;
-                    break 'outer;
-                }
+            Ok(None) => None,
+            Err(e) => {
+                log::error!("[{}] unable to prepare encryption: {e:?}", scan.game_name);
+                return Some(BackupInfo::total_failure(&snapshot, BackupError::App(e)));
             }
+        };
+
+        log::info!(
+            "[{}] creating a pre-restore snapshot: {}",
+            &scan.game_name,
+            backup.name()
+        );
+        let backup_info = self.execute_backup(&backup, &snapshot, format, cipher.as_ref());
+        backup.prune_failures(&backup_info);
+        if backup.needed() {
+            self.insert_backup(backup);
+            self.forget_excess_snapshots();
+            self.save();
         }
+        self.prune_irrelevant_parents();
+        Some(backup_info)
     }
 
-    pub fn set_backup_locked(&mut self, backup_name: &str, locked: bool) {
-        'outer: for backup in &mut self.mapping.backups {
-            if backup.name == backup_name {
-                backup.locked = locked;
-                break 'outer;
-            }
-            for child in &mut backup.children {
-                if child.name == backup_name {
-                    child.locked = locked;
-                    break 'outer;
+    /// Like `restore`, but first take a snapshot of the live files that would be overwritten.
+    /// If the snapshot fails, then nothing is restored.
+    /// Restoring a snapshot doesn't make another one.
+    pub fn restore_with_snapshot(
+        &mut self,
+        scan: &ScanInfo,
+        toggled: &ToggledRegistry,
+        now: &chrono::DateTime<chrono::Utc>,
+        format: &BackupFormats,
+    ) -> BackupInfo {
+        if !scan.backup.as_ref().is_some_and(|x| x.pre_restore()) {
+            if let Some(snapshot) = self.snapshot_before_restore(scan, now, format) {
+                if !snapshot.successful() {
+                    log::error!("[{}] aborting restore because the snapshot failed", &scan.game_name);
+                    return BackupInfo::total_failure(scan, BackupError::App(Error::RestoreSnapshotFailed));
                 }
             }
         }
+
+        self.restore(scan, toggled)
     }
 
-    /// Returns whether the backup is valid.
-    pub fn validate(&self, backup_id: BackupId) -> bool {
-        if let Some((backup, diff)) = self.find_by_id(&backup_id) {
-            match backup.format() {
-                BackupFormat::Simple => {
-                    for file in backup.files.keys() {
-                        let original_path = StrictPath::new(file.to_string());
-                        let stored = self
-                            .mapping
-                            .game_file_immutable(&self.path, &original_path, &backup.name);
-                        if !stored.is_file() {
-                            #[cfg(test)]
-                            eprintln!("can't find {}", stored.render());
-                            return false;
-                        }
-                    }
-                }
-                BackupFormat::Zip => {
-                    let Ok(handle) = self.path.joined(&backup.name).open() else {
-                        return false;
-                    };
-                    let Ok(mut archive) = zip::ZipArchive::new(handle) else {
-                        return false;
-                    };
+    /// The most recent snapshot made before a restore, if any.
+    pub fn latest_pre_restore_snapshot(&self) -> Option<(BackupId, chrono::DateTime<chrono::Utc>)> {
+        self.mapping
+            .backups
+            .iter()
+            .rev()
+            .find(|x| x.pre_restore)
+            .map(|x| (BackupId::Named(x.name.clone()), x.when))
+    }
 
-                    for file in backup.files.keys() {
-                        let original_path = StrictPath::new(file.to_string());
-                        let stored = self.mapping.game_file_for_zip_immutable(&original_path);
-                        if archive.by_name(&stored).is_err() {
-                            #[cfg(test)]
-                            eprintln!("can't find {}", stored);
-                            return false;
-                        }
-                    }
+    /// The backup made just before the given one, if any.
+    pub fn previous_backup_id(&self, id: &BackupId) -> Option<BackupId> {
+        let (full, diff) = self.find_by_id(id)?;
+        let name = diff.map(|x| &x.name).unwrap_or(&full.name);
+
+        let mut previous = None;
+        for full in &self.mapping.backups {
+            for candidate in std::iter::once(&full.name).chain(full.children.iter().map(|x| &x.name)) {
+                if candidate == name {
+                    return previous.map(|x: &String| BackupId::Named(x.clone()));
                 }
+                previous = Some(candidate);
             }
+        }
+        None
+    }
 
-            if let Some(backup) = diff {
-                match backup.format() {
-                    BackupFormat::Simple => {
-                        for (file, data) in &backup.files {
-                            if data.is_none() {
-                                // File is deliberately omitted.
-                                continue;
-                            }
-
-                            let original_path = StrictPath::new(file.to_string());
-                            let stored = self
-                                .mapping
-                                .game_file_immutable(&self.path, &original_path, &backup.name);
-                            if !stored.is_file() {
-                                #[cfg(test)]
-                                eprintln!("can't find {}", stored.render());
-                                return false;
-                            }
-                        }
-                    }
-                    BackupFormat::Zip => {
-                        let Ok(handle) = self.path.joined(&backup.name).open() else {
-                            return false;
-                        };
-                        let Ok(mut archive) = zip::ZipArchive::new(handle) else {
-                            return false;
-                        };
+    /// When `from` is unset, every file in the `to` backup is considered new.
+    /// Returns `None` if either backup can't be found.
+    pub fn compare_backups(&self, from: Option<&BackupId>, to: &BackupId) -> Option<BackupComparison> {
+        let before = match from {
+            Some(from) => Some(self.find_by_id(from)?),
+            None => None,
+        };
+        let after = self.find_by_id(to)?;
 
-                        for (file, data) in &backup.files {
-                            if data.is_none() {
-                                // File is deliberately omitted.
-                                continue;
-                            }
+        let before_files = before
+            .map(|(full, diff)| Self::stored_file_sources(full, diff))
+            .unwrap_or_default();
+        let after_files = Self::stored_file_sources(after.0, after.1);
 
-                            let original_path = StrictPath::new(file.to_string());
-                            let stored = self.mapping.game_file_for_zip_immutable(&original_path);
-                            if archive.by_name(&stored).is_err() {
-                                #[cfg(test)]
-                                eprintln!("can't find {}", stored);
-                                return false;
-                            }
-                        }
-                    }
-                }
+        let mut files = BTreeMap::new();
+        for file in before_files.keys().chain(after_files.keys()) {
+            let from = before_files.get(file).map(|(_, data)| data);
+            let to = after_files.get(file).map(|(_, data)| data);
+            let change = compare_file_versions(from, to);
+            if change.is_changed() {
+                files.insert(
+                    file.to_string(),
+                    FileComparison {
+                        change,
+                        from: from.cloned(),
+                        to: to.cloned(),
+                    },
+                );
             }
         }
 
-        true
+        Some(BackupComparison {
+            from: before.map(|(full, diff)| BackupReference::new(full, diff)),
+            to: BackupReference::new(after.0, after.1),
+            files,
+        })
     }
-}
 
-#[derive(Clone, Debug, Default, Eq, PartialEq)]
-pub enum BackupKind {
-    #[default]
-    Full,
-    Differential,
-}
+    /// List each backup where the file was added, changed, or removed, from oldest to newest.
+    /// The file path is matched against the rendered paths in the mapping file.
+    pub fn file_history(&self, file: &str) -> Vec<FileHistoryEntry> {
+        let file = StrictPath::new(file.to_string()).render();
 
-#[derive(Clone, Debug, Default)]
-pub struct BackupLayout {
-    pub base: StrictPath,
-    games: HashMap<String, StrictPath>,
-    games_lowercase: HashMap<String, StrictPath>,
-}
+        let mut history = vec![];
+        let mut previous = None;
+        for full in &self.mapping.backups {
+            for diff in std::iter::once(None).chain(full.children.iter().map(Some)) {
+                let current = match diff.map(|diff| diff.files.get(&file)) {
+                    None | Some(None) => full.files.get(&file),
+                    Some(Some(data)) => data.as_ref(),
+                };
 
-impl BackupLayout {
-    pub fn new(base: StrictPath) -> Self {
-        let games = Self::load(&base);
-        let games_lowercase = games.iter().map(|(k, v)| (k.to_lowercase(), v.clone())).collect();
-        Self {
-            base,
-            games,
-            games_lowercase,
+                let change = compare_file_versions(previous, current);
+                if change.is_changed() {
+                    let BackupReference { name, when } = BackupReference::new(full, diff);
+                    history.push(FileHistoryEntry {
+                        backup: name,
+                        when,
+                        change,
+                        hash: current.map(|x| x.hash.clone()),
+                        size: current.map(|x| x.size),
+                    });
+                }
+                previous = current;
+            }
         }
-    }
 
-    pub fn load(base: &StrictPath) -> HashMap<String, StrictPath> {
-        let mut overall = HashMap::new();
+        history
+    }
 
-        let Ok(base_interpreted) = base.interpret() else {
-            return HashMap::new();
-        };
+    pub fn scan_for_restoration(
+        &mut self,
+        name: &str,
+        id: &BackupId,
+        redirects: &[RedirectConfig],
+        reverse_redirects_on_restore: bool,
+        toggled_paths: &ToggledPaths,
+        toggled_registry: &ToggledRegistry,
+        steam_accounts: &[SteamAccountRedirect],
+    ) -> ScanInfo {
+        log::trace!("[{name}] beginning scan for restore");
 
-        for game_dir in walkdir::WalkDir::new(base_interpreted)
-            .max_depth(1)
-            .follow_links(false)
-            .into_iter()
-            .skip(1) // the base path itself
-            .filter_map(crate::scan::filter_map_walkdir)
-            .filter(|x| x.file_type().is_dir())
-        {
-            let game_dir = StrictPath::from(&game_dir);
-            let mapping_file = game_dir.joined("mapping.yaml");
-            if mapping_file.is_file() {
-                match IndividualMapping::load(&mapping_file) {
-                    Ok(mapping) => {
-                        overall.insert(mapping.name.clone(), game_dir);
-                    }
-                    Err(e) => {
-                        log::warn!("Ignoring unloadable mapping: {:?} | {:?}", &mapping_file, e);
-                    }
-                }
-            }
-        }
+        let mut found_files = HashMap::new();
+        let mut found_registry_keys = HashMap::new();
+        #[cfg_attr(not(target_os = "windows"), allow(unused_mut))]
+        let mut available_backups = vec![];
+        let mut backup = None;
 
-        overall
-    }
+        let id = self.verify_id(id);
 
-    pub fn game_layout(&self, name: &str) -> GameLayout {
-        let path = self.game_folder(name);
+        if self.path.is_dir() {
+            self.migrate_backups(true);
+            found_files = self.restorable_files(
+                &id,
+                ScanKind::Restore,
+                redirects,
+                reverse_redirects_on_restore,
+                toggled_paths,
+            );
+            available_backups = self.restorable_backups_flattened();
+            backup = self.find_by_id_flattened(&id);
 
-        match GameLayout::load(path.clone()) {
-            Ok(mut x) => {
-                if x.mapping.name != name {
-                    // This can happen if the game name changed in the manifest,
-                    // but differs only by capitalization when we're on a case-insensitive OS.
-                    // If we don't adjust it, it'll always show up as a new game.
-                    log::info!("Updating renamed game: {} -> {}", &x.mapping.name, name);
-                    x.mapping.name = name.to_string();
+            let steam_accounts: Vec<_> = steam_accounts
+                .iter()
+                .filter_map(|x| Some((steam_account_id(x.source)?, steam_account_id(x.target)?)))
+                .collect();
+            for (scan_key, file) in found_files.iter_mut() {
+                let Some(redirected) = steam_accounts
+                    .iter()
+                    .find_map(|(source, target)| redirect_steam_account(file.effective(scan_key), *source, *target))
+                else {
+                    continue;
+                };
+                file.change = ScanChange::evaluate_restore(&redirected, &file.hash);
+                file.ignored = toggled_paths.is_ignored(name, &redirected);
+                file.redirected = Some(redirected);
+            }
+
+            if let Some(backup) = &backup {
+                for (scan_key, file) in found_files.iter_mut() {
+                    file.conflict = self.is_restore_conflict(scan_key, file, backup.when());
                 }
-                x
             }
-            Err(_) => GameLayout {
-                path,
-                mapping: IndividualMapping::new(name.to_string()),
-            },
         }
-    }
 
-    pub fn try_game_layout(&self, name: &str) -> Option<GameLayout> {
-        let path = self.game_folder(name);
+        #[cfg(target_os = "windows")]
+        let wine_prefixes = vec![];
+        #[cfg(not(target_os = "windows"))]
+        let wine_prefixes = self.restorable_wine_prefixes(&id, redirects, reverse_redirects_on_restore);
 
-        GameLayout::load(path).ok().map(|mut x| {
-            if x.mapping.name != name {
-                // This can happen if the game name changed in the manifest,
-                // but differs only by capitalization when we're on a case-insensitive OS.
-                // If we don't adjust it, it'll always show up as a new game.
-                log::info!("Updating renamed game: {} -> {}", &x.mapping.name, name);
-                x.mapping.name = name.to_string();
-            }
-            x
-        })
-    }
+        {
+            use crate::scan::{RegistryItem, ScannedRegistryValue, ScannedRegistryValues};
 
-    fn contains_game(&self, name: &str) -> bool {
-        self.games.contains_key(name)
-            || (!Os::HOST.is_case_sensitive() && self.games_lowercase.contains_key(&name.to_lowercase()))
-    }
+            #[cfg(target_os = "windows")]
+            let hives = self.try_registry_content(&id);
+            // Without a prefix, there would be nowhere to restore the registry.
+            #[cfg(not(target_os = "windows"))]
+            let hives = if wine_prefixes.is_empty() {
+                Ok(None)
+            } else {
+                self.try_registry_content(&id)
+            };
+            #[cfg(not(target_os = "windows"))]
+            let wine = registry::wine::Prefixes::load(&wine_prefixes);
 
-    pub fn latest_backup(
-        &self,
-        name: &str,
-        scan_kind: ScanKind,
-        redirects: &[RedirectConfig],
-        reverse_redirects_on_restore: bool,
-        toggled_paths: &ToggledPaths,
-    ) -> Option<LatestBackup> {
-        if self.contains_game(name) {
-            let game_layout = self.game_layout(name);
-            let scan = game_layout.latest_backup(scan_kind, redirects, reverse_redirects_on_restore, toggled_paths);
-            scan.map(|scan| LatestBackup {
-                scan,
-                registry_content: if cfg!(target_os = "windows") {
-                    game_layout.registry_content(&BackupId::Latest)
-                } else {
+            let hives = match hives {
+                Ok(hives) => hives,
+                Err(e) => {
+                    log::warn!("[{name}] unable to read registry from backup: {e:?}");
+                    // Keep a placeholder so that the restore can report the failure.
+                    found_registry_keys.insert(
+                        RegistryItem::new(self.path.render()),
+                        ScannedRegistry {
+                            ignored: false,
+                            change: ScanChange::Unknown,
+                            values: ScannedRegistryValues::new(),
+                        },
+                    );
                     None
-                },
-            })
-        } else {
-            None
-        }
-    }
-
-    fn generate_total_rename(original_name: &str) -> String {
-        format!("ludusavi-renamed-{}", encode_base64_for_folder(original_name))
-    }
-
-    pub fn game_folder(&self, game_name: &str) -> StrictPath {
-        match self.games.get::<str>(game_name) {
-            Some(game) => game.clone(),
-            None => {
-                let mut safe_name = escape_folder_name(game_name);
-
-                if safe_name.matches(SAFE).count() == safe_name.len() {
-                    // It's unreadable now, so do a total rename.
-                    safe_name = Self::generate_total_rename(game_name);
                 }
+            };
 
-                self.base.joined(&safe_name)
-            }
-        }
-    }
+            if let Some(hives) = hives {
+                for (hive_name, keys) in hives.0.iter() {
+                    for (key_name, entries) in keys.0.iter() {
+                        #[cfg(target_os = "windows")]
+                        let live_entries = registry::win::try_read_registry_key(hive_name, key_name);
+                        #[cfg(not(target_os = "windows"))]
+                        let live_entries = wine.get(hive_name, key_name).cloned();
+                        let mut live_values = ScannedRegistryValues::new();
 
-    pub fn restorable_games(&self) -> Vec<String> {
-        self.games.keys().cloned().collect()
-    }
+                        let path = RegistryItem::from_hive_and_key(hive_name, key_name);
 
-    pub fn restorable_game_set(&self) -> BTreeSet<String> {
-        self.games.keys().cloned().collect()
-    }
-}
+                        for (entry_name, entry) in entries.0.iter() {
+                            live_values.insert(
+                                entry_name.clone(),
+                                ScannedRegistryValue {
+                                    ignored: toggled_registry.is_ignored(name, &path, Some(entry_name)),
+                                    change: live_entries
+                                        .as_ref()
+                                        .and_then(|x| x.0.get(entry_name))
+                                        .map(|live_entry| {
+                                            if entry == live_entry {
+                                                ScanChange::Same
+                                            } else {
+                                                ScanChange::Different
+                                            }
+                                        })
+                                        .unwrap_or(ScanChange::New),
+                                },
+                            );
+                        }
 
-#[cfg(test)]
-mod tests {
-    use velcro::{btree_map, hash_map};
+                        let ignored = toggled_registry.is_ignored(name, &path, None)
+                            && entries
+                                .0
+                                .keys()
+                                .all(|x| toggled_registry.is_ignored(name, &path, Some(x)));
 
-    use super::*;
-    use crate::testing::{drives_x, make_original_path, mapping_file_key, repo, repo_raw, s};
+                        found_registry_keys.insert(
+                            path,
+                            ScannedRegistry {
+                                ignored,
+                                change: match &live_entries {
+                                    None => ScanChange::New,
+                                    Some(_) => ScanChange::Same,
+                                },
+                                values: live_values,
+                            },
+                        );
+                    }
+                }
+            }
+        }
 
-    mod individual_mapping {
-        use pretty_assertions::assert_eq;
+        let has_backups = !available_backups.is_empty();
 
-        use super::*;
+        log::trace!("[{name}] completed scan for restore");
 
-        #[test]
-        fn can_generate_drive_folder_name() {
-            let mut mapping = IndividualMapping::new("foo".to_owned());
-            assert_eq!("drive-0", mapping.drive_folder_name(""));
-            assert_eq!("drive-C", mapping.drive_folder_name("C:"));
-            assert_eq!("drive-D", mapping.drive_folder_name("D:"));
-            assert_eq!("drive-____C", mapping.drive_folder_name(r#"\\?\C:"#));
-            assert_eq!("drive-__remote", mapping.drive_folder_name(r#"\\remote"#));
+        ScanInfo {
+            game_name: name.to_string(),
+            found_files,
+            found_registry_keys,
+            wine_prefixes,
+            available_backups,
+            backup,
+            has_backups,
         }
     }
 
-    mod backup_layout {
-        use pretty_assertions::assert_eq;
+    pub fn restore(&self, scan: &ScanInfo, toggled: &ToggledRegistry) -> BackupInfo {
+        log::trace!("[{}] beginning restore", &scan.game_name);
 
-        use crate::testing::{repo_file_raw, repo_path, repo_path_raw};
+        let mut failed_files = HashMap::new();
+        let mut failed_registry = HashMap::new();
 
-        use super::*;
+        let mut containers: HashMap<StrictPath, ZipReader> = HashMap::new();
+        let mut failed_containers: HashMap<StrictPath, BackupError> = HashMap::new();
+        // Tar archives can't be read out of order,
+        // so we gather everything we need from each one and extract it in a single pass.
+        let mut tar_files: HashMap<StrictPath, HashMap<String, (&StrictPath, &StrictPath)>> = HashMap::new();
+        let blob_store = self.blob_store();
+        let mut ciphers = HashMap::new();
 
-        fn layout() -> BackupLayout {
-            BackupLayout::new(StrictPath::new(format!("{}/tests/backup", repo_raw())))
-        }
+        for (scan_key, file) in &scan.found_files {
+            let target = file.effective(scan_key);
 
-        fn game_layout(name: &str, path: &str) -> GameLayout {
-            GameLayout {
-                path: StrictPath::new(path.to_string()),
-                mapping: IndividualMapping::new(name.to_string()),
+            if !file.change().is_changed() || file.ignored {
+                log::info!(
+                    "[{}] skipping file; change={:?}, ignored={}: {:?} -> {:?}",
+                    self.mapping.name,
+                    file.change,
+                    file.ignored,
+                    scan_key,
+                    &target
+                );
+                continue;
             }
-        }
 
-        fn drives() -> BTreeMap<String, String> {
-            let (drive, _) = StrictPath::cwd().split_drive();
-            let folder = IndividualMapping::new_drive_folder_name(&drive);
-            btree_map! { folder: drive }
-        }
+            let container_format = file.container.as_ref().map(|x| {
+                if blob_store.is_prefix_of(x) {
+                    BackupFormat::Dedup
+                } else {
+                    format_from_backup_name(&x.raw())
+                }
+            });
 
-        #[test]
-        fn can_find_existing_game_folder_with_matching_name() {
-            assert_eq!(repo_path_raw("tests/backup/game1"), layout().game_folder("game1"));
-        }
+            if let (Some(container), Some(BackupFormat::TarZstd)) = (file.container.as_ref(), container_format) {
+                tar_files
+                    .entry(container.clone())
+                    .or_default()
+                    .insert(scan_key.raw(), (scan_key, target));
+                continue;
+            }
 
-        #[test]
-        fn can_find_existing_game_folder_with_rename() {
-            assert_eq!(
-                repo_path_raw("tests/backup/game3-renamed"),
+            let cipher = match self.encryption_for(file.container.as_ref().unwrap_or(scan_key)) {
+                None => None,
+                Some(encryption) => match self.cipher_for(encryption, &mut ciphers) {
+                    Ok(cipher) => Some(cipher),
+                    Err(e) => {
+                        log::error!(
+                            "[{}] unable to decrypt: {:?} -> {:?} | {e:?}",
+                            self.mapping.name,
+                            scan_key,
+                            &target
+                        );
+                        failed_files.insert(scan_key.clone(), BackupError::App(e));
+                        continue;
+                    }
+                },
+            };
+
+            if let (Some(container), Some(BackupFormat::Zip)) = (file.container.as_ref(), container_format) {
+                if let Some(e) = failed_containers.get(container) {
+                    log::warn!(
+                        "[{}] skipping file because container had failed to load: {:?} -> {:?} -> {:?}",
+                        self.mapping.name,
+                        &container,
+                        scan_key,
+                        &target,
+                    );
+                    failed_files.insert(scan_key.clone(), e.clone());
+                    continue;
+                }
+
+                if !containers.contains_key(container) {
+                    log::debug!("[{}] loading zip archive: {:?}", &self.mapping.name, &container);
+                    let archive = match self.open_zip(container, cipher.as_ref()) {
+                        Ok(archive) => archive,
+                        Err(e) => {
+                            log::error!(
+                                "[{}] failed to load zip archive: {:?} | {e:?}",
+                                &self.mapping.name,
+                                &container
+                            );
+                            failed_containers.insert(container.clone(), BackupError::Raw(e.to_string()));
+                            failed_files.insert(scan_key.clone(), BackupError::Raw(e.to_string()));
+                            continue;
+                        }
+                    };
+                    log::debug!("[{}] loaded zip archive: {:?}", &self.mapping.name, &container);
+                    containers.insert(container.clone(), archive);
+                }
+            }
+
+            let outcome = match &file.container {
+                None => match &cipher {
+                    None => self.restore_file_from_simple(target, scan_key),
+                    Some(cipher) => self.restore_file_from_encrypted(target, scan_key, cipher),
+                },
+                Some(blob) if container_format == Some(BackupFormat::Dedup) => {
+                    self.restore_file_from_blob(target, blob)
+                }
+                Some(container) => {
+                    let Some(archive) = containers.get_mut(container) else {
+                        continue;
+                    };
+                    self.restore_file_from_zip(target, scan_key, archive)
+                }
+            };
+
+            match outcome {
+                Ok(_) => {
+                    log::info!("[{}] restored: {:?} -> {:?}", &self.mapping.name, scan_key, &target);
+                }
+                Err(e) => {
+                    log::error!(
+                        "[{}] failed to restore: {:?} -> {:?} | {e}",
+                        self.mapping.name,
+                        scan_key,
+                        &target
+                    );
+                    failed_files.insert(scan_key.clone(), BackupError::Raw(e.to_string()));
+                }
+            }
+        }
+
+        for (container, wanted) in tar_files {
+            log::debug!("[{}] reading tar archive: {:?}", &self.mapping.name, &container);
+            let mut failed = self.restore_files_from_tar(&container, &wanted);
+            for (scan_key, target) in wanted.values() {
+                match failed.remove(*scan_key) {
+                    None => {
+                        log::info!("[{}] restored: {:?} -> {:?}", &self.mapping.name, scan_key, &target);
+                    }
+                    Some(e) => {
+                        log::error!(
+                            "[{}] failed to restore: {:?} -> {:?} | {e}",
+                            self.mapping.name,
+                            scan_key,
+                            &target
+                        );
+                        failed_files.insert((*scan_key).clone(), BackupError::Raw(e));
+                    }
+                }
+            }
+        }
+
+        {
+            // This is empty if the registry was excluded from the restore.
+            let registry_selected = !scan.found_registry_keys.is_empty();
+            if let Some(backup) = scan.backup.as_ref().filter(|_| registry_selected) {
+                match self.try_registry_content(&backup.id()) {
+                    Ok(Some(hives)) => {
+                        if let Err(failed) = hives.restore_scan(scan, toggled) {
+                            failed_registry.extend(failed);
+                        }
+                    }
+                    Ok(None) => {}
+                    Err(e) => {
+                        log::error!("[{}] failed to read registry from backup | {e:?}", self.mapping.name);
+                        for (item, _) in scan.found_registry_keys.iter().filter(|(_, x)| !x.ignored) {
+                            failed_registry.insert(item.clone(), BackupError::App(e.clone()));
+                        }
+                    }
+                }
+            }
+        }
+
+        log::trace!("[{}] completed restore", &scan.game_name);
+
+        BackupInfo {
+            failed_files,
+            failed_registry,
+        }
+    }
+
+    fn restore_file_from_simple(&self, target: &StrictPath, scan_key: &StrictPath) -> Result<(), AnyError> {
+        log::trace!(
+            "[{}] about to restore (simple): {:?} -> {:?}",
+            self.mapping.name,
+            scan_key,
+            &target
+        );
+
+        Ok(scan_key.copy_to_path(&self.mapping.name, target)?)
+    }
+
+    fn restore_file_from_encrypted(
+        &self,
+        target: &StrictPath,
+        scan_key: &StrictPath,
+        cipher: &Cipher,
+    ) -> Result<(), AnyError> {
+        log::trace!(
+            "[{}] about to restore (encrypted): {:?} -> {:?}",
+            self.mapping.name,
+            scan_key,
+            &target
+        );
+
+        let mtime = scan_key.get_mtime()?;
+        let mut reader = cipher.reader(scan_key.open()?)?;
+        target.create_parent_dir()?;
+        target.unset_readonly()?;
+        std::io::copy(&mut reader, &mut target.create()?)?;
+        target.set_mtime(mtime)?;
+        Ok(())
+    }
+
+    fn restore_file_from_blob(&self, target: &StrictPath, blob: &StrictPath) -> Result<(), AnyError> {
+        log::trace!(
+            "[{}] about to restore (dedup): {:?} -> {:?}",
+            self.mapping.name,
+            blob,
+            &target
+        );
+
+        Ok(blob.copy_to_path(&self.mapping.name, target)?)
+    }
+
+    fn restore_file_from_zip(
+        &self,
+        target: &StrictPath,
+        scan_key: &StrictPath,
+        archive: &mut ZipReader,
+    ) -> Result<(), AnyError> {
+        log::debug!(
+            "[{}] about to restore (zip): {:?} -> {:?}",
+            self.mapping.name,
+            scan_key,
+            &target
+        );
+
+        if let Err(e) = target.create_parent_dir() {
+            log::error!(
+                "[{}] unable to create parent directories: {:?} | {e}",
+                self.mapping.name,
+                &target
+            );
+            return Err(Box::new(e));
+        }
+        if let Err(e) = target.unset_readonly() {
+            log::warn!(
+                "[{}] failed to unset read-only on target: {:?} | {e}",
+                self.mapping.name,
+                &target
+            );
+            return Err(e);
+        }
+        let mut target_handle = match target.create() {
+            Ok(x) => x,
+            Err(e) => {
+                log::warn!(
+                    "[{}] failed to get handle: {:?} -> {:?} | {e}",
+                    self.mapping.name,
+                    scan_key,
+                    &target
+                );
+                return Err(Box::new(e));
+            }
+        };
+        let mut source_file = archive.by_name(&scan_key.raw())?;
+        if let Err(e) = std::io::copy(&mut source_file, &mut target_handle) {
+            log::warn!(
+                "[{}] failed to copy to target: {:?} -> {:?} | {e}",
+                self.mapping.name,
+                &scan_key,
+                &target,
+            );
+            return Err(Box::new(e));
+        }
+
+        let mtime = source_file.last_modified();
+        if let Err(e) = target.set_mtime_zip(mtime) {
+            log::error!(
+                "[{}] unable to set modification time: {:?} -> {:?} to {:#?} | {e:?}",
+                self.mapping.name,
+                scan_key,
+                &target,
+                mtime
+            );
+            return Err("unable to set modification time".into());
+        }
+
+        Ok(())
+    }
+
+    /// Returns any files that could not be restored, with the reason.
+    fn restore_files_from_tar(
+        &self,
+        container: &StrictPath,
+        wanted: &HashMap<String, (&StrictPath, &StrictPath)>,
+    ) -> HashMap<StrictPath, String> {
+        let fail_all = |error: String| -> HashMap<StrictPath, String> {
+            wanted
+                .values()
+                .map(|(scan_key, _)| ((*scan_key).clone(), error.clone()))
+                .collect()
+        };
+
+        let handle = match container.open() {
+            Ok(x) => x,
+            Err(e) => {
+                log::error!(
+                    "[{}] failed to open tar archive: {:?} | {e:?}",
+                    &self.mapping.name,
+                    &container
+                );
+                return fail_all(e.to_string());
+            }
+        };
+        let decoder = match zstd::Decoder::new(handle) {
+            Ok(x) => x,
+            Err(e) => {
+                log::error!(
+                    "[{}] failed to decompress tar archive: {:?} | {e:?}",
+                    &self.mapping.name,
+                    &container
+                );
+                return fail_all(e.to_string());
+            }
+        };
+        let mut archive = tar::Archive::new(decoder);
+        archive.set_preserve_mtime(true);
+        archive.set_preserve_permissions(true);
+
+        let entries = match archive.entries() {
+            Ok(x) => x,
+            Err(e) => {
+                log::error!(
+                    "[{}] failed to parse tar archive: {:?} | {e:?}",
+                    &self.mapping.name,
+                    &container
+                );
+                return fail_all(e.to_string());
+            }
+        };
+
+        let mut pending = fail_all("File not found in archive".to_string());
+
+        for entry in entries {
+            let mut entry = match entry {
+                Ok(x) => x,
+                Err(e) => {
+                    log::error!(
+                        "[{}] failed to read tar archive: {:?} | {e:?}",
+                        &self.mapping.name,
+                        &container
+                    );
+                    for error in pending.values_mut() {
+                        *error = e.to_string();
+                    }
+                    break;
+                }
+            };
+            let Ok(name) = entry.path().map(|x| x.to_string_lossy().to_string()) else {
+                continue;
+            };
+            let Some((scan_key, target)) = wanted.get(&name) else {
+                continue;
+            };
+
+            log::debug!(
+                "[{}] about to restore (tar): {:?} -> {:?}",
+                self.mapping.name,
+                scan_key,
+                &target
+            );
+
+            if let Err(e) = target.create_parent_dir() {
+                log::error!(
+                    "[{}] unable to create parent directories: {:?} | {e}",
+                    self.mapping.name,
+                    &target
+                );
+                pending.insert((*scan_key).clone(), e.to_string());
+                continue;
+            }
+            if let Err(e) = target.unset_readonly() {
+                log::warn!(
+                    "[{}] failed to unset read-only on target: {:?} | {e}",
+                    self.mapping.name,
+                    &target
+                );
+                pending.insert((*scan_key).clone(), e.to_string());
+                continue;
+            }
+            let outcome = target
+                .as_std_path_buf()
+                .map_err(|e| e.to_string())
+                .and_then(|target| entry.unpack(target).map_err(|e| e.to_string()));
+            match outcome {
+                Ok(_) => {
+                    pending.remove(*scan_key);
+                }
+                Err(e) => {
+                    log::warn!(
+                        "[{}] failed to extract to target: {:?} -> {:?} | {e}",
+                        self.mapping.name,
+                        &scan_key,
+                        &target,
+                    );
+                    pending.insert((*scan_key).clone(), e);
+                }
+            }
+        }
+
+        pending
+    }
+
+    fn copy_encrypted(source: &StrictPath, target: &StrictPath, cipher: &Cipher) -> std::io::Result<()> {
+        let mtime = source.get_mtime()?;
+        target.create_parent_dir()?;
+        let mut writer = cipher.writer(target.create_rw()?)?;
+        std::io::copy(&mut source.open()?, &mut writer)?;
+        writer.finish()?;
+        // The stored file's mtime stands in for the original's, like with unencrypted backups.
+        target.set_mtime(mtime)
+    }
+
+    /// Find the encryption settings for the backup that holds a given stored file or archive, if it's encrypted.
+    fn encryption_for(&self, stored: &StrictPath) -> Option<&EncryptionInfo> {
+        for full in &self.mapping.backups {
+            let backups = std::iter::once((&full.name, &full.encryption))
+                .chain(full.children.iter().map(|diff| (&diff.name, &diff.encryption)));
+            for (name, encryption) in backups {
+                let Some(encryption) = encryption else {
+                    continue;
+                };
+                let root = self.path.joined(name);
+                if &root == stored || root.is_prefix_of(stored) {
+                    return Some(encryption);
+                }
+            }
+        }
+        None
+    }
+
+    /// Deriving a key is deliberately slow, so we only do it once per backup.
+    fn cipher_for(
+        &self,
+        encryption: &EncryptionInfo,
+        ciphers: &mut HashMap<String, Result<Cipher, Error>>,
+    ) -> Result<Cipher, Error> {
+        ciphers
+            .entry(encryption.salt.clone())
+            .or_insert_with(|| Cipher::unlock(self.passphrase.as_ref(), encryption))
+            .clone()
+    }
+
+    fn open_zip(&self, path: &StrictPath, cipher: Option<&Cipher>) -> Result<ZipReader, AnyError> {
+        let reader: Box<dyn ReadSeek> = match cipher {
+            None => Box::new(path.open()?),
+            Some(cipher) => Box::new(cipher.reader(path.open()?)?),
+        };
+        Ok(zip::ZipArchive::new(reader)?)
+    }
+
+    /// Open a zip backup, decrypting it if necessary.
+    fn open_zip_in(&self, backup: &str) -> Option<ZipReader> {
+        let path = self.path.joined(backup);
+        let cipher = match self.encryption_for(&path) {
+            None => None,
+            Some(encryption) => Some(Cipher::unlock(self.passphrase.as_ref(), encryption).ok()?),
+        };
+        self.open_zip(&path, cipher.as_ref()).ok()
+    }
+
+    /// Map each entry in a tar archive to its hash, if requested.
+    /// The archive can only be read sequentially, so this is done in one pass.
+    fn tar_entries(&self, backup: &str, hash: bool) -> Option<HashMap<String, Option<String>>> {
+        let handle = self.path.joined(backup).open().ok()?;
+        let decoder = zstd::Decoder::new(handle).ok()?;
+        let mut archive = tar::Archive::new(decoder);
+
+        let mut entries = HashMap::new();
+        for entry in archive.entries().ok()? {
+            let mut entry = entry.ok()?;
+            let name = entry.path().ok()?.to_string_lossy().to_string();
+            let hash = if hash { sha1_reader(&mut entry) } else { None };
+            entries.insert(name, hash);
+        }
+        Some(entries)
+    }
+
+    fn mapping_file(path: &StrictPath) -> StrictPath {
+        path.joined("mapping.yaml")
+    }
+
+    fn find_irrelevant_backup_files(&self, backup: &str, relevant_files: &[StrictPath]) -> Vec<StrictPath> {
+        #[allow(clippy::needless_collect)]
+        let relevant_files: Vec<_> = relevant_files.iter().filter_map(|x| x.interpret().ok()).collect();
+        let mut irrelevant_files = vec![];
+
+        let Ok(walk_path) = self.path.joined(backup).interpret() else {
+            return vec![];
+        };
+
+        for format in registry::Format::ALL {
+            let Ok(path) = self.path.joined(format.filename()).interpreted() else {
+                continue;
+            };
+            if !relevant_files.contains(&path.raw()) && path.is_file() {
+                irrelevant_files.push(path);
+            }
+        }
+
+        for drive_dir in walkdir::WalkDir::new(walk_path)
+            .max_depth(1)
+            .follow_links(false)
+            .into_iter()
+            .filter_map(crate::scan::filter_map_walkdir)
+            .filter(|x| x.file_name().to_string_lossy().starts_with("drive-"))
+        {
+            for file in walkdir::WalkDir::new(drive_dir.path())
+                .max_depth(100)
+                .follow_links(false)
+                .into_iter()
+                .filter_map(crate::scan::filter_map_walkdir)
+                .filter(|x| x.file_type().is_file())
+            {
+                let backup_file = StrictPath::new(file.path().display().to_string());
+                let Ok(backup_path) = backup_file.interpret() else {
+                    continue;
+                };
+                if !relevant_files.contains(&backup_path) {
+                    irrelevant_files.push(backup_file);
+                }
+            }
+        }
+
+        irrelevant_files
+    }
+
+    /// Find files in the game's folder that none of its backups refer to,
+    /// like leftovers from an interrupted backup or conversion.
+    pub fn find_orphans(&self) -> Vec<OrphanedItem> {
+        let Ok(walk_path) = self.path.interpret() else {
+            return vec![];
+        };
+
+        // Backups from before multi-backup support don't list their files until they're migrated.
+        let mut layout = self.clone();
+        layout.migrate_legacy_backup(false);
+
+        let mut referenced = HashSet::new();
+        referenced.extend(Self::mapping_file(&self.path).interpret().ok());
+        for full in &layout.mapping.backups {
+            layout.add_referenced_files(&full.name, full.format(), full.files.keys(), &mut referenced);
+            for diff in &full.children {
+                let files = diff.files.iter().filter(|(_, v)| v.is_some()).map(|(k, _)| k);
+                layout.add_referenced_files(&diff.name, diff.format(), files, &mut referenced);
+            }
+        }
+
+        let mut orphans = vec![];
+        for file in walkdir::WalkDir::new(walk_path)
+            .follow_links(false)
+            .into_iter()
+            .filter_map(crate::scan::filter_map_walkdir)
+            .filter(|x| x.file_type().is_file())
+        {
+            let path = StrictPath::from(&file);
+            let Ok(interpreted) = path.interpret() else {
+                continue;
+            };
+            if referenced.contains(&interpreted) {
+                continue;
+            }
+
+            let kind = match format_from_backup_name(&file.file_name().to_string_lossy()) {
+                BackupFormat::Zip | BackupFormat::TarZstd => OrphanKind::Archive,
+                BackupFormat::Simple | BackupFormat::Dedup => OrphanKind::File,
+            };
+            orphans.push(OrphanedItem {
+                size: path.size(),
+                path,
+                kind,
+            });
+        }
+
+        orphans
+    }
+
+    fn add_referenced_files<'a>(
+        &self,
+        backup: &str,
+        format: BackupFormat,
+        files: impl Iterator<Item = &'a String>,
+        referenced: &mut HashSet<String>,
+    ) {
+        match format {
+            BackupFormat::Simple => {
+                for file in files {
+                    let stored =
+                        self.mapping
+                            .game_file_immutable(&self.path, &StrictPath::new(file.to_string()), backup);
+                    referenced.extend(stored.interpret().ok());
+                }
+                // Older backups may have registry data without a hash, so we keep both formats regardless.
+                for format in registry::Format::ALL {
+                    referenced.extend(self.path.joined(backup).joined(format.filename()).interpret().ok());
+                }
+            }
+            BackupFormat::Zip | BackupFormat::TarZstd => {
+                referenced.extend(self.path.joined(backup).interpret().ok());
+            }
+            BackupFormat::Dedup => {}
+        }
+    }
+
+    pub fn remove_irrelevant_backup_files(&self, backup: &str, relevant_files: &[StrictPath]) {
+        // TODO: Remove empty directories as well.
+        log::trace!(
+            "[{}] looking for irrelevant backup files in {}",
+            self.mapping.name,
+            backup
+        );
+        for file in self.find_irrelevant_backup_files(backup, relevant_files) {
+            log::debug!("[{}] removing irrelevant backup file: {:?}", self.mapping.name, &file);
+            let _ = file.remove();
+        }
+        log::trace!("[{}] done removing irrelevant backup files", self.mapping.name);
+    }
+
+    pub fn set_backup_comment(&mut self, backup_name: &str, comment: &str) {
+        let comment = if comment.is_empty() {
+            None
+        } else {
+            Some(comment.to_string())
+        };
+
+        'outer: for backup in &mut self.mapping.backups {
+            if backup.name == backup_name {
+                backup.comment = comment;
+                break 'outer;
+            }
+            for child in &mut backup.children {
+                if child.name == backup_name {
+                    child.comment = comment;
+                    break 'outer;
+                }
+            }
+        }
+    }
+
+    pub fn set_backup_locked(&mut self, backup_name: &str, locked: bool) {
+        'outer: for backup in &mut self.mapping.backups {
+            if backup.name == backup_name {
+                backup.locked = locked;
+                break 'outer;
+            }
+            for child in &mut backup.children {
+                if child.name == backup_name {
+                    child.locked = locked;
+                    break 'outer;
+                }
+            }
+        }
+    }
+
+    /// Returns any problems with the backup and the full backup that it builds on.
+    /// A deep validation also re-hashes the stored content
+    /// to make sure that it still matches the mapping file.
+    pub fn validate(&self, backup_id: BackupId, deep: bool) -> Vec<ValidationIssue> {
+        let mut issues = vec![];
+
+        if let Some((full, diff)) = self.find_by_id(&backup_id) {
+            self.validate_full_backup(full, deep, &mut issues);
+            if let Some(diff) = diff {
+                self.validate_differential_backup(diff, deep, &mut issues);
+            }
+        }
+
+        issues
+    }
+
+    /// Returns any problems with every backup of the game,
+    /// checking each full backup only once for all of its children.
+    pub fn validate_all(&self, deep: bool) -> Vec<ValidationIssue> {
+        let mut issues = vec![];
+
+        for full in &self.mapping.backups {
+            self.validate_full_backup(full, deep, &mut issues);
+            for diff in &full.children {
+                self.validate_differential_backup(diff, deep, &mut issues);
+            }
+        }
+
+        issues
+    }
+
+    fn validate_full_backup(&self, backup: &FullBackup, deep: bool, issues: &mut Vec<ValidationIssue>) {
+        self.validate_backup(
+            &backup.name,
+            backup.format(),
+            backup.files.iter(),
+            backup.registry.hash.as_deref(),
+            deep,
+            issues,
+        );
+    }
+
+    fn validate_differential_backup(&self, backup: &DifferentialBackup, deep: bool, issues: &mut Vec<ValidationIssue>) {
+        self.validate_backup(
+            &backup.name,
+            backup.format(),
+            // Omitted files have no data, so they're skipped.
+            backup
+                .files
+                .iter()
+                .filter_map(|(file, data)| data.as_ref().map(|data| (file, data))),
+            backup.registry.as_ref().and_then(|x| x.hash.as_deref()),
+            deep,
+            issues,
+        );
+    }
+
+    fn validate_backup<'a>(
+        &self,
+        backup: &str,
+        format: BackupFormat,
+        files: impl Iterator<Item = (&'a String, &'a IndividualMappingFile)>,
+        registry_hash: Option<&str>,
+        deep: bool,
+        issues: &mut Vec<ValidationIssue>,
+    ) {
+        match format {
+            BackupFormat::Simple => {
+                // The content only needs to be decrypted if we're going to hash it.
+                let cipher = match self.encryption_for(&self.path.joined(backup)) {
+                    Some(encryption) if deep => match Cipher::unlock(self.passphrase.as_ref(), encryption) {
+                        Ok(cipher) => Some(cipher),
+                        Err(_) => {
+                            issues.push(ValidationIssue::unreadable_backup(backup));
+                            return;
+                        }
+                    },
+                    _ => None,
+                };
+
+                for (file, data) in files {
+                    let original_path = StrictPath::new(file.to_string());
+                    let stored = self.mapping.game_file_immutable(&self.path, &original_path, backup);
+                    if !stored.is_file() {
+                        issues.push(ValidationIssue::missing_file(backup, file));
+                        continue;
+                    }
+
+                    if deep {
+                        let actual = match &cipher {
+                            None => stored.try_sha1().ok(),
+                            Some(cipher) => stored
+                                .read_bytes()
+                                .ok()
+                                .and_then(|content| cipher.decrypt(&content).ok())
+                                .and_then(|content| sha1_reader(content.as_slice())),
+                        };
+                        issues.extend(ValidationIssue::compare_file(backup, file, &data.hash, actual));
+                    }
+                }
+            }
+            BackupFormat::Zip => {
+                let Some(mut archive) = self.open_zip_in(backup) else {
+                    issues.push(ValidationIssue::unreadable_backup(backup));
+                    return;
+                };
+
+                for (file, data) in files {
+                    let original_path = StrictPath::new(file.to_string());
+                    let stored = self.mapping.game_file_for_zip_immutable(&original_path);
+                    match archive.by_name(&stored) {
+                        Ok(entry) => {
+                            if deep {
+                                issues.extend(ValidationIssue::compare_file(
+                                    backup,
+                                    file,
+                                    &data.hash,
+                                    sha1_reader(entry),
+                                ));
+                            }
+                        }
+                        Err(_) => {
+                            issues.push(ValidationIssue::missing_file(backup, file));
+                        }
+                    }
+                }
+            }
+            BackupFormat::TarZstd => {
+                let Some(entries) = self.tar_entries(backup, deep) else {
+                    issues.push(ValidationIssue::unreadable_backup(backup));
+                    return;
+                };
+
+                for (file, data) in files {
+                    let original_path = StrictPath::new(file.to_string());
+                    let stored = self.mapping.game_file_for_zip_immutable(&original_path);
+                    match entries.get(&stored) {
+                        Some(actual) => {
+                            if deep {
+                                issues.extend(ValidationIssue::compare_file(backup, file, &data.hash, actual.clone()));
+                            }
+                        }
+                        None => {
+                            issues.push(ValidationIssue::missing_file(backup, file));
+                        }
+                    }
+                }
+            }
+            BackupFormat::Dedup => {
+                for (file, data) in files {
+                    let stored = self.blob_file(&data.hash);
+                    if !stored.is_file() || stored.size() != data.size {
+                        issues.push(ValidationIssue::missing_file(backup, file));
+                        continue;
+                    }
+
+                    if deep {
+                        issues.extend(ValidationIssue::compare_file(
+                            backup,
+                            file,
+                            &data.hash,
+                            stored.try_sha1().ok(),
+                        ));
+                    }
+                }
+            }
+        }
+
+        if deep {
+            if let Some(expected) = registry_hash {
+                let actual = self
+                    .registry_content_in(backup, &format)
+                    .ok()
+                    .flatten()
+                    .and_then(|hives| hives.sha1(registry::Format::Reg));
+                if actual.as_deref() != Some(expected) {
+                    issues.push(ValidationIssue::ChangedRegistry {
+                        backup: backup.to_string(),
+                        expected: expected.to_string(),
+                        actual,
+                    });
+                }
+            }
+        }
+    }
+}
+
+/// A problem found while validating a backup.
+#[derive(Clone, Debug, Eq, PartialEq, serde::Serialize, schemars::JsonSchema)]
+#[serde(tag = "kind", rename_all = "camelCase")]
+pub enum ValidationIssue {
+    /// The backup could not be opened or decrypted.
+    UnreadableBackup { backup: String },
+    /// A file listed in the mapping could not be found.
+    MissingFile { backup: String, file: String },
+    /// A file could be found, but not read or decrypted.
+    UnreadableFile { backup: String, file: String },
+    /// A file's content does not match the hash recorded when it was backed up.
+    ChangedFile {
+        backup: String,
+        file: String,
+        expected: String,
+        actual: String,
+    },
+    /// The registry content does not match the hash recorded when it was backed up.
+    /// If it could not be read at all, then `actual` is unset.
+    ChangedRegistry {
+        backup: String,
+        expected: String,
+        actual: Option<String>,
+    },
+}
+
+impl ValidationIssue {
+    fn unreadable_backup(backup: &str) -> Self {
+        Self::UnreadableBackup {
+            backup: backup.to_string(),
+        }
+    }
+
+    fn missing_file(backup: &str, file: &str) -> Self {
+        Self::MissingFile {
+            backup: backup.to_string(),
+            file: file.to_string(),
+        }
+    }
+
+    fn compare_file(backup: &str, file: &str, expected: &str, actual: Option<String>) -> Option<Self> {
+        match actual {
+            None => Some(Self::UnreadableFile {
+                backup: backup.to_string(),
+                file: file.to_string(),
+            }),
+            Some(actual) if actual != expected => Some(Self::ChangedFile {
+                backup: backup.to_string(),
+                file: file.to_string(),
+                expected: expected.to_string(),
+                actual,
+            }),
+            Some(_) => None,
+        }
+    }
+
+    pub fn backup(&self) -> &str {
+        match self {
+            Self::UnreadableBackup { backup }
+            | Self::MissingFile { backup, .. }
+            | Self::UnreadableFile { backup, .. }
+            | Self::ChangedFile { backup, .. }
+            | Self::ChangedRegistry { backup, .. } => backup,
+        }
+    }
+}
+
+/// Selects files by their paths, such as for restoring part of a backup.
+/// A glob without a slash only needs to match the file name.
+#[derive(Clone, Debug, Default)]
+pub struct FileSelection {
+    globs: Option<globset::GlobSet>,
+}
+
+impl FileSelection {
+    pub fn new(globs: &[String]) -> Result<Self, Error> {
+        if globs.is_empty() {
+            return Ok(Self::default());
+        }
+
+        let mut builder = globset::GlobSetBuilder::new();
+        for glob in globs {
+            let normalized = if glob.contains(['/', '\\']) {
+                glob.replace('\\', "/")
+            } else {
+                format!("**/{glob}")
+            };
+            builder.add(Self::build(&normalized).ok_or_else(|| Error::CliInvalidGlob { glob: glob.clone() })?);
+        }
+
+        Ok(Self {
+            globs: Some(
+                builder
+                    .build()
+                    .map_err(|_| Error::CliInvalidGlob { glob: globs.join(", ") })?,
+            ),
+        })
+    }
+
+    /// Select a file or folder, including any of its children.
+    pub fn path(path: &StrictPath) -> Self {
+        let escaped = globset::escape(&path.globbable());
+
+        let mut builder = globset::GlobSetBuilder::new();
+        for variant in [escaped.clone(), format!("{escaped}/**")] {
+            if let Some(glob) = Self::build(&variant) {
+                builder.add(glob);
+            }
+        }
+
+        Self {
+            globs: builder.build().ok(),
+        }
+    }
+
+    fn build(glob: &str) -> Option<globset::Glob> {
+        globset::GlobBuilder::new(glob)
+            .literal_separator(true)
+            .backslash_escape(false)
+            .case_insensitive(true)
+            .build()
+            .ok()
+    }
+
+    pub fn is_all(&self) -> bool {
+        self.globs.is_none()
+    }
+
+    pub fn matches(&self, path: &StrictPath) -> bool {
+        self.globs.as_ref().map(|x| x.is_match(path.render())).unwrap_or(true)
+    }
+}
+
+fn compare_file_versions(before: Option<&IndividualMappingFile>, after: Option<&IndividualMappingFile>) -> ScanChange {
+    match (before, after) {
+        (None, None) => ScanChange::Same,
+        (None, Some(_)) => ScanChange::New,
+        (Some(_), None) => ScanChange::Removed,
+        (Some(before), Some(after)) if before.hash == after.hash => ScanChange::Same,
+        (Some(_), Some(_)) => ScanChange::Different,
+    }
+}
+
+/// Identifies one backup in a comparison or file history.
+#[derive(Clone, Debug, Default, Eq, PartialEq, serde::Serialize, serde::Deserialize, schemars::JsonSchema)]
+#[serde(default, rename_all = "camelCase")]
+pub struct BackupReference {
+    pub name: String,
+    pub when: chrono::DateTime<chrono::Utc>,
+}
+
+impl BackupReference {
+    fn new(full: &FullBackup, diff: Option<&DifferentialBackup>) -> Self {
+        match diff {
+            None => Self {
+                name: full.name.clone(),
+                when: full.when,
+            },
+            Some(diff) => Self {
+                name: diff.name.clone(),
+                when: diff.when,
+            },
+        }
+    }
+}
+
+/// The files that differ between two backups of a game.
+#[derive(Clone, Debug, Default, Eq, PartialEq, serde::Serialize, serde::Deserialize, schemars::JsonSchema)]
+#[serde(default, rename_all = "camelCase")]
+pub struct BackupComparison {
+    /// The older backup, if there is one.
+    pub from: Option<BackupReference>,
+    /// The newer backup.
+    pub to: BackupReference,
+    /// Each key is a file path.
+    /// Files that are the same in both backups are omitted.
+    pub files: BTreeMap<String, FileComparison>,
+}
+
+#[derive(Clone, Debug, Default, Eq, PartialEq, serde::Serialize, serde::Deserialize, schemars::JsonSchema)]
+#[serde(default, rename_all = "camelCase")]
+pub struct FileComparison {
+    /// How the file in the newer backup compares to the older one.
+    pub change: ScanChange,
+    /// The file in the older backup, if it was present.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub from: Option<IndividualMappingFile>,
+    /// The file in the newer backup, if it is present.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub to: Option<IndividualMappingFile>,
+}
+
+/// A backup where a file was added, changed, or removed.
+#[derive(Clone, Debug, Default, Eq, PartialEq, serde::Serialize, serde::Deserialize, schemars::JsonSchema)]
+#[serde(default, rename_all = "camelCase")]
+pub struct FileHistoryEntry {
+    pub backup: String,
+    pub when: chrono::DateTime<chrono::Utc>,
+    /// How the file compares to the previous backup.
+    pub change: ScanChange,
+    /// Unset when the file was removed.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub hash: Option<String>,
+    /// Unset when the file was removed.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub size: Option<u64>,
+}
+
+/// Space used by backups, based on the file sizes recorded in each `mapping.yaml`.
+/// For deduplicated backups, this is the size of the files before deduplication.
+#[derive(Clone, Debug, Default, Eq, PartialEq, serde::Serialize, schemars::JsonSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct BackupStats {
+    /// Total size of the files stored in all backups.
+    pub bytes: u64,
+    /// How many full backups there are.
+    pub full_backups: usize,
+    /// Size of the files stored in full backups.
+    pub full_bytes: u64,
+    /// How many differential backups there are.
+    pub differential_backups: usize,
+    /// Size of the files stored in differential backups.
+    pub differential_bytes: u64,
+    /// How much space could be saved by storing each unique file only once,
+    /// as the deduplicated backup format does.
+    pub duplicate_bytes: u64,
+    /// When the oldest backup was made.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub oldest: Option<chrono::DateTime<chrono::Utc>>,
+    /// When the newest backup was made.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub newest: Option<chrono::DateTime<chrono::Utc>>,
+    /// Size of the files stored by the backups made in each month (`YYYY-MM`, in UTC).
+    pub growth: BTreeMap<String, u64>,
+}
+
+impl BackupStats {
+    /// `seen` holds the content hashes found so far,
+    /// so that it can be shared to find duplicates across games.
+    fn add_mapping(&mut self, mapping: &IndividualMapping, seen: &mut HashSet<String>) {
+        for full in &mapping.backups {
+            let bytes = full.size();
+            self.full_backups += 1;
+            self.full_bytes += bytes;
+            self.add_backup(full.when, bytes, full.format(), full.files.values(), seen);
+
+            for diff in &full.children {
+                let bytes = diff.size();
+                self.differential_backups += 1;
+                self.differential_bytes += bytes;
+                self.add_backup(diff.when, bytes, diff.format(), diff.files.values().flatten(), seen);
+            }
+        }
+    }
+
+    fn add_backup<'a>(
+        &mut self,
+        when: chrono::DateTime<chrono::Utc>,
+        bytes: u64,
+        format: BackupFormat,
+        files: impl Iterator<Item = &'a IndividualMappingFile>,
+        seen: &mut HashSet<String>,
+    ) {
+        self.bytes += bytes;
+        self.oldest = Some(self.oldest.map_or(when, |x| x.min(when)));
+        self.newest = Some(self.newest.map_or(when, |x| x.max(when)));
+        *self.growth.entry(when.format("%Y-%m").to_string()).or_default() += bytes;
+
+        for file in files {
+            // Deduplicated backups already store each unique file once.
+            if !seen.insert(file.hash.clone()) && format != BackupFormat::Dedup {
+                self.duplicate_bytes += file.size;
+            }
+        }
+    }
+}
+
+/// Backup statistics for each game and across all of them.
+#[derive(Clone, Debug, Default, Eq, PartialEq)]
+pub struct BackupLayoutStats {
+    pub games: BTreeMap<String, BackupStats>,
+    pub overall: BackupStats,
+}
+
+/// What kind of unused item was found in the backup folder.
+#[derive(Clone, Copy, Debug, Eq, PartialEq, Ord, PartialOrd, serde::Serialize, schemars::JsonSchema)]
+#[serde(rename_all = "camelCase")]
+pub enum OrphanKind {
+    /// A file in a game's folder that no backup refers to.
+    File,
+    /// A zip or tar.zst archive that no backup refers to.
+    Archive,
+    /// A folder without a `mapping.yaml`.
+    GameFolder,
+    /// A folder whose `mapping.yaml` can't be read.
+    /// This may still contain backups if the mapping was only damaged
+    /// or was written by a newer version of Ludusavi.
+    InvalidMapping,
+    /// A deduplicated blob that no backup refers to.
+    Blob,
+}
+
+/// A file or folder in the backup directory that can be removed without affecting any backup.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct OrphanedItem {
+    pub path: StrictPath,
+    pub kind: OrphanKind,
+    pub size: u64,
+}
+
+#[derive(Clone, Debug, Default, Eq, PartialEq)]
+pub enum BackupKind {
+    #[default]
+    Full,
+    Differential,
+}
+
+/// Space used by backup content.
+/// Deduplicated blobs are tracked separately, since they may be shared between backups and games.
+#[derive(Clone, Debug, Default, Eq, PartialEq)]
+pub struct StorageUsage {
+    /// Files stored in each backup's own folder or archive.
+    pub plain: u64,
+    /// Size of each blob, by hash.
+    pub blobs: HashMap<String, u64>,
+}
+
+impl StorageUsage {
+    fn add<'a>(&mut self, format: BackupFormat, files: impl Iterator<Item = &'a IndividualMappingFile>) {
+        for file in files {
+            match format {
+                BackupFormat::Dedup => {
+                    self.blobs.insert(file.hash.clone(), file.size);
+                }
+                BackupFormat::Simple | BackupFormat::Zip | BackupFormat::TarZstd => {
+                    self.plain += file.size;
+                }
+            }
+        }
+    }
+
+    /// Combine with another usage, counting any shared blobs once.
+    pub fn merge(&mut self, other: &Self) {
+        self.plain += other.plain;
+        self.blobs
+            .extend(other.blobs.iter().map(|(hash, size)| (hash.clone(), *size)));
+    }
+
+    pub fn total(&self) -> u64 {
+        self.plain + self.blobs.values().sum::<u64>()
+    }
+}
+
+#[derive(Clone, Debug, Default)]
+pub struct BackupLayout {
+    pub base: StrictPath,
+    games: HashMap<String, StrictPath>,
+    games_lowercase: HashMap<String, StrictPath>,
+    passphrase: Option<Passphrase>,
+    /// Space used by each game's backups, for enforcing the total quota.
+    /// This is shared so that games backed up in parallel see each other's usage.
+    usage: Arc<Mutex<Option<HashMap<String, StorageUsage>>>>,
+}
+
+impl BackupLayout {
+    pub fn new(base: StrictPath) -> Self {
+        let games = Self::load(&base);
+        let games_lowercase = games.iter().map(|(k, v)| (k.to_lowercase(), v.clone())).collect();
+        Self {
+            base,
+            games,
+            games_lowercase,
+            passphrase: None,
+            usage: Default::default(),
+        }
+    }
+
+    /// Game layouts from this point on will use this passphrase for encrypted backups.
+    pub fn with_passphrase(mut self, passphrase: Option<Passphrase>) -> Self {
+        self.passphrase = passphrase;
+        self
+    }
+
+    pub fn load(base: &StrictPath) -> HashMap<String, StrictPath> {
+        let mut overall = HashMap::new();
+
+        let Ok(base_interpreted) = base.interpret() else {
+            return HashMap::new();
+        };
+
+        for game_dir in walkdir::WalkDir::new(base_interpreted)
+            .max_depth(1)
+            .follow_links(false)
+            .into_iter()
+            .skip(1) // the base path itself
+            .filter_map(crate::scan::filter_map_walkdir)
+            .filter(|x| x.file_type().is_dir())
+        {
+            let game_dir = StrictPath::from(&game_dir);
+            let mapping_file = game_dir.joined("mapping.yaml");
+            if mapping_file.is_file() {
+                match IndividualMapping::load(&mapping_file) {
+                    Ok(mapping) => {
+                        overall.insert(mapping.name.clone(), game_dir);
+                    }
+                    Err(e) => {
+                        log::warn!("Ignoring unloadable mapping: {:?} | {:?}", &mapping_file, e);
+                    }
+                }
+            }
+        }
+
+        overall
+    }
+
+    pub fn game_layout(&self, name: &str) -> GameLayout {
+        let path = self.game_folder(name);
+
+        match GameLayout::load(path.clone()) {
+            Ok(mut x) => {
+                if x.mapping.name != name {
+                    // This can happen if the game name changed in the manifest,
+                    // but differs only by capitalization when we're on a case-insensitive OS.
+                    // If we don't adjust it, it'll always show up as a new game.
+                    log::info!("Updating renamed game: {} -> {}", &x.mapping.name, name);
+                    x.mapping.name = name.to_string();
+                }
+                x.with_passphrase(self.passphrase.clone())
+            }
+            Err(_) => GameLayout {
+                path,
+                mapping: IndividualMapping::new(name.to_string()),
+                passphrase: self.passphrase.clone(),
+            },
+        }
+    }
+
+    pub fn try_game_layout(&self, name: &str) -> Option<GameLayout> {
+        let path = self.game_folder(name);
+
+        GameLayout::load(path).ok().map(|mut x| {
+            if x.mapping.name != name {
+                // This can happen if the game name changed in the manifest,
+                // but differs only by capitalization when we're on a case-insensitive OS.
+                // If we don't adjust it, it'll always show up as a new game.
+                log::info!("Updating renamed game: {} -> {}", &x.mapping.name, name);
+                x.mapping.name = name.to_string();
+            }
+            x.with_passphrase(self.passphrase.clone())
+        })
+    }
+
+    /// Find the snapshots made before the most recent restore, which may have covered several games.
+    /// Restoring these will undo it.
+    pub fn last_restore_snapshots(&self) -> BTreeMap<String, BackupId> {
+        let snapshots: Vec<_> = self
+            .games
+            .keys()
+            .filter_map(|name| {
+                let (id, when) = self.try_game_layout(name)?.latest_pre_restore_snapshot()?;
+                Some((name.clone(), id, when))
+            })
+            .collect();
+
+        let Some(last) = snapshots.iter().map(|(_, _, when)| *when).max() else {
+            return BTreeMap::new();
+        };
+
+        snapshots
+            .into_iter()
+            .filter(|(_, _, when)| *when == last)
+            .map(|(name, id, _)| (name, id))
+            .collect()
+    }
+
+    /// Back up a game while respecting the total quota across all games, if any.
+    pub fn back_up_game(
+        &self,
+        name: &str,
+        scan: &ScanInfo,
+        now: &chrono::DateTime<chrono::Utc>,
+        format: &BackupFormats,
+        retention: Retention,
+    ) -> Option<BackupInfo> {
+        let mut layout = self.game_layout(name);
+
+        let Some(total_quota) = retention.total_quota else {
+            return layout.back_up(scan, now, format, retention, None);
+        };
+
+        // Games may be backed up in parallel, so we reserve the most that this backup could add
+        // before releasing the lock, and then we record the actual usage once it's done.
+        // A new backup can't be bigger than the files that it would include.
+        let space = {
+            let mut usage = self.usage.lock().unwrap_or_else(|e| e.into_inner());
+            let usage = usage.get_or_insert_with(|| {
+                self.games
+                    .iter()
+                    .filter_map(|(name, path)| {
+                        GameLayout::load(path.clone())
+                            .ok()
+                            .map(|layout| (name.clone(), layout.mapping.usage()))
+                    })
+                    .collect()
+            });
+
+            let mut others = StorageUsage::default();
+            for (_, other) in usage.iter().filter(|(k, _)| *k != name) {
+                others.merge(other);
+            }
+
+            let mut reserved = layout.mapping.usage();
+            // Blobs that another game already stores don't take up any extra space for this one.
+            let shared: u64 = reserved
+                .blobs
+                .iter()
+                .filter(|(hash, _)| others.blobs.contains_key(*hash))
+                .map(|(_, size)| size)
+                .sum();
+
+            reserved.plain += scan
+                .found_files
+                .values()
+                .filter(|x| !x.ignored)
+                .map(|x| x.size)
+                .sum::<u64>();
+            usage.insert(name.to_string(), reserved);
+
+            (total_quota + shared).saturating_sub(others.total())
+        };
+
+        let info = layout.back_up(scan, now, format, retention, Some(space));
+
+        let mut usage = self.usage.lock().unwrap_or_else(|e| e.into_inner());
+        if let Some(usage) = usage.as_mut() {
+            usage.insert(name.to_string(), layout.mapping.usage());
+        }
+
+        info
+    }
+
+    /// Whether any game has an encrypted backup, in which case we'll need a passphrase to restore it.
+    pub fn has_encrypted_backups(&self) -> bool {
+        self.games.values().any(|path| {
+            GameLayout::load(path.clone()).is_ok_and(|layout| {
+                layout
+                    .mapping
+                    .backups
+                    .iter()
+                    .any(|full| full.encryption.is_some() || full.children.iter().any(|diff| diff.encryption.is_some()))
+            })
+        })
+    }
+
+    fn contains_game(&self, name: &str) -> bool {
+        self.games.contains_key(name)
+            || (!Os::HOST.is_case_sensitive() && self.games_lowercase.contains_key(&name.to_lowercase()))
+    }
+
+    pub fn latest_backup(
+        &self,
+        name: &str,
+        scan_kind: ScanKind,
+        redirects: &[RedirectConfig],
+        reverse_redirects_on_restore: bool,
+        toggled_paths: &ToggledPaths,
+    ) -> Option<LatestBackup> {
+        if self.contains_game(name) {
+            let game_layout = self.game_layout(name);
+            let scan = game_layout.latest_backup(scan_kind, redirects, reverse_redirects_on_restore, toggled_paths);
+            scan.map(|scan| LatestBackup {
+                scan,
+                registry_content: if cfg!(target_os = "windows") {
+                    game_layout.registry_content(&BackupId::Latest)
+                } else {
+                    None
+                },
+            })
+        } else {
+            None
+        }
+    }
+
+    fn generate_total_rename(original_name: &str) -> String {
+        format!("ludusavi-renamed-{}", encode_base64_for_folder(original_name))
+    }
+
+    pub fn game_folder(&self, game_name: &str) -> StrictPath {
+        match self.games.get::<str>(game_name) {
+            Some(game) => game.clone(),
+            None => self.new_game_folder(game_name),
+        }
+    }
+
+    fn new_game_folder(&self, game_name: &str) -> StrictPath {
+        let mut safe_name = escape_folder_name(game_name);
+
+        if safe_name.matches(SAFE).count() == safe_name.len() {
+            // It's unreadable now, so do a total rename.
+            safe_name = Self::generate_total_rename(game_name);
+        }
+
+        self.base.joined(&safe_name)
+    }
+
+    pub fn restorable_games(&self) -> Vec<String> {
+        self.games.keys().cloned().collect()
+    }
+
+    pub fn restorable_game_set(&self) -> BTreeSet<String> {
+        self.games.keys().cloned().collect()
+    }
+
+    /// Folders to include when synchronizing specific games with the cloud.
+    /// The blob store is always included because deduplicated backups depend on it.
+    pub fn cloud_folders<'a>(&self, games: impl IntoIterator<Item = &'a String>) -> Vec<String> {
+        let mut folders: Vec<_> = games.into_iter().filter_map(|x| self.game_folder(x).leaf()).collect();
+        folders.push(BLOB_FOLDER.to_string());
+        folders
+    }
+
+    /// Count how many backups refer to each blob in the shared store.
+    /// This rereads every mapping file so that it reflects any backups made since `BackupLayout::new`.
+    /// Returns `None` if any mapping could not be read, since we can't safely tell what's unreferenced.
+    pub fn blob_references(&self) -> Option<HashMap<String, usize>> {
+        let mut references = HashMap::<String, usize>::new();
+
+        let base = self.base.interpret().ok()?;
+        for game_dir in walkdir::WalkDir::new(base)
+            .max_depth(1)
+            .follow_links(false)
+            .into_iter()
+            .skip(1) // the base path itself
+            .filter_map(crate::scan::filter_map_walkdir)
+            .filter(|x| x.file_type().is_dir())
+        {
+            let mapping_file = StrictPath::from(&game_dir).joined("mapping.yaml");
+            if !mapping_file.is_file() {
+                continue;
+            }
+            let mapping = IndividualMapping::load(&mapping_file).ok()?;
+
+            for full in &mapping.backups {
+                if full.format() == BackupFormat::Dedup {
+                    for file in full.files.values() {
+                        *references.entry(file.hash.clone()).or_default() += 1;
+                    }
+                    if let Some(hash) = &full.registry.hash {
+                        *references.entry(hash.clone()).or_default() += 1;
+                    }
+                }
+
+                for diff in full.children.iter().filter(|x| x.format() == BackupFormat::Dedup) {
+                    for file in diff.files.values().flatten() {
+                        *references.entry(file.hash.clone()).or_default() += 1;
+                    }
+                    if let Some(hash) = diff.registry.as_ref().and_then(|x| x.hash.as_ref()) {
+                        *references.entry(hash.clone()).or_default() += 1;
+                    }
+                }
+            }
+        }
+
+        Some(references)
+    }
+
+    /// Remove blobs that are no longer referenced by any backup.
+    /// Games may be backed up in parallel, so this should only run once all of them are done.
+    pub fn prune_blobs(&self) {
+        let store = self.base.joined(BLOB_FOLDER);
+        let Ok(store_interpreted) = store.interpret() else {
+            return;
+        };
+        if !store.is_dir() {
+            return;
+        }
+
+        let Some(references) = self.blob_references() else {
+            log::warn!("Skipping blob pruning because some mappings could not be loaded");
+            return;
+        };
+
+        for blob in walkdir::WalkDir::new(store_interpreted)
+            .max_depth(2)
+            .follow_links(false)
+            .into_iter()
+            .filter_map(crate::scan::filter_map_walkdir)
+            .filter(|x| x.file_type().is_file())
+        {
+            let hash = blob.file_name().to_string_lossy();
+            if !references.contains_key(hash.as_ref() as &str) {
+                let blob = StrictPath::from(&blob);
+                log::debug!("Removing unreferenced blob: {:?}", &blob);
+                let _ = blob.remove();
+            }
+        }
+    }
+
+    /// Find the renamed games (old name -> new name) that still have backups under their old name.
+    /// Games that already have backups under the new name are skipped,
+    /// since we can't merge two sets of backups.
+    pub fn pending_renames(&self, renames: &BTreeMap<String, String>) -> BTreeMap<String, String> {
+        renames
+            .iter()
+            .filter(|(old, new)| self.games.contains_key(*old) && !self.games.contains_key(*new))
+            .map(|(old, new)| (old.clone(), new.clone()))
+            .collect()
+    }
+
+    /// Move a game's backups to the folder for its new name and update its `mapping.yaml`.
+    pub fn migrate_renamed_game(&self, old: &str, new: &str) -> Result<(), Error> {
+        let Some(mut layout) = self.try_game_layout(old) else {
+            return Err(Error::RestorationSourceInvalid {
+                path: self.game_folder(old),
+            });
+        };
+
+        let target = self.new_game_folder(new);
+        if !target.equivalent(&layout.path) {
+            // On case-insensitive systems, a change in capitalization will look like the same folder.
+            let same_folder = !Os::HOST.is_case_sensitive()
+                && target.interpret().ok().map(|x| x.to_lowercase())
+                    == layout.path.interpret().ok().map(|x| x.to_lowercase());
+            if target.exists() && !same_folder {
+                return Err(Error::CannotPrepareBackupTarget { path: target });
+            }
+
+            log::info!("Moving backups for renamed game: {} -> {}", old, new);
+            if let Err(e) = layout.path.move_to(&target) {
+                log::error!("Unable to move backups: {:?} -> {:?} | {e:?}", &layout.path, &target);
+                return Err(Error::CannotPrepareBackupTarget { path: target });
+            }
+            layout.path = target;
+        }
+
+        layout.mapping.name = new.to_string();
+        layout.save();
+        Ok(())
+    }
+
+    /// Gather statistics for these games' backups.
+    pub fn stats<'a>(&self, games: impl IntoIterator<Item = &'a String>) -> BackupLayoutStats {
+        let mut stats = BackupLayoutStats::default();
+        let mut seen = HashSet::new();
+
+        for name in games {
+            let Some(layout) = self.try_game_layout(name) else {
+                continue;
+            };
+
+            let mut game = BackupStats::default();
+            game.add_mapping(&layout.mapping, &mut HashSet::new());
+            stats.overall.add_mapping(&layout.mapping, &mut seen);
+            stats.games.insert(name.clone(), game);
+        }
+
+        stats
+    }
+
+    /// Find everything in the backup folder that no backup refers to:
+    /// stray files and archives in game folders, folders without a mapping,
+    /// and unreferenced blobs.
+    /// Folders with a mapping that can't be read are reported separately.
+    /// Unlike `BackupLayout::new`, this rereads every mapping file.
+    pub fn find_orphans(&self) -> Vec<OrphanedItem> {
+        let mut orphans = vec![];
+
+        let Ok(base) = self.base.interpret() else {
+            return orphans;
+        };
+
+        for game_dir in walkdir::WalkDir::new(base)
+            .max_depth(1)
+            .follow_links(false)
+            .into_iter()
+            .skip(1) // the base path itself
+            .filter_map(crate::scan::filter_map_walkdir)
+            .filter(|x| x.file_type().is_dir())
+        {
+            // Game folders never start with a dot, so these belong to something else.
+            if game_dir.file_name().to_string_lossy().starts_with('.') {
+                continue;
+            }
+
+            let game_dir = StrictPath::from(&game_dir);
+            match GameLayout::load(game_dir.clone()) {
+                Ok(layout) => orphans.extend(layout.find_orphans()),
+                Err(_) => {
+                    let kind = if GameLayout::mapping_file(&game_dir).exists() {
+                        log::warn!("Unable to read mapping in backup folder: {:?}", &game_dir);
+                        OrphanKind::InvalidMapping
+                    } else {
+                        OrphanKind::GameFolder
+                    };
+                    let size = Self::folder_size(&game_dir);
+                    orphans.push(OrphanedItem {
+                        path: game_dir,
+                        kind,
+                        size,
+                    });
+                }
+            }
+        }
+
+        let store = self.base.joined(BLOB_FOLDER);
+        if let (Ok(store_interpreted), true) = (store.interpret(), store.is_dir()) {
+            match self.blob_references() {
+                Some(references) => {
+                    for blob in walkdir::WalkDir::new(store_interpreted)
+                        .max_depth(2)
+                        .follow_links(false)
+                        .into_iter()
+                        .filter_map(crate::scan::filter_map_walkdir)
+                        .filter(|x| x.file_type().is_file())
+                    {
+                        let hash = blob.file_name().to_string_lossy();
+                        if !references.contains_key(hash.as_ref() as &str) {
+                            let blob = StrictPath::from(&blob);
+                            orphans.push(OrphanedItem {
+                                size: blob.size(),
+                                path: blob,
+                                kind: OrphanKind::Blob,
+                            });
+                        }
+                    }
+                }
+                None => {
+                    log::warn!("Skipping unreferenced blobs because some mappings could not be loaded");
+                }
+            }
+        }
+
+        orphans.sort_by_key(|x| x.path.render());
+        orphans
+    }
+
+    /// Remove the given orphans, along with any folders that end up empty.
+    /// Returns the items that could not be removed.
+    pub fn remove_orphans(&self, orphans: &[OrphanedItem]) -> Vec<StrictPath> {
+        let mut failed = vec![];
+
+        let base = self.base.interpret().ok();
+
+        for orphan in orphans {
+            log::debug!("Removing orphaned {:?}: {:?}", orphan.kind, &orphan.path);
+            if let Err(e) = orphan.path.remove() {
+                log::error!("Unable to remove orphan: {:?} | {e:?}", &orphan.path);
+                failed.push(orphan.path.clone());
+                continue;
+            }
+
+            // Clean up any folders that are now empty, but leave game folders and the blob store in place.
+            let mut folder = orphan.path.parent();
+            while let Some(current) = folder {
+                let Some(parent) = current.parent() else {
+                    break;
+                };
+                if base.is_none() || parent.interpret().ok() == base {
+                    break;
+                }
+                // This only succeeds for empty folders.
+                if current.as_std_path_buf().and_then(std::fs::remove_dir).is_err() {
+                    break;
+                }
+                folder = Some(parent);
+            }
+        }
+
+        failed
+    }
+
+    fn folder_size(folder: &StrictPath) -> u64 {
+        let Ok(folder) = folder.interpret() else {
+            return 0;
+        };
+
+        walkdir::WalkDir::new(folder)
+            .follow_links(false)
+            .into_iter()
+            .filter_map(crate::scan::filter_map_walkdir)
+            .filter(|x| x.file_type().is_file())
+            .filter_map(|x| x.metadata().ok())
+            .map(|x| x.len())
+            .sum()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use velcro::{btree_map, hash_map};
+
+    use super::*;
+    use crate::testing::{drives_x, make_original_path, mapping_file_key, repo, repo_raw, s};
+
+    mod individual_mapping {
+        use pretty_assertions::assert_eq;
+
+        use super::*;
+
+        #[test]
+        fn can_generate_drive_folder_name() {
+            let mut mapping = IndividualMapping::new("foo".to_owned());
+            assert_eq!("drive-0", mapping.drive_folder_name(""));
+            assert_eq!("drive-C", mapping.drive_folder_name("C:"));
+            assert_eq!("drive-D", mapping.drive_folder_name("D:"));
+            assert_eq!("drive-____C", mapping.drive_folder_name(r#"\\?\C:"#));
+            assert_eq!("drive-__remote", mapping.drive_folder_name(r#"\\remote"#));
+        }
+    }
+
+    mod backup_layout {
+        use pretty_assertions::assert_eq;
+
+        use crate::testing::{repo_file_raw, repo_path, repo_path_raw};
+
+        use super::*;
+
+        fn layout() -> BackupLayout {
+            BackupLayout::new(StrictPath::new(format!("{}/tests/backup", repo_raw())))
+        }
+
+        fn game_layout(name: &str, path: &str) -> GameLayout {
+            GameLayout {
+                path: StrictPath::new(path.to_string()),
+                mapping: IndividualMapping::new(name.to_string()),
+                ..Default::default()
+            }
+        }
+
+        fn drives() -> BTreeMap<String, String> {
+            let (drive, _) = StrictPath::cwd().split_drive();
+            let folder = IndividualMapping::new_drive_folder_name(&drive);
+            btree_map! { folder: drive }
+        }
+
+        #[test]
+        fn can_find_existing_game_folder_with_matching_name() {
+            assert_eq!(repo_path_raw("tests/backup/game1"), layout().game_folder("game1"));
+        }
+
+        #[test]
+        fn can_find_existing_game_folder_with_rename() {
+            assert_eq!(
+                repo_path_raw("tests/backup/game3-renamed"),
                 layout().game_folder("game3")
             );
         }
 
         #[test]
-        fn can_determine_game_folder_that_does_not_exist_without_rename() {
+        fn can_determine_game_folder_that_does_not_exist_without_rename() {
+            assert_eq!(
+                repo_path("tests/backup/nonexistent"),
+                layout().game_folder("nonexistent")
+            );
+        }
+
+        #[test]
+        fn can_determine_game_folder_that_does_not_exist_with_partial_rename() {
+            assert_eq!(repo_path("tests/backup/foo_bar"), layout().game_folder("foo:bar"));
+        }
+
+        #[test]
+        fn can_determine_game_folder_that_does_not_exist_with_total_rename() {
+            assert_eq!(
+                repo_path("tests/backup/ludusavi-renamed-Kioq"),
+                layout().game_folder("***")
+            );
+        }
+
+        #[test]
+        fn can_determine_game_folder_by_escaping_dots_at_start_and_end() {
+            assert_eq!(repo_path("tests/backup/_._"), layout().game_folder("..."));
+        }
+
+        #[test]
+        fn can_find_irrelevant_backup_files() {
+            assert_eq!(
+                vec![repo_path_raw("tests/backup/game1/drive-X/file2.txt")],
+                game_layout("game1", &repo_file_raw("tests/backup/game1"))
+                    .find_irrelevant_backup_files(".", &[repo_path("tests/backup/game1/drive-X/file1.txt")])
+            );
+            assert_eq!(
+                Vec::<StrictPath>::new(),
+                game_layout("game1", &repo_file("tests/backup/game1")).find_irrelevant_backup_files(
+                    ".",
+                    &[
+                        repo_path("tests/backup/game1/drive-X/file1.txt"),
+                        repo_path("tests/backup/game1/drive-X/file2.txt"),
+                    ]
+                )
+            );
+        }
+
+        #[test]
+        fn can_find_pending_renames() {
+            let renames = btree_map! {
+                s("game1"): s("game1-new"),
+                s("game3"): s("game1"),
+                s("nonexistent"): s("other"),
+            };
+            assert_eq!(
+                btree_map! { s("game1"): s("game1-new") },
+                layout().pending_renames(&renames)
+            );
+        }
+
+        #[test]
+        fn can_gather_stats() {
+            let when = chrono::NaiveDate::from_ymd_opt(2000, 1, 2)
+                .unwrap()
+                .and_hms_opt(3, 4, 5)
+                .unwrap()
+                .and_utc();
+            let game = BackupStats {
+                bytes: 3,
+                full_backups: 1,
+                full_bytes: 3,
+                differential_backups: 0,
+                differential_bytes: 0,
+                duplicate_bytes: 0,
+                oldest: Some(when),
+                newest: Some(when),
+                growth: btree_map! { s("2000-01"): 3 },
+            };
+
+            assert_eq!(
+                BackupLayoutStats {
+                    games: btree_map! {
+                        s("game1"): game.clone(),
+                        s("game1-zipped"): game,
+                    },
+                    overall: BackupStats {
+                        bytes: 6,
+                        full_backups: 2,
+                        full_bytes: 6,
+                        differential_backups: 0,
+                        differential_bytes: 0,
+                        duplicate_bytes: 3,
+                        oldest: Some(when),
+                        newest: Some(when),
+                        growth: btree_map! { s("2000-01"): 6 },
+                    },
+                },
+                layout().stats(&[s("game1"), s("game1-zipped"), s("unknown")]),
+            );
+        }
+
+        #[test]
+        fn can_find_orphans() {
+            // The blobs are skipped because of the invalid mapping.
+            assert_eq!(
+                vec![
+                    OrphanedItem {
+                        path: repo_path_raw("tests/backup/game1-zipped/test.tar.zst"),
+                        kind: OrphanKind::Archive,
+                        size: 112,
+                    },
+                    OrphanedItem {
+                        path: repo_path_raw("tests/backup/ignored-invalid-mapping"),
+                        kind: OrphanKind::InvalidMapping,
+                        size: 49,
+                    },
+                    OrphanedItem {
+                        path: repo_path_raw("tests/backup/ignored-no-mapping"),
+                        kind: OrphanKind::GameFolder,
+                        size: 0,
+                    },
+                ],
+                layout().find_orphans(),
+            );
+        }
+
+        fn past() -> chrono::DateTime<chrono::Utc> {
+            chrono::NaiveDate::from_ymd_opt(2000, 1, 2)
+                .unwrap()
+                .and_hms_opt(3, 4, 1)
+                .unwrap()
+                .and_local_timezone(chrono::Utc)
+                .unwrap()
+        }
+
+        fn past2() -> chrono::DateTime<chrono::Utc> {
+            chrono::NaiveDate::from_ymd_opt(2000, 1, 2)
+                .unwrap()
+                .and_hms_opt(3, 4, 2)
+                .unwrap()
+                .and_local_timezone(chrono::Utc)
+                .unwrap()
+        }
+
+        fn now() -> chrono::DateTime<chrono::Utc> {
+            chrono::NaiveDate::from_ymd_opt(2000, 1, 2)
+                .unwrap()
+                .and_hms_opt(3, 4, 5)
+                .unwrap()
+                .and_local_timezone(chrono::Utc)
+                .unwrap()
+        }
+
+        fn now_str() -> String {
+            "20000102T030405Z".to_string()
+        }
+
+        fn repo_file(path: &str) -> String {
+            format!("{}/{}", repo_raw(), path)
+        }
+
+        #[test]
+        fn can_plan_backup_when_empty() {
+            let scan = ScanInfo::default();
+            let layout = GameLayout {
+                path: StrictPath::new(format!("{}/tests/backup/game1", repo_raw())),
+                mapping: IndividualMapping::new("game1".to_string()),
+                ..Default::default()
+            };
+            assert_eq!(
+                None,
+                layout.plan_backup(&scan, &now(), &BackupFormats::default(), Retention::default())
+            );
+        }
+
+        #[test]
+        fn can_plan_backup_kind_when_first_time() {
+            let layout = GameLayout::default();
+            assert_eq!(BackupKind::Full, layout.plan_backup_kind(Retention::default()));
+        }
+
+        #[test]
+        fn can_plan_backup_kind_when_merged_single_full() {
+            let layout = GameLayout {
+                mapping: IndividualMapping {
+                    backups: VecDeque::from_iter(vec![FullBackup::default()]),
+                    ..Default::default()
+                },
+                ..Default::default()
+            };
+            assert_eq!(BackupKind::Full, layout.plan_backup_kind(Retention::new(1, 0)));
+        }
+
+        #[test]
+        fn can_plan_backup_kind_when_locked_single_full() {
+            let layout = GameLayout {
+                mapping: IndividualMapping {
+                    backups: VecDeque::from_iter(vec![FullBackup {
+                        locked: true,
+                        ..Default::default()
+                    }]),
+                    ..Default::default()
+                },
+                ..Default::default()
+            };
+            assert_eq!(BackupKind::Full, layout.plan_backup_kind(Retention::new(1, 0)));
+        }
+
+        #[test]
+        fn can_plan_backup_kind_when_multiple_full() {
+            let layout = GameLayout {
+                mapping: IndividualMapping {
+                    backups: VecDeque::from_iter(vec![FullBackup::default()]),
+                    ..Default::default()
+                },
+                ..Default::default()
+            };
+            assert_eq!(BackupKind::Full, layout.plan_backup_kind(Retention::new(2, 0)));
+        }
+
+        #[test]
+        fn can_plan_backup_kind_when_single_full_with_differential() {
+            let layout = GameLayout {
+                mapping: IndividualMapping {
+                    backups: VecDeque::from_iter(vec![FullBackup::default()]),
+                    ..Default::default()
+                },
+                ..Default::default()
+            };
+            assert_eq!(BackupKind::Differential, layout.plan_backup_kind(Retention::new(1, 1)));
+        }
+
+        #[test]
+        fn can_plan_backup_kind_when_single_full_with_differential_rollover() {
+            let layout = GameLayout {
+                mapping: IndividualMapping {
+                    backups: VecDeque::from_iter(vec![FullBackup {
+                        children: VecDeque::from(vec![DifferentialBackup::default()]),
+                        ..Default::default()
+                    }]),
+                    ..Default::default()
+                },
+                ..Default::default()
+            };
+            assert_eq!(BackupKind::Differential, layout.plan_backup_kind(Retention::new(1, 1)));
+        }
+
+        #[test]
+        fn can_plan_backup_kind_when_multiple_full_with_differential_room_remaining() {
+            let layout = GameLayout {
+                mapping: IndividualMapping {
+                    backups: VecDeque::from_iter(vec![
+                        FullBackup {
+                            children: VecDeque::from(vec![
+                                DifferentialBackup::default(),
+                                DifferentialBackup::default(),
+                            ]),
+                            ..Default::default()
+                        },
+                        FullBackup {
+                            children: VecDeque::from(vec![DifferentialBackup::default()]),
+                            ..Default::default()
+                        },
+                    ]),
+                    ..Default::default()
+                },
+                ..Default::default()
+            };
+            assert_eq!(BackupKind::Differential, layout.plan_backup_kind(Retention::new(2, 2)));
+        }
+
+        #[test]
+        fn can_plan_backup_kind_when_multiple_full_with_differential_at_limit() {
+            let layout = GameLayout {
+                mapping: IndividualMapping {
+                    backups: VecDeque::from_iter(vec![
+                        FullBackup {
+                            children: VecDeque::from(vec![
+                                DifferentialBackup::default(),
+                                DifferentialBackup::default(),
+                            ]),
+                            ..Default::default()
+                        },
+                        FullBackup {
+                            children: VecDeque::from(vec![
+                                DifferentialBackup::default(),
+                                DifferentialBackup::default(),
+                            ]),
+                            ..Default::default()
+                        },
+                    ]),
+                    ..Default::default()
+                },
+                ..Default::default()
+            };
+            assert_eq!(BackupKind::Full, layout.plan_backup_kind(Retention::new(2, 2)));
+        }
+
+        #[test]
+        fn can_plan_backup_kind_when_single_full_with_differential_at_limit_but_locked() {
+            let layout = GameLayout {
+                mapping: IndividualMapping {
+                    backups: VecDeque::from_iter(vec![FullBackup {
+                        children: VecDeque::from(vec![
+                            DifferentialBackup::default(),
+                            DifferentialBackup {
+                                locked: true,
+                                ..Default::default()
+                            },
+                        ]),
+                        ..Default::default()
+                    }]),
+                    ..Default::default()
+                },
+                ..Default::default()
+            };
+            assert_eq!(BackupKind::Differential, layout.plan_backup_kind(Retention::new(1, 2)));
+        }
+
+        #[test]
+        fn can_plan_full_backup_with_files() {
+            let scan = ScanInfo {
+                found_files: hash_map! {
+                    repo_file("new").into(): ScannedFile::with_change(1, "n", ScanChange::New),
+                    repo_file("different").into(): ScannedFile::with_change(2, "d", ScanChange::Different),
+                    repo_file("removed").into(): ScannedFile::with_change(3, "r", ScanChange::Removed),
+                    repo_file("same").into(): ScannedFile::with_change(5, "s", ScanChange::Same),
+                    repo_file("unknown").into(): ScannedFile::with_change(6, "u", ScanChange::Unknown),
+                },
+                ..Default::default()
+            };
+            let layout = GameLayout::default();
+            assert_eq!(
+                FullBackup {
+                    name: ".".to_string(),
+                    when: now(),
+                    os: Some(Os::HOST),
+                    files: btree_map! {
+                        StrictPath::new(repo_file("new")).render(): IndividualMappingFile { hash: "n".into(), size: 1 },
+                        StrictPath::new(repo_file("different")).render(): IndividualMappingFile { hash: "d".into(), size: 2 },
+                        StrictPath::new(repo_file("same")).render(): IndividualMappingFile { hash: "s".into(), size: 5 },
+                    },
+                    ..Default::default()
+                },
+                layout.plan_full_backup(&scan, &now(), &BackupFormats::default(), Retention::default()),
+            );
+        }
+
+        #[test]
+        #[cfg(target_os = "windows")]
+        fn can_plan_full_backup_with_registry() {
+            use crate::scan::registry::{Entries, Entry, Hives, Keys};
+
+            // `Hives` only loads values that actually exist.
+            // Realistically, if a value is marked as removed`, then it won't exist, so `Hives` won't load it.
+            // The removed value here only makes it into the plan because it actually does exist.
+            let scan = ScanInfo {
+                found_registry_keys: hash_map! {
+                    "HKEY_CURRENT_USER/Software/Ludusavi".into(): ScannedRegistry::new().change_as(ScanChange::New).ignored(),
+                    "HKEY_CURRENT_USER/Software/Ludusavi/game3".into(): ScannedRegistry::new().change_as(ScanChange::Different)
+                        .with_value("binary", ScanChange::New, false)
+                        .with_value("dword", ScanChange::Different, false)
+                        .with_value("expandSz", ScanChange::Removed, false)
+                        .with_value("multiSz", ScanChange::Same, false)
+                        .with_value("qword", ScanChange::Same, true)
+                        .with_value("sz", ScanChange::Unknown, false),
+                    "HKEY_CURRENT_USER/Software/Ludusavi/other".into(): ScannedRegistry::new().change_as(ScanChange::Removed)
+                },
+                ..Default::default()
+            };
+            let layout = GameLayout::default();
+            let hives = Hives(btree_map! {
+                s("HKEY_CURRENT_USER"): Keys(btree_map! {
+                    s("Software\\Ludusavi\\game3"): Entries(btree_map! {
+                        s("sz"): Entry::Sz("foo".into()),
+                        s("multiSz"): Entry::MultiSz("bar".into()),
+                        s("expandSz"): Entry::ExpandSz("baz".into()),
+                        s("dword"): Entry::Dword(1),
+                        s("binary"): Entry::Binary(vec![65]),
+                    }),
+                })
+            });
+            assert_eq!(
+                FullBackup {
+                    name: ".".to_string(),
+                    when: now(),
+                    os: Some(Os::HOST),
+                    registry: IndividualMappingRegistry {
+                        hash: hives.sha1(registry::Format::Reg),
+                        wine_prefixes: vec![],
+                    },
+                    ..Default::default()
+                },
+                layout.plan_full_backup(&scan, &now(), &BackupFormats::default(), Retention::default()),
+            );
+        }
+
+        #[test]
+        fn can_plan_differential_backup_with_files() {
+            let scan = ScanInfo {
+                found_files: hash_map! {
+                    repo_file("new").into(): ScannedFile::with_change(1, "n", ScanChange::New),
+                    repo_file("different").into(): ScannedFile::with_change(2, "d+", ScanChange::Different),
+                    repo_file("removed").into(): ScannedFile::with_change(0, "", ScanChange::Removed),
+                    repo_file("same").into(): ScannedFile::with_change(5, "s", ScanChange::Same),
+                    repo_file("unknown").into(): ScannedFile::with_change(6, "u", ScanChange::Unknown),
+                },
+                ..Default::default()
+            };
+            let layout = GameLayout {
+                mapping: IndividualMapping {
+                    drives: drives(),
+                    backups: VecDeque::from_iter(vec![FullBackup {
+                        name: ".".to_string(),
+                        when: past(),
+                        files: btree_map! {
+                            StrictPath::new(repo_file("different")).render(): IndividualMappingFile { hash: "d".into(), size: 2 },
+                            StrictPath::new(repo_file("removed")).render(): IndividualMappingFile { hash: "r".into(), size: 3 },
+                            StrictPath::new(repo_file("same")).render(): IndividualMappingFile { hash: "s".into(), size: 5 },
+                        },
+                        ..Default::default()
+                    }]),
+                    ..Default::default()
+                },
+                ..Default::default()
+            };
+            assert_eq!(
+                DifferentialBackup {
+                    name: format!("backup-{}-diff", now_str()),
+                    when: now(),
+                    os: Some(Os::HOST),
+                    files: btree_map! {
+                        StrictPath::new(repo_file("new")).render(): Some(IndividualMappingFile { hash: "n".into(), size: 1 }),
+                        StrictPath::new(repo_file("different")).render(): Some(IndividualMappingFile { hash: "d+".into(), size: 2 }),
+                        StrictPath::new(repo_file("removed")).render(): None,
+                    },
+                    registry: None,
+                    ..Default::default()
+                },
+                layout.plan_differential_backup(&scan, &now(), &BackupFormats::default(), Retention::default()),
+            );
+        }
+
+        #[test]
+        fn can_plan_second_differential_backup_with_different_ignored_files() {
+            let scan = ScanInfo {
+                found_files: hash_map! {
+                    // Ignored in first differential backup:
+                    repo_file("file1").into(): ScannedFile::with_change(1, "1", ScanChange::New).ignored(),
+                    // Newly ignored:
+                    repo_file("file2").into(): ScannedFile::with_change(2, "2", ScanChange::Same).ignored(),
+                    // Just here to keep the backup from being inert (all ignores):
+                    repo_file("file3").into(): ScannedFile::with_change(3, "3", ScanChange::Same),
+                },
+                ..Default::default()
+            };
+            let layout = GameLayout {
+                mapping: IndividualMapping {
+                    drives: drives(),
+                    backups: VecDeque::from_iter(vec![FullBackup {
+                        name: ".".to_string(),
+                        when: past(),
+                        files: btree_map! {
+                            StrictPath::new(repo_file("file1")).render(): IndividualMappingFile { hash: "1".into(), size: 1 },
+                            StrictPath::new(repo_file("file2")).render(): IndividualMappingFile { hash: "2".into(), size: 2 },
+                            StrictPath::new(repo_file("file3")).render(): IndividualMappingFile { hash: "3".into(), size: 3 },
+                        },
+                        children: VecDeque::from([DifferentialBackup {
+                            name: format!("backup-{}-diff", now_str()),
+                            when: now(),
+                            os: Some(Os::HOST),
+                            files: btree_map! {
+                                StrictPath::new(repo_file("file1")).render(): None,
+                            },
+                            ..Default::default()
+                        }]),
+                        ..Default::default()
+                    }]),
+                    ..Default::default()
+                },
+                ..Default::default()
+            };
+            assert_eq!(
+                DifferentialBackup {
+                    name: format!("backup-{}-diff", now_str()),
+                    when: now(),
+                    os: Some(Os::HOST),
+                    files: btree_map! {
+                        // This matches the latest composite,
+                        // but we have to reiterate this in the new differential:
+                        StrictPath::new(repo_file("file1")).render(): None,
+                        // New ignore:
+                        StrictPath::new(repo_file("file2")).render(): None,
+                    },
+                    registry: None,
+                    ..Default::default()
+                },
+                layout.plan_differential_backup(&scan, &now(), &BackupFormats::default(), Retention::default()),
+            );
+        }
+
+        #[test]
+        #[cfg(target_os = "windows")]
+        fn can_plan_differential_backup_with_registry_new() {
+            use crate::scan::registry::{Entries, Hives, Keys};
+
+            let scan = ScanInfo {
+                found_registry_keys: hash_map! {
+                    "HKEY_CURRENT_USER/Software/Ludusavi/other".into(): ScannedRegistry::new().change_as(ScanChange::New)
+                },
+                ..Default::default()
+            };
+            let layout = GameLayout {
+                mapping: IndividualMapping {
+                    backups: VecDeque::from_iter(vec![FullBackup {
+                        name: ".".to_string(),
+                        when: past(),
+                        registry: IndividualMappingRegistry {
+                            hash: None,
+                            wine_prefixes: vec![],
+                        },
+                        ..Default::default()
+                    }]),
+                    ..Default::default()
+                },
+                ..Default::default()
+            };
+            let hives = Hives(btree_map! {
+                s("HKEY_CURRENT_USER"): Keys(btree_map! {
+                    s("Software\\Ludusavi\\other"): Entries::default()
+                })
+            });
+            assert_eq!(
+                DifferentialBackup {
+                    name: format!("backup-{}-diff", now_str()),
+                    when: now(),
+                    os: Some(Os::HOST),
+                    registry: Some(IndividualMappingRegistry {
+                        hash: hives.sha1(registry::Format::Reg),
+                        wine_prefixes: vec![],
+                    }),
+                    ..Default::default()
+                },
+                layout.plan_differential_backup(&scan, &now(), &BackupFormats::default(), Retention::default()),
+            );
+        }
+
+        #[test]
+        #[cfg(target_os = "windows")]
+        fn can_plan_differential_backup_with_registry_changed() {
+            use crate::scan::registry::{Entries, Hives, Keys};
+
+            let scan = ScanInfo {
+                found_registry_keys: hash_map! {
+                    "HKEY_CURRENT_USER/Software/Ludusavi/other".into(): ScannedRegistry::new().change_as(ScanChange::Different)
+                        .with_value("removed", ScanChange::Removed, false)
+                        // Fake registry values are ignored because `Hives` re-reads the actual registry.
+                        .with_value("fake", ScanChange::New, false)
+                },
+                ..Default::default()
+            };
+            let layout = GameLayout {
+                mapping: IndividualMapping {
+                    backups: VecDeque::from_iter(vec![FullBackup {
+                        name: ".".to_string(),
+                        when: past(),
+                        registry: IndividualMappingRegistry {
+                            hash: Some("foo".into()),
+                            wine_prefixes: vec![],
+                        },
+                        ..Default::default()
+                    }]),
+                    ..Default::default()
+                },
+                ..Default::default()
+            };
+            let hives = Hives(btree_map! {
+                s("HKEY_CURRENT_USER"): Keys(btree_map! {
+                    s("Software\\Ludusavi\\other"): Entries::default()
+                })
+            });
+            assert_eq!(
+                DifferentialBackup {
+                    name: format!("backup-{}-diff", now_str()),
+                    when: now(),
+                    os: Some(Os::HOST),
+                    registry: Some(IndividualMappingRegistry {
+                        hash: hives.sha1(registry::Format::Reg),
+                        wine_prefixes: vec![],
+                    }),
+                    ..Default::default()
+                },
+                layout.plan_differential_backup(&scan, &now(), &BackupFormats::default(), Retention::default()),
+            );
+        }
+
+        #[test]
+        #[cfg(target_os = "windows")]
+        fn can_plan_differential_backup_with_registry_unchanged() {
+            use crate::scan::registry::{Entries, Hives, Keys};
+
+            let scan = ScanInfo {
+                found_registry_keys: hash_map! {
+                    "HKEY_CURRENT_USER/Software/Ludusavi/other".into(): ScannedRegistry::new().change_as(ScanChange::Same)
+                },
+                ..Default::default()
+            };
+            let hives = Hives(btree_map! {
+                s("HKEY_CURRENT_USER"): Keys(btree_map! {
+                    s("Software\\Ludusavi\\other"): Entries::default()
+                })
+            });
+            let layout = GameLayout {
+                mapping: IndividualMapping {
+                    backups: VecDeque::from_iter(vec![FullBackup {
+                        name: ".".to_string(),
+                        when: past(),
+                        registry: IndividualMappingRegistry {
+                            hash: hives.sha1(registry::Format::Reg),
+                            wine_prefixes: vec![],
+                        },
+                        ..Default::default()
+                    }]),
+                    ..Default::default()
+                },
+                ..Default::default()
+            };
+            assert_eq!(
+                DifferentialBackup {
+                    name: format!("backup-{}-diff", now_str()),
+                    when: now(),
+                    os: Some(Os::HOST),
+                    registry: None,
+                    ..Default::default()
+                },
+                layout.plan_differential_backup(&scan, &now(), &BackupFormats::default(), Retention::default()),
+            );
+        }
+
+        #[test]
+        #[cfg(target_os = "windows")]
+        fn can_plan_differential_backup_with_registry_removed() {
+            let scan = ScanInfo {
+                found_registry_keys: hash_map! {
+                    "HKEY_CURRENT_USER/Software/Ludusavi/other".into(): ScannedRegistry::new().change_as(ScanChange::Removed)
+                },
+                ..Default::default()
+            };
+            let layout = GameLayout {
+                mapping: IndividualMapping {
+                    backups: VecDeque::from_iter(vec![FullBackup {
+                        name: ".".to_string(),
+                        when: past(),
+                        registry: IndividualMappingRegistry {
+                            hash: Some("foo".into()),
+                            wine_prefixes: vec![],
+                        },
+                        ..Default::default()
+                    }]),
+                    ..Default::default()
+                },
+                ..Default::default()
+            };
+            assert_eq!(
+                DifferentialBackup {
+                    name: format!("backup-{}-diff", now_str()),
+                    when: now(),
+                    os: Some(Os::HOST),
+                    registry: Some(IndividualMappingRegistry {
+                        hash: None,
+                        wine_prefixes: vec![],
+                    }),
+                    ..Default::default()
+                },
+                layout.plan_differential_backup(&scan, &now(), &BackupFormats::default(), Retention::default()),
+            );
+        }
+
+        #[test]
+        fn can_forget_excess_backups_without_locks() {
+            let mut layout = GameLayout {
+                mapping: IndividualMapping {
+                    backups: VecDeque::from_iter(vec![
+                        FullBackup {
+                            name: "1".to_string(),
+                            children: VecDeque::from_iter(vec![DifferentialBackup {
+                                name: "1-a".to_string(),
+                                ..Default::default()
+                            }]),
+                            ..Default::default()
+                        },
+                        FullBackup {
+                            name: "2".to_string(),
+                            children: VecDeque::from_iter(vec![
+                                DifferentialBackup {
+                                    name: "2-a".to_string(),
+                                    ..Default::default()
+                                },
+                                DifferentialBackup {
+                                    name: "2-b".to_string(),
+                                    ..Default::default()
+                                },
+                            ]),
+                            ..Default::default()
+                        },
+                    ]),
+                    ..Default::default()
+                },
+                ..Default::default()
+            };
+
+            layout.forget_excess_backups(Retention::new(1, 1), &now());
+            assert_eq!(
+                VecDeque::from_iter(vec![FullBackup {
+                    name: "2".to_string(),
+                    children: VecDeque::from_iter(vec![DifferentialBackup {
+                        name: "2-b".to_string(),
+                        ..Default::default()
+                    }]),
+                    ..Default::default()
+                },]),
+                layout.mapping.backups,
+            );
+        }
+
+        #[test]
+        fn can_forget_excess_backups_without_locks_using_duplicate_name() {
+            let mut layout = GameLayout {
+                mapping: IndividualMapping {
+                    backups: VecDeque::from_iter(vec![
+                        FullBackup {
+                            name: ".".to_string(),
+                            comment: Some("old".to_string()),
+                            ..Default::default()
+                        },
+                        FullBackup {
+                            name: ".".to_string(),
+                            comment: Some("new".to_string()),
+                            ..Default::default()
+                        },
+                    ]),
+                    ..Default::default()
+                },
+                ..Default::default()
+            };
+
+            layout.forget_excess_backups(Retention::new(1, 0), &now());
+            assert_eq!(
+                VecDeque::from_iter(vec![FullBackup {
+                    name: ".".to_string(),
+                    comment: Some("new".to_string()),
+                    ..Default::default()
+                },]),
+                layout.mapping.backups,
+            );
+        }
+
+        #[test]
+        fn can_forget_excess_backups_with_locks() {
+            let mut layout = GameLayout {
+                mapping: IndividualMapping {
+                    backups: VecDeque::from_iter(vec![
+                        FullBackup {
+                            name: "1".to_string(),
+                            locked: true,
+                            children: VecDeque::from_iter(vec![
+                                DifferentialBackup {
+                                    name: "1-a".to_string(),
+                                    ..Default::default()
+                                },
+                                DifferentialBackup {
+                                    name: "1-b".to_string(),
+                                    ..Default::default()
+                                },
+                            ]),
+                            ..Default::default()
+                        },
+                        FullBackup {
+                            name: "2".to_string(),
+                            children: VecDeque::from_iter(vec![
+                                DifferentialBackup {
+                                    name: "2-a".to_string(),
+                                    ..Default::default()
+                                },
+                                DifferentialBackup {
+                                    name: "2-b".to_string(),
+                                    locked: true,
+                                    ..Default::default()
+                                },
+                                DifferentialBackup {
+                                    name: "2-c".to_string(),
+                                    ..Default::default()
+                                },
+                            ]),
+                            ..Default::default()
+                        },
+                        FullBackup {
+                            name: "3".to_string(),
+                            ..Default::default()
+                        },
+                        FullBackup {
+                            name: "4".to_string(),
+                            ..Default::default()
+                        },
+                    ]),
+                    ..Default::default()
+                },
+                ..Default::default()
+            };
+
+            layout.forget_excess_backups(Retention::new(1, 1), &now());
+            assert_eq!(
+                VecDeque::from_iter(vec![
+                    FullBackup {
+                        name: "1".to_string(),
+                        locked: true,
+                        children: VecDeque::from_iter(vec![DifferentialBackup {
+                            name: "1-b".to_string(),
+                            ..Default::default()
+                        }]),
+                        ..Default::default()
+                    },
+                    FullBackup {
+                        name: "2".to_string(),
+                        children: VecDeque::from_iter(vec![
+                            DifferentialBackup {
+                                name: "2-b".to_string(),
+                                locked: true,
+                                ..Default::default()
+                            },
+                            DifferentialBackup {
+                                name: "2-c".to_string(),
+                                ..Default::default()
+                            }
+                        ]),
+                        ..Default::default()
+                    },
+                    FullBackup {
+                        name: "4".to_string(),
+                        ..Default::default()
+                    },
+                ]),
+                layout.mapping.backups,
+            );
+        }
+
+        #[test]
+        fn can_forget_excess_backups_with_calendar() {
+            let day1 = |hour| {
+                chrono::NaiveDate::from_ymd_opt(2000, 1, 1)
+                    .unwrap()
+                    .and_hms_opt(hour, 0, 0)
+                    .unwrap()
+                    .and_local_timezone(chrono::Utc)
+                    .unwrap()
+            };
+
+            let mut layout = GameLayout {
+                mapping: IndividualMapping {
+                    backups: VecDeque::from_iter(vec![
+                        FullBackup {
+                            name: "1".to_string(),
+                            when: day1(1),
+                            ..Default::default()
+                        },
+                        FullBackup {
+                            name: "2".to_string(),
+                            when: day1(2),
+                            children: VecDeque::from_iter(vec![
+                                DifferentialBackup {
+                                    name: "2-a".to_string(),
+                                    when: day1(3),
+                                    ..Default::default()
+                                },
+                                DifferentialBackup {
+                                    name: "2-b".to_string(),
+                                    when: day1(4),
+                                    ..Default::default()
+                                },
+                            ]),
+                            ..Default::default()
+                        },
+                        FullBackup {
+                            name: "3".to_string(),
+                            when: now(),
+                            ..Default::default()
+                        },
+                    ]),
+                    ..Default::default()
+                },
+                ..Default::default()
+            };
+
+            let mut retention = Retention::new(1, 0);
+            retention.calendar.daily = 2;
+
+            layout.forget_excess_backups(retention, &now());
+            assert_eq!(
+                VecDeque::from_iter(vec![
+                    FullBackup {
+                        name: "2".to_string(),
+                        when: day1(2),
+                        children: VecDeque::from_iter(vec![DifferentialBackup {
+                            name: "2-b".to_string(),
+                            when: day1(4),
+                            ..Default::default()
+                        }]),
+                        ..Default::default()
+                    },
+                    FullBackup {
+                        name: "3".to_string(),
+                        when: now(),
+                        ..Default::default()
+                    },
+                ]),
+                layout.mapping.backups,
+            );
+        }
+
+        fn sized_full_backup(name: &str, hour: u32, size: u64, locked: bool) -> FullBackup {
+            FullBackup {
+                name: name.to_string(),
+                when: chrono::NaiveDate::from_ymd_opt(2000, 1, 1)
+                    .unwrap()
+                    .and_hms_opt(hour, 0, 0)
+                    .unwrap()
+                    .and_local_timezone(chrono::Utc)
+                    .unwrap(),
+                locked,
+                files: BTreeMap::from([(
+                    "file".to_string(),
+                    IndividualMappingFile {
+                        hash: "hash".to_string(),
+                        size,
+                    },
+                )]),
+                ..Default::default()
+            }
+        }
+
+        #[test]
+        fn can_plan_quota_by_forgetting_oldest_unlocked_backups() {
+            let layout = GameLayout {
+                mapping: IndividualMapping {
+                    backups: VecDeque::from_iter(vec![
+                        sized_full_backup("1", 1, 10, true),
+                        sized_full_backup("2", 2, 10, false),
+                        sized_full_backup("3", 3, 10, false),
+                    ]),
+                    ..Default::default()
+                },
+                ..Default::default()
+            };
+            let new = Backup::Full(sized_full_backup("4", 4, 10, false));
+
             assert_eq!(
-                repo_path("tests/backup/nonexistent"),
-                layout().game_folder("nonexistent")
+                Ok(vec![
+                    ("2".to_string(), sized_full_backup("2", 2, 10, false).when),
+                    ("3".to_string(), sized_full_backup("3", 3, 10, false).when),
+                ]),
+                layout.plan_quota(&new, &now(), Retention::new(5, 0), 20),
             );
         }
 
         #[test]
-        fn can_determine_game_folder_that_does_not_exist_with_partial_rename() {
-            assert_eq!(repo_path("tests/backup/foo_bar"), layout().game_folder("foo:bar"));
+        fn cannot_plan_quota_when_remaining_backups_are_locked() {
+            let layout = GameLayout {
+                mapping: IndividualMapping {
+                    backups: VecDeque::from_iter(vec![
+                        sized_full_backup("1", 1, 10, true),
+                        sized_full_backup("2", 2, 10, true),
+                    ]),
+                    ..Default::default()
+                },
+                ..Default::default()
+            };
+            let new = Backup::Full(sized_full_backup("3", 3, 10, false));
+
+            assert_eq!(
+                Err(Error::BackupQuotaExceeded { size: 30, quota: 25 }),
+                layout.plan_quota(&new, &now(), Retention::new(5, 0), 25),
+            );
         }
 
         #[test]
-        fn can_determine_game_folder_that_does_not_exist_with_total_rename() {
+        fn cannot_plan_quota_when_new_backup_is_too_big() {
+            let layout = GameLayout::default();
+            let new = Backup::Full(sized_full_backup("1", 1, 10, false));
+
+            assert_eq!(
+                Err(Error::BackupQuotaExceeded { size: 10, quota: 5 }),
+                layout.plan_quota(&new, &now(), Retention::new(5, 0), 5),
+            );
+        }
+
+        #[test]
+        fn can_plan_quota_with_shared_blobs() {
+            let layout = GameLayout {
+                mapping: IndividualMapping {
+                    backups: VecDeque::from_iter(vec![
+                        sized_full_backup("1.dedup", 1, 10, false),
+                        sized_full_backup("2.dedup", 2, 10, false),
+                    ]),
+                    ..Default::default()
+                },
+                ..Default::default()
+            };
+            assert_eq!(10, layout.mapping.size());
+
+            let new = Backup::Full(sized_full_backup("3.dedup", 3, 10, false));
+            assert_eq!(Ok(vec![]), layout.plan_quota(&new, &now(), Retention::new(5, 0), 10));
+        }
+
+        fn make_path(file: &str) -> StrictPath {
+            repo_path(&format!("tests/backup/game1/{}", file))
+        }
+
+        fn make_restorable_path(backup: &str, file: &str) -> StrictPath {
+            StrictPath::relative(
+                format!(
+                    "{backup}/drive-{}/{file}",
+                    if cfg!(target_os = "windows") { "X" } else { "0" }
+                ),
+                Some(repo_file_raw("tests/backup/game1")),
+            )
+        }
+
+        fn make_restorable_path_zip(file: &str) -> StrictPath {
+            StrictPath::relative(
+                format!("drive-{}/{file}", if cfg!(target_os = "windows") { "X" } else { "0" }),
+                None,
+            )
+        }
+
+        #[test]
+        fn can_report_restorable_files_for_full_backup_in_simple_format() {
+            let layout = GameLayout {
+                path: StrictPath::new(format!("{}/tests/backup/game1", repo_raw())),
+                mapping: IndividualMapping {
+                    name: "game1".to_string(),
+                    drives: drives_x(),
+                    backups: VecDeque::from(vec![FullBackup {
+                        name: "backup-1".into(),
+                        when: past(),
+                        files: btree_map! {
+                            mapping_file_key("/file1.txt"): IndividualMappingFile { hash: "old".into(), size: 1 },
+                            mapping_file_key("/file2.txt"): IndividualMappingFile { hash: "old".into(), size: 2 },
+                        },
+                        ..Default::default()
+                    }]),
+                },
+                ..Default::default()
+            };
+            assert_eq!(
+                hash_map! {
+                    make_restorable_path("backup-1", "file1.txt"): ScannedFile {
+                        size: 1,
+                        hash: "old".into(),
+                        original_path: Some(make_original_path("/file1.txt")),
+                        ignored: false,
+                        change: Default::default(),
+                        container: None,
+                        redirected: None,
+                        conflict: false,
+                        filtered: false,
+                        steam_account: None,
+                    },
+                    make_restorable_path("backup-1", "file2.txt"): ScannedFile {
+                        size: 2,
+                        hash: "old".into(),
+                        original_path: Some(make_original_path("/file2.txt")),
+                        ignored: false,
+                        change: Default::default(),
+                        container: None,
+                        redirected: None,
+                        conflict: false,
+                        filtered: false,
+                        steam_account: None,
+                    },
+                },
+                layout.restorable_files(&BackupId::Latest, ScanKind::Backup, &[], false, &Default::default()),
+            );
+        }
+
+        #[test]
+        fn can_report_restorable_files_for_full_backup_in_zip_format() {
+            let layout = GameLayout {
+                path: StrictPath::new(format!("{}/tests/backup/game1", repo_raw())),
+                mapping: IndividualMapping {
+                    name: "game1".to_string(),
+                    drives: drives_x(),
+                    backups: VecDeque::from(vec![FullBackup {
+                        name: "backup-1.zip".into(),
+                        when: past(),
+                        files: btree_map! {
+                            mapping_file_key("/file1.txt"): IndividualMappingFile { hash: "old".into(), size: 1 },
+                            mapping_file_key("/file2.txt"): IndividualMappingFile { hash: "old".into(), size: 2 },
+                        },
+                        ..Default::default()
+                    }]),
+                },
+                ..Default::default()
+            };
+            assert_eq!(
+                hash_map! {
+                    make_restorable_path_zip("file1.txt"): ScannedFile {
+                        size: 1,
+                        hash: "old".into(),
+                        original_path: Some(make_original_path("/file1.txt")),
+                        ignored: false,
+                        change: Default::default(),
+                        container: Some(make_path("backup-1.zip")),
+                        redirected: None,
+                        conflict: false,
+                        filtered: false,
+                        steam_account: None,
+                    },
+                    make_restorable_path_zip("file2.txt"): ScannedFile {
+                        size: 2,
+                        hash: "old".into(),
+                        original_path: Some(make_original_path("/file2.txt")),
+                        ignored: false,
+                        change: Default::default(),
+                        container: Some(make_path("backup-1.zip")),
+                        redirected: None,
+                        conflict: false,
+                        filtered: false,
+                        steam_account: None,
+                    },
+                },
+                layout.restorable_files(&BackupId::Latest, ScanKind::Backup, &[], false, &Default::default()),
+            );
+        }
+
+        #[test]
+        fn can_report_restorable_files_for_full_backup_in_tar_format() {
+            let layout = GameLayout {
+                path: StrictPath::new(format!("{}/tests/backup/game1", repo_raw())),
+                mapping: IndividualMapping {
+                    name: "game1".to_string(),
+                    drives: drives_x(),
+                    backups: VecDeque::from(vec![FullBackup {
+                        name: "backup-1.tar.zst".into(),
+                        when: past(),
+                        files: btree_map! {
+                            mapping_file_key("/file1.txt"): IndividualMappingFile { hash: "old".into(), size: 1 },
+                            mapping_file_key("/file2.txt"): IndividualMappingFile { hash: "old".into(), size: 2 },
+                        },
+                        ..Default::default()
+                    }]),
+                },
+                ..Default::default()
+            };
+            assert_eq!(
+                hash_map! {
+                    make_restorable_path_zip("file1.txt"): ScannedFile {
+                        size: 1,
+                        hash: "old".into(),
+                        original_path: Some(make_original_path("/file1.txt")),
+                        ignored: false,
+                        change: Default::default(),
+                        container: Some(make_path("backup-1.tar.zst")),
+                        redirected: None,
+                        conflict: false,
+                        filtered: false,
+                        steam_account: None,
+                    },
+                    make_restorable_path_zip("file2.txt"): ScannedFile {
+                        size: 2,
+                        hash: "old".into(),
+                        original_path: Some(make_original_path("/file2.txt")),
+                        ignored: false,
+                        change: Default::default(),
+                        container: Some(make_path("backup-1.tar.zst")),
+                        redirected: None,
+                        conflict: false,
+                        filtered: false,
+                        steam_account: None,
+                    },
+                },
+                layout.restorable_files(&BackupId::Latest, ScanKind::Backup, &[], false, &Default::default()),
+            );
+        }
+
+        #[test]
+        fn can_report_restorable_files_for_full_backup_in_dedup_format() {
+            let layout = GameLayout {
+                path: StrictPath::new(format!("{}/tests/backup/game1", repo_raw())),
+                mapping: IndividualMapping {
+                    name: "game1".to_string(),
+                    drives: drives_x(),
+                    backups: VecDeque::from(vec![FullBackup {
+                        name: "backup-1.dedup".into(),
+                        when: past(),
+                        files: btree_map! {
+                            mapping_file_key("/file1.txt"): IndividualMappingFile { hash: "abc".into(), size: 1 },
+                            mapping_file_key("/file2.txt"): IndividualMappingFile { hash: "def".into(), size: 2 },
+                        },
+                        ..Default::default()
+                    }]),
+                },
+                ..Default::default()
+            };
+            assert_eq!(
+                hash_map! {
+                    make_restorable_path_zip("file1.txt"): ScannedFile {
+                        size: 1,
+                        hash: "abc".into(),
+                        original_path: Some(make_original_path("/file1.txt")),
+                        ignored: false,
+                        change: Default::default(),
+                        container: Some(repo_path("tests/backup/.blobs/ab/abc")),
+                        redirected: None,
+                        conflict: false,
+                        filtered: false,
+                        steam_account: None,
+                    },
+                    make_restorable_path_zip("file2.txt"): ScannedFile {
+                        size: 2,
+                        hash: "def".into(),
+                        original_path: Some(make_original_path("/file2.txt")),
+                        ignored: false,
+                        change: Default::default(),
+                        container: Some(repo_path("tests/backup/.blobs/de/def")),
+                        redirected: None,
+                        conflict: false,
+                        filtered: false,
+                        steam_account: None,
+                    },
+                },
+                layout.restorable_files(&BackupId::Latest, ScanKind::Backup, &[], false, &Default::default()),
+            );
+        }
+
+        #[test]
+        fn can_report_restorable_files_for_differential_backup_in_simple_format() {
+            let layout = GameLayout {
+                path: StrictPath::new(format!("{}/tests/backup/game1", repo_raw())),
+                mapping: IndividualMapping {
+                    name: "game1".to_string(),
+                    drives: drives_x(),
+                    backups: VecDeque::from(vec![FullBackup {
+                        name: "backup-1".into(),
+                        when: past(),
+                        files: btree_map! {
+                            mapping_file_key("/unchanged.txt"): IndividualMappingFile { hash: "old".into(), size: 1 },
+                            mapping_file_key("/changed.txt"): IndividualMappingFile { hash: "old".into(), size: 2 },
+                            mapping_file_key("/delete.txt"): IndividualMappingFile { hash: "old".into(), size: 3 },
+                        },
+                        children: VecDeque::from([DifferentialBackup {
+                            name: "backup-2".into(),
+                            when: past2(),
+                            files: btree_map! {
+                                mapping_file_key("/changed.txt"): Some(IndividualMappingFile { hash: "new".into(), size: 2 }),
+                                mapping_file_key("/delete.txt"): None,
+                                mapping_file_key("/added.txt"): Some(IndividualMappingFile { hash: "new".into(), size: 5 }),
+                            },
+                            ..Default::default()
+                        }]),
+                        ..Default::default()
+                    }]),
+                },
+                ..Default::default()
+            };
             assert_eq!(
-                repo_path("tests/backup/ludusavi-renamed-Kioq"),
-                layout().game_folder("***")
+                hash_map! {
+                    make_restorable_path("backup-1", "unchanged.txt"): ScannedFile {
+                        size: 1,
+                        hash: "old".into(),
+                        original_path: Some(make_original_path("/unchanged.txt")),
+                        ignored: false,
+                        change: Default::default(),
+                        container: None,
+                        redirected: None,
+                        conflict: false,
+                        filtered: false,
+                        steam_account: None,
+                    },
+                    make_restorable_path("backup-2", "changed.txt"): ScannedFile {
+                        size: 2,
+                        hash: "new".into(),
+                        original_path: Some(make_original_path("/changed.txt")),
+                        ignored: false,
+                        change: Default::default(),
+                        container: None,
+                        redirected: None,
+                        conflict: false,
+                        filtered: false,
+                        steam_account: None,
+                    },
+                    make_restorable_path("backup-2", "added.txt"): ScannedFile {
+                        size: 5,
+                        hash: "new".into(),
+                        original_path: Some(make_original_path("/added.txt")),
+                        ignored: false,
+                        change: Default::default(),
+                        container: None,
+                        redirected: None,
+                        conflict: false,
+                        filtered: false,
+                        steam_account: None,
+                    },
+                },
+                layout.restorable_files(&BackupId::Latest, ScanKind::Backup, &[], false, &Default::default()),
             );
         }
 
         #[test]
-        fn can_determine_game_folder_by_escaping_dots_at_start_and_end() {
-            assert_eq!(repo_path("tests/backup/_._"), layout().game_folder("..."));
-        }
-
-        #[test]
-        fn can_find_irrelevant_backup_files() {
-            assert_eq!(
-                vec![repo_path_raw("tests/backup/game1/drive-X/file2.txt")],
-                game_layout("game1", &repo_file_raw("tests/backup/game1"))
-                    .find_irrelevant_backup_files(".", &[repo_path("tests/backup/game1/drive-X/file1.txt")])
-            );
+        fn can_report_restorable_files_for_differential_backup_in_zip_format() {
+            let layout = GameLayout {
+                path: StrictPath::new(format!("{}/tests/backup/game1", repo_raw())),
+                mapping: IndividualMapping {
+                    name: "game1".to_string(),
+                    drives: drives_x(),
+                    backups: VecDeque::from(vec![FullBackup {
+                        name: "backup-1.zip".into(),
+                        when: past(),
+                        files: btree_map! {
+                            mapping_file_key("/unchanged.txt"): IndividualMappingFile { hash: "old".into(), size: 1 },
+                            mapping_file_key("/changed.txt"): IndividualMappingFile { hash: "old".into(), size: 2 },
+                            mapping_file_key("/delete.txt"): IndividualMappingFile { hash: "old".into(), size: 3 },
+                        },
+                        children: VecDeque::from([DifferentialBackup {
+                            name: "backup-2.zip".into(),
+                            when: past2(),
+                            files: btree_map! {
+                                mapping_file_key("/changed.txt"): Some(IndividualMappingFile { hash: "new".into(), size: 2 }),
+                                mapping_file_key("/delete.txt"): None,
+                                mapping_file_key("/added.txt"): Some(IndividualMappingFile { hash: "new".into(), size: 5 }),
+                            },
+                            ..Default::default()
+                        }]),
+                        ..Default::default()
+                    }]),
+                },
+                ..Default::default()
+            };
             assert_eq!(
-                Vec::<StrictPath>::new(),
-                game_layout("game1", &repo_file("tests/backup/game1")).find_irrelevant_backup_files(
-                    ".",
-                    &[
-                        repo_path("tests/backup/game1/drive-X/file1.txt"),
-                        repo_path("tests/backup/game1/drive-X/file2.txt"),
-                    ]
-                )
+                hash_map! {
+                    make_restorable_path_zip("unchanged.txt"): ScannedFile {
+                        size: 1,
+                        hash: "old".into(),
+                        original_path: Some(make_original_path("/unchanged.txt")),
+                        ignored: false,
+                        change: Default::default(),
+                        container: Some(make_path("backup-1.zip")),
+                        redirected: None,
+                        conflict: false,
+                        filtered: false,
+                        steam_account: None,
+                    },
+                    make_restorable_path_zip("changed.txt"): ScannedFile {
+                        size: 2,
+                        hash: "new".into(),
+                        original_path: Some(make_original_path("/changed.txt")),
+                        ignored: false,
+                        change: Default::default(),
+                        container: Some(make_path("backup-2.zip")),
+                        redirected: None,
+                        conflict: false,
+                        filtered: false,
+                        steam_account: None,
+                    },
+                    make_restorable_path_zip("added.txt"): ScannedFile {
+                        size: 5,
+                        hash: "new".into(),
+                        original_path: Some(make_original_path("/added.txt")),
+                        ignored: false,
+                        change: Default::default(),
+                        container: Some(make_path("backup-2.zip")),
+                        redirected: None,
+                        conflict: false,
+                        filtered: false,
+                        steam_account: None,
+                    },
+                },
+                layout.restorable_files(&BackupId::Latest, ScanKind::Backup, &[], false, &Default::default()),
             );
         }
+    }
 
-        fn past() -> chrono::DateTime<chrono::Utc> {
-            chrono::NaiveDate::from_ymd_opt(2000, 1, 2)
-                .unwrap()
-                .and_hms_opt(3, 4, 1)
-                .unwrap()
-                .and_local_timezone(chrono::Utc)
-                .unwrap()
-        }
+    mod game_layout {
+        use pretty_assertions::assert_eq;
 
-        fn past2() -> chrono::DateTime<chrono::Utc> {
-            chrono::NaiveDate::from_ymd_opt(2000, 1, 2)
-                .unwrap()
-                .and_hms_opt(3, 4, 2)
-                .unwrap()
-                .and_local_timezone(chrono::Utc)
-                .unwrap()
-        }
+        use crate::testing::{drives_x_always, drives_x_static, repo_file, repo_file_raw};
+
+        use super::*;
 
         fn now() -> chrono::DateTime<chrono::Utc> {
             chrono::NaiveDate::from_ymd_opt(2000, 1, 2)
@@ -2334,324 +6671,302 @@ mod tests {
                 .unwrap()
         }
 
-        fn now_str() -> String {
-            "20000102T030405Z".to_string()
-        }
-
-        fn repo_file(path: &str) -> String {
-            format!("{}/{}", repo_raw(), path)
+        fn restorable_file_simple(backup: &str, file: &str) -> StrictPath {
+            StrictPath::relative(
+                format!(
+                    "{backup}/drive-{}/{file}",
+                    if cfg!(target_os = "windows") { "X" } else { "0" }
+                ),
+                Some(repo_file_raw("tests/backup/game1")),
+            )
         }
 
         #[test]
-        fn can_plan_backup_when_empty() {
-            let scan = ScanInfo::default();
-            let layout = GameLayout {
-                path: StrictPath::new(format!("{}/tests/backup/game1", repo_raw())),
-                mapping: IndividualMapping::new("game1".to_string()),
-            };
-            assert_eq!(
-                None,
-                layout.plan_backup(&scan, &now(), &BackupFormats::default(), Retention::default())
+        fn can_scan_game_for_restoration_with_files() {
+            let mut layout = GameLayout::new(
+                StrictPath::new(format!("{}/tests/backup/game1", repo())),
+                IndividualMapping {
+                    name: "game1".to_string(),
+                    drives: drives_x(),
+                    backups: VecDeque::from(vec![FullBackup {
+                        name: ".".into(),
+                        when: now(),
+                        files: btree_map! {
+                            mapping_file_key("/file1.txt"): IndividualMappingFile { hash: "3a52ce780950d4d969792a2559cd519d7ee8c727".into(), size: 1 },
+                            mapping_file_key("/file2.txt"): IndividualMappingFile { hash: "9d891e731f75deae56884d79e9816736b7488080".into(), size: 2 },
+                        },
+                        ..Default::default()
+                    }]),
+                },
             );
-        }
-
-        #[test]
-        fn can_plan_backup_kind_when_first_time() {
-            let layout = GameLayout::default();
-            assert_eq!(BackupKind::Full, layout.plan_backup_kind(Retention::default()));
-        }
-
-        #[test]
-        fn can_plan_backup_kind_when_merged_single_full() {
-            let layout = GameLayout {
-                mapping: IndividualMapping {
-                    backups: VecDeque::from_iter(vec![FullBackup::default()]),
-                    ..Default::default()
+            let backups = vec![Backup::Full(FullBackup {
+                name: ".".to_string(),
+                when: now(),
+                files: btree_map! {
+                    mapping_file_key("/file1.txt"): IndividualMappingFile {
+                        hash: "3a52ce780950d4d969792a2559cd519d7ee8c727".into(),
+                        size: 1,
+                    },
+                    mapping_file_key("/file2.txt"): IndividualMappingFile {
+                        hash: "9d891e731f75deae56884d79e9816736b7488080".into(),
+                        size: 2,
+                    },
                 },
                 ..Default::default()
-            };
-            assert_eq!(BackupKind::Full, layout.plan_backup_kind(Retention::new(1, 0)));
-        }
+            })];
 
-        #[test]
-        fn can_plan_backup_kind_when_locked_single_full() {
-            let layout = GameLayout {
-                mapping: IndividualMapping {
-                    backups: VecDeque::from_iter(vec![FullBackup {
-                        locked: true,
-                        ..Default::default()
-                    }]),
-                    ..Default::default()
+            assert_eq!(
+                ScanInfo {
+                    game_name: s("game1"),
+                    found_files: hash_map! {
+                        restorable_file_simple(".", "file1.txt"): ScannedFile {
+                            size: 1,
+                            hash: "3a52ce780950d4d969792a2559cd519d7ee8c727".into(),
+                            original_path: Some(make_original_path("/file1.txt")),
+                            ignored: false,
+                            change: ScanChange::New,
+                            container: None,
+                            redirected: None,
+                            conflict: false,
+                            filtered: false,
+                            steam_account: None,
+                        },
+                        restorable_file_simple(".", "file2.txt"): ScannedFile {
+                            size: 2,
+                            hash: "9d891e731f75deae56884d79e9816736b7488080".into(),
+                            original_path: Some(make_original_path("/file2.txt")),
+                            ignored: false,
+                            change: ScanChange::New,
+                            container: None,
+                            redirected: None,
+                            conflict: false,
+                            filtered: false,
+                            steam_account: None,
+                        },
+                    },
+                    found_registry_keys: Default::default(),
+                    wine_prefixes: vec![],
+                    available_backups: backups.clone(),
+                    backup: Some(backups[0].clone()),
+                    has_backups: true,
                 },
-                ..Default::default()
-            };
-            assert_eq!(BackupKind::Full, layout.plan_backup_kind(Retention::new(1, 0)));
+                layout.scan_for_restoration(
+                    "game1",
+                    &BackupId::Latest,
+                    &[],
+                    false,
+                    &Default::default(),
+                    &Default::default(),
+                    &[]
+                ),
+            );
         }
 
         #[test]
-        fn can_plan_backup_kind_when_multiple_full() {
-            let layout = GameLayout {
-                mapping: IndividualMapping {
-                    backups: VecDeque::from_iter(vec![FullBackup::default()]),
-                    ..Default::default()
-                },
-                ..Default::default()
-            };
-            assert_eq!(BackupKind::Full, layout.plan_backup_kind(Retention::new(2, 0)));
+        fn can_select_files_for_restoration_into_destination() {
+            let mut layout =
+                BackupLayout::new(StrictPath::new(format!("{}/tests/backup", repo()))).game_layout("game1");
+            let mut scan = layout.scan_for_restoration(
+                "game1",
+                &BackupId::Latest,
+                &[],
+                false,
+                &Default::default(),
+                &Default::default(),
+                &[],
+            );
+            let selection = FileSelection::new(&["file1.txt".to_string()]).unwrap();
+            let destination = StrictPath::new(format!("{}/tests/extract", repo()));
+
+            layout.select_for_restoration(&mut scan, &selection, Some(&destination));
+
+            assert_eq!(
+                vec![Some(StrictPath::new(format!(
+                    "{}/tests/extract/game1/drive-X/file1.txt",
+                    repo()
+                )))],
+                scan.found_files
+                    .values()
+                    .map(|x| x.redirected.clone())
+                    .collect::<Vec<_>>(),
+            );
+            assert_eq!(
+                vec![ScanChange::New],
+                scan.found_files.values().map(|x| x.change).collect::<Vec<_>>(),
+            );
         }
 
         #[test]
-        fn can_plan_backup_kind_when_single_full_with_differential() {
-            let layout = GameLayout {
-                mapping: IndividualMapping {
-                    backups: VecDeque::from_iter(vec![FullBackup::default()]),
-                    ..Default::default()
-                },
-                ..Default::default()
-            };
-            assert_eq!(BackupKind::Differential, layout.plan_backup_kind(Retention::new(1, 1)));
+        fn can_select_files_by_glob() {
+            let selection = FileSelection::new(&["*.sav".to_string(), "/saves/**".to_string()]).unwrap();
+
+            assert!(!selection.is_all());
+            assert!(selection.matches(&StrictPath::from("/game/slot1.sav")));
+            assert!(selection.matches(&StrictPath::from("/saves/sub/config.ini")));
+            assert!(!selection.matches(&StrictPath::from("/game/config.ini")));
+            assert!(FileSelection::new(&[]).unwrap().is_all());
+            assert_eq!(
+                Some(Error::CliInvalidGlob { glob: "[".to_string() }),
+                FileSelection::new(&["[".to_string()]).err(),
+            );
         }
 
         #[test]
-        fn can_plan_backup_kind_when_single_full_with_differential_rollover() {
-            let layout = GameLayout {
-                mapping: IndividualMapping {
-                    backups: VecDeque::from_iter(vec![FullBackup {
-                        children: VecDeque::from(vec![DifferentialBackup::default()]),
-                        ..Default::default()
-                    }]),
-                    ..Default::default()
-                },
-                ..Default::default()
-            };
-            assert_eq!(BackupKind::Differential, layout.plan_backup_kind(Retention::new(1, 1)));
+        fn can_select_files_by_path() {
+            let selection = FileSelection::path(&StrictPath::from("/saves/[slot]"));
+
+            assert!(selection.matches(&StrictPath::from("/saves/[slot]")));
+            assert!(selection.matches(&StrictPath::from("/saves/[slot]/file.sav")));
+            assert!(!selection.matches(&StrictPath::from("/saves/s")));
+            assert!(!selection.matches(&StrictPath::from("/saves/other/file.sav")));
         }
 
         #[test]
-        fn can_plan_backup_kind_when_multiple_full_with_differential_room_remaining() {
-            let layout = GameLayout {
-                mapping: IndividualMapping {
-                    backups: VecDeque::from_iter(vec![
-                        FullBackup {
-                            children: VecDeque::from(vec![
-                                DifferentialBackup::default(),
-                                DifferentialBackup::default(),
-                            ]),
-                            ..Default::default()
+        fn can_scan_game_for_restoration_with_registry() {
+            let mut layout =
+                BackupLayout::new(StrictPath::new(format!("{}/tests/backup", repo()))).game_layout("game3");
+            if cfg!(target_os = "windows") {
+                assert_eq!(
+                    ScanInfo {
+                        game_name: s("game3"),
+                        found_files: Default::default(),
+                        found_registry_keys: hash_map! {
+                            "HKEY_CURRENT_USER/Software/Ludusavi/game3".into(): ScannedRegistry::new().change_as(ScanChange::Same)
+                                .with_value_same("binary")
+                                .with_value_same("dword")
+                                .with_value_same("expandSz")
+                                .with_value_same("multiSz")
+                                .with_value_same("qword")
+                                .with_value_same("sz")
                         },
-                        FullBackup {
-                            children: VecDeque::from(vec![DifferentialBackup::default()]),
+                        wine_prefixes: vec![],
+                        available_backups: vec![Backup::Full(FullBackup {
+                            name: ".".to_string(),
+                            when: now(),
+                            registry: IndividualMappingRegistry {
+                                hash: Some("4e2cab4b4e3ab853e5767fae35f317c26c655c52".into()),
+                                wine_prefixes: vec![],
+                            },
                             ..Default::default()
-                        },
-                    ]),
-                    ..Default::default()
-                },
-                ..Default::default()
-            };
-            assert_eq!(BackupKind::Differential, layout.plan_backup_kind(Retention::new(2, 2)));
-        }
-
-        #[test]
-        fn can_plan_backup_kind_when_multiple_full_with_differential_at_limit() {
-            let layout = GameLayout {
-                mapping: IndividualMapping {
-                    backups: VecDeque::from_iter(vec![
-                        FullBackup {
-                            children: VecDeque::from(vec![
-                                DifferentialBackup::default(),
-                                DifferentialBackup::default(),
-                            ]),
+                        })],
+                        backup: Some(Backup::Full(FullBackup {
+                            name: ".".to_string(),
+                            when: now(),
+                            registry: IndividualMappingRegistry {
+                                hash: Some("4e2cab4b4e3ab853e5767fae35f317c26c655c52".into()),
+                                wine_prefixes: vec![],
+                            },
                             ..Default::default()
-                        },
-                        FullBackup {
-                            children: VecDeque::from(vec![
-                                DifferentialBackup::default(),
-                                DifferentialBackup::default(),
-                            ]),
+                        })),
+                        has_backups: true,
+                    },
+                    layout.scan_for_restoration(
+                        "game3",
+                        &BackupId::Latest,
+                        &[],
+                        false,
+                        &Default::default(),
+                        &Default::default(),
+                        &[]
+                    ),
+                );
+            } else {
+                assert_eq!(
+                    ScanInfo {
+                        game_name: s("game3"),
+                        found_files: Default::default(),
+                        found_registry_keys: Default::default(),
+                        wine_prefixes: vec![],
+                        available_backups: vec![Backup::Full(FullBackup {
+                            name: ".".to_string(),
+                            when: now(),
+                            registry: IndividualMappingRegistry {
+                                hash: Some("4e2cab4b4e3ab853e5767fae35f317c26c655c52".into()),
+                                wine_prefixes: vec![],
+                            },
                             ..Default::default()
-                        },
-                    ]),
-                    ..Default::default()
-                },
-                ..Default::default()
-            };
-            assert_eq!(BackupKind::Full, layout.plan_backup_kind(Retention::new(2, 2)));
-        }
-
-        #[test]
-        fn can_plan_backup_kind_when_single_full_with_differential_at_limit_but_locked() {
-            let layout = GameLayout {
-                mapping: IndividualMapping {
-                    backups: VecDeque::from_iter(vec![FullBackup {
-                        children: VecDeque::from(vec![
-                            DifferentialBackup::default(),
-                            DifferentialBackup {
-                                locked: true,
-                                ..Default::default()
+                        })],
+                        backup: Some(Backup::Full(FullBackup {
+                            name: ".".to_string(),
+                            when: now(),
+                            registry: IndividualMappingRegistry {
+                                hash: Some("4e2cab4b4e3ab853e5767fae35f317c26c655c52".into()),
+                                wine_prefixes: vec![],
                             },
-                        ]),
-                        ..Default::default()
-                    }]),
-                    ..Default::default()
-                },
-                ..Default::default()
-            };
-            assert_eq!(BackupKind::Differential, layout.plan_backup_kind(Retention::new(1, 2)));
-        }
-
-        #[test]
-        fn can_plan_full_backup_with_files() {
-            let scan = ScanInfo {
-                found_files: hash_map! {
-                    repo_file("new").into(): ScannedFile::with_change(1, "n", ScanChange::New),
-                    repo_file("different").into(): ScannedFile::with_change(2, "d", ScanChange::Different),
-                    repo_file("removed").into(): ScannedFile::with_change(3, "r", ScanChange::Removed),
-                    repo_file("same").into(): ScannedFile::with_change(5, "s", ScanChange::Same),
-                    repo_file("unknown").into(): ScannedFile::with_change(6, "u", ScanChange::Unknown),
-                },
-                ..Default::default()
-            };
-            let layout = GameLayout::default();
-            assert_eq!(
-                FullBackup {
-                    name: ".".to_string(),
-                    when: now(),
-                    os: Some(Os::HOST),
-                    files: btree_map! {
-                        StrictPath::new(repo_file("new")).render(): IndividualMappingFile { hash: "n".into(), size: 1 },
-                        StrictPath::new(repo_file("different")).render(): IndividualMappingFile { hash: "d".into(), size: 2 },
-                        StrictPath::new(repo_file("same")).render(): IndividualMappingFile { hash: "s".into(), size: 5 },
+                            ..Default::default()
+                        })),
+                        has_backups: true,
                     },
-                    ..Default::default()
-                },
-                layout.plan_full_backup(&scan, &now(), &BackupFormats::default(), Retention::default()),
-            );
+                    layout.scan_for_restoration(
+                        "game3",
+                        &BackupId::Latest,
+                        &[],
+                        false,
+                        &Default::default(),
+                        &Default::default(),
+                        &[]
+                    ),
+                );
+            }
         }
 
         #[test]
-        #[cfg(target_os = "windows")]
-        fn can_plan_full_backup_with_registry() {
-            use crate::scan::registry::{Entries, Entry, Hives, Keys};
-
-            // `Hives` only loads values that actually exist.
-            // Realistically, if a value is marked as removed`, then it won't exist, so `Hives` won't load it.
-            // The removed value here only makes it into the plan because it actually does exist.
-            let scan = ScanInfo {
-                found_registry_keys: hash_map! {
-                    "HKEY_CURRENT_USER/Software/Ludusavi".into(): ScannedRegistry::new().change_as(ScanChange::New).ignored(),
-                    "HKEY_CURRENT_USER/Software/Ludusavi/game3".into(): ScannedRegistry::new().change_as(ScanChange::Different)
-                        .with_value("binary", ScanChange::New, false)
-                        .with_value("dword", ScanChange::Different, false)
-                        .with_value("expandSz", ScanChange::Removed, false)
-                        .with_value("multiSz", ScanChange::Same, false)
-                        .with_value("qword", ScanChange::Same, true)
-                        .with_value("sz", ScanChange::Unknown, false),
-                    "HKEY_CURRENT_USER/Software/Ludusavi/other".into(): ScannedRegistry::new().change_as(ScanChange::Removed)
-                },
-                ..Default::default()
-            };
-            let layout = GameLayout::default();
-            let hives = Hives(btree_map! {
-                s("HKEY_CURRENT_USER"): Keys(btree_map! {
-                    s("Software\\Ludusavi\\game3"): Entries(btree_map! {
-                        s("sz"): Entry::Sz("foo".into()),
-                        s("multiSz"): Entry::MultiSz("bar".into()),
-                        s("expandSz"): Entry::ExpandSz("baz".into()),
-                        s("dword"): Entry::Dword(1),
-                        s("binary"): Entry::Binary(vec![65]),
-                    }),
-                })
-            });
-            assert_eq!(
-                FullBackup {
-                    name: ".".to_string(),
-                    when: now(),
-                    os: Some(Os::HOST),
-                    registry: IndividualMappingRegistry {
-                        hash: hives.sha1(registry::Format::Reg),
-                    },
+        fn can_validate_a_simple_full_backup_when_valid() {
+            let layout = GameLayout {
+                mapping: IndividualMapping {
+                    drives: drives_x_always(),
+                    backups: VecDeque::from(vec![FullBackup {
+                        name: ".".into(),
+                        files: btree_map! {
+                            mapping_file_key("/file1.txt"): IndividualMappingFile { hash: "3a52ce780950d4d969792a2559cd519d7ee8c727".into(), size: 1 },
+                            mapping_file_key("/file2.txt"): IndividualMappingFile { hash: "9d891e731f75deae56884d79e9816736b7488080".into(), size: 2 },
+                        },
+                        ..Default::default()
+                    }]),
                     ..Default::default()
                 },
-                layout.plan_full_backup(&scan, &now(), &BackupFormats::default(), Retention::default()),
-            );
+                path: StrictPath::new(format!("{}/tests/backup/game1", repo_raw())),
+                ..Default::default()
+            };
+            assert!(layout.validate(BackupId::Latest, false).is_empty());
         }
 
         #[test]
-        fn can_plan_differential_backup_with_files() {
-            let scan = ScanInfo {
-                found_files: hash_map! {
-                    repo_file("new").into(): ScannedFile::with_change(1, "n", ScanChange::New),
-                    repo_file("different").into(): ScannedFile::with_change(2, "d+", ScanChange::Different),
-                    repo_file("removed").into(): ScannedFile::with_change(0, "", ScanChange::Removed),
-                    repo_file("same").into(): ScannedFile::with_change(5, "s", ScanChange::Same),
-                    repo_file("unknown").into(): ScannedFile::with_change(6, "u", ScanChange::Unknown),
-                },
-                ..Default::default()
-            };
+        fn can_validate_a_simple_full_backup_when_invalid() {
             let layout = GameLayout {
                 mapping: IndividualMapping {
-                    drives: drives(),
-                    backups: VecDeque::from_iter(vec![FullBackup {
-                        name: ".".to_string(),
-                        when: past(),
+                    drives: drives_x_always(),
+                    backups: VecDeque::from(vec![FullBackup {
+                        name: ".".into(),
                         files: btree_map! {
-                            StrictPath::new(repo_file("different")).render(): IndividualMappingFile { hash: "d".into(), size: 2 },
-                            StrictPath::new(repo_file("removed")).render(): IndividualMappingFile { hash: "r".into(), size: 3 },
-                            StrictPath::new(repo_file("same")).render(): IndividualMappingFile { hash: "s".into(), size: 5 },
+                            mapping_file_key("/fake.txt"): IndividualMappingFile { hash: "9d891e731f75deae56884d79e9816736b7488080".into(), size: 2 },
                         },
                         ..Default::default()
                     }]),
                     ..Default::default()
                 },
+                path: StrictPath::new(format!("{}/tests/backup/game1", repo_raw())),
                 ..Default::default()
             };
-            assert_eq!(
-                DifferentialBackup {
-                    name: format!("backup-{}-diff", now_str()),
-                    when: now(),
-                    os: Some(Os::HOST),
-                    files: btree_map! {
-                        StrictPath::new(repo_file("new")).render(): Some(IndividualMappingFile { hash: "n".into(), size: 1 }),
-                        StrictPath::new(repo_file("different")).render(): Some(IndividualMappingFile { hash: "d+".into(), size: 2 }),
-                        StrictPath::new(repo_file("removed")).render(): None,
-                    },
-                    registry: None,
-                    ..Default::default()
-                },
-                layout.plan_differential_backup(&scan, &now(), &BackupFormats::default(), Retention::default()),
-            );
+            assert!(!layout.validate(BackupId::Latest, false).is_empty());
         }
 
         #[test]
-        fn can_plan_second_differential_backup_with_different_ignored_files() {
-            let scan = ScanInfo {
-                found_files: hash_map! {
-                    // Ignored in first differential backup:
-                    repo_file("file1").into(): ScannedFile::with_change(1, "1", ScanChange::New).ignored(),
-                    // Newly ignored:
-                    repo_file("file2").into(): ScannedFile::with_change(2, "2", ScanChange::Same).ignored(),
-                    // Just here to keep the backup from being inert (all ignores):
-                    repo_file("file3").into(): ScannedFile::with_change(3, "3", ScanChange::Same),
-                },
-                ..Default::default()
-            };
+        fn can_validate_a_simple_diff_backup_when_valid() {
             let layout = GameLayout {
                 mapping: IndividualMapping {
-                    drives: drives(),
-                    backups: VecDeque::from_iter(vec![FullBackup {
-                        name: ".".to_string(),
-                        when: past(),
+                    drives: drives_x_always(),
+                    backups: VecDeque::from(vec![FullBackup {
+                        name: ".".into(),
                         files: btree_map! {
-                            StrictPath::new(repo_file("file1")).render(): IndividualMappingFile { hash: "1".into(), size: 1 },
-                            StrictPath::new(repo_file("file2")).render(): IndividualMappingFile { hash: "2".into(), size: 2 },
-                            StrictPath::new(repo_file("file3")).render(): IndividualMappingFile { hash: "3".into(), size: 3 },
+                            mapping_file_key("/file1.txt"): IndividualMappingFile { hash: "3a52ce780950d4d969792a2559cd519d7ee8c727".into(), size: 1 },
+                            mapping_file_key("/file2.txt"): IndividualMappingFile { hash: "9d891e731f75deae56884d79e9816736b7488080".into(), size: 2 },
                         },
-                        children: VecDeque::from([DifferentialBackup {
-                            name: format!("backup-{}-diff", now_str()),
-                            when: now(),
-                            os: Some(Os::HOST),
+                        children: VecDeque::from(vec![DifferentialBackup {
+                            name: ".".into(),
                             files: btree_map! {
-                                StrictPath::new(repo_file("file1")).render(): None,
+                                mapping_file_key("/file1.txt"): None,
+                                mapping_file_key("/file2.txt"): Some(IndividualMappingFile { hash: "9d891e731f75deae56884d79e9816736b7488080".into(), size: 2 }),
                             },
                             ..Default::default()
                         }]),
@@ -2659,317 +6974,327 @@ mod tests {
                     }]),
                     ..Default::default()
                 },
+                path: StrictPath::new(format!("{}/tests/backup/game1", repo_raw())),
                 ..Default::default()
             };
-            assert_eq!(
-                DifferentialBackup {
-                    name: format!("backup-{}-diff", now_str()),
-                    when: now(),
-                    os: Some(Os::HOST),
-                    files: btree_map! {
-                        // This matches the latest composite,
-                        // but we have to reiterate this in the new differential:
-                        StrictPath::new(repo_file("file1")).render(): None,
-                        // New ignore:
-                        StrictPath::new(repo_file("file2")).render(): None,
-                    },
-                    registry: None,
-                    ..Default::default()
-                },
-                layout.plan_differential_backup(&scan, &now(), &BackupFormats::default(), Retention::default()),
-            );
+            assert!(layout.validate(BackupId::Latest, false).is_empty());
         }
 
         #[test]
-        #[cfg(target_os = "windows")]
-        fn can_plan_differential_backup_with_registry_new() {
-            use crate::scan::registry::{Entries, Hives, Keys};
-
-            let scan = ScanInfo {
-                found_registry_keys: hash_map! {
-                    "HKEY_CURRENT_USER/Software/Ludusavi/other".into(): ScannedRegistry::new().change_as(ScanChange::New)
+        fn can_validate_a_simple_diff_backup_when_invalid() {
+            let layout = GameLayout {
+                mapping: IndividualMapping {
+                    drives: drives_x_always(),
+                    backups: VecDeque::from(vec![FullBackup {
+                        name: ".".into(),
+                        files: btree_map! {
+                            mapping_file_key("/file1.txt"): IndividualMappingFile { hash: "3a52ce780950d4d969792a2559cd519d7ee8c727".into(), size: 1 },
+                            mapping_file_key("/file2.txt"): IndividualMappingFile { hash: "9d891e731f75deae56884d79e9816736b7488080".into(), size: 2 },
+                        },
+                        children: VecDeque::from(vec![DifferentialBackup {
+                            name: ".".into(),
+                            files: btree_map! {
+                                mapping_file_key("/fake.txt"): Some(IndividualMappingFile { hash: "9d891e731f75deae56884d79e9816736b7488080".into(), size: 2 }),
+                            },
+                            ..Default::default()
+                        }]),
+                        ..Default::default()
+                    }]),
+                    ..Default::default()
                 },
+                path: StrictPath::new(format!("{}/tests/backup/game1", repo_raw())),
                 ..Default::default()
             };
+            assert!(!layout.validate(BackupId::Latest, false).is_empty());
+        }
+
+        #[test]
+        fn can_validate_a_zip_full_backup_when_valid() {
             let layout = GameLayout {
                 mapping: IndividualMapping {
-                    backups: VecDeque::from_iter(vec![FullBackup {
-                        name: ".".to_string(),
-                        when: past(),
-                        registry: IndividualMappingRegistry { hash: None },
+                    drives: drives_x_always(),
+                    backups: VecDeque::from(vec![FullBackup {
+                        name: "test.zip".into(),
+                        files: btree_map! {
+                            mapping_file_key("/file1.txt"): IndividualMappingFile { hash: "3a52ce780950d4d969792a2559cd519d7ee8c727".into(), size: 1 },
+                            mapping_file_key("/file2.txt"): IndividualMappingFile { hash: "9d891e731f75deae56884d79e9816736b7488080".into(), size: 2 },
+                        },
                         ..Default::default()
                     }]),
                     ..Default::default()
                 },
+                path: StrictPath::new(format!("{}/tests/backup/game1-zipped", repo_raw())),
                 ..Default::default()
             };
-            let hives = Hives(btree_map! {
-                s("HKEY_CURRENT_USER"): Keys(btree_map! {
-                    s("Software\\Ludusavi\\other"): Entries::default()
-                })
-            });
-            assert_eq!(
-                DifferentialBackup {
-                    name: format!("backup-{}-diff", now_str()),
-                    when: now(),
-                    os: Some(Os::HOST),
-                    registry: Some(IndividualMappingRegistry {
-                        hash: hives.sha1(registry::Format::Reg),
-                    }),
-                    ..Default::default()
-                },
-                layout.plan_differential_backup(&scan, &now(), &BackupFormats::default(), Retention::default()),
-            );
+            assert!(layout.validate(BackupId::Latest, false).is_empty());
         }
 
         #[test]
-        #[cfg(target_os = "windows")]
-        fn can_plan_differential_backup_with_registry_changed() {
-            use crate::scan::registry::{Entries, Hives, Keys};
-
-            let scan = ScanInfo {
-                found_registry_keys: hash_map! {
-                    "HKEY_CURRENT_USER/Software/Ludusavi/other".into(): ScannedRegistry::new().change_as(ScanChange::Different)
-                        .with_value("removed", ScanChange::Removed, false)
-                        // Fake registry values are ignored because `Hives` re-reads the actual registry.
-                        .with_value("fake", ScanChange::New, false)
+        fn can_validate_a_zip_full_backup_when_invalid() {
+            let layout = GameLayout {
+                mapping: IndividualMapping {
+                    drives: drives_x_always(),
+                    backups: VecDeque::from(vec![FullBackup {
+                        name: "test.zip".into(),
+                        files: btree_map! {
+                            mapping_file_key("/fake.txt"): IndividualMappingFile { hash: "9d891e731f75deae56884d79e9816736b7488080".into(), size: 2 },
+                        },
+                        ..Default::default()
+                    }]),
+                    ..Default::default()
                 },
+                path: StrictPath::new(format!("{}/tests/backup/game1-zipped", repo_raw())),
                 ..Default::default()
             };
+            assert!(!layout.validate(BackupId::Latest, false).is_empty());
+        }
+
+        #[test]
+        fn can_validate_a_zip_diff_backup_when_valid() {
             let layout = GameLayout {
                 mapping: IndividualMapping {
-                    backups: VecDeque::from_iter(vec![FullBackup {
-                        name: ".".to_string(),
-                        when: past(),
-                        registry: IndividualMappingRegistry {
-                            hash: Some("foo".into()),
+                    drives: drives_x_always(),
+                    backups: VecDeque::from(vec![FullBackup {
+                        name: "test.zip".into(),
+                        files: btree_map! {
+                            mapping_file_key("/file1.txt"): IndividualMappingFile { hash: "3a52ce780950d4d969792a2559cd519d7ee8c727".into(), size: 1 },
+                            mapping_file_key("/file2.txt"): IndividualMappingFile { hash: "9d891e731f75deae56884d79e9816736b7488080".into(), size: 2 },
                         },
+                        children: VecDeque::from(vec![DifferentialBackup {
+                            name: "test.zip".into(),
+                            files: btree_map! {
+                                mapping_file_key("/file1.txt"): None,
+                                mapping_file_key("/file2.txt"): Some(IndividualMappingFile { hash: "9d891e731f75deae56884d79e9816736b7488080".into(), size: 2 }),
+                            },
+                            ..Default::default()
+                        }]),
                         ..Default::default()
                     }]),
                     ..Default::default()
                 },
+                path: StrictPath::new(format!("{}/tests/backup/game1-zipped", repo_raw())),
                 ..Default::default()
             };
-            let hives = Hives(btree_map! {
-                s("HKEY_CURRENT_USER"): Keys(btree_map! {
-                    s("Software\\Ludusavi\\other"): Entries::default()
-                })
-            });
-            assert_eq!(
-                DifferentialBackup {
-                    name: format!("backup-{}-diff", now_str()),
-                    when: now(),
-                    os: Some(Os::HOST),
-                    registry: Some(IndividualMappingRegistry {
-                        hash: hives.sha1(registry::Format::Reg),
-                    }),
-                    ..Default::default()
-                },
-                layout.plan_differential_backup(&scan, &now(), &BackupFormats::default(), Retention::default()),
-            );
+            assert!(layout.validate(BackupId::Latest, false).is_empty());
         }
 
         #[test]
-        #[cfg(target_os = "windows")]
-        fn can_plan_differential_backup_with_registry_unchanged() {
-            use crate::scan::registry::{Entries, Hives, Keys};
-
-            let scan = ScanInfo {
-                found_registry_keys: hash_map! {
-                    "HKEY_CURRENT_USER/Software/Ludusavi/other".into(): ScannedRegistry::new().change_as(ScanChange::Same)
+        fn can_load_stored_files_from_a_zip_diff_backup() {
+            let layout = GameLayout {
+                mapping: IndividualMapping {
+                    drives: drives_x_always(),
+                    backups: VecDeque::from(vec![FullBackup {
+                        name: "test.zip".into(),
+                        files: btree_map! {
+                            mapping_file_key("/file1.txt"): IndividualMappingFile { hash: "3a52ce780950d4d969792a2559cd519d7ee8c727".into(), size: 1 },
+                            mapping_file_key("/file2.txt"): IndividualMappingFile { hash: "9d891e731f75deae56884d79e9816736b7488080".into(), size: 2 },
+                        },
+                        children: VecDeque::from(vec![DifferentialBackup {
+                            name: "test.zip".into(),
+                            files: btree_map! {
+                                mapping_file_key("/file1.txt"): None,
+                                mapping_file_key("/file2.txt"): Some(IndividualMappingFile { hash: "9d891e731f75deae56884d79e9816736b7488080".into(), size: 2 }),
+                            },
+                            ..Default::default()
+                        }]),
+                        ..Default::default()
+                    }]),
+                    ..Default::default()
                 },
+                path: StrictPath::new(format!("{}/tests/backup/game1-zipped", repo_raw())),
                 ..Default::default()
             };
-            let hives = Hives(btree_map! {
-                s("HKEY_CURRENT_USER"): Keys(btree_map! {
-                    s("Software\\Ludusavi\\other"): Entries::default()
+            let (full, diff) = layout.find_by_id(&BackupId::Latest).unwrap();
+            let mut stored = BTreeMap::new();
+            layout
+                .read_stored_files(GameLayout::stored_file_sources(full, diff), |file, x| {
+                    stored.insert(file, x);
+                    Ok(())
                 })
-            });
+                .unwrap();
+
+            assert_eq!(vec![&mapping_file_key("/file2.txt")], stored.keys().collect::<Vec<_>>());
+            assert_eq!(2, stored[&mapping_file_key("/file2.txt")].content.len());
+        }
+
+        #[test]
+        fn can_load_stored_files_from_a_tar_backup() {
             let layout = GameLayout {
                 mapping: IndividualMapping {
-                    backups: VecDeque::from_iter(vec![FullBackup {
-                        name: ".".to_string(),
-                        when: past(),
-                        registry: IndividualMappingRegistry {
-                            hash: hives.sha1(registry::Format::Reg),
+                    drives: drives_x_always(),
+                    backups: VecDeque::from(vec![FullBackup {
+                        name: "test.tar.zst".into(),
+                        files: btree_map! {
+                            mapping_file_key("/file1.txt"): IndividualMappingFile { hash: "3a52ce780950d4d969792a2559cd519d7ee8c727".into(), size: 1 },
+                            mapping_file_key("/file2.txt"): IndividualMappingFile { hash: "9d891e731f75deae56884d79e9816736b7488080".into(), size: 2 },
                         },
                         ..Default::default()
                     }]),
                     ..Default::default()
                 },
+                path: StrictPath::new(format!("{}/tests/backup/game1-zipped", repo_raw())),
                 ..Default::default()
             };
+            let (full, diff) = layout.find_by_id(&BackupId::Latest).unwrap();
+            let mut stored = BTreeMap::new();
+            layout
+                .read_stored_files(GameLayout::stored_file_sources(full, diff), |file, x| {
+                    stored.insert(file, x);
+                    Ok(())
+                })
+                .unwrap();
+
             assert_eq!(
-                DifferentialBackup {
-                    name: format!("backup-{}-diff", now_str()),
-                    when: now(),
-                    os: Some(Os::HOST),
-                    registry: None,
-                    ..Default::default()
-                },
-                layout.plan_differential_backup(&scan, &now(), &BackupFormats::default(), Retention::default()),
+                vec![
+                    (&mapping_file_key("/file1.txt"), 1),
+                    (&mapping_file_key("/file2.txt"), 2)
+                ],
+                stored.iter().map(|(k, v)| (k, v.content.len())).collect::<Vec<_>>(),
             );
         }
 
         #[test]
-        #[cfg(target_os = "windows")]
-        fn can_plan_differential_backup_with_registry_removed() {
-            let scan = ScanInfo {
-                found_registry_keys: hash_map! {
-                    "HKEY_CURRENT_USER/Software/Ludusavi/other".into(): ScannedRegistry::new().change_as(ScanChange::Removed)
-                },
-                ..Default::default()
-            };
+        fn cannot_load_stored_files_when_content_does_not_match() {
             let layout = GameLayout {
                 mapping: IndividualMapping {
-                    backups: VecDeque::from_iter(vec![FullBackup {
-                        name: ".".to_string(),
-                        when: past(),
-                        registry: IndividualMappingRegistry {
-                            hash: Some("foo".into()),
+                    drives: drives_x_always(),
+                    backups: VecDeque::from(vec![FullBackup {
+                        name: "test.zip".into(),
+                        files: btree_map! {
+                            mapping_file_key("/file1.txt"): IndividualMappingFile { hash: "wrong".into(), size: 1 },
                         },
                         ..Default::default()
                     }]),
                     ..Default::default()
                 },
+                path: StrictPath::new(format!("{}/tests/backup/game1-zipped", repo_raw())),
                 ..Default::default()
             };
+            let (full, diff) = layout.find_by_id(&BackupId::Latest).unwrap();
+
             assert_eq!(
-                DifferentialBackup {
-                    name: format!("backup-{}-diff", now_str()),
-                    when: now(),
-                    os: Some(Os::HOST),
-                    registry: Some(IndividualMappingRegistry { hash: None }),
-                    ..Default::default()
-                },
-                layout.plan_differential_backup(&scan, &now(), &BackupFormats::default(), Retention::default()),
+                Some(Error::InvalidBackups),
+                layout
+                    .read_stored_files(GameLayout::stored_file_sources(full, diff), |_, _| Ok(()))
+                    .err()
             );
         }
 
         #[test]
-        fn can_forget_excess_backups_without_locks() {
+        fn cannot_consolidate_a_tar_backup() {
             let mut layout = GameLayout {
                 mapping: IndividualMapping {
-                    backups: VecDeque::from_iter(vec![
-                        FullBackup {
-                            name: "1".to_string(),
-                            children: VecDeque::from_iter(vec![DifferentialBackup {
-                                name: "1-a".to_string(),
-                                ..Default::default()
-                            }]),
-                            ..Default::default()
-                        },
-                        FullBackup {
-                            name: "2".to_string(),
-                            children: VecDeque::from_iter(vec![
-                                DifferentialBackup {
-                                    name: "2-a".to_string(),
-                                    ..Default::default()
-                                },
-                                DifferentialBackup {
-                                    name: "2-b".to_string(),
-                                    ..Default::default()
-                                },
-                            ]),
+                    drives: drives_x_always(),
+                    backups: VecDeque::from(vec![FullBackup {
+                        name: "test.tar.zst".into(),
+                        children: VecDeque::from(vec![DifferentialBackup {
+                            name: "test.tar.zst".into(),
                             ..Default::default()
-                        },
-                    ]),
+                        }]),
+                        ..Default::default()
+                    }]),
                     ..Default::default()
                 },
+                path: StrictPath::new(format!("{}/tests/backup/game1-zipped", repo_raw())),
                 ..Default::default()
             };
 
-            layout.forget_excess_backups(Retention::new(1, 1));
             assert_eq!(
-                VecDeque::from_iter(vec![FullBackup {
-                    name: "2".to_string(),
-                    children: VecDeque::from_iter(vec![DifferentialBackup {
-                        name: "2-b".to_string(),
+                Err(Error::UnsupportedBackupFormat {
+                    format: BackupFormat::TarZstd
+                }),
+                layout.consolidate(&BackupId::Latest, &now(), &Default::default(), Retention::default()),
+            );
+        }
+
+        #[test]
+        fn does_not_consolidate_a_full_backup() {
+            let mut layout = GameLayout {
+                mapping: IndividualMapping {
+                    drives: drives_x_always(),
+                    backups: VecDeque::from(vec![FullBackup {
+                        name: "test.zip".into(),
                         ..Default::default()
                     }]),
                     ..Default::default()
-                },]),
-                layout.mapping.backups,
+                },
+                path: StrictPath::new(format!("{}/tests/backup/game1-zipped", repo_raw())),
+                ..Default::default()
+            };
+
+            assert_eq!(
+                Ok(None),
+                layout.consolidate(&BackupId::Latest, &now(), &Default::default(), Retention::default()),
             );
         }
 
         #[test]
-        fn can_forget_excess_backups_without_locks_using_duplicate_name() {
+        fn does_not_consolidate_a_differential_backup_with_newer_siblings() {
             let mut layout = GameLayout {
                 mapping: IndividualMapping {
-                    backups: VecDeque::from_iter(vec![
-                        FullBackup {
-                            name: ".".to_string(),
-                            comment: Some("old".to_string()),
-                            ..Default::default()
-                        },
-                        FullBackup {
-                            name: ".".to_string(),
-                            comment: Some("new".to_string()),
-                            ..Default::default()
-                        },
-                    ]),
+                    drives: drives_x_always(),
+                    backups: VecDeque::from(vec![FullBackup {
+                        name: "backup-1.zip".into(),
+                        children: VecDeque::from(vec![
+                            DifferentialBackup {
+                                name: "backup-2-diff.zip".into(),
+                                ..Default::default()
+                            },
+                            DifferentialBackup {
+                                name: "backup-3-diff.zip".into(),
+                                ..Default::default()
+                            },
+                        ]),
+                        ..Default::default()
+                    }]),
                     ..Default::default()
                 },
+                path: StrictPath::new(format!("{}/tests/backup/game1-zipped", repo_raw())),
                 ..Default::default()
             };
 
-            layout.forget_excess_backups(Retention::new(1, 0));
             assert_eq!(
-                VecDeque::from_iter(vec![FullBackup {
-                    name: ".".to_string(),
-                    comment: Some("new".to_string()),
-                    ..Default::default()
-                },]),
-                layout.mapping.backups,
+                Err(Error::NewerBackupsDependOnChain),
+                layout.consolidate(
+                    &BackupId::Named("backup-2-diff.zip".into()),
+                    &now(),
+                    &Default::default(),
+                    Retention::default()
+                ),
             );
+
+            let (full, diff) = layout.find_by_id(&BackupId::Latest).unwrap();
+            assert_eq!("backup-1.zip", full.name);
+            assert_eq!(Some("backup-3-diff.zip"), diff.map(|x| x.name.as_str()));
+            assert_eq!(2, full.children.len());
         }
 
         #[test]
-        fn can_forget_excess_backups_with_locks() {
+        fn inserts_consolidated_backup_before_newer_ones() {
+            let time = |second| {
+                chrono::NaiveDate::from_ymd_opt(2000, 1, 2)
+                    .unwrap()
+                    .and_hms_opt(3, 4, second)
+                    .unwrap()
+                    .and_local_timezone(chrono::Utc)
+                    .unwrap()
+            };
             let mut layout = GameLayout {
                 mapping: IndividualMapping {
-                    backups: VecDeque::from_iter(vec![
-                        FullBackup {
-                            name: "1".to_string(),
-                            locked: true,
-                            children: VecDeque::from_iter(vec![
-                                DifferentialBackup {
-                                    name: "1-a".to_string(),
-                                    ..Default::default()
-                                },
-                                DifferentialBackup {
-                                    name: "1-b".to_string(),
-                                    ..Default::default()
-                                },
-                            ]),
-                            ..Default::default()
-                        },
-                        FullBackup {
-                            name: "2".to_string(),
-                            children: VecDeque::from_iter(vec![
-                                DifferentialBackup {
-                                    name: "2-a".to_string(),
-                                    ..Default::default()
-                                },
-                                DifferentialBackup {
-                                    name: "2-b".to_string(),
-                                    locked: true,
-                                    ..Default::default()
-                                },
-                                DifferentialBackup {
-                                    name: "2-c".to_string(),
-                                    ..Default::default()
-                                },
-                            ]),
-                            ..Default::default()
-                        },
+                    backups: VecDeque::from(vec![
                         FullBackup {
-                            name: "3".to_string(),
+                            name: "backup-1".into(),
+                            when: time(1),
+                            children: VecDeque::from(vec![DifferentialBackup {
+                                name: "backup-2-diff".into(),
+                                when: time(2),
+                                ..Default::default()
+                            }]),
                             ..Default::default()
                         },
                         FullBackup {
-                            name: "4".to_string(),
+                            name: "backup-3".into(),
+                            when: time(3),
                             ..Default::default()
                         },
                     ]),
@@ -2978,463 +7303,432 @@ mod tests {
                 ..Default::default()
             };
 
-            layout.forget_excess_backups(Retention::new(1, 1));
+            layout.insert_full_backup_chronologically(FullBackup {
+                name: "backup-2".into(),
+                when: time(2),
+                ..Default::default()
+            });
+
             assert_eq!(
-                VecDeque::from_iter(vec![
-                    FullBackup {
-                        name: "1".to_string(),
-                        locked: true,
-                        children: VecDeque::from_iter(vec![DifferentialBackup {
-                            name: "1-b".to_string(),
-                            ..Default::default()
-                        }]),
-                        ..Default::default()
-                    },
-                    FullBackup {
-                        name: "2".to_string(),
-                        children: VecDeque::from_iter(vec![
-                            DifferentialBackup {
-                                name: "2-b".to_string(),
-                                locked: true,
-                                ..Default::default()
-                            },
-                            DifferentialBackup {
-                                name: "2-c".to_string(),
-                                ..Default::default()
-                            }
-                        ]),
-                        ..Default::default()
-                    },
-                    FullBackup {
-                        name: "4".to_string(),
-                        ..Default::default()
-                    },
-                ]),
-                layout.mapping.backups,
+                vec!["backup-1", "backup-2", "backup-3"],
+                layout
+                    .mapping
+                    .backups
+                    .iter()
+                    .map(|x| x.name.as_str())
+                    .collect::<Vec<_>>(),
             );
+            assert_eq!("backup-3", layout.find_by_id(&BackupId::Latest).unwrap().0.name);
         }
 
-        fn make_path(file: &str) -> StrictPath {
-            repo_path(&format!("tests/backup/game1/{}", file))
+        fn layout_with_file_changes() -> GameLayout {
+            GameLayout {
+                mapping: IndividualMapping {
+                    drives: drives_x_always(),
+                    backups: VecDeque::from(vec![FullBackup {
+                        name: "backup-1".into(),
+                        when: now(),
+                        files: btree_map! {
+                            mapping_file_key("/file1.txt"): IndividualMappingFile { hash: "old".into(), size: 1 },
+                            mapping_file_key("/file2.txt"): IndividualMappingFile { hash: "same".into(), size: 2 },
+                            mapping_file_key("/file3.txt"): IndividualMappingFile { hash: "removed".into(), size: 3 },
+                        },
+                        children: VecDeque::from(vec![DifferentialBackup {
+                            name: "backup-2".into(),
+                            when: now(),
+                            files: btree_map! {
+                                mapping_file_key("/file1.txt"): Some(IndividualMappingFile { hash: "new".into(), size: 4 }),
+                                mapping_file_key("/file3.txt"): None,
+                                mapping_file_key("/file4.txt"): Some(IndividualMappingFile { hash: "added".into(), size: 5 }),
+                            },
+                            ..Default::default()
+                        }]),
+                        ..Default::default()
+                    }]),
+                    ..Default::default()
+                },
+                ..Default::default()
+            }
         }
 
-        fn make_restorable_path(backup: &str, file: &str) -> StrictPath {
-            StrictPath::relative(
-                format!(
-                    "{backup}/drive-{}/{file}",
-                    if cfg!(target_os = "windows") { "X" } else { "0" }
-                ),
-                Some(repo_file_raw("tests/backup/game1")),
-            )
-        }
+        #[test]
+        fn can_find_latest_pre_restore_snapshot() {
+            let later = now() + chrono::Duration::hours(1);
+            let layout = GameLayout {
+                mapping: IndividualMapping {
+                    backups: VecDeque::from(vec![
+                        FullBackup {
+                            name: "backup-1-pre-restore".into(),
+                            when: now(),
+                            comment: Some("pre-restore 2000-01-02".into()),
+                            pre_restore: true,
+                            ..Default::default()
+                        },
+                        FullBackup {
+                            name: "backup-2-pre-restore".into(),
+                            when: later,
+                            comment: Some("pre-restore 2000-01-02".into()),
+                            pre_restore: true,
+                            ..Default::default()
+                        },
+                        FullBackup {
+                            name: "backup-3".into(),
+                            when: later,
+                            comment: Some("regular".into()),
+                            ..Default::default()
+                        },
+                    ]),
+                    ..Default::default()
+                },
+                ..Default::default()
+            };
 
-        fn make_restorable_path_zip(file: &str) -> StrictPath {
-            StrictPath::relative(
-                format!("drive-{}/{file}", if cfg!(target_os = "windows") { "X" } else { "0" }),
-                None,
-            )
+            assert_eq!(
+                Some((BackupId::Named("backup-2-pre-restore".into()), later)),
+                layout.latest_pre_restore_snapshot(),
+            );
+            assert_eq!(None, layout_with_file_changes().latest_pre_restore_snapshot());
         }
 
         #[test]
-        fn can_report_restorable_files_for_full_backup_in_simple_format() {
+        fn can_redirect_wine_prefixes_for_restore() {
             let layout = GameLayout {
-                path: StrictPath::new(format!("{}/tests/backup/game1", repo_raw())),
                 mapping: IndividualMapping {
-                    name: "game1".to_string(),
-                    drives: drives_x(),
                     backups: VecDeque::from(vec![FullBackup {
                         name: "backup-1".into(),
-                        when: past(),
-                        files: btree_map! {
-                            mapping_file_key("/file1.txt"): IndividualMappingFile { hash: "old".into(), size: 1 },
-                            mapping_file_key("/file2.txt"): IndividualMappingFile { hash: "old".into(), size: 2 },
+                        registry: IndividualMappingRegistry {
+                            hash: Some("foo".into()),
+                            wine_prefixes: vec!["/old/prefix".into()],
                         },
                         ..Default::default()
                     }]),
+                    ..Default::default()
                 },
+                ..Default::default()
             };
+            let redirects = vec![RedirectConfig {
+                kind: crate::resource::config::RedirectKind::Restore,
+                source: StrictPath::new("/old".into()),
+                target: StrictPath::new("/new".into()),
+            }];
+
             assert_eq!(
-                hash_map! {
-                    make_restorable_path("backup-1", "file1.txt"): ScannedFile {
-                        size: 1,
-                        hash: "old".into(),
-                        original_path: Some(make_original_path("/file1.txt")),
-                        ignored: false,
-                        change: Default::default(),
-                        container: None,
-                        redirected: None,
-                    },
-                    make_restorable_path("backup-1", "file2.txt"): ScannedFile {
-                        size: 2,
-                        hash: "old".into(),
-                        original_path: Some(make_original_path("/file2.txt")),
-                        ignored: false,
-                        change: Default::default(),
-                        container: None,
-                        redirected: None,
-                    },
-                },
-                layout.restorable_files(&BackupId::Latest, ScanKind::Backup, &[], false, &Default::default()),
+                vec![StrictPath::new("/new/prefix".into())],
+                layout.restorable_wine_prefixes(&BackupId::Latest, &redirects, false),
+            );
+            assert_eq!(
+                vec![StrictPath::new("/old/prefix".into())],
+                layout.restorable_wine_prefixes(&BackupId::Latest, &[], false),
             );
         }
 
         #[test]
-        fn can_report_restorable_files_for_full_backup_in_zip_format() {
-            let layout = GameLayout {
-                path: StrictPath::new(format!("{}/tests/backup/game1", repo_raw())),
+        fn pre_restore_snapshots_stay_out_of_the_regular_chain() {
+            let later = now() + chrono::Duration::hours(1);
+            let mut layout = GameLayout {
                 mapping: IndividualMapping {
-                    name: "game1".to_string(),
-                    drives: drives_x(),
                     backups: VecDeque::from(vec![FullBackup {
-                        name: "backup-1.zip".into(),
-                        when: past(),
-                        files: btree_map! {
-                            mapping_file_key("/file1.txt"): IndividualMappingFile { hash: "old".into(), size: 1 },
-                            mapping_file_key("/file2.txt"): IndividualMappingFile { hash: "old".into(), size: 2 },
-                        },
+                        name: "backup-1".into(),
+                        when: now(),
                         ..Default::default()
                     }]),
+                    ..Default::default()
                 },
+                ..Default::default()
             };
+
+            // A restore with `--snapshot`.
+            for when in [now(), later] {
+                layout.insert_backup(Backup::Full(FullBackup {
+                    name: format!("backup-{}-pre-restore", when.timestamp()),
+                    when,
+                    pre_restore: true,
+                    ..Default::default()
+                }));
+                layout.forget_excess_snapshots();
+            }
             assert_eq!(
-                hash_map! {
-                    make_restorable_path_zip("file1.txt"): ScannedFile {
-                        size: 1,
-                        hash: "old".into(),
-                        original_path: Some(make_original_path("/file1.txt")),
-                        ignored: false,
-                        change: Default::default(),
-                        container: Some(make_path("backup-1.zip")),
-                        redirected: None,
-                    },
-                    make_restorable_path_zip("file2.txt"): ScannedFile {
-                        size: 2,
-                        hash: "old".into(),
-                        original_path: Some(make_original_path("/file2.txt")),
-                        ignored: false,
-                        change: Default::default(),
-                        container: Some(make_path("backup-1.zip")),
-                        redirected: None,
-                    },
-                },
-                layout.restorable_files(&BackupId::Latest, ScanKind::Backup, &[], false, &Default::default()),
+                Some((
+                    BackupId::Named(format!("backup-{}-pre-restore", later.timestamp())),
+                    later
+                )),
+                layout.latest_pre_restore_snapshot(),
+            );
+            assert_eq!(2, layout.mapping.backups.len());
+
+            // A default restore then uses the regular backup rather than the snapshot.
+            let (full, diff) = layout.find_by_id(&BackupId::Latest).unwrap();
+            assert_eq!(("backup-1", None), (full.name.as_str(), diff));
+
+            // New differential backups attach to the regular backup,
+            // and the snapshot doesn't count toward retention.
+            let retention = Retention::new(1, 1);
+            assert_eq!(BackupKind::Differential, layout.plan_backup_kind(retention));
+            layout.insert_backup(Backup::Differential(DifferentialBackup {
+                name: "backup-2".into(),
+                when: later,
+                ..Default::default()
+            }));
+            layout.forget_excess_backups(retention, &later);
+            assert_eq!(
+                vec![
+                    "backup-1".to_string(),
+                    format!("backup-{}-pre-restore", later.timestamp())
+                ],
+                layout
+                    .mapping
+                    .backups
+                    .iter()
+                    .map(|x| x.name.clone())
+                    .collect::<Vec<_>>(),
+            );
+            assert_eq!(
+                vec!["backup-2".to_string()],
+                layout.mapping.backups[0]
+                    .children
+                    .iter()
+                    .map(|x| x.name.clone())
+                    .collect::<Vec<_>>(),
             );
         }
 
         #[test]
-        fn can_report_restorable_files_for_differential_backup_in_simple_format() {
-            let layout = GameLayout {
-                path: StrictPath::new(format!("{}/tests/backup/game1", repo_raw())),
-                mapping: IndividualMapping {
-                    name: "game1".to_string(),
-                    drives: drives_x(),
-                    backups: VecDeque::from(vec![FullBackup {
-                        name: "backup-1".into(),
-                        when: past(),
-                        files: btree_map! {
-                            mapping_file_key("/unchanged.txt"): IndividualMappingFile { hash: "old".into(), size: 1 },
-                            mapping_file_key("/changed.txt"): IndividualMappingFile { hash: "old".into(), size: 2 },
-                            mapping_file_key("/delete.txt"): IndividualMappingFile { hash: "old".into(), size: 3 },
-                        },
-                        children: VecDeque::from([DifferentialBackup {
-                            name: "backup-2".into(),
-                            when: past2(),
-                            files: btree_map! {
-                                mapping_file_key("/changed.txt"): Some(IndividualMappingFile { hash: "new".into(), size: 2 }),
-                                mapping_file_key("/delete.txt"): None,
-                                mapping_file_key("/added.txt"): Some(IndividualMappingFile { hash: "new".into(), size: 5 }),
-                            },
-                            ..Default::default()
-                        }]),
-                        ..Default::default()
-                    }]),
+        fn can_resolve_restore_conflicts() {
+            let mut scan = ScanInfo {
+                found_files: hash_map! {
+                    repo_file("conflict-1").into(): ScannedFile::with_change(1, "a", ScanChange::Different).conflicted(),
+                    repo_file("conflict-2").into(): ScannedFile::with_change(1, "b", ScanChange::Different).conflicted(),
+                    repo_file("different").into(): ScannedFile::with_change(1, "c", ScanChange::Different),
                 },
+                ..Default::default()
             };
+            let layout = GameLayout::default();
+
+            let failed = layout.resolve_conflicts(&mut scan.clone(), ConflictPolicy::Overwrite, &now(), |_| {
+                panic!("unexpected prompt")
+            });
+            assert!(failed.is_empty());
+
+            let mut prompted = vec![];
+            let failed = layout.resolve_conflicts(&mut scan, ConflictPolicy::Prompt, &now(), |path| {
+                prompted.push(path.clone());
+                if path.raw().ends_with("conflict-1") {
+                    ConflictPolicy::Skip
+                } else {
+                    ConflictPolicy::Overwrite
+                }
+            });
+            assert!(failed.is_empty());
+            assert_eq!(2, prompted.len());
             assert_eq!(
                 hash_map! {
-                    make_restorable_path("backup-1", "unchanged.txt"): ScannedFile {
-                        size: 1,
-                        hash: "old".into(),
-                        original_path: Some(make_original_path("/unchanged.txt")),
-                        ignored: false,
-                        change: Default::default(),
-                        container: None,
-                        redirected: None,
-                    },
-                    make_restorable_path("backup-2", "changed.txt"): ScannedFile {
-                        size: 2,
-                        hash: "new".into(),
-                        original_path: Some(make_original_path("/changed.txt")),
-                        ignored: false,
-                        change: Default::default(),
-                        container: None,
-                        redirected: None,
-                    },
-                    make_restorable_path("backup-2", "added.txt"): ScannedFile {
-                        size: 5,
-                        hash: "new".into(),
-                        original_path: Some(make_original_path("/added.txt")),
-                        ignored: false,
-                        change: Default::default(),
-                        container: None,
-                        redirected: None,
-                    },
+                    repo_file("conflict-1").into(): ScannedFile::with_change(1, "a", ScanChange::Different).conflicted().ignored(),
+                    repo_file("conflict-2").into(): ScannedFile::with_change(1, "b", ScanChange::Different).conflicted(),
+                    repo_file("different").into(): ScannedFile::with_change(1, "c", ScanChange::Different),
                 },
-                layout.restorable_files(&BackupId::Latest, ScanKind::Backup, &[], false, &Default::default()),
+                scan.found_files,
             );
+
+            let mut scan = ScanInfo {
+                found_files: hash_map! {
+                    repo_file("conflict").into(): ScannedFile::with_change(1, "a", ScanChange::Different).conflicted(),
+                },
+                ..Default::default()
+            };
+            let failed =
+                layout.resolve_conflicts(&mut scan, ConflictPolicy::Skip, &now(), |_| ConflictPolicy::Overwrite);
+            assert!(failed.is_empty());
+            assert!(scan.found_files.values().all(|x| x.ignored));
         }
 
         #[test]
-        fn can_report_restorable_files_for_differential_backup_in_zip_format() {
-            let layout = GameLayout {
-                path: StrictPath::new(format!("{}/tests/backup/game1", repo_raw())),
-                mapping: IndividualMapping {
-                    name: "game1".to_string(),
-                    drives: drives_x(),
-                    backups: VecDeque::from(vec![FullBackup {
-                        name: "backup-1.zip".into(),
-                        when: past(),
-                        files: btree_map! {
-                            mapping_file_key("/unchanged.txt"): IndividualMappingFile { hash: "old".into(), size: 1 },
-                            mapping_file_key("/changed.txt"): IndividualMappingFile { hash: "old".into(), size: 2 },
-                            mapping_file_key("/delete.txt"): IndividualMappingFile { hash: "old".into(), size: 3 },
+        fn can_gather_stats_with_differential_backups() {
+            let later = now() + chrono::Duration::days(31);
+            let mapping = IndividualMapping {
+                backups: VecDeque::from(vec![FullBackup {
+                    name: "backup-1".into(),
+                    when: now(),
+                    files: btree_map! {
+                        mapping_file_key("/file1.txt"): IndividualMappingFile { hash: "a".into(), size: 1 },
+                        mapping_file_key("/file2.txt"): IndividualMappingFile { hash: "b".into(), size: 2 },
+                    },
+                    children: VecDeque::from(vec![
+                        DifferentialBackup {
+                            name: "backup-2".into(),
+                            when: later,
+                            files: btree_map! {
+                                mapping_file_key("/file1.txt"): Some(IndividualMappingFile { hash: "c".into(), size: 4 }),
+                                mapping_file_key("/file2.txt"): None,
+                                mapping_file_key("/file3.txt"): Some(IndividualMappingFile { hash: "a".into(), size: 1 }),
+                            },
+                            ..Default::default()
                         },
-                        children: VecDeque::from([DifferentialBackup {
-                            name: "backup-2.zip".into(),
-                            when: past2(),
+                        DifferentialBackup {
+                            name: "backup-3.dedup".into(),
+                            when: later,
                             files: btree_map! {
-                                mapping_file_key("/changed.txt"): Some(IndividualMappingFile { hash: "new".into(), size: 2 }),
-                                mapping_file_key("/delete.txt"): None,
-                                mapping_file_key("/added.txt"): Some(IndividualMappingFile { hash: "new".into(), size: 5 }),
+                                mapping_file_key("/file4.txt"): Some(IndividualMappingFile { hash: "b".into(), size: 2 }),
                             },
                             ..Default::default()
-                        }]),
-                        ..Default::default()
-                    }]),
-                },
+                        },
+                    ]),
+                    ..Default::default()
+                }]),
+                ..Default::default()
             };
+
+            let mut stats = BackupStats::default();
+            stats.add_mapping(&mapping, &mut HashSet::new());
             assert_eq!(
-                hash_map! {
-                    make_restorable_path_zip("unchanged.txt"): ScannedFile {
-                        size: 1,
-                        hash: "old".into(),
-                        original_path: Some(make_original_path("/unchanged.txt")),
-                        ignored: false,
-                        change: Default::default(),
-                        container: Some(make_path("backup-1.zip")),
-                        redirected: None,
-                    },
-                    make_restorable_path_zip("changed.txt"): ScannedFile {
-                        size: 2,
-                        hash: "new".into(),
-                        original_path: Some(make_original_path("/changed.txt")),
-                        ignored: false,
-                        change: Default::default(),
-                        container: Some(make_path("backup-2.zip")),
-                        redirected: None,
-                    },
-                    make_restorable_path_zip("added.txt"): ScannedFile {
-                        size: 5,
-                        hash: "new".into(),
-                        original_path: Some(make_original_path("/added.txt")),
-                        ignored: false,
-                        change: Default::default(),
-                        container: Some(make_path("backup-2.zip")),
-                        redirected: None,
+                BackupStats {
+                    bytes: 10,
+                    full_backups: 1,
+                    full_bytes: 3,
+                    differential_backups: 2,
+                    differential_bytes: 7,
+                    duplicate_bytes: 1,
+                    oldest: Some(now()),
+                    newest: Some(later),
+                    growth: btree_map! {
+                        s("2000-01"): 3,
+                        s("2000-02"): 7,
                     },
                 },
-                layout.restorable_files(&BackupId::Latest, ScanKind::Backup, &[], false, &Default::default()),
+                stats,
             );
         }
-    }
 
-    mod game_layout {
-        use pretty_assertions::assert_eq;
+        #[test]
+        fn can_find_previous_backup_id() {
+            let layout = layout_with_file_changes();
 
-        use crate::testing::{drives_x_always, drives_x_static, repo_file_raw};
+            assert_eq!(
+                Some(BackupId::Named("backup-1".into())),
+                layout.previous_backup_id(&BackupId::Latest)
+            );
+            assert_eq!(None, layout.previous_backup_id(&BackupId::Named("backup-1".into())));
+            assert_eq!(None, layout.previous_backup_id(&BackupId::Named("unknown".into())));
+        }
 
-        use super::*;
+        #[test]
+        fn can_compare_backups() {
+            let layout = layout_with_file_changes();
 
-        fn now() -> chrono::DateTime<chrono::Utc> {
-            chrono::NaiveDate::from_ymd_opt(2000, 1, 2)
-                .unwrap()
-                .and_hms_opt(3, 4, 5)
-                .unwrap()
-                .and_local_timezone(chrono::Utc)
-                .unwrap()
+            assert_eq!(
+                Some(BackupComparison {
+                    from: Some(BackupReference {
+                        name: "backup-1".into(),
+                        when: now(),
+                    }),
+                    to: BackupReference {
+                        name: "backup-2".into(),
+                        when: now(),
+                    },
+                    files: btree_map! {
+                        mapping_file_key("/file1.txt"): FileComparison {
+                            change: ScanChange::Different,
+                            from: Some(IndividualMappingFile { hash: "old".into(), size: 1 }),
+                            to: Some(IndividualMappingFile { hash: "new".into(), size: 4 }),
+                        },
+                        mapping_file_key("/file3.txt"): FileComparison {
+                            change: ScanChange::Removed,
+                            from: Some(IndividualMappingFile { hash: "removed".into(), size: 3 }),
+                            to: None,
+                        },
+                        mapping_file_key("/file4.txt"): FileComparison {
+                            change: ScanChange::New,
+                            from: None,
+                            to: Some(IndividualMappingFile { hash: "added".into(), size: 5 }),
+                        },
+                    },
+                }),
+                layout.compare_backups(Some(&BackupId::Named("backup-1".into())), &BackupId::Latest),
+            );
+            assert_eq!(
+                None,
+                layout.compare_backups(Some(&BackupId::Named("unknown".into())), &BackupId::Latest),
+            );
         }
 
-        fn restorable_file_simple(backup: &str, file: &str) -> StrictPath {
-            StrictPath::relative(
-                format!(
-                    "{backup}/drive-{}/{file}",
-                    if cfg!(target_os = "windows") { "X" } else { "0" }
-                ),
-                Some(repo_file_raw("tests/backup/game1")),
-            )
+        #[test]
+        fn can_compare_backup_without_previous_backup() {
+            let layout = layout_with_file_changes();
+            let comparison = layout
+                .compare_backups(None, &BackupId::Named("backup-1".into()))
+                .unwrap();
+
+            assert_eq!(None, comparison.from);
+            assert_eq!(
+                vec![ScanChange::New, ScanChange::New, ScanChange::New],
+                comparison.files.values().map(|x| x.change).collect::<Vec<_>>(),
+            );
         }
 
         #[test]
-        fn can_scan_game_for_restoration_with_files() {
-            let mut layout = GameLayout::new(
-                StrictPath::new(format!("{}/tests/backup/game1", repo())),
-                IndividualMapping {
-                    name: "game1".to_string(),
-                    drives: drives_x(),
-                    backups: VecDeque::from(vec![FullBackup {
-                        name: ".".into(),
+        fn can_get_file_history() {
+            let layout = layout_with_file_changes();
+
+            assert_eq!(
+                vec![
+                    FileHistoryEntry {
+                        backup: "backup-1".into(),
                         when: now(),
-                        files: btree_map! {
-                            mapping_file_key("/file1.txt"): IndividualMappingFile { hash: "3a52ce780950d4d969792a2559cd519d7ee8c727".into(), size: 1 },
-                            mapping_file_key("/file2.txt"): IndividualMappingFile { hash: "9d891e731f75deae56884d79e9816736b7488080".into(), size: 2 },
-                        },
-                        ..Default::default()
-                    }]),
-                },
-            );
-            let backups = vec![Backup::Full(FullBackup {
-                name: ".".to_string(),
-                when: now(),
-                files: btree_map! {
-                    mapping_file_key("/file1.txt"): IndividualMappingFile {
-                        hash: "3a52ce780950d4d969792a2559cd519d7ee8c727".into(),
-                        size: 1,
+                        change: ScanChange::New,
+                        hash: Some("removed".into()),
+                        size: Some(3),
                     },
-                    mapping_file_key("/file2.txt"): IndividualMappingFile {
-                        hash: "9d891e731f75deae56884d79e9816736b7488080".into(),
-                        size: 2,
+                    FileHistoryEntry {
+                        backup: "backup-2".into(),
+                        when: now(),
+                        change: ScanChange::Removed,
+                        hash: None,
+                        size: None,
                     },
-                },
-                ..Default::default()
-            })];
-
+                ],
+                layout.file_history(&mapping_file_key("/file3.txt")),
+            );
+            assert_eq!(1, layout.file_history(&mapping_file_key("/file2.txt")).len());
             assert_eq!(
-                ScanInfo {
-                    game_name: s("game1"),
-                    found_files: hash_map! {
-                        restorable_file_simple(".", "file1.txt"): ScannedFile {
-                            size: 1,
-                            hash: "3a52ce780950d4d969792a2559cd519d7ee8c727".into(),
-                            original_path: Some(make_original_path("/file1.txt")),
-                            ignored: false,
-                            change: ScanChange::New,
-                            container: None,
-                            redirected: None,
-                        },
-                        restorable_file_simple(".", "file2.txt"): ScannedFile {
-                            size: 2,
-                            hash: "9d891e731f75deae56884d79e9816736b7488080".into(),
-                            original_path: Some(make_original_path("/file2.txt")),
-                            ignored: false,
-                            change: ScanChange::New,
-                            container: None,
-                            redirected: None,
-                        },
-                    },
-                    found_registry_keys: Default::default(),
-                    available_backups: backups.clone(),
-                    backup: Some(backups[0].clone()),
-                    has_backups: true,
-                },
-                layout.scan_for_restoration(
-                    "game1",
-                    &BackupId::Latest,
-                    &[],
-                    false,
-                    &Default::default(),
-                    &Default::default()
-                ),
+                Vec::<FileHistoryEntry>::new(),
+                layout.file_history(&mapping_file_key("/unknown.txt"))
             );
         }
 
         #[test]
-        fn can_scan_game_for_restoration_with_registry() {
-            let mut layout =
-                BackupLayout::new(StrictPath::new(format!("{}/tests/backup", repo()))).game_layout("game3");
-            if cfg!(target_os = "windows") {
-                assert_eq!(
-                    ScanInfo {
-                        game_name: s("game3"),
-                        found_files: Default::default(),
-                        found_registry_keys: hash_map! {
-                            "HKEY_CURRENT_USER/Software/Ludusavi/game3".into(): ScannedRegistry::new().change_as(ScanChange::Same)
-                                .with_value_same("binary")
-                                .with_value_same("dword")
-                                .with_value_same("expandSz")
-                                .with_value_same("multiSz")
-                                .with_value_same("qword")
-                                .with_value_same("sz")
+        fn can_validate_a_zip_diff_backup_when_invalid() {
+            let layout = GameLayout {
+                mapping: IndividualMapping {
+                    drives: drives_x_always(),
+                    backups: VecDeque::from(vec![FullBackup {
+                        name: "test.zip".into(),
+                        files: btree_map! {
+                            mapping_file_key("/file1.txt"): IndividualMappingFile { hash: "3a52ce780950d4d969792a2559cd519d7ee8c727".into(), size: 1 },
+                            mapping_file_key("/file2.txt"): IndividualMappingFile { hash: "9d891e731f75deae56884d79e9816736b7488080".into(), size: 2 },
                         },
-                        available_backups: vec![Backup::Full(FullBackup {
-                            name: ".".to_string(),
-                            when: now(),
-                            registry: IndividualMappingRegistry {
-                                hash: Some("4e2cab4b4e3ab853e5767fae35f317c26c655c52".into()),
-                            },
-                            ..Default::default()
-                        })],
-                        backup: Some(Backup::Full(FullBackup {
-                            name: ".".to_string(),
-                            when: now(),
-                            registry: IndividualMappingRegistry {
-                                hash: Some("4e2cab4b4e3ab853e5767fae35f317c26c655c52".into()),
-                            },
-                            ..Default::default()
-                        })),
-                        has_backups: true,
-                    },
-                    layout.scan_for_restoration(
-                        "game3",
-                        &BackupId::Latest,
-                        &[],
-                        false,
-                        &Default::default(),
-                        &Default::default()
-                    ),
-                );
-            } else {
-                assert_eq!(
-                    ScanInfo {
-                        game_name: s("game3"),
-                        found_files: Default::default(),
-                        found_registry_keys: Default::default(),
-                        available_backups: vec![Backup::Full(FullBackup {
-                            name: ".".to_string(),
-                            when: now(),
-                            registry: IndividualMappingRegistry {
-                                hash: Some("4e2cab4b4e3ab853e5767fae35f317c26c655c52".into()),
-                            },
-                            ..Default::default()
-                        })],
-                        backup: Some(Backup::Full(FullBackup {
-                            name: ".".to_string(),
-                            when: now(),
-                            registry: IndividualMappingRegistry {
-                                hash: Some("4e2cab4b4e3ab853e5767fae35f317c26c655c52".into())
+                        children: VecDeque::from(vec![DifferentialBackup {
+                            name: "test.zip".into(),
+                            files: btree_map! {
+                                mapping_file_key("/fake.txt"): Some(IndividualMappingFile { hash: "9d891e731f75deae56884d79e9816736b7488080".into(), size: 2 }),
                             },
                             ..Default::default()
-                        })),
-                        has_backups: true,
-                    },
-                    layout.scan_for_restoration(
-                        "game3",
-                        &BackupId::Latest,
-                        &[],
-                        false,
-                        &Default::default(),
-                        &Default::default()
-                    ),
-                );
-            }
+                        }]),
+                        ..Default::default()
+                    }]),
+                    ..Default::default()
+                },
+                path: StrictPath::new(format!("{}/tests/backup/game1-zipped", repo_raw())),
+                ..Default::default()
+            };
+            assert!(!layout.validate(BackupId::Latest, false).is_empty());
         }
 
         #[test]
-        fn can_validate_a_simple_full_backup_when_valid() {
+        fn can_validate_a_tar_full_backup_when_valid() {
             let layout = GameLayout {
                 mapping: IndividualMapping {
                     drives: drives_x_always(),
                     backups: VecDeque::from(vec![FullBackup {
-                        name: ".".into(),
+                        name: "test.tar.zst".into(),
                         files: btree_map! {
                             mapping_file_key("/file1.txt"): IndividualMappingFile { hash: "3a52ce780950d4d969792a2559cd519d7ee8c727".into(), size: 1 },
                             mapping_file_key("/file2.txt"): IndividualMappingFile { hash: "9d891e731f75deae56884d79e9816736b7488080".into(), size: 2 },
@@ -3443,19 +7737,19 @@ mod tests {
                     }]),
                     ..Default::default()
                 },
-                path: StrictPath::new(format!("{}/tests/backup/game1", repo_raw())),
+                path: StrictPath::new(format!("{}/tests/backup/game1-zipped", repo_raw())),
                 ..Default::default()
             };
-            assert!(layout.validate(BackupId::Latest));
+            assert!(layout.validate(BackupId::Latest, false).is_empty());
         }
 
         #[test]
-        fn can_validate_a_simple_full_backup_when_invalid() {
+        fn can_validate_a_tar_full_backup_when_invalid() {
             let layout = GameLayout {
                 mapping: IndividualMapping {
                     drives: drives_x_always(),
                     backups: VecDeque::from(vec![FullBackup {
-                        name: ".".into(),
+                        name: "test.tar.zst".into(),
                         files: btree_map! {
                             mapping_file_key("/fake.txt"): IndividualMappingFile { hash: "9d891e731f75deae56884d79e9816736b7488080".into(), size: 2 },
                         },
@@ -3463,31 +7757,23 @@ mod tests {
                     }]),
                     ..Default::default()
                 },
-                path: StrictPath::new(format!("{}/tests/backup/game1", repo_raw())),
+                path: StrictPath::new(format!("{}/tests/backup/game1-zipped", repo_raw())),
                 ..Default::default()
             };
-            assert!(!layout.validate(BackupId::Latest));
+            assert!(!layout.validate(BackupId::Latest, false).is_empty());
         }
 
         #[test]
-        fn can_validate_a_simple_diff_backup_when_valid() {
+        fn can_validate_a_dedup_full_backup_when_valid() {
             let layout = GameLayout {
                 mapping: IndividualMapping {
                     drives: drives_x_always(),
                     backups: VecDeque::from(vec![FullBackup {
-                        name: ".".into(),
+                        name: "test.dedup".into(),
                         files: btree_map! {
                             mapping_file_key("/file1.txt"): IndividualMappingFile { hash: "3a52ce780950d4d969792a2559cd519d7ee8c727".into(), size: 1 },
                             mapping_file_key("/file2.txt"): IndividualMappingFile { hash: "9d891e731f75deae56884d79e9816736b7488080".into(), size: 2 },
                         },
-                        children: VecDeque::from(vec![DifferentialBackup {
-                            name: ".".into(),
-                            files: btree_map! {
-                                mapping_file_key("/file1.txt"): None,
-                                mapping_file_key("/file2.txt"): Some(IndividualMappingFile { hash: "9d891e731f75deae56884d79e9816736b7488080".into(), size: 2 }),
-                            },
-                            ..Default::default()
-                        }]),
                         ..Default::default()
                     }]),
                     ..Default::default()
@@ -3495,27 +7781,19 @@ mod tests {
                 path: StrictPath::new(format!("{}/tests/backup/game1", repo_raw())),
                 ..Default::default()
             };
-            assert!(layout.validate(BackupId::Latest));
+            assert!(layout.validate(BackupId::Latest, false).is_empty());
         }
 
         #[test]
-        fn can_validate_a_simple_diff_backup_when_invalid() {
+        fn can_validate_a_dedup_full_backup_when_invalid() {
             let layout = GameLayout {
                 mapping: IndividualMapping {
                     drives: drives_x_always(),
                     backups: VecDeque::from(vec![FullBackup {
-                        name: ".".into(),
+                        name: "test.dedup".into(),
                         files: btree_map! {
-                            mapping_file_key("/file1.txt"): IndividualMappingFile { hash: "3a52ce780950d4d969792a2559cd519d7ee8c727".into(), size: 1 },
-                            mapping_file_key("/file2.txt"): IndividualMappingFile { hash: "9d891e731f75deae56884d79e9816736b7488080".into(), size: 2 },
+                            mapping_file_key("/fake.txt"): IndividualMappingFile { hash: "0000000000000000000000000000000000000000".into(), size: 2 },
                         },
-                        children: VecDeque::from(vec![DifferentialBackup {
-                            name: ".".into(),
-                            files: btree_map! {
-                                mapping_file_key("/fake.txt"): Some(IndividualMappingFile { hash: "9d891e731f75deae56884d79e9816736b7488080".into(), size: 2 }),
-                            },
-                            ..Default::default()
-                        }]),
                         ..Default::default()
                     }]),
                     ..Default::default()
@@ -3523,16 +7801,16 @@ mod tests {
                 path: StrictPath::new(format!("{}/tests/backup/game1", repo_raw())),
                 ..Default::default()
             };
-            assert!(!layout.validate(BackupId::Latest));
+            assert!(!layout.validate(BackupId::Latest, false).is_empty());
         }
 
         #[test]
-        fn can_validate_a_zip_full_backup_when_valid() {
+        fn can_validate_a_simple_full_backup_deeply_when_valid() {
             let layout = GameLayout {
                 mapping: IndividualMapping {
                     drives: drives_x_always(),
                     backups: VecDeque::from(vec![FullBackup {
-                        name: "test.zip".into(),
+                        name: ".".into(),
                         files: btree_map! {
                             mapping_file_key("/file1.txt"): IndividualMappingFile { hash: "3a52ce780950d4d969792a2559cd519d7ee8c727".into(), size: 1 },
                             mapping_file_key("/file2.txt"): IndividualMappingFile { hash: "9d891e731f75deae56884d79e9816736b7488080".into(), size: 2 },
@@ -3541,34 +7819,44 @@ mod tests {
                     }]),
                     ..Default::default()
                 },
-                path: StrictPath::new(format!("{}/tests/backup/game1-zipped", repo_raw())),
+                path: StrictPath::new(format!("{}/tests/backup/game1", repo_raw())),
                 ..Default::default()
             };
-            assert!(layout.validate(BackupId::Latest));
+            assert_eq!(Vec::<ValidationIssue>::new(), layout.validate(BackupId::Latest, true));
         }
 
         #[test]
-        fn can_validate_a_zip_full_backup_when_invalid() {
+        fn can_validate_a_simple_full_backup_deeply_when_content_changed() {
             let layout = GameLayout {
                 mapping: IndividualMapping {
                     drives: drives_x_always(),
                     backups: VecDeque::from(vec![FullBackup {
-                        name: "test.zip".into(),
+                        name: ".".into(),
                         files: btree_map! {
-                            mapping_file_key("/fake.txt"): IndividualMappingFile { hash: "9d891e731f75deae56884d79e9816736b7488080".into(), size: 2 },
+                            mapping_file_key("/file1.txt"): IndividualMappingFile { hash: "0000000000000000000000000000000000000000".into(), size: 1 },
+                            mapping_file_key("/file2.txt"): IndividualMappingFile { hash: "9d891e731f75deae56884d79e9816736b7488080".into(), size: 2 },
                         },
                         ..Default::default()
                     }]),
                     ..Default::default()
                 },
-                path: StrictPath::new(format!("{}/tests/backup/game1-zipped", repo_raw())),
+                path: StrictPath::new(format!("{}/tests/backup/game1", repo_raw())),
                 ..Default::default()
             };
-            assert!(!layout.validate(BackupId::Latest));
+            assert!(layout.validate(BackupId::Latest, false).is_empty());
+            assert_eq!(
+                vec![ValidationIssue::ChangedFile {
+                    backup: ".".into(),
+                    file: mapping_file_key("/file1.txt"),
+                    expected: "0000000000000000000000000000000000000000".into(),
+                    actual: "3a52ce780950d4d969792a2559cd519d7ee8c727".into(),
+                }],
+                layout.validate(BackupId::Latest, true),
+            );
         }
 
         #[test]
-        fn can_validate_a_zip_diff_backup_when_valid() {
+        fn can_validate_a_zip_diff_backup_deeply_when_content_changed() {
             let layout = GameLayout {
                 mapping: IndividualMapping {
                     drives: drives_x_always(),
@@ -3582,7 +7870,7 @@ mod tests {
                             name: "test.zip".into(),
                             files: btree_map! {
                                 mapping_file_key("/file1.txt"): None,
-                                mapping_file_key("/file2.txt"): Some(IndividualMappingFile { hash: "9d891e731f75deae56884d79e9816736b7488080".into(), size: 2 }),
+                                mapping_file_key("/file2.txt"): Some(IndividualMappingFile { hash: "0000000000000000000000000000000000000000".into(), size: 2 }),
                             },
                             ..Default::default()
                         }]),
@@ -3593,35 +7881,73 @@ mod tests {
                 path: StrictPath::new(format!("{}/tests/backup/game1-zipped", repo_raw())),
                 ..Default::default()
             };
-            assert!(layout.validate(BackupId::Latest));
+            assert_eq!(
+                vec![ValidationIssue::ChangedFile {
+                    backup: "test.zip".into(),
+                    file: mapping_file_key("/file2.txt"),
+                    expected: "0000000000000000000000000000000000000000".into(),
+                    actual: "9d891e731f75deae56884d79e9816736b7488080".into(),
+                }],
+                layout.validate(BackupId::Latest, true),
+            );
         }
 
         #[test]
-        fn can_validate_a_zip_diff_backup_when_invalid() {
+        fn can_validate_a_tar_full_backup_deeply_when_valid() {
             let layout = GameLayout {
                 mapping: IndividualMapping {
                     drives: drives_x_always(),
                     backups: VecDeque::from(vec![FullBackup {
-                        name: "test.zip".into(),
+                        name: "test.tar.zst".into(),
                         files: btree_map! {
                             mapping_file_key("/file1.txt"): IndividualMappingFile { hash: "3a52ce780950d4d969792a2559cd519d7ee8c727".into(), size: 1 },
                             mapping_file_key("/file2.txt"): IndividualMappingFile { hash: "9d891e731f75deae56884d79e9816736b7488080".into(), size: 2 },
                         },
-                        children: VecDeque::from(vec![DifferentialBackup {
+                        ..Default::default()
+                    }]),
+                    ..Default::default()
+                },
+                path: StrictPath::new(format!("{}/tests/backup/game1-zipped", repo_raw())),
+                ..Default::default()
+            };
+            assert_eq!(Vec::<ValidationIssue>::new(), layout.validate(BackupId::Latest, true));
+        }
+
+        #[test]
+        fn can_validate_all_backups_and_report_missing_files() {
+            let layout = GameLayout {
+                mapping: IndividualMapping {
+                    drives: drives_x_always(),
+                    backups: VecDeque::from(vec![
+                        FullBackup {
                             name: "test.zip".into(),
                             files: btree_map! {
-                                mapping_file_key("/fake.txt"): Some(IndividualMappingFile { hash: "9d891e731f75deae56884d79e9816736b7488080".into(), size: 2 }),
+                                mapping_file_key("/fake.txt"): IndividualMappingFile { hash: "9d891e731f75deae56884d79e9816736b7488080".into(), size: 2 },
                             },
                             ..Default::default()
-                        }]),
-                        ..Default::default()
-                    }]),
+                        },
+                        FullBackup {
+                            name: "missing.zip".into(),
+                            ..Default::default()
+                        },
+                    ]),
                     ..Default::default()
                 },
                 path: StrictPath::new(format!("{}/tests/backup/game1-zipped", repo_raw())),
                 ..Default::default()
             };
-            assert!(!layout.validate(BackupId::Latest));
+            assert_eq!(
+                vec![
+                    ValidationIssue::MissingFile {
+                        backup: "test.zip".into(),
+                        file: mapping_file_key("/fake.txt"),
+                    },
+                    ValidationIssue::UnreadableBackup {
+                        backup: "missing.zip".into(),
+                    },
+                ],
+                layout.validate_all(false),
+            );
         }
 
         #[test]
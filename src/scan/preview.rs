@@ -17,6 +17,9 @@ pub struct ScanInfo {
     /// and should be used in its raw form.
     pub found_files: HashMap<StrictPath, ScannedFile>,
     pub found_registry_keys: HashMap<RegistryItem, ScannedRegistry>,
+    /// Wine prefixes whose `*.reg` files hold the `found_registry_keys`.
+    /// This is only used outside of Windows.
+    pub wine_prefixes: Vec<StrictPath>,
     /// Only populated by a restoration scan.
     pub available_backups: Vec<Backup>,
     /// Only populated by a restoration scan.
@@ -81,7 +84,7 @@ impl ScanInfo {
 
     pub fn update_ignored(&mut self, toggled_paths: &ToggledPaths, toggled_registry: &ToggledRegistry) {
         for (scan_key, v) in self.found_files.iter_mut() {
-            v.ignored = toggled_paths.is_ignored(&self.game_name, v.effective(scan_key));
+            v.ignored = v.filtered || toggled_paths.is_ignored(&self.game_name, v.effective(scan_key));
         }
         for (scan_key, v) in self.found_registry_keys.iter_mut() {
             v.ignored = toggled_registry.is_ignored(&self.game_name, scan_key, None);
@@ -2,10 +2,16 @@
 
 #[cfg(target_os = "windows")]
 pub mod win;
+#[cfg(not(target_os = "windows"))]
+pub mod wine;
 
-use std::collections::BTreeMap;
+use std::collections::{BTreeMap, HashMap};
 
-use crate::prelude::StrictPath;
+use crate::{
+    prelude::StrictPath,
+    resource::config::ToggledRegistry,
+    scan::{BackupError, ScanInfo},
+};
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub enum Format {
@@ -141,6 +147,39 @@ impl Hives {
         let (hive, key) = path.split_hive()?;
         self.get(&hive, &key)
     }
+
+    /// Reads the keys found during a backup scan,
+    /// either from the Windows registry or from the game's Wine prefixes.
+    pub fn back_up_scan(&mut self, scan: &ScanInfo) -> Result<(), HashMap<RegistryItem, BackupError>> {
+        #[cfg(target_os = "windows")]
+        {
+            self.back_up(&scan.game_name, &scan.found_registry_keys)
+        }
+        #[cfg(not(target_os = "windows"))]
+        {
+            if scan.found_registry_keys.is_empty() {
+                return Ok(());
+            }
+            let prefixes = wine::Prefixes::load(&scan.wine_prefixes);
+            self.back_up_from_wine(&scan.game_name, &scan.found_registry_keys, &prefixes)
+        }
+    }
+
+    /// Writes the keys back to the Windows registry or to the game's Wine prefixes.
+    pub fn restore_scan(
+        &self,
+        scan: &ScanInfo,
+        toggled: &ToggledRegistry,
+    ) -> Result<(), HashMap<RegistryItem, BackupError>> {
+        #[cfg(target_os = "windows")]
+        {
+            self.restore(&scan.game_name, toggled)
+        }
+        #[cfg(not(target_os = "windows"))]
+        {
+            self.restore_to_wine(&scan.game_name, toggled, &scan.wine_prefixes)
+        }
+    }
 }
 
 impl Entry {
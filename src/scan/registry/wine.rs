@@ -0,0 +1,600 @@
+use std::{
+    collections::HashMap,
+    sync::{Arc, Mutex},
+    time::SystemTime,
+};
+
+use once_cell::sync::Lazy;
+
+use crate::{
+    prelude::StrictPath,
+    resource::config::{BackupFilter, ToggledRegistry},
+    scan::{
+        registry::{Entries, Hives, Keys, RegistryItem},
+        BackupError, ScanChange, ScannedRegistry, ScannedRegistryValue, ScannedRegistryValues,
+    },
+};
+
+/// Registry files in a Wine prefix, along with the hive that each one holds.
+const FILES: &[(&str, &str)] = &[("user.reg", "HKEY_CURRENT_USER"), ("system.reg", "HKEY_LOCAL_MACHINE")];
+
+/// Parsed prefixes along with the modification times of their files.
+type Cache = HashMap<StrictPath, (Vec<Option<SystemTime>>, Arc<Hives>)>;
+
+/// A shared prefix would otherwise be parsed again for every game that has registry entries.
+static CACHE: Lazy<Mutex<Cache>> = Lazy::new(Default::default);
+
+fn read_file(file: &StrictPath) -> Option<regashii::Registry> {
+    if !file.is_file() {
+        return None;
+    }
+
+    match regashii::Registry::deserialize_file(file.as_std_path_buf().ok()?) {
+        Ok(registry) => Some(registry),
+        Err(e) => {
+            log::warn!("Unable to parse Wine registry file: {:?} | {e:?}", file);
+            None
+        }
+    }
+}
+
+/// Wine keeps some informational comments at the top of the file (e.g., `;; All keys relative to \\Machine`),
+/// which the parser discards, so we carry them over from the original content.
+fn serialize(registry: &regashii::Registry, original: &str) -> String {
+    let content = registry.serialize();
+    let comments: Vec<_> = original
+        .lines()
+        .skip(1)
+        .take_while(|line| line.starts_with(";;"))
+        .collect();
+
+    match content.split_once('\n') {
+        Some((header, rest)) if !comments.is_empty() => format!("{header}\n{}\n{rest}", comments.join("\n")),
+        _ => content,
+    }
+}
+
+/// Write a registry file without leaving a partial file behind if something goes wrong,
+/// and keep a copy of the original content next to it.
+fn write_file(target: &StrictPath, registry: &regashii::Registry) -> Result<(), String> {
+    let original = target.try_read().map_err(|e| e.to_string())?;
+
+    let backup = StrictPath::new(format!("{}.ludusavi.bak", target.render()));
+    backup.write_with_content(&original).map_err(|e| e.to_string())?;
+
+    let temp = StrictPath::new(format!("{}.ludusavi.tmp", target.render()));
+    temp.write_with_content(&serialize(registry, &original))
+        .map_err(|e| e.to_string())?;
+    temp.move_to(target).map_err(|e| {
+        let _ = temp.remove();
+        e.to_string()
+    })
+}
+
+/// Wine keeps the registry in memory while its server is running and writes it back on shutdown,
+/// so any changes that we make in the meantime would be lost.
+/// The server creates a socket in a folder named after the prefix's device and inode.
+#[cfg(unix)]
+fn server_running(prefix: &StrictPath) -> bool {
+    use std::os::unix::fs::MetadataExt;
+
+    let Ok(metadata) = prefix.metadata() else {
+        return false;
+    };
+
+    StrictPath::new(format!(
+        "/tmp/.wine-{}/server-{:x}-{:x}/socket",
+        metadata.uid(),
+        metadata.dev(),
+        metadata.ino()
+    ))
+    .exists()
+}
+
+#[cfg(not(unix))]
+fn server_running(_prefix: &StrictPath) -> bool {
+    false
+}
+
+fn value_name(name: &str) -> regashii::ValueName {
+    if name.is_empty() {
+        regashii::ValueName::Default
+    } else {
+        regashii::ValueName::Named(name.to_string())
+    }
+}
+
+/// Registry content loaded from the `*.reg` files of some Wine prefixes.
+#[derive(Clone, Debug, Default)]
+pub struct Prefixes(Vec<(StrictPath, Arc<Hives>)>);
+
+impl Prefixes {
+    pub fn load<'a>(prefixes: impl IntoIterator<Item = &'a StrictPath>) -> Self {
+        let mut loaded = vec![];
+
+        for prefix in prefixes {
+            if loaded.iter().any(|(x, _)| x == prefix) {
+                continue;
+            }
+            if let Some(hives) = Self::load_prefix(prefix) {
+                log::trace!("Loaded Wine registry from prefix: {:?}", prefix);
+                loaded.push((prefix.clone(), hives));
+            }
+        }
+
+        Self(loaded)
+    }
+
+    fn load_prefix(prefix: &StrictPath) -> Option<Arc<Hives>> {
+        let mtimes: Vec<_> = FILES
+            .iter()
+            .map(|(file, _)| prefix.joined(file).get_mtime().ok())
+            .collect();
+        if mtimes.iter().all(|x| x.is_none()) {
+            return None;
+        }
+
+        if let Ok(cache) = CACHE.lock() {
+            if let Some((cached_mtimes, hives)) = cache.get(prefix) {
+                if *cached_mtimes == mtimes {
+                    return Some(hives.clone());
+                }
+            }
+        }
+
+        let hives = Arc::new(Self::parse_prefix(prefix)?);
+        if let Ok(mut cache) = CACHE.lock() {
+            cache.insert(prefix.clone(), (mtimes, hives.clone()));
+        }
+        Some(hives)
+    }
+
+    fn parse_prefix(prefix: &StrictPath) -> Option<Hives> {
+        let mut hives = Hives::default();
+        let mut found = false;
+
+        for (file, hive_name) in FILES {
+            let Some(registry) = read_file(&prefix.joined(file)) else {
+                continue;
+            };
+            found = true;
+
+            // Wine stores each file's keys relative to its hive.
+            let mut rooted = regashii::Registry::new(regashii::Format::Regedit5);
+            for (key_name, key) in registry.keys() {
+                rooted.insert(format!("{}\\{}", hive_name, key_name.raw()).into(), key.clone());
+            }
+            hives.0.extend(Hives::from(rooted).0);
+        }
+
+        found.then_some(hives)
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.0.is_empty()
+    }
+
+    /// Wine prefixes that contain any of the found keys.
+    pub fn containing(&self, found: &HashMap<RegistryItem, ScannedRegistry>) -> Vec<StrictPath> {
+        self.0
+            .iter()
+            .filter(|(_, hives)| found.keys().any(|item| hives.get_path(item).is_some()))
+            .map(|(prefix, _)| prefix.clone())
+            .collect()
+    }
+
+    /// Returns the key's entries from the first prefix that has it.
+    pub fn get(&self, hive_name: &str, key: &str) -> Option<&Entries> {
+        self.0.iter().find_map(|(_, hives)| hives.get(hive_name, key))
+    }
+
+    pub fn scan_registry(
+        &self,
+        game: &str,
+        path: &str,
+        filter: &BackupFilter,
+        toggled: &ToggledRegistry,
+        previous: Option<&Hives>,
+    ) -> HashMap<RegistryItem, ScannedRegistry> {
+        let mut found = HashMap::new();
+
+        let Some((hive_name, key)) = RegistryItem::new(path.to_string()).split_hive() else {
+            return found;
+        };
+        let key = key.trim_end_matches('\\').to_lowercase();
+
+        for (_, hives) in &self.0 {
+            let Some(keys) = hives.0.get(&hive_name) else {
+                continue;
+            };
+
+            for (live_key, live_entries) in &keys.0 {
+                let normalized = live_key.to_lowercase();
+                if normalized != key && !normalized.starts_with(&format!("{key}\\")) {
+                    continue;
+                }
+                if live_key.contains('/') {
+                    // TODO: Handle key names containing a slash.
+                    continue;
+                }
+
+                let path = RegistryItem::from_hive_and_key(&hive_name, live_key);
+                if filter.is_registry_ignored(&path) || found.contains_key(&path) {
+                    continue;
+                }
+
+                let previous_entries = previous.and_then(|x| x.get(&hive_name, live_key));
+                let mut live_values = ScannedRegistryValues::new();
+
+                for (live_entry_name, live_entry) in &live_entries.0 {
+                    live_values.insert(
+                        live_entry_name.clone(),
+                        ScannedRegistryValue {
+                            ignored: toggled.is_ignored(game, &path, Some(live_entry_name)),
+                            change: previous_entries
+                                .and_then(|x| x.0.get(live_entry_name))
+                                .map(|x| {
+                                    if x == live_entry {
+                                        ScanChange::Same
+                                    } else {
+                                        ScanChange::Different
+                                    }
+                                })
+                                .unwrap_or(ScanChange::New),
+                        },
+                    );
+                }
+
+                found.insert(
+                    path.clone(),
+                    ScannedRegistry {
+                        ignored: toggled.is_ignored(game, &path, None),
+                        change: match (previous, previous_entries) {
+                            (Some(_), Some(_)) => ScanChange::Same,
+                            _ => ScanChange::New,
+                        },
+                        values: live_values,
+                    },
+                );
+            }
+        }
+
+        found
+    }
+}
+
+impl Hives {
+    /// Since this backs up items that we already found during the scan,
+    /// there shouldn't be any errors normally.
+    pub fn back_up_from_wine(
+        &mut self,
+        game: &str,
+        scan: &HashMap<RegistryItem, ScannedRegistry>,
+        prefixes: &Prefixes,
+    ) -> Result<(), HashMap<RegistryItem, BackupError>> {
+        let mut failed = HashMap::new();
+
+        for (scan_key, scanned) in scan {
+            if scanned.ignored && scanned.values.values().all(|x| x.ignored) {
+                continue;
+            }
+            match scanned.change {
+                ScanChange::New | ScanChange::Different | ScanChange::Same => (),
+                ScanChange::Removed | ScanChange::Unknown => continue,
+            }
+
+            let Some((hive_name, key)) = scan_key.split_hive() else {
+                log::error!("[{game}] Unable to split hive: {:?}", scan_key);
+                failed.insert(
+                    scan_key.clone(),
+                    BackupError::Raw(format!("Unable to split hive: {}", scan_key.raw())),
+                );
+                continue;
+            };
+
+            let Some(entries) = prefixes.get(&hive_name, &key) else {
+                log::error!("[{game}] Unable to find key in Wine prefixes: {:?}", scan_key);
+                failed.insert(
+                    scan_key.clone(),
+                    BackupError::Raw(format!("Unable to find key: {}", scan_key.raw())),
+                );
+                continue;
+            };
+
+            let parent = self.0.entry(hive_name).or_default().0.entry(key).or_default();
+            for (name, entry) in &entries.0 {
+                let ignored = scanned.values.get(name).map(|x| x.ignored).unwrap_or_default();
+                if !ignored && entry.is_set() {
+                    parent.0.insert(name.clone(), entry.clone());
+                }
+            }
+        }
+
+        if failed.is_empty() {
+            Ok(())
+        } else {
+            Err(failed)
+        }
+    }
+
+    /// Merges the backed up keys into the registry files of each Wine prefix.
+    /// Keys and values that are not in the backup are left alone.
+    pub fn restore_to_wine(
+        &self,
+        game_name: &str,
+        toggled: &ToggledRegistry,
+        prefixes: &[StrictPath],
+    ) -> Result<(), HashMap<RegistryItem, BackupError>> {
+        let mut failed = HashMap::new();
+
+        for prefix in prefixes {
+            let running = server_running(prefix);
+            if running {
+                log::warn!(
+                    "[{}] Registry - Wine is running in prefix, skipping: {:?}",
+                    game_name,
+                    prefix
+                );
+            }
+
+            for (file, hive_name) in FILES {
+                let Some(keys) = self.0.get(*hive_name) else {
+                    continue;
+                };
+
+                if running {
+                    for key_name in keys.0.keys() {
+                        failed.insert(
+                            RegistryItem::from_hive_and_key(hive_name, key_name),
+                            BackupError::Raw(format!("Wine is running in prefix: {}", prefix.render())),
+                        );
+                    }
+                    continue;
+                }
+
+                let target = prefix.joined(file);
+                let Some(mut registry) = read_file(&target) else {
+                    log::error!("[{}] Registry - unable to read Wine file: {:?}", game_name, &target);
+                    for key_name in keys.0.keys() {
+                        failed.insert(
+                            RegistryItem::from_hive_and_key(hive_name, key_name),
+                            BackupError::Raw(format!("Unable to read Wine registry: {}", target.render())),
+                        );
+                    }
+                    continue;
+                };
+
+                merge_keys(&mut registry, game_name, hive_name, keys, toggled, &mut failed);
+
+                if let Err(e) = write_file(&target, &registry) {
+                    log::error!(
+                        "[{}] Registry - unable to write Wine file: {:?} | {e}",
+                        game_name,
+                        &target
+                    );
+                    for key_name in keys.0.keys() {
+                        failed.insert(
+                            RegistryItem::from_hive_and_key(hive_name, key_name),
+                            BackupError::Raw(e.clone()),
+                        );
+                    }
+                }
+            }
+        }
+
+        if failed.is_empty() {
+            Ok(())
+        } else {
+            Err(failed)
+        }
+    }
+}
+
+fn merge_keys(
+    registry: &mut regashii::Registry,
+    game_name: &str,
+    hive_name: &str,
+    keys: &Keys,
+    toggled: &ToggledRegistry,
+    failed: &mut HashMap<RegistryItem, BackupError>,
+) {
+    for (key_name, entries) in &keys.0 {
+        let path = RegistryItem::from_hive_and_key(hive_name, key_name);
+
+        if toggled.is_ignored(game_name, &path, None)
+            && entries.0.keys().all(|x| toggled.is_ignored(game_name, &path, Some(x)))
+        {
+            continue;
+        }
+
+        let mut key = regashii::Key::new();
+        for (entry_name, entry) in &entries.0 {
+            if toggled.is_ignored(game_name, &path, Some(entry_name)) {
+                continue;
+            }
+
+            match regashii::Value::try_from(entry.clone()) {
+                Ok(value) => key.insert(value_name(entry_name), value),
+                Err(_) => {
+                    log::warn!(
+                        "[{}] Registry - unparsed entry: {:?} ; {} | {:?}",
+                        game_name,
+                        &path,
+                        entry_name,
+                        entry
+                    );
+                    failed.insert(path.clone(), BackupError::Raw(format!("Unparsed entry: {:?}", entry)));
+                }
+            }
+        }
+
+        // Wine records a modification time after the key name, which we want to keep.
+        let key_name = registry.key_name(&key_name.as_str().into());
+        key.set_addendum(registry.keys().get(&key_name).and_then(|x| x.addendum().cloned()));
+
+        registry.insert(key_name, key);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use pretty_assertions::assert_eq;
+    use velcro::{btree_map, hash_map};
+
+    use super::*;
+    use crate::{
+        scan::registry::Entry,
+        testing::{repo, s},
+    };
+
+    fn prefixes() -> Prefixes {
+        Prefixes::load(&[StrictPath::new(format!("{}/tests/wine-registry", repo()))])
+    }
+
+    #[test]
+    fn can_load_prefix() {
+        let prefixes = prefixes();
+        assert_eq!(
+            Some(&Entries(btree_map! {
+                s("sz"): Entry::Sz(s("foo")),
+                s("dword"): Entry::Dword(1),
+            })),
+            prefixes.get("HKEY_CURRENT_USER", "Software\\Ludusavi\\game3"),
+        );
+        assert_eq!(
+            Some(&Entries(btree_map! {
+                s("installed"): Entry::Dword(1),
+            })),
+            prefixes.get("HKEY_LOCAL_MACHINE", "Software\\Ludusavi\\game3"),
+        );
+    }
+
+    #[test]
+    fn can_scan_key_with_subkeys() {
+        assert_eq!(
+            hash_map! {
+                RegistryItem::new(s("HKEY_CURRENT_USER/Software/Ludusavi/game3")): ScannedRegistry::new()
+                    .change_as(ScanChange::New)
+                    .with_value_new("sz")
+                    .with_value_new("dword"),
+                RegistryItem::new(s("HKEY_CURRENT_USER/Software/Ludusavi/game3/sub")): ScannedRegistry::new()
+                    .change_as(ScanChange::New),
+            },
+            prefixes().scan_registry(
+                "game3",
+                "HKEY_CURRENT_USER/Software/Ludusavi/game3",
+                &BackupFilter::default(),
+                &ToggledRegistry::default(),
+                None,
+            ),
+        );
+    }
+
+    #[test]
+    fn can_back_up_scanned_keys() {
+        let prefixes = prefixes();
+        let scan = prefixes.scan_registry(
+            "game3",
+            "HKEY_CURRENT_USER/Software/Ludusavi/game3",
+            &BackupFilter::default(),
+            &ToggledRegistry::default(),
+            None,
+        );
+
+        let mut hives = Hives::default();
+        hives.back_up_from_wine("game3", &scan, &prefixes).unwrap();
+
+        assert_eq!(
+            Hives(btree_map! {
+                s("HKEY_CURRENT_USER"): Keys(btree_map! {
+                    s("Software\\Ludusavi\\game3"): Entries(btree_map! {
+                        s("sz"): Entry::Sz(s("foo")),
+                        s("dword"): Entry::Dword(1),
+                    }),
+                    s("Software\\Ludusavi\\game3\\sub"): Entries::default(),
+                })
+            }),
+            hives,
+        );
+    }
+
+    #[test]
+    fn can_merge_keys_into_wine_registry() {
+        let mut registry = regashii::Registry::deserialize(
+            r#"WINE REGISTRY Version 2
+;; All keys relative to \\User\\S-1-5-21-0-0-0-1000
+
+#arch=win64
+
+[Software\\Ludusavi\\game3] 1700000000
+#time=1da0000000000000
+"dword"=dword:00000001
+"other"="kept"
+"#,
+        )
+        .unwrap();
+
+        let keys = Keys(btree_map! {
+            s("Software\\Ludusavi\\game3"): Entries(btree_map! {
+                s("dword"): Entry::Dword(2),
+                s("sz"): Entry::Sz(s("foo")),
+            }),
+            s("Software\\Ludusavi\\new"): Entries(btree_map! {
+                s(""): Entry::Sz(s("default")),
+            }),
+        });
+
+        let mut failed = HashMap::new();
+        merge_keys(
+            &mut registry,
+            "game3",
+            "HKEY_CURRENT_USER",
+            &keys,
+            &ToggledRegistry::default(),
+            &mut failed,
+        );
+
+        assert!(failed.is_empty());
+        assert_eq!(
+            r#"
+WINE REGISTRY Version 2
+#arch=win64
+
+[Software\\Ludusavi\\game3] 1700000000
+#time=1da0000000000000
+"dword"=dword:00000002
+"other"="kept"
+"sz"="foo"
+
+[Software\\Ludusavi\\new]
+@="default"
+"#
+            .trim(),
+            registry.serialize().trim(),
+        );
+    }
+
+    #[test]
+    fn can_serialize_with_original_header_comments() {
+        let original = r#"WINE REGISTRY Version 2
+;; All keys relative to \\Machine
+
+#arch=win64
+
+[Software\\Ludusavi] 1700000000
+"#;
+        let registry = regashii::Registry::deserialize(original).unwrap();
+
+        assert_eq!(
+            r#"
+WINE REGISTRY Version 2
+;; All keys relative to \\Machine
+#arch=win64
+
+[Software\\Ludusavi] 1700000000
+"#
+            .trim(),
+            serialize(&registry, original).trim(),
+        );
+    }
+}
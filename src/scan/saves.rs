@@ -16,6 +16,16 @@ pub struct ScannedFile {
     /// An enclosing archive file, if any, depending on the `BackupFormat`.
     pub container: Option<StrictPath>,
     pub redirected: Option<StrictPath>,
+    /// Only set by a restoration scan:
+    /// the local file was changed after the backup was made,
+    /// or it doesn't match any version in the backups.
+    pub conflict: bool,
+    /// Only set by a backup scan:
+    /// the file is ignored because of the backup filter's file rules.
+    pub filtered: bool,
+    /// Only set by a backup scan:
+    /// the Steam account that the file belongs to, based on the account IDs in its path.
+    pub steam_account: Option<u32>,
 }
 
 impl ScannedFile {
@@ -29,6 +39,9 @@ impl ScannedFile {
             change: Default::default(),
             container: None,
             redirected: None,
+            conflict: false,
+            filtered: false,
+            steam_account: None,
         }
     }
 
@@ -42,6 +55,9 @@ impl ScannedFile {
             change,
             container: None,
             redirected: None,
+            conflict: false,
+            filtered: false,
+            steam_account: None,
         }
     }
 
@@ -51,6 +67,25 @@ impl ScannedFile {
         self
     }
 
+    #[cfg(test)]
+    pub fn filtered(mut self) -> Self {
+        self.filtered = true;
+        self.ignored = true;
+        self
+    }
+
+    #[cfg(test)]
+    pub fn steam_account(mut self, id: u32) -> Self {
+        self.steam_account = Some(id);
+        self
+    }
+
+    #[cfg(test)]
+    pub fn conflicted(mut self) -> Self {
+        self.conflict = true;
+        self
+    }
+
     #[cfg(test)]
     pub fn change_as(mut self, change: ScanChange) -> Self {
         self.change = change;
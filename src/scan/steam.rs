@@ -1,7 +1,10 @@
-use std::collections::HashMap;
+use std::collections::{BTreeMap, HashMap};
 
 use crate::prelude::StrictPath;
 
+/// Difference between a SteamID64 and the account ID that it contains (SteamID3).
+const STEAM_ID64_BASE: u64 = 76561197960265728;
+
 #[derive(Clone, Debug, Default)]
 pub struct SteamShortcuts(HashMap<String, SteamShortcut>);
 
@@ -56,3 +59,209 @@ impl SteamShortcuts {
         self.0.get(name)
     }
 }
+
+/// Normalizes a SteamID64 or a SteamID3 account ID to the account ID.
+pub fn steam_account_id(id: u64) -> Option<u32> {
+    u32::try_from(id.checked_sub(STEAM_ID64_BASE).unwrap_or(id)).ok()
+}
+
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct SteamAccount {
+    /// Account ID, as used for the `userdata` folders.
+    pub id: u32,
+    pub name: Option<String>,
+}
+
+impl SteamAccount {
+    pub fn id64(&self) -> u64 {
+        self.id as u64 + STEAM_ID64_BASE
+    }
+
+    /// Forms that a game may use in place of `<storeUserId>`.
+    pub fn ids(&self) -> [String; 2] {
+        [self.id.to_string(), self.id64().to_string()]
+    }
+}
+
+#[derive(Debug, serde::Deserialize)]
+struct LoginUser {
+    #[serde(rename = "AccountName")]
+    account_name: Option<String>,
+    #[serde(rename = "PersonaName")]
+    persona_name: Option<String>,
+}
+
+/// Accounts that have signed into a Steam installation.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct SteamAccounts(Vec<SteamAccount>);
+
+impl SteamAccounts {
+    /// Combines `config/loginusers.vdf` with the numbered folders in `userdata`,
+    /// since either one may be missing accounts that the other knows about.
+    pub fn scan(root: &StrictPath) -> Self {
+        let mut accounts = BTreeMap::<u32, SteamAccount>::new();
+
+        let login_users = root.joined("config/loginusers.vdf");
+        if let Some(content) = login_users.read() {
+            match keyvalues_serde::from_str::<HashMap<String, LoginUser>>(&content) {
+                Ok(users) => {
+                    for (id, user) in users {
+                        let Some(id) = id.parse().ok().and_then(steam_account_id) else {
+                            continue;
+                        };
+                        accounts.insert(
+                            id,
+                            SteamAccount {
+                                id,
+                                name: user.persona_name.or(user.account_name),
+                            },
+                        );
+                    }
+                }
+                Err(e) => {
+                    log::warn!("Unable to parse Steam login users: {:?} | {e:?}", &login_users);
+                }
+            }
+        }
+
+        if let Ok(entries) = root.joined("userdata").read_dir() {
+            for entry in entries.filter_map(|x| x.ok()) {
+                let Some(id) = entry.file_name().to_str().and_then(|x| x.parse::<u32>().ok()) else {
+                    continue;
+                };
+                if id != 0 && entry.path().is_dir() {
+                    accounts.entry(id).or_insert(SteamAccount { id, name: None });
+                }
+            }
+        }
+
+        Self(accounts.into_values().collect())
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.0.is_empty()
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = &SteamAccount> {
+        self.0.iter()
+    }
+
+    pub fn extend(&mut self, other: &Self) {
+        for account in &other.0 {
+            if !self.0.iter().any(|x| x.id == account.id) {
+                self.0.push(account.clone());
+            }
+        }
+    }
+
+    /// Finds the account whose ID appears as a folder or file name in the path.
+    pub fn find_in(&self, path: &StrictPath) -> Option<u32> {
+        let rendered = path.render();
+        let parts: Vec<_> = rendered.split('/').collect();
+        self.0
+            .iter()
+            .find(|account| account.ids().iter().any(|id| parts.contains(&id.as_str())))
+            .map(|x| x.id)
+    }
+}
+
+/// Rewrites the path components that refer to one Steam account so that they refer to another.
+/// Both forms of the account ID are handled.
+pub fn redirect_steam_account(path: &StrictPath, source: u32, target: u32) -> Option<StrictPath> {
+    if source == target {
+        return None;
+    }
+
+    let source = SteamAccount { id: source, name: None };
+    let target = SteamAccount { id: target, name: None };
+    let [source_id, source_id64] = source.ids();
+    let [target_id, target_id64] = target.ids();
+
+    let rendered = path.render();
+    let mut changed = false;
+    let parts: Vec<_> = rendered
+        .split('/')
+        .map(|part| {
+            if part == source_id {
+                changed = true;
+                target_id.as_str()
+            } else if part == source_id64 {
+                changed = true;
+                target_id64.as_str()
+            } else {
+                part
+            }
+        })
+        .collect();
+
+    changed.then(|| StrictPath::new(parts.join("/")))
+}
+
+#[cfg(test)]
+mod tests {
+    use pretty_assertions::assert_eq;
+
+    use super::*;
+    use crate::testing::{repo, s};
+
+    #[test]
+    fn can_normalize_account_id() {
+        assert_eq!(Some(1), steam_account_id(1));
+        assert_eq!(Some(1), steam_account_id(76561197960265729));
+    }
+
+    #[test]
+    fn can_scan_accounts_from_login_users_and_userdata() {
+        assert_eq!(
+            SteamAccounts(vec![
+                SteamAccount {
+                    id: 1,
+                    name: Some(s("First")),
+                },
+                SteamAccount { id: 2, name: None },
+                SteamAccount {
+                    id: 3,
+                    name: Some(s("Third")),
+                },
+            ]),
+            SteamAccounts::scan(&StrictPath::new(format!("{}/tests/launchers/steam", repo()))),
+        );
+    }
+
+    #[test]
+    fn can_find_account_in_path() {
+        let accounts = SteamAccounts(vec![
+            SteamAccount { id: 1, name: None },
+            SteamAccount { id: 2, name: None },
+        ]);
+
+        assert_eq!(
+            Some(2),
+            accounts.find_in(&StrictPath::new(s("/steam/userdata/2/101/remote/save.dat")))
+        );
+        assert_eq!(
+            Some(1),
+            accounts.find_in(&StrictPath::new(s("/Documents/Game/76561197960265729/save.dat")))
+        );
+        assert_eq!(
+            None,
+            accounts.find_in(&StrictPath::new(s("/Documents/Game 1/save.dat")))
+        );
+    }
+
+    #[test]
+    fn can_redirect_steam_account() {
+        assert_eq!(
+            Some(StrictPath::new(s("/steam/userdata/2/101/remote/save.dat"))),
+            redirect_steam_account(&StrictPath::new(s("/steam/userdata/1/101/remote/save.dat")), 1, 2),
+        );
+        assert_eq!(
+            Some(StrictPath::new(s("/Documents/Game/76561197960265730/save.dat"))),
+            redirect_steam_account(&StrictPath::new(s("/Documents/Game/76561197960265729/save.dat")), 1, 2),
+        );
+        assert_eq!(
+            None,
+            redirect_steam_account(&StrictPath::new(s("/Documents/Game/10/save.dat")), 1, 2),
+        );
+    }
+}
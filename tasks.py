@@ -95,6 +95,7 @@ def docs_cli(ctx):
         "manifest --help",
         "cloud --help",
         "wrap --help",
+        "watch --help",
         "api --help",
         "schema --help",
     ]